@@ -0,0 +1,39 @@
+/*
+ * Copyright (C) ton.dev. All Rights Reserved.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.  You may obtain a copy of the
+ * License at:
+ *
+ * https://www.ever.dev/licenses
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific ton.dev software governing permissions and limitations
+ * under the License.
+ */
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde_json::{Map, Value};
+use ton_dev_block_json::parse_config;
+
+fn zerostate_config() -> Map<String, Value> {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    map["master"]["config"].as_object().unwrap().clone()
+}
+
+// the one pass dispatch of `parse_config` on the full zerostate config and on a short one
+fn bench_parse_config(c: &mut Criterion) {
+    let full = zerostate_config();
+    let mut three = Map::new();
+    for name in ["p1", "p15", "p34"] {
+        three.insert(name.to_string(), full[name].clone());
+    }
+    c.bench_function("parse_config full", |b| b.iter(|| parse_config(black_box(&full)).unwrap()));
+    c.bench_function("parse_config three params", |b| b.iter(|| parse_config(black_box(&three)).unwrap()));
+}
+
+criterion_group!(benches, bench_parse_config);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+#!/bin/sh
+# The deserializer alone must build for the browser: no ton_api (the `remp` feature),
+# no rayon and no file system. The tests of src/tests/test_wasm.rs use only this part
+# of the crate and run natively, wasm32-unknown-unknown has no test runner by default.
+set -e
+
+cd "$(dirname "$0")/.."
+
+rustup target add wasm32-unknown-unknown
+cargo check --lib --target wasm32-unknown-unknown --no-default-features --features deserialize
+cargo test --lib --no-default-features --features deserialize test_wasm
@@ -0,0 +1,145 @@
+/*
+ * Copyright (C) ton.dev. All Rights Reserved.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.  You may obtain a copy of the
+ * License at:
+ *
+ * https://www.ever.dev/licenses
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific ton.dev software governing permissions and limitations
+ * under the License.
+ */
+
+use serde_json::Value;
+use ton_dev_block::{
+    ed25519_create_private_key, ConfigParam12, ConfigParam18, ConfigParam32, ConfigParam34,
+    ConfigParam36, ConfigParamEnum, ConfigParams, SigPubKey, StoragePrices, UInt256,
+    ValidatorDescr, ValidatorSet, WorkchainDescr, WorkchainFormat, WorkchainFormat1,
+};
+
+use crate::parse_config;
+
+// xorshift64*, enough to get reproducible configs without extra dependencies
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self((seed ^ 0x9e37_79b9_7f4a_7c15) | 1)
+    }
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+    fn bool(&mut self) -> bool {
+        self.next() & 1 == 1
+    }
+    fn bytes32(&mut self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        bytes.chunks_mut(8).for_each(|chunk| chunk.copy_from_slice(&self.next().to_le_bytes()));
+        bytes
+    }
+}
+
+fn arbitrary_workchain(rng: &mut Rng) -> WorkchainDescr {
+    let mut descr = WorkchainDescr::new();
+    descr.enabled_since = rng.next() as u32;
+    let min_split = rng.below(5) as u8;
+    descr.set_min_split(min_split).unwrap();
+    descr.set_max_split(min_split + rng.below(28) as u8).unwrap();
+    descr.active = rng.bool();
+    descr.accept_msgs = rng.bool();
+    descr.flags = rng.below(1 << 12) as u16;
+    descr.zerostate_root_hash = UInt256::from(rng.bytes32());
+    descr.zerostate_file_hash = UInt256::from(rng.bytes32());
+    descr.version = rng.below(16) as u32;
+    descr.format = WorkchainFormat::Basic(
+        WorkchainFormat1::with_params(rng.next() as i32, rng.below(1 << 32))
+    );
+    descr
+}
+
+fn arbitrary_validator_set(rng: &mut Rng, with_adnl: bool) -> ValidatorSet {
+    let count = 1 + rng.below(16) as usize;
+    let list = (0..count).map(|_| {
+        let key = ed25519_create_private_key(&rng.bytes32()).unwrap();
+        let adnl_addr = if with_adnl && rng.bool() {
+            Some(UInt256::from(rng.bytes32()))
+        } else {
+            None
+        };
+        ValidatorDescr::with_params(
+            SigPubKey::from_bytes(&key.verifying_key()).unwrap(),
+            1 + rng.below(1 << 40),
+            adnl_addr,
+            None,
+        )
+    }).collect::<Vec<_>>();
+    let utime_since = rng.below(1 << 31) as u32;
+    let utime_until = utime_since + 1 + rng.below(1 << 20) as u32;
+    let main = 1 + rng.below(count as u64) as u16;
+    ValidatorSet::new(utime_since, utime_until, main, list).unwrap()
+}
+
+/// Builds the random but valid config with the workchain descriptions (p12),
+/// storage prices (p18) and validator sets (p32, p34, p36), equal seeds give equal configs.
+pub fn arbitrary_config(seed: u64) -> ConfigParams {
+    let mut rng = Rng::new(seed);
+    let mut config = ConfigParams::new();
+
+    let mut p12 = ConfigParam12::new();
+    for _ in 0..1 + rng.below(4) {
+        p12.insert(rng.below(256) as i32, &arbitrary_workchain(&mut rng)).unwrap();
+    }
+    config.set_config(ConfigParamEnum::ConfigParam12(p12)).unwrap();
+
+    let mut p18 = ConfigParam18::default();
+    let mut utime_since = 0;
+    for _ in 0..1 + rng.below(8) {
+        let mut prices = StoragePrices::new();
+        prices.utime_since = utime_since;
+        prices.bit_price_ps = rng.below(1 << 40);
+        prices.cell_price_ps = rng.below(1 << 40);
+        prices.mc_bit_price_ps = rng.below(1 << 40);
+        prices.mc_cell_price_ps = rng.below(1 << 40);
+        p18.insert(&prices).unwrap();
+        utime_since += 1 + rng.below(1 << 24) as u32;
+    }
+    config.set_config(ConfigParamEnum::ConfigParam18(p18)).unwrap();
+
+    let mut p32 = ConfigParam32::new();
+    p32.prev_validators = arbitrary_validator_set(&mut rng, true);
+    config.set_config(ConfigParamEnum::ConfigParam32(p32)).unwrap();
+
+    // p34 is read without adnl addresses
+    let mut p34 = ConfigParam34::new();
+    p34.cur_validators = arbitrary_validator_set(&mut rng, false);
+    config.set_config(ConfigParamEnum::ConfigParam34(p34)).unwrap();
+
+    let mut p36 = ConfigParam36::new();
+    p36.next_validators = arbitrary_validator_set(&mut rng, true);
+    config.set_config(ConfigParamEnum::ConfigParam36(p36)).unwrap();
+
+    config
+}
+
+/// Fuzzing entry for `cargo fuzz`: feeds arbitrary bytes as JSON into `parse_config`.
+/// Parsing errors are expected, a panic is a bug. The `{"config": {...}}` wrappers
+/// are unwrapped by `parse_config` itself, so they are fuzzed as well.
+pub fn fuzz_parse_config(data: &[u8]) {
+    if let Ok(Value::Object(map)) = serde_json::from_slice::<Value>(data) {
+        let _ = parse_config(&map);
+    }
+}
+
+#[cfg(all(test, feature = "serialize"))]
+#[path = "tests/test_arbitrary.rs"]
+mod tests;
@@ -1,14 +1,16 @@
-use crate::block_parser::entry::get_sharding_depth;
-use crate::block_parser::get_partition;
+use crate::block_parser::block::deliver;
+use crate::block_parser::entry::{get_sharding_depth, raw_entry};
+use crate::block_parser::{get_partition, insert_block_location};
 use crate::{
-    BlockParserConfig, BlockParsingError, EntryConfig, JsonReducer, ParsedBlock, ParsedEntry,
+    BlockParserConfig, BlockParsingError, EntryConfig, JsonReducer, ParsedEntry, ParsedEntrySink,
     ParsingBlock,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap};
 use ton_dev_block::{Account, Deserializable, Serializable, ShardAccounts, Transaction};
-use ton_dev_block::{fail, AccountId, Cell, ExceptionCode, SliceData, UInt256};
+use ton_dev_block::{fail, AccountId, BlockIdExt, Cell, ExceptionCode, SliceData, UInt256};
 use ton_dev_block::{write_boc, BuilderData, Result};
 
+#[derive(Debug, PartialEq)]
 pub(crate) enum AccountTransition {
     None,
     Changed,
@@ -20,14 +22,17 @@ pub(crate) struct ParserAccounts<'a, R: JsonReducer> {
     max_account_bytes_size: Option<usize>,
     accounts_sharding_depth: u32,
     accounts_config: &'a Option<EntryConfig<R>>,
-    changed: HashSet<AccountId>,
-    deleted: HashSet<AccountId>,
+    // ordered so the entries come in the same order on every parsing, see `ParserCheckpoint`
+    changed: BTreeSet<AccountId>,
+    deleted: BTreeSet<AccountId>,
     last_trans_chain_order: HashMap<AccountId, String>,
     last_trans_lt: HashMap<AccountId, u64>,
+    last_trans_hash: HashMap<AccountId, UInt256>,
     update: Option<(ShardAccounts, ShardAccounts)>,
+    raw: bool,
 }
 
-fn read_accounts(cell: Cell) -> Result<ShardAccounts> {
+pub(crate) fn read_accounts(cell: Cell) -> Result<ShardAccounts> {
     // we cannot read full ShardStateUnsplit because some of its references can be pruned
     // ShardStateUnsplit::construct_from_cell(cell)?.read_accounts()
     const SHARD_STATE_UNSPLIT_PFX: u32 = 0x9023afe2;
@@ -68,23 +73,25 @@ impl<'a, R: JsonReducer> ParserAccounts<'a, R> {
             max_account_bytes_size: config.max_account_bytes_size,
             accounts_sharding_depth: get_sharding_depth(&config.accounts),
             accounts_config: &config.accounts,
-            changed: HashSet::new(),
-            deleted: HashSet::new(),
+            changed: BTreeSet::new(),
+            deleted: BTreeSet::new(),
             last_trans_chain_order: HashMap::new(),
             last_trans_lt: HashMap::new(),
+            last_trans_hash: HashMap::new(),
             update: updates,
+            raw: config.raw,
         })
     }
 
-    pub(crate) fn insert_entries(&mut self, result: &mut ParsedBlock) -> Result<()> {
+    pub(crate) fn insert_entries(&mut self, sink: &mut impl ParsedEntrySink) -> Result<()> {
         let now = std::time::Instant::now();
         let workchain_id = self.parsing.id.shard().workchain_id();
-        let Some(shard_state) = self.parsing.shard_state else {
+        let Some(shard_accounts) = self.shard_accounts()? else {
             Err(BlockParsingError::InvalidData(
                 "Can not parse accounts: required shard state is not specified.".to_string(),
             ))?
         };
-        let shard_accounts = shard_state.read_accounts()?;
+        // only accounts with transactions in the block are emitted
         for account_id in self.changed.iter() {
             let acc = shard_accounts.account(account_id)?.ok_or_else(|| {
                 BlockParsingError::InvalidData(
@@ -93,29 +100,47 @@ impl<'a, R: JsonReducer> ParserAccounts<'a, R> {
                         .to_string(),
                 )
             })?;
+            if self.raw {
+                let entry = self.prepare_raw_account_entry(account_id, workchain_id, &acc.account_cell())?;
+                deliver("account", entry, |entry| sink.on_account(entry))?;
+                continue;
+            }
             let acc = acc.read_account()?;
 
             let last_trans_chain_order = self.last_trans_chain_order.remove(account_id);
-            result.accounts.push(Self::prepare_account_entry(
+            let last_trans_lt = self.last_trans_lt.remove(account_id);
+            let entry = Self::prepare_account_entry(
                 acc,
                 self.get_code_hash_from(UpdateSide::Old, account_id)?,
                 last_trans_chain_order,
+                last_trans_lt,
+                Some(self.parsing.id),
                 self.max_account_bytes_size,
                 self.accounts_sharding_depth,
                 self.accounts_config,
-            )?);
+            )?;
+            deliver("account", entry, |entry| sink.on_account(entry))?;
         }
 
         for account_id in &self.deleted {
+            if self.raw {
+                let cell = Account::default().serialize()?;
+                let entry = self.prepare_raw_account_entry(account_id, workchain_id, &cell)?;
+                deliver("account", entry, |entry| sink.on_account(entry))?;
+                continue;
+            }
             let last_trans_chain_order = self.last_trans_chain_order.remove(account_id);
             let last_trans_lt = self.last_trans_lt.remove(account_id);
-            result.accounts.push(self.prepare_deleted_account_entry(
+            let last_trans_hash = self.last_trans_hash.remove(account_id);
+            let entry = self.prepare_deleted_account_entry(
                 account_id.clone(),
                 workchain_id,
                 self.get_code_hash_from(UpdateSide::Old, account_id)?,
                 last_trans_chain_order,
                 last_trans_lt,
-            )?);
+                last_trans_hash,
+            )?;
+            deliver("account", entry, |entry| sink.on_account(entry))?;
         }
         log::trace!(
             "TIME: accounts {} {}ms;   {}",
@@ -150,6 +175,7 @@ impl<'a, R: JsonReducer> ParserAccounts<'a, R> {
         &mut self,
         account_id: &AccountId,
         transaction: &Transaction,
+        transaction_hash: &UInt256,
         chain_order: &Option<String>,
     ) -> Result<()> {
         if let Some(chain_order) = chain_order {
@@ -159,9 +185,21 @@ impl<'a, R: JsonReducer> ParserAccounts<'a, R> {
         let last_trans_lt =
             transaction.logical_time() + transaction.out_msgs.len().unwrap_or(0) as u64 + 1;
         self.last_trans_lt.insert(account_id.clone(), last_trans_lt);
+        self.last_trans_hash
+            .insert(account_id.clone(), transaction_hash.clone());
         Ok(())
     }
 
+    fn shard_accounts(&self) -> Result<Option<ShardAccounts>> {
+        if let Some(accounts) = self.parsing.shard_accounts {
+            Ok(Some(accounts.clone()))
+        } else if let Some(state) = self.parsing.shard_state {
+            Ok(Some(state.read_accounts()?))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub(crate) fn get_code_hash(&self, account_id: &AccountId) -> Result<Option<String>> {
         Ok(if let Some(hash) = self.get_code_hash_from(UpdateSide::Old, account_id)? {
             Some(hash.to_hex_string())
@@ -177,8 +215,8 @@ impl<'a, R: JsonReducer> ParserAccounts<'a, R> {
                 UpdateSide::New => &updates.1,
             };
             accounts.account(id)
-        } else if let Some(state) = self.parsing.shard_state {
-            state.read_accounts()?.account(id)
+        } else if let Some(accounts) = self.shard_accounts()? {
+            accounts.account(id)
         } else {
             Ok(None)
         };
@@ -194,10 +232,29 @@ impl<'a, R: JsonReducer> ParserAccounts<'a, R> {
         })
     }
 
+    fn prepare_raw_account_entry(
+        &self,
+        account_id: &AccountId,
+        workchain_id: i32,
+        cell: &Cell,
+    ) -> Result<ParsedEntry> {
+        let partition = get_partition(self.accounts_sharding_depth, account_id.clone())?;
+        let address = crate::construct_address(workchain_id, account_id.clone())?;
+        Ok(raw_entry(
+            "account",
+            crate::account_doc_id(&address),
+            self.parsing.id,
+            &write_boc(cell)?,
+            partition,
+        ))
+    }
+
     pub(crate) fn prepare_account_entry(
         account: Account,
         prev_code_hash: Option<UInt256>,
         last_trans_chain_order: Option<String>,
+        last_trans_lt: Option<u64>,
+        block_id: Option<&BlockIdExt>,
         max_account_bytes_size: Option<usize>,
         accounts_sharding_depth: u32,
         accounts_config: &Option<EntryConfig<R>>,
@@ -252,6 +309,14 @@ impl<'a, R: JsonReducer> ParserAccounts<'a, R> {
                 last_trans_chain_order.into(),
             );
         }
+        if let Some(lt) = last_trans_lt {
+            // taken from the block, it is the same as in the account unless the state is stale
+            doc.insert("last_trans_lt".to_owned(), crate::u64_to_string(lt).into());
+            doc.insert("last_trans_lt_dec".to_owned(), lt.to_string().into());
+        }
+        if let Some(block_id) = block_id {
+            insert_block_location(&mut doc, block_id);
+        }
         ParsedEntry::reduced(doc, partition, accounts_config)
     }
 
@@ -262,6 +327,7 @@ impl<'a, R: JsonReducer> ParserAccounts<'a, R> {
         prev_code_hash: Option<UInt256>,
         last_trans_chain_order: Option<String>,
         last_trans_lt: Option<u64>,
+        last_trans_hash: Option<UInt256>,
     ) -> Result<ParsedEntry> {
         let partition = get_partition(self.accounts_sharding_depth, account_id.clone())?;
         let set = crate::DeletedAccountSerializationSet {
@@ -280,6 +346,10 @@ impl<'a, R: JsonReducer> ParserAccounts<'a, R> {
         if let Some(lt) = last_trans_lt {
             doc.insert("last_trans_lt".to_owned(), crate::u64_to_string(lt).into());
         }
+        if let Some(hash) = last_trans_hash {
+            doc.insert("last_trans_hash".to_owned(), hash.as_hex_string().into());
+        }
+        insert_block_location(&mut doc, self.parsing.id);
         ParsedEntry::reduced(doc, partition, self.accounts_config)
     }
 }
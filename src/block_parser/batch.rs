@@ -0,0 +1,73 @@
+use crate::block_parser::block::deliver;
+use crate::{ParsedEntry, ParsedEntrySink, ParserCheckpoint};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use ton_dev_block::{BlockIdExt, Result};
+
+/// Keeps only the latest account entry of every account of the batch adding `last_block_id`,
+/// all the other entries are passed to the sink as they come.
+/// The kept entries are delivered by `finish` ordered by the entry id
+pub(crate) struct LatestAccountsSink<'s, S: ParsedEntrySink> {
+    sink: &'s mut S,
+    block_id: String,
+    accounts: BTreeMap<String, ParsedEntry>,
+}
+
+impl<'s, S: ParsedEntrySink> LatestAccountsSink<'s, S> {
+    pub(crate) fn new(sink: &'s mut S) -> Self {
+        Self {
+            sink,
+            block_id: String::new(),
+            accounts: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the block the following account entries are taken from
+    pub(crate) fn start_block(&mut self, block_id: &BlockIdExt) {
+        self.block_id = crate::block_doc_id(block_id);
+    }
+
+    pub(crate) fn finish(self) -> Result<()> {
+        for (_, entry) in self.accounts {
+            deliver("account", entry, |entry| self.sink.on_account(entry))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'s, S: ParsedEntrySink> ParsedEntrySink for LatestAccountsSink<'s, S> {
+    fn on_block(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.sink.on_block(entry)
+    }
+    fn on_proof(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.sink.on_proof(entry)
+    }
+    fn on_transaction(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.sink.on_transaction(entry)
+    }
+    fn on_message(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.sink.on_message(entry)
+    }
+    fn on_account(&mut self, mut entry: ParsedEntry) -> Result<()> {
+        entry
+            .body
+            .insert("last_block_id".to_owned(), Value::String(self.block_id.clone()));
+        self.accounts.insert(entry.id.clone(), entry);
+        Ok(())
+    }
+    fn on_remp_receipt(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.sink.on_remp_receipt(entry)
+    }
+    fn on_account_summary(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.sink.on_account_summary(entry)
+    }
+    fn on_out_msg_queue_update(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.sink.on_out_msg_queue_update(entry)
+    }
+    fn on_key_block_config(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.sink.on_key_block_config(entry)
+    }
+    fn on_checkpoint(&mut self, checkpoint: &ParserCheckpoint) -> Result<()> {
+        self.sink.on_checkpoint(checkpoint)
+    }
+}
@@ -1,6 +1,7 @@
 use crate::block_parser::entry::ParsedEntry;
-use ton_dev_block::{Block, BlockIdExt, BlockProof, ShardStateUnsplit};
-use ton_dev_block::Cell;
+use crate::ParserCheckpoint;
+use ton_dev_block::{Block, BlockIdExt, BlockProof, ShardAccounts, ShardStateUnsplit};
+use ton_dev_block::{error, Cell, Result};
 
 #[derive(Default)]
 pub struct ParsedBlock {
@@ -9,6 +10,166 @@ pub struct ParsedBlock {
     pub accounts: Vec<ParsedEntry>,
     pub transactions: Vec<ParsedEntry>,
     pub messages: Vec<ParsedEntry>,
+    pub remp_receipts: Vec<ParsedEntry>,
+    pub account_summaries: Vec<ParsedEntry>,
+    pub out_msg_queue_updates: Vec<ParsedEntry>,
+    pub key_block_config: Option<ParsedEntry>,
+}
+
+/// Receives the entries as soon as the block parser prepares them,
+/// an error returned from any method aborts the parsing
+pub trait ParsedEntrySink {
+    fn on_block(&mut self, entry: ParsedEntry) -> Result<()>;
+    fn on_proof(&mut self, entry: ParsedEntry) -> Result<()>;
+    fn on_transaction(&mut self, entry: ParsedEntry) -> Result<()>;
+    fn on_message(&mut self, entry: ParsedEntry) -> Result<()>;
+    fn on_account(&mut self, entry: ParsedEntry) -> Result<()>;
+    // the entries below are produced only when enabled in the parser config,
+    // so the sinks not enabling them don't have to implement the methods
+    fn on_remp_receipt(&mut self, _entry: ParsedEntry) -> Result<()> {
+        Ok(())
+    }
+    fn on_account_summary(&mut self, _entry: ParsedEntry) -> Result<()> {
+        Ok(())
+    }
+    fn on_out_msg_queue_update(&mut self, _entry: ParsedEntry) -> Result<()> {
+        Ok(())
+    }
+    fn on_key_block_config(&mut self, _entry: ParsedEntry) -> Result<()> {
+        Ok(())
+    }
+    /// Called by [`crate::BlockParser::resume_into`] after every delivered entry,
+    /// the checkpoint can be stored to resume the parsing from the next entry
+    fn on_checkpoint(&mut self, _checkpoint: &ParserCheckpoint) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ParsedEntrySink for ParsedBlock {
+    fn on_block(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.block = Some(entry);
+        Ok(())
+    }
+    fn on_proof(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.proof = Some(entry);
+        Ok(())
+    }
+    fn on_transaction(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.transactions.push(entry);
+        Ok(())
+    }
+    fn on_message(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.messages.push(entry);
+        Ok(())
+    }
+    fn on_account(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.accounts.push(entry);
+        Ok(())
+    }
+    fn on_remp_receipt(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.remp_receipts.push(entry);
+        Ok(())
+    }
+    fn on_account_summary(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.account_summaries.push(entry);
+        Ok(())
+    }
+    fn on_out_msg_queue_update(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.out_msg_queue_updates.push(entry);
+        Ok(())
+    }
+    fn on_key_block_config(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.key_block_config = Some(entry);
+        Ok(())
+    }
+}
+
+/// Renames the `id` field of the entries and removes their body fields
+/// before passing them to the sink
+pub(crate) struct DocFieldsSink<'s, S: ParsedEntrySink> {
+    sink: &'s mut S,
+    name: &'static str,
+    omit_bodies: bool,
+}
+
+impl<'s, S: ParsedEntrySink> DocFieldsSink<'s, S> {
+    pub(crate) fn new(field: crate::DocIdField, omit_bodies: bool, sink: &'s mut S) -> Self {
+        Self {
+            sink,
+            name: field.name(),
+            omit_bodies,
+        }
+    }
+
+    fn rename(&self, mut entry: ParsedEntry) -> ParsedEntry {
+        if self.omit_bodies {
+            crate::omit_body_fields(&mut entry.body);
+        }
+        if self.name == "id" {
+            return entry;
+        }
+        // rebuilt to keep the field order
+        entry.body = std::mem::take(&mut entry.body)
+            .into_iter()
+            .map(|(key, value)| match key.as_str() {
+                "id" => (self.name.to_owned(), value),
+                _ => (key, value),
+            })
+            .collect();
+        entry
+    }
+}
+
+impl<'s, S: ParsedEntrySink> ParsedEntrySink for DocFieldsSink<'s, S> {
+    fn on_block(&mut self, entry: ParsedEntry) -> Result<()> {
+        let entry = self.rename(entry);
+        self.sink.on_block(entry)
+    }
+    fn on_proof(&mut self, entry: ParsedEntry) -> Result<()> {
+        let entry = self.rename(entry);
+        self.sink.on_proof(entry)
+    }
+    fn on_transaction(&mut self, entry: ParsedEntry) -> Result<()> {
+        let entry = self.rename(entry);
+        self.sink.on_transaction(entry)
+    }
+    fn on_message(&mut self, entry: ParsedEntry) -> Result<()> {
+        let entry = self.rename(entry);
+        self.sink.on_message(entry)
+    }
+    fn on_account(&mut self, entry: ParsedEntry) -> Result<()> {
+        let entry = self.rename(entry);
+        self.sink.on_account(entry)
+    }
+    fn on_remp_receipt(&mut self, entry: ParsedEntry) -> Result<()> {
+        let entry = self.rename(entry);
+        self.sink.on_remp_receipt(entry)
+    }
+    fn on_account_summary(&mut self, entry: ParsedEntry) -> Result<()> {
+        let entry = self.rename(entry);
+        self.sink.on_account_summary(entry)
+    }
+    fn on_out_msg_queue_update(&mut self, entry: ParsedEntry) -> Result<()> {
+        let entry = self.rename(entry);
+        self.sink.on_out_msg_queue_update(entry)
+    }
+    fn on_key_block_config(&mut self, entry: ParsedEntry) -> Result<()> {
+        let entry = self.rename(entry);
+        self.sink.on_key_block_config(entry)
+    }
+    fn on_checkpoint(&mut self, checkpoint: &ParserCheckpoint) -> Result<()> {
+        self.sink.on_checkpoint(checkpoint)
+    }
+}
+
+/// Passes the entry to the sink adding the entry kind and id to the sink error
+pub(crate) fn deliver(
+    kind: &str,
+    entry: ParsedEntry,
+    on_entry: impl FnOnce(ParsedEntry) -> Result<()>,
+) -> Result<()> {
+    let id = entry.id.clone();
+    on_entry(entry).map_err(|err| error!("Entry sink failed on {} {}: {}", kind, id, err))
 }
 
 pub struct ParsingBlock<'a> {
@@ -20,4 +181,6 @@ pub struct ParsingBlock<'a> {
     pub mc_seq_no: Option<u32>,
     pub proof: Option<&'a BlockProof>,
     pub shard_state: Option<&'a ShardStateUnsplit>,
+    /// Accounts of the shard state after the block, may be given instead of `shard_state`
+    pub shard_accounts: Option<&'a ShardAccounts>,
 }
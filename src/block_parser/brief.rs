@@ -0,0 +1,76 @@
+use serde_json::{Map, Value};
+use ton_dev_block::{
+    read_single_root_boc, BlockIdExt, BlockInfo, Deserializable, ExtBlkRef, SliceData,
+};
+use ton_dev_block::{fail, Result, UInt256};
+
+const BLOCK_TAG: u32 = 0x11ef55aa;
+
+/// Block header fields available without reading the block extra
+#[derive(Clone, Debug, PartialEq)]
+pub struct BriefBlockInfo {
+    /// File hash is calculated from the given bytes, it differs from the one of the full block
+    /// if some branches of the given boc are pruned
+    pub id: BlockIdExt,
+    pub gen_utime: u32,
+    pub key_block: bool,
+    pub prev_ref: ExtBlkRef,
+    pub prev_alt_ref: Option<ExtBlkRef>,
+    pub master_ref_seq_no: Option<u32>,
+}
+
+/// Reads only the block info cell, account blocks and message descriptions are not touched
+/// so the extra may be pruned away
+pub fn parse_block_header(bytes: &[u8]) -> Result<BriefBlockInfo> {
+    let root = read_single_root_boc(bytes)?;
+    let mut slice = SliceData::load_cell(root.clone())?;
+    let tag = slice.get_next_u32()?;
+    if tag != BLOCK_TAG {
+        fail!("invalid block tag {:x}", tag)
+    }
+    let info = BlockInfo::construct_from_cell(slice.reference(0)?)?;
+    let prev = info.read_prev_ref()?;
+    // the level 0 hash is the hash of the full block even if some branches are pruned
+    let id = BlockIdExt::with_params(
+        info.shard().clone(),
+        info.seq_no(),
+        root.hash(0),
+        UInt256::calc_file_hash(bytes),
+    );
+    Ok(BriefBlockInfo {
+        id,
+        gen_utime: info.gen_utime().as_u32(),
+        key_block: info.key_block(),
+        prev_ref: prev.prev1()?,
+        prev_alt_ref: prev.prev2()?,
+        master_ref_seq_no: info.read_master_ref()?.map(|master| master.master.seq_no),
+    })
+}
+
+/// Writes the brief info with the same field names as the block document
+pub fn brief_block_json(info: &BriefBlockInfo) -> Map<String, Value> {
+    let mode = crate::SerializationMode::Standart;
+    let (workchain_id, shard) = crate::shard_ident_to_json(info.id.shard());
+    let mut map = Map::new();
+    map.insert("id".to_owned(), crate::block_doc_id(&info.id).into());
+    map.insert("file_hash".to_owned(), info.id.file_hash().as_hex_string().into());
+    map.insert("workchain_id".to_owned(), workchain_id.into());
+    map.insert("shard".to_owned(), shard.into());
+    map.insert("seq_no".to_owned(), info.id.seq_no().into());
+    map.insert("gen_utime".to_owned(), info.gen_utime.into());
+    map.insert("key_block".to_owned(), info.key_block.into());
+    map.insert(
+        "prev_ref".to_owned(),
+        crate::serialize::serialize_block_ref(&info.prev_ref, None, mode),
+    );
+    if let Some(prev_alt_ref) = &info.prev_alt_ref {
+        map.insert(
+            "prev_alt_ref".to_owned(),
+            crate::serialize::serialize_block_ref(prev_alt_ref, None, mode),
+        );
+    }
+    if let Some(seq_no) = info.master_ref_seq_no {
+        map.insert("master_ref_seq_no".to_owned(), seq_no.into());
+    }
+    map
+}
@@ -0,0 +1,131 @@
+use crate::{ParsedEntry, ParsedEntrySink};
+use std::fmt;
+use std::str::FromStr;
+use ton_dev_block::{error, fail, BlockIdExt, Result};
+
+/// Position in the entries of the block: the count of entries already delivered to the sink.
+/// The parser delivers the entries in the same order on every parsing of the block with the
+/// same configuration, so the count is enough to skip them on resumption.
+/// Written as `{block root hash prefix}:{count}`, e.g. `89ed400a43e76664:17`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParserCheckpoint {
+    block: u64,
+    delivered: u64,
+}
+
+impl ParserCheckpoint {
+    fn block_prefix(block_id: &BlockIdExt) -> u64 {
+        u64::from_be_bytes(block_id.root_hash().as_slice()[0..8].try_into().unwrap())
+    }
+
+    /// Count of the entries delivered before the checkpoint
+    pub fn delivered(&self) -> u64 {
+        self.delivered
+    }
+}
+
+impl fmt::Display for ParserCheckpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}:{}", self.block, self.delivered)
+    }
+}
+
+impl FromStr for ParserCheckpoint {
+    type Err = ton_dev_block::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (block, delivered) = s
+            .split_once(':')
+            .ok_or_else(|| error!("Invalid parser checkpoint `{}`", s))?;
+        if block.len() != 16 {
+            fail!("Invalid parser checkpoint `{}`", s)
+        }
+        Ok(Self {
+            block: u64::from_str_radix(block, 16)
+                .map_err(|err| error!("Invalid parser checkpoint `{}`: {}", s, err))?,
+            delivered: delivered
+                .parse()
+                .map_err(|err| error!("Invalid parser checkpoint `{}`: {}", s, err))?,
+        })
+    }
+}
+
+/// Skips the entries delivered before the checkpoint and reports
+/// the new checkpoint to the sink after every delivered entry
+pub(crate) struct ResumingSink<'s, S: ParsedEntrySink> {
+    sink: &'s mut S,
+    block: u64,
+    skip: u64,
+    delivered: u64,
+}
+
+impl<'s, S: ParsedEntrySink> ResumingSink<'s, S> {
+    pub(crate) fn new(
+        block_id: &BlockIdExt,
+        checkpoint: Option<&ParserCheckpoint>,
+        sink: &'s mut S,
+    ) -> Result<Self> {
+        let block = ParserCheckpoint::block_prefix(block_id);
+        let skip = match checkpoint {
+            Some(checkpoint) if checkpoint.block != block => fail!(
+                "The parser checkpoint {} was taken on another block than {}",
+                checkpoint,
+                block_id
+            ),
+            Some(checkpoint) => checkpoint.delivered,
+            None => 0,
+        };
+        Ok(Self {
+            sink,
+            block,
+            skip,
+            delivered: 0,
+        })
+    }
+
+    fn pass(
+        &mut self,
+        entry: ParsedEntry,
+        on_entry: impl FnOnce(&mut S, ParsedEntry) -> Result<()>,
+    ) -> Result<()> {
+        self.delivered += 1;
+        if self.delivered <= self.skip {
+            return Ok(());
+        }
+        on_entry(&mut *self.sink, entry)?;
+        self.sink.on_checkpoint(&ParserCheckpoint {
+            block: self.block,
+            delivered: self.delivered,
+        })
+    }
+}
+
+impl<'s, S: ParsedEntrySink> ParsedEntrySink for ResumingSink<'s, S> {
+    fn on_block(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.pass(entry, |sink, entry| sink.on_block(entry))
+    }
+    fn on_proof(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.pass(entry, |sink, entry| sink.on_proof(entry))
+    }
+    fn on_transaction(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.pass(entry, |sink, entry| sink.on_transaction(entry))
+    }
+    fn on_message(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.pass(entry, |sink, entry| sink.on_message(entry))
+    }
+    fn on_account(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.pass(entry, |sink, entry| sink.on_account(entry))
+    }
+    fn on_remp_receipt(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.pass(entry, |sink, entry| sink.on_remp_receipt(entry))
+    }
+    fn on_account_summary(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.pass(entry, |sink, entry| sink.on_account_summary(entry))
+    }
+    fn on_out_msg_queue_update(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.pass(entry, |sink, entry| sink.on_out_msg_queue_update(entry))
+    }
+    fn on_key_block_config(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.pass(entry, |sink, entry| sink.on_key_block_config(entry))
+    }
+}
@@ -1,7 +1,12 @@
 use crate::block_parser::{BlockParsingError, JsonReducer};
 use crate::EntryConfig;
+use flate2::{write::DeflateEncoder, Compression};
 use serde_json::{Map, Value};
-use ton_dev_block::Result;
+use std::io::Write;
+use ton_dev_block::{base64_decode, base64_encode, BlockIdExt, Result};
+
+const DEFLATABLE_FIELDS: [&str; 4] = ["boc", "body", "code", "data"];
+const BODY_FIELDS: [&str; 6] = ["boc", "boc1", "body", "code", "data", "library"];
 
 #[derive(Clone)]
 pub struct ParsedEntry {
@@ -23,11 +28,14 @@ impl ParsedEntry {
     }
 
     pub fn reduced<R: JsonReducer>(
-        body: Map<String, Value>,
+        mut body: Map<String, Value>,
         partition: Option<u32>,
         config: &Option<EntryConfig<R>>,
     ) -> Result<Self> {
         if let Some(config) = config {
+            if let Some(max_size) = config.max_boc_field_size {
+                deflate_boc_fields(&mut body, max_size)?;
+            }
             if let Some(reducer) = &config.reducer {
                 return Self::new(reducer.reduce(body)?, partition);
             }
@@ -36,6 +44,59 @@ impl ParsedEntry {
     }
 }
 
+/// Entry of the raw parsing mode, see [`crate::BlockParserConfig::raw`]
+pub(crate) fn raw_entry(
+    kind: &str,
+    id: String,
+    block_id: &BlockIdExt,
+    boc: &[u8],
+    partition: Option<u32>,
+) -> ParsedEntry {
+    let mut body = Map::with_capacity(4);
+    body.insert("id".to_owned(), id.clone().into());
+    body.insert("block_id".to_owned(), crate::block_doc_id(block_id).into());
+    body.insert("kind".to_owned(), kind.into());
+    body.insert("boc".to_owned(), base64_encode(boc).into());
+    ParsedEntry {
+        id,
+        body,
+        partition,
+    }
+}
+
 pub(crate) fn get_sharding_depth<R: JsonReducer>(config: &Option<EntryConfig<R>>) -> u32 {
     config.as_ref().map_or(0, |x| x.sharding_depth.unwrap_or(0))
 }
+
+/// Removes the base64 `boc`, `boc1`, `body`, `code`, `data` and `library` fields
+/// together with their deflated forms, the hashes and all the other fields are kept.
+/// The accounts without the cells are still read by [`crate::parse_state`] if they are active.
+pub fn omit_body_fields(doc: &mut Map<String, Value>) {
+    for name in BODY_FIELDS {
+        doc.remove(name);
+        for suffix in ["_deflated", "_length", "_encoding"] {
+            doc.remove(&format!("{}{}", name, suffix));
+        }
+    }
+}
+
+/// Replaces every base64 `boc`, `body`, `code` and `data` field longer than `max_size`
+/// with `{name}_deflated` containing base64 of the deflated bytes,
+/// `{name}_length` with the original length in bytes and `{name}_encoding` set to "deflate".
+/// Use [`crate::PathMap::get_cell_maybe_deflated`] to read such fields back.
+pub fn deflate_boc_fields(doc: &mut Map<String, Value>, max_size: usize) -> Result<()> {
+    for name in DEFLATABLE_FIELDS {
+        let bytes = match doc.get(name) {
+            Some(Value::String(value)) if value.len() > max_size => base64_decode(value)?,
+            _ => continue,
+        };
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&bytes)?;
+        let deflated = encoder.finish()?;
+        doc.remove(name);
+        doc.insert(format!("{}_deflated", name), base64_encode(deflated).into());
+        doc.insert(format!("{}_length", name), bytes.len().into());
+        doc.insert(format!("{}_encoding", name), "deflate".into());
+    }
+    Ok(())
+}
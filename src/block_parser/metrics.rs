@@ -0,0 +1,107 @@
+use crate::{ParsedEntry, ParsedEntrySink, ParserCheckpoint};
+use std::time::Duration;
+use ton_dev_block::Result;
+
+/// Counters of one block parsing, see [`crate::BlockParser::parse_with_metrics`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParseMetrics {
+    pub blocks: u64,
+    pub proofs: u64,
+    pub transactions: u64,
+    pub messages: u64,
+    pub accounts: u64,
+    pub remp_receipts: u64,
+    pub account_summaries: u64,
+    pub out_msg_queue_updates: u64,
+    pub key_block_configs: u64,
+    /// Set only when the timing was requested
+    pub timings: Option<ParseTimings>,
+}
+
+impl ParseMetrics {
+    /// Count of the entries of all kinds
+    pub fn entries(&self) -> u64 {
+        self.blocks
+            + self.proofs
+            + self.transactions
+            + self.messages
+            + self.accounts
+            + self.remp_receipts
+            + self.account_summaries
+            + self.out_msg_queue_updates
+            + self.key_block_configs
+    }
+}
+
+/// Parsing duration split by phase
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParseTimings {
+    /// Reading the block header and the chain order
+    pub deserialize: Duration,
+    /// Walking the account blocks to order the transactions
+    pub walk_transactions: Duration,
+    /// The rest of the parsing, mostly preparing and delivering the entries
+    pub serialize: Duration,
+}
+
+/// Counts the entries before passing them to the sink, the bodies are not serialized
+/// by the parser, so their size is left to the sinks writing them out
+pub(crate) struct MetricsSink<'s, S: ParsedEntrySink> {
+    sink: &'s mut S,
+    pub(crate) metrics: ParseMetrics,
+}
+
+impl<'s, S: ParsedEntrySink> MetricsSink<'s, S> {
+    pub(crate) fn new(sink: &'s mut S) -> Self {
+        Self {
+            sink,
+            metrics: ParseMetrics::default(),
+        }
+    }
+
+    fn count(&mut self, counter: impl FnOnce(&mut ParseMetrics) -> &mut u64) {
+        *counter(&mut self.metrics) += 1;
+    }
+}
+
+impl<'s, S: ParsedEntrySink> ParsedEntrySink for MetricsSink<'s, S> {
+    fn on_block(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.count(|metrics| &mut metrics.blocks);
+        self.sink.on_block(entry)
+    }
+    fn on_proof(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.count(|metrics| &mut metrics.proofs);
+        self.sink.on_proof(entry)
+    }
+    fn on_transaction(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.count(|metrics| &mut metrics.transactions);
+        self.sink.on_transaction(entry)
+    }
+    fn on_message(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.count(|metrics| &mut metrics.messages);
+        self.sink.on_message(entry)
+    }
+    fn on_account(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.count(|metrics| &mut metrics.accounts);
+        self.sink.on_account(entry)
+    }
+    fn on_remp_receipt(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.count(|metrics| &mut metrics.remp_receipts);
+        self.sink.on_remp_receipt(entry)
+    }
+    fn on_account_summary(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.count(|metrics| &mut metrics.account_summaries);
+        self.sink.on_account_summary(entry)
+    }
+    fn on_out_msg_queue_update(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.count(|metrics| &mut metrics.out_msg_queue_updates);
+        self.sink.on_out_msg_queue_update(entry)
+    }
+    fn on_key_block_config(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.count(|metrics| &mut metrics.key_block_configs);
+        self.sink.on_key_block_config(entry)
+    }
+    fn on_checkpoint(&mut self, checkpoint: &ParserCheckpoint) -> Result<()> {
+        self.sink.on_checkpoint(checkpoint)
+    }
+}
@@ -1,16 +1,25 @@
 mod accounts;
+mod batch;
 mod block;
+mod brief;
+mod checkpoint;
 mod entry;
+mod metrics;
 mod parser;
+mod queue_updates;
 mod transactions;
 mod reducers;
+mod summaries;
 
 use serde_json::{Map, Value};
 use std::time::{Duration, SystemTime};
-use ton_dev_block::{error, MsgAddrStd, MsgAddressInt, Result, SliceData, UInt256};
+use ton_dev_block::{error, BlockIdExt, MsgAddrStd, MsgAddressInt, Result, SliceData, UInt256};
 
-pub use block::{ParsedBlock, ParsingBlock};
-pub use entry::ParsedEntry;
+pub use block::{ParsedBlock, ParsedEntrySink, ParsingBlock};
+pub use brief::{brief_block_json, parse_block_header, BriefBlockInfo};
+pub use checkpoint::ParserCheckpoint;
+pub use entry::{deflate_boc_fields, omit_body_fields, ParsedEntry};
+pub use metrics::{ParseMetrics, ParseTimings};
 pub use parser::{BlockParser, BlockParserConfig, EntryConfig};
 pub use reducers::JsonFieldsReducer;
 
@@ -105,6 +114,14 @@ pub(crate) fn is_account_none(hash: &UInt256) -> bool {
     *hash == ACCOUNT_NONE_HASH
 }
 
+/// Adds the location of the source block, the same field names are used for all entry kinds
+pub(crate) fn insert_block_location(doc: &mut Map<String, Value>, block_id: &BlockIdExt) {
+    let (workchain_id, shard) = crate::shard_ident_to_json(block_id.shard());
+    doc.insert("workchain_id".to_owned(), workchain_id.into());
+    doc.insert("shard".to_owned(), shard.into());
+    doc.insert("seq_no".to_owned(), block_id.seq_no().into());
+}
+
 pub(crate) fn is_minter_address(address: &MsgAddressInt) -> bool {
     *address == *MINTER_ADDRESS
 }
@@ -1,22 +1,39 @@
 use crate::block_parser::accounts::{AccountTransition, ParserAccounts};
-use crate::block_parser::block::{ParsedBlock, ParsingBlock};
-use crate::block_parser::entry::{get_sharding_depth, ParsedEntry};
-use crate::block_parser::transactions::ParserTransactions;
+use crate::block_parser::batch::LatestAccountsSink;
+use crate::block_parser::block::{
+    deliver, DocFieldsSink, ParsedBlock, ParsedEntrySink, ParsingBlock,
+};
+use crate::block_parser::checkpoint::{ParserCheckpoint, ResumingSink};
+use crate::block_parser::entry::{get_sharding_depth, raw_entry, ParsedEntry};
+use crate::block_parser::metrics::{MetricsSink, ParseMetrics, ParseTimings};
+use crate::block_parser::queue_updates::ParserOutQueueUpdates;
+use crate::block_parser::summaries::ParserAccountSummaries;
+use crate::block_parser::transactions::{MessageDirections, ParserTransactions};
 use crate::block_parser::{
-    is_account_none, unix_time_to_system_time, ParserTraceEvent, ParserTracer,
+    insert_block_location, is_account_none, unix_time_to_system_time, ParserTraceEvent,
+    ParserTracer,
 };
 use crate::JsonReducer;
-use serde_json::Value;
-use std::collections::BTreeMap;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
 use ton_dev_block::{
     Account, AccountBlock, AccountStatus, BlockIdExt, BlockProcessingStatus, BlockProof,
-    Deserializable, HashmapAugType, Transaction,
+    Deserializable, HashmapAugType, Serializable, Transaction,
 };
 use ton_dev_block::{fail, HashmapType, Result, SliceData, UInt256};
+#[cfg(feature = "remp")]
+use {
+    ton_api::ton::ton_node::{rempmessagestatus, rempreceipt, RempMessageLevel, RempMessageStatus},
+    ton_api::IntoBoxed,
+    ton_dev_block::Message,
+};
 
 pub struct EntryConfig<R: JsonReducer> {
     pub sharding_depth: Option<u32>,
     pub reducer: Option<R>,
+    /// Base64 `boc`, `body`, `code` and `data` fields longer than this are deflated,
+    /// see [`crate::deflate_boc_fields`]
+    pub max_boc_field_size: Option<usize>,
 }
 
 pub struct BlockParserConfig<R: JsonReducer> {
@@ -25,9 +42,66 @@ pub struct BlockParserConfig<R: JsonReducer> {
     pub accounts: Option<EntryConfig<R>>,
     pub transactions: Option<EntryConfig<R>>,
     pub messages: Option<EntryConfig<R>>,
+    /// One entry per account with transactions in the block: transaction count, gas used,
+    /// total fees and the lt range, sharded by the account address
+    pub account_summaries: Option<EntryConfig<R>>,
+    /// One entry per message put into or removed from the outbound message queue by the block
+    /// (`kind` is `enqueue` or `dequeue`), sharded by the destination prefix
+    pub out_msg_queue_updates: Option<EntryConfig<R>>,
+    /// One entry per key block with the full config as of the block and `config_addr`,
+    /// the entry id is the block seq_no
+    pub key_block_configs: Option<EntryConfig<R>>,
 
     pub max_account_bytes_size: Option<usize>,
     pub is_node_se: bool,
+    /// Attach merkle proof of inclusion into the block to every transaction entry
+    /// (all entries get proofs anyway when parsing `with_proofs`)
+    pub transaction_proofs: bool,
+    /// Emit the `IncludedIntoBlock` REMP receipt for every external inbound message,
+    /// the receipts are read by [`crate::parse_remp_status`]
+    pub remp_receipts: bool,
+    /// Block, proof, transaction, message and account entries carry only `id`, `block_id`,
+    /// `kind` and the base64 `boc` of the cell, their serializers and reducers are skipped.
+    /// The partitions are the same as in the full mode.
+    pub raw: bool,
+    /// Name of the id field of all the entries, `ParsedEntry::id` is the same for all names
+    pub doc_id_field: crate::DocIdField,
+    /// Remove the cells (`boc`, `body`, `code`, `data`, etc.) from all the entries keeping
+    /// only their hashes and the other fields, see [`crate::omit_body_fields`].
+    /// The proofs are kept, can't be combined with `raw`.
+    pub omit_bodies: bool,
+    /// Masterchain account ids of the special accounts, usually the p31 fundamental smc
+    /// addresses with the config and elector ones. When given, the transaction entries get
+    /// `is_special`, it is false for the workchain transactions
+    pub special_accounts: Option<HashSet<UInt256>>,
+    /// Add `msg_hash_normalized` to the external inbound message entries,
+    /// see [`crate::normalized_message_hash`]
+    pub normalized_msg_hashes: bool,
+}
+
+// no entries are produced by default, `R` doesn't have to implement `Default`
+impl<R: JsonReducer> Default for BlockParserConfig<R> {
+    fn default() -> Self {
+        Self {
+            blocks: None,
+            proofs: None,
+            accounts: None,
+            transactions: None,
+            messages: None,
+            account_summaries: None,
+            out_msg_queue_updates: None,
+            key_block_configs: None,
+            max_account_bytes_size: None,
+            is_node_se: false,
+            transaction_proofs: false,
+            remp_receipts: false,
+            raw: false,
+            doc_id_field: crate::DocIdField::default(),
+            omit_bodies: false,
+            special_accounts: None,
+            normalized_msg_hashes: false,
+        }
+    }
 }
 
 pub struct BlockParser<T: ParserTracer, R: JsonReducer> {
@@ -47,12 +121,90 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
     }
 
     pub fn parse(&self, block: ParsingBlock, with_proofs: bool) -> Result<ParsedBlock> {
-        if self.config.accounts.is_some() && block.shard_state.is_none() {
-            fail!("Shard state should be specified because the block parser was configured with account parsing.");
+        let mut result = ParsedBlock::default();
+        self.parse_into(block, with_proofs, &mut result)?;
+        Ok(result)
+    }
+
+    /// Same as `parse` but also returns the entry counters,
+    /// the phase durations are measured only if `with_timings` is set
+    pub fn parse_with_metrics(
+        &self,
+        block: ParsingBlock,
+        with_proofs: bool,
+        with_timings: bool,
+    ) -> Result<(ParsedBlock, ParseMetrics)> {
+        let mut result = ParsedBlock::default();
+        let metrics = self.parse_into_with_metrics(block, with_proofs, with_timings, &mut result)?;
+        Ok((result, metrics))
+    }
+
+    /// Parses the block passing every entry to the sink right after it is prepared
+    pub fn parse_into<S: ParsedEntrySink>(
+        &self,
+        block: ParsingBlock,
+        with_proofs: bool,
+        sink: &mut S,
+    ) -> Result<()> {
+        self.parse_timed(block, with_proofs, sink, &mut None)
+    }
+
+    /// Same as `parse_into` but also returns the counters of the entries passed to the sink
+    pub fn parse_into_with_metrics<S: ParsedEntrySink>(
+        &self,
+        block: ParsingBlock,
+        with_proofs: bool,
+        with_timings: bool,
+        sink: &mut S,
+    ) -> Result<ParseMetrics> {
+        let mut timings = with_timings.then(ParseTimings::default);
+        let mut sink = MetricsSink::new(sink);
+        self.parse_timed(block, with_proofs, &mut sink, &mut timings)?;
+        Ok(ParseMetrics {
+            timings,
+            ..sink.metrics
+        })
+    }
+
+    fn parse_timed<S: ParsedEntrySink>(
+        &self,
+        block: ParsingBlock,
+        with_proofs: bool,
+        sink: &mut S,
+        timings: &mut Option<ParseTimings>,
+    ) -> Result<()> {
+        match (self.config.doc_id_field, self.config.omit_bodies) {
+            (crate::DocIdField::Id, false) => self.parse_entries(block, with_proofs, sink, timings),
+            (field, omit_bodies) => self.parse_entries(
+                block,
+                with_proofs,
+                &mut DocFieldsSink::new(field, omit_bodies, sink),
+                timings,
+            ),
+        }
+    }
+
+    fn parse_entries<S: ParsedEntrySink>(
+        &self,
+        block: ParsingBlock,
+        with_proofs: bool,
+        sink: &mut S,
+        timings: &mut Option<ParseTimings>,
+    ) -> Result<()> {
+        if self.config.accounts.is_some() && block.shard_state.is_none() && block.shard_accounts.is_none() {
+            fail!("Shard state or shard accounts should be specified because the block parser was configured with account parsing.");
+        }
+        if self.config.raw && self.config.omit_bodies {
+            fail!("The block parser was configured with both raw entries and omitted bodies, raw entries have nothing but the boc.");
+        }
+        #[cfg(not(feature = "remp"))]
+        if self.config.remp_receipts {
+            fail!("The block parser was configured with REMP receipts but the `remp` feature is disabled.");
         }
         let now = std::time::Instant::now();
+        let started = now;
 
-        let block_id_str = block.id.root_hash().as_hex_string();
+        let block_id_str = crate::block_doc_id(block.id);
 
         let block_order = if let Some(mc_seq_no) = block.mc_seq_no {
             Some(crate::block_order(block.block, mc_seq_no)?)
@@ -76,14 +228,22 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
             now.elapsed().as_millis(),
             block_id_str
         );
-
-        let mut result = ParsedBlock::default();
+        if let Some(timings) = timings.as_mut() {
+            timings.deserialize = now.elapsed();
+        }
 
         let include_accounts = self.config.accounts.is_some();
         let include_transactions = self.config.transactions.is_some();
         let include_messages = self.config.messages.is_some();
-
-        if include_accounts || include_transactions || include_messages {
+        let include_remp_receipts = self.config.remp_receipts;
+        let include_summaries = self.config.account_summaries.is_some();
+
+        if include_accounts
+            || include_transactions
+            || include_messages
+            || include_remp_receipts
+            || include_summaries
+        {
             // Transactions and messages
 
             let now = std::time::Instant::now();
@@ -92,23 +252,25 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
             let mut block_transactions = BTreeMap::new();
 
             let mut accounts = ParserAccounts::new(&self.config, &block)?;
-            let transactions =
-                ParserTransactions::new(&self.config, &self.tracer, &block, with_proofs);
+            let mut summaries = ParserAccountSummaries::new(&self.config, block.id);
+            let transactions = ParserTransactions::new(
+                &self.config,
+                &self.tracer,
+                &block,
+                with_proofs,
+                ut.as_u32(),
+            );
             let mut tr_count = 0;
 
             let block_extra = block.block.read_extra()?;
+            let directions = if include_messages {
+                MessageDirections::read(&block_extra)?
+            } else {
+                MessageDirections::default()
+            };
             block_extra.read_account_blocks()?.iterate_objects(
                 &mut |account_block: AccountBlock| {
                     let state_upd = account_block.read_state_update()?;
-                    let (mut transition, check_existed) = if is_account_none(&state_upd.new_hash) {
-                        (
-                            AccountTransition::Deleted,
-                            self.config.is_node_se && is_account_none(&state_upd.old_hash),
-                        )
-                    } else {
-                        (AccountTransition::Changed, false)
-                    };
-
                     let mut account_existed = false;
 
                     account_block.transactions().iterate_slices(
@@ -129,10 +291,12 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
                             Ok(true)
                         },
                     )?;
-                    if check_existed && !account_existed {
-                        transition = AccountTransition::None;
-                    }
                     if include_accounts {
+                        let transition = account_transition(
+                            &state_upd.old_hash,
+                            &state_upd.new_hash,
+                            account_existed,
+                        );
                         accounts.set_transition(account_block.account_id(), transition)?;
                     }
 
@@ -143,6 +307,9 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
                 "TIME: prepare transactions order {}ms",
                 now.elapsed().as_millis()
             );
+            if let Some(timings) = timings.as_mut() {
+                timings.walk_transactions = now.elapsed();
+            }
             let now = std::time::Instant::now();
 
             let mut prepared_messages = Default::default();
@@ -153,10 +320,38 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
 
                 let account_id = transaction.account_id().clone();
                 if include_accounts {
-                    accounts.set_last_transaction(&account_id, &transaction, &transaction_order)?;
+                    accounts.set_last_transaction(
+                        &account_id,
+                        &transaction,
+                        &cell.repr_hash(),
+                        &transaction_order,
+                    )?;
+                }
+
+                // the description is read once for the summary and the transaction entry
+                let descr = if include_summaries || include_transactions {
+                    Some(transaction.read_description()?)
+                } else {
+                    None
+                };
+                if let Some(descr) = descr.as_ref().filter(|_| include_summaries) {
+                    summaries.add_transaction(&transaction, descr);
                 }
 
-                let code_hash = accounts.get_code_hash(&account_id)?;
+                #[cfg(feature = "remp")]
+                if include_remp_receipts {
+                    if let Some(message_id) = external_in_message_id(&transaction)? {
+                        let entry = remp_receipt_entry(block.id, &message_id, ut.as_u32())?;
+                        deliver("remp receipt", entry, |entry| sink.on_remp_receipt(entry))?;
+                    }
+                }
+
+                // raw entries have no code hashes, so the accounts are not read for them
+                let code_hash = if self.config.raw {
+                    None
+                } else {
+                    accounts.get_code_hash(&account_id)?
+                };
                 if include_messages {
                     transactions.parse_messages_from_transaction(
                         &transaction,
@@ -167,16 +362,16 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
                     )?;
                 }
 
-                if include_transactions {
-                    result
-                        .transactions
-                        .push(transactions.prepare_transaction_entry(
-                            cell,
-                            transaction,
-                            workchain_id,
-                            transaction_order,
-                            &code_hash,
-                        )?);
+                if let Some(descr) = descr.filter(|_| include_transactions) {
+                    let entry = transactions.prepare_transaction_entry(
+                        cell,
+                        transaction,
+                        &descr,
+                        workchain_id,
+                        transaction_order,
+                        &code_hash,
+                    )?;
+                    deliver("transaction", entry, |entry| sink.on_transaction(entry))?;
                 }
             }
             log::debug!(
@@ -185,11 +380,30 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
             );
 
             if include_messages {
-                result.messages = transactions.finish_prepared_messages(prepared_messages)?;
+                transactions.parse_transit_messages(&directions, &mut prepared_messages)?;
+                let entries =
+                    transactions.finish_prepared_messages(prepared_messages, &directions)?;
+                for entry in entries {
+                    deliver("message", entry, |entry| sink.on_message(entry))?;
+                }
             }
 
             if include_accounts {
-                accounts.insert_entries(&mut result)?;
+                accounts.insert_entries(sink)?;
+            }
+
+            if include_summaries {
+                summaries.insert_entries(sink)?;
+            }
+        }
+
+        if self.config.out_msg_queue_updates.is_some() {
+            ParserOutQueueUpdates::new(&self.config, block.id).insert_entries(block.block, sink)?;
+        }
+
+        if self.config.key_block_configs.is_some() && block_info.key_block() {
+            if let Some(entry) = self.prepare_key_block_config_entry(&block)? {
+                deliver("key block config", entry, |entry| sink.on_key_block_config(entry))?;
             }
         }
 
@@ -198,7 +412,8 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
         // Block
 
         if self.config.blocks.is_some() {
-            result.block = Some(self.prepare_block_entry(&block, &block_order)?);
+            let entry = self.prepare_block_entry(&block, &block_order)?;
+            deliver("block", entry, |entry| sink.on_block(entry))?;
         }
 
         log::debug!(
@@ -210,7 +425,8 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
         if self.config.proofs.is_some() {
             if let Some(proof) = block.proof {
                 let now = std::time::Instant::now();
-                result.proof = Some(self.prepare_block_proof_entry(&block, proof, &block_order)?);
+                let entry = self.prepare_block_proof_entry(&block, proof, &block_order)?;
+                deliver("proof", entry, |entry| sink.on_proof(entry))?;
                 log::trace!(
                     "TIME: block proof {}ms;   {}",
                     now.elapsed().as_millis(),
@@ -219,7 +435,45 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
             }
         }
 
-        Ok(result)
+        if let Some(timings) = timings.as_mut() {
+            timings.serialize = started
+                .elapsed()
+                .saturating_sub(timings.deserialize + timings.walk_transactions);
+        }
+
+        Ok(())
+    }
+
+    /// Parses the consecutive blocks one by one like `parse_into` but the account entries
+    /// are kept until all the blocks are parsed: every account touched by the batch is
+    /// delivered once at the end with its latest state and `last_block_id` of the last block
+    /// touching it. Only the account entries are kept in memory, one per distinct account.
+    pub fn parse_batch_into<'b, S: ParsedEntrySink>(
+        &self,
+        blocks: impl IntoIterator<Item = ParsingBlock<'b>>,
+        with_proofs: bool,
+        sink: &mut S,
+    ) -> Result<()> {
+        let mut sink = LatestAccountsSink::new(sink);
+        for block in blocks {
+            sink.start_block(block.id);
+            self.parse_into(block, with_proofs, &mut sink)?;
+        }
+        sink.finish()
+    }
+
+    /// Same as `parse_into` but skips the entries delivered before the checkpoint
+    /// and passes the new checkpoint to [`ParsedEntrySink::on_checkpoint`] after every entry.
+    /// The parser configuration must be the same as the one the checkpoint was taken with.
+    pub fn resume_into<S: ParsedEntrySink>(
+        &self,
+        block: ParsingBlock,
+        with_proofs: bool,
+        checkpoint: Option<&ParserCheckpoint>,
+        sink: &mut S,
+    ) -> Result<()> {
+        let mut sink = ResumingSink::new(block.id, checkpoint, sink)?;
+        self.parse_into(block, with_proofs, &mut sink)
     }
 
     fn prepare_block_entry(
@@ -227,6 +481,11 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
         block: &ParsingBlock,
         block_order: &Option<String>,
     ) -> Result<ParsedEntry> {
+        if self.config.raw {
+            let partition = get_block_partition(self.block_sharding_depth, block.id);
+            let id = crate::block_doc_id(block.id);
+            return Ok(raw_entry("block", id, block.id, block.data, partition));
+        }
         let set = crate::BlockSerializationSetFH {
             block: block.block,
             id: block.id.root_hash(),
@@ -239,6 +498,7 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
         if let Some(block_order) = block_order {
             doc.insert("chain_order".to_owned(), Value::String(block_order.clone()));
         }
+        insert_block_location(&mut doc, block.id);
         let partition = get_block_partition(self.block_sharding_depth, block.id);
         ParsedEntry::reduced(doc, partition, &self.config.blocks)
     }
@@ -250,13 +510,39 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
         block_order: &Option<String>,
     ) -> Result<ParsedEntry> {
         let partition = get_block_partition(self.block_sharding_depth, block.id);
+        if self.config.raw {
+            let id = crate::block_doc_id(block.id);
+            let boc = proof.write_to_bytes()?;
+            return Ok(raw_entry("proof", id, block.id, &boc, partition));
+        }
         let mut doc = crate::db_serialize_block_proof("id", proof)?;
         if let Some(chain_order) = block_order {
             doc.insert("chain_order".to_owned(), Value::String(chain_order.clone()));
         }
+        insert_block_location(&mut doc, block.id);
         ParsedEntry::reduced(doc, partition, &self.config.proofs)
     }
 
+    fn prepare_key_block_config_entry(&self, block: &ParsingBlock) -> Result<Option<ParsedEntry>> {
+        let config = match block.block.read_extra()?.read_custom()? {
+            Some(master) => match master.config() {
+                Some(config) => config.clone(),
+                None => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+        let mut doc = Map::new();
+        doc.insert("id".to_owned(), block.id.seq_no().to_string().into());
+        doc.insert("block_id".to_owned(), crate::block_doc_id(block.id).into());
+        crate::serialize_config(&mut doc, &config, crate::SerializationMode::Standart)?;
+        insert_block_location(&mut doc, block.id);
+        let partition = get_block_partition(
+            get_sharding_depth(&self.config.key_block_configs),
+            block.id,
+        );
+        ParsedEntry::reduced(doc, partition, &self.config.key_block_configs).map(Some)
+    }
+
     pub fn prepare_account_entry(
         &self,
         account: Account,
@@ -267,6 +553,8 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
             account,
             prev_code_hash,
             last_trans_chain_order,
+            None,
+            None,
             self.config.max_account_bytes_size,
             get_sharding_depth(&self.config.accounts),
             &self.config.accounts,
@@ -274,6 +562,56 @@ impl<T: ParserTracer, R: JsonReducer> BlockParser<T, R> {
     }
 }
 
+#[cfg(feature = "remp")]
+fn external_in_message_id(transaction: &Transaction) -> Result<Option<UInt256>> {
+    match transaction.in_msg_cell() {
+        Some(cell) if Message::construct_from_cell(cell.clone())?.is_inbound_external() => {
+            Ok(Some(cell.repr_hash()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Receipt of the collator level acceptance, the block parser has no key
+/// so the signature is empty and `source_id` is zero
+#[cfg(feature = "remp")]
+fn remp_receipt_entry(
+    block_id: &BlockIdExt,
+    message_id: &UInt256,
+    gen_utime: u32,
+) -> Result<ParsedEntry> {
+    let receipt = rempreceipt::RempReceipt {
+        message_id: message_id.clone(),
+        status: RempMessageStatus::TonNode_RempAccepted(rempmessagestatus::RempAccepted {
+            level: RempMessageLevel::TonNode_RempCollator,
+            block_id: block_id.clone(),
+            master_id: BlockIdExt::default(),
+        }),
+        timestamp: gen_utime as i64,
+        source_id: UInt256::default(),
+    }
+    .into_boxed();
+    let mut doc = Map::new();
+    doc.insert("id".to_owned(), crate::hash_doc_id(message_id).into());
+    doc.extend(crate::db_serialize_remp_status(&receipt, &[])?);
+    ParsedEntry::new(doc, None)
+}
+
+fn account_transition(
+    old_hash: &UInt256,
+    new_hash: &UInt256,
+    account_existed: bool,
+) -> AccountTransition {
+    if !is_account_none(new_hash) {
+        AccountTransition::Changed
+    } else if is_account_none(old_hash) && !account_existed {
+        // account did not exist before and after the block (e.g. it only bounced a message)
+        AccountTransition::None
+    } else {
+        AccountTransition::Deleted
+    }
+}
+
 fn get_block_partition(sharding_depth: u32, block_id: &BlockIdExt) -> Option<u32> {
     if sharding_depth > 0 {
         let partitioning_info =
@@ -0,0 +1,164 @@
+use crate::block_parser::block::deliver;
+use crate::block_parser::entry::get_sharding_depth;
+use crate::block_parser::insert_block_location;
+use crate::{BlockParserConfig, EntryConfig, JsonReducer, ParsedEntry, ParsedEntrySink};
+use serde_json::Map;
+use ton_dev_block::{
+    Block, BlockIdExt, HashmapAugType, MsgEnvelope, OutMsg, Result, Serializable, UInt256,
+};
+
+/// Changes of the outbound message queue made by the block: the messages put into the queue
+/// (new and transit ones) and the messages removed from it, read from the out msg descr
+pub(crate) struct ParserOutQueueUpdates<'a, R: JsonReducer> {
+    block_id: &'a BlockIdExt,
+    config: &'a Option<EntryConfig<R>>,
+    sharding_depth: u32,
+}
+
+struct QueueUpdate {
+    kind: &'static str,
+    out_msg_type: &'static str,
+    msg_env_hash: UInt256,
+    msg_id: Option<UInt256>,
+    dst_workchain: i32,
+    dst_prefix: u64,
+    lt: u64,
+    import_block_lt: Option<u64>,
+}
+
+impl QueueUpdate {
+    fn with_envelope(
+        kind: &'static str,
+        out_msg_type: &'static str,
+        env: MsgEnvelope,
+        import_block_lt: Option<u64>,
+    ) -> Result<Self> {
+        let (_, next_prefix) = env.calc_cur_next_prefix()?;
+        Ok(Self {
+            kind,
+            out_msg_type,
+            msg_env_hash: env.serialize()?.repr_hash(),
+            msg_id: Some(env.message_cell().repr_hash()),
+            dst_workchain: next_prefix.workchain_id,
+            dst_prefix: next_prefix.prefix,
+            lt: env.read_message()?.lt().unwrap_or_default(),
+            import_block_lt,
+        })
+    }
+}
+
+impl<'a, R: JsonReducer> ParserOutQueueUpdates<'a, R> {
+    pub(crate) fn new(config: &'a BlockParserConfig<R>, block_id: &'a BlockIdExt) -> Self {
+        Self {
+            block_id,
+            config: &config.out_msg_queue_updates,
+            sharding_depth: get_sharding_depth(&config.out_msg_queue_updates),
+        }
+    }
+
+    /// The blocks without the out msg descr section, e.g. given by the proofs
+    /// with the extra pruned, emit nothing
+    pub(crate) fn insert_entries(&self, block: &Block, sink: &mut impl ParsedEntrySink) -> Result<()> {
+        if block.extra_cell().is_pruned() {
+            return Ok(());
+        }
+        let extra = block.read_extra()?;
+        if extra.out_msg_descr_cell().is_pruned() {
+            return Ok(());
+        }
+        extra.read_out_msg_descr()?.iterate_objects(|out_msg| {
+            if let Some(update) = read_queue_update(&out_msg)? {
+                let entry = self.prepare_entry(update)?;
+                deliver("out msg queue update", entry, |entry| {
+                    sink.on_out_msg_queue_update(entry)
+                })?;
+            }
+            Ok(true)
+        })?;
+        Ok(())
+    }
+
+    fn prepare_entry(&self, update: QueueUpdate) -> Result<ParsedEntry> {
+        let block_id = crate::block_doc_id(self.block_id);
+        let mut doc = Map::new();
+        doc.insert(
+            "id".to_owned(),
+            format!("{}:{}", block_id, update.msg_env_hash.as_hex_string()).into(),
+        );
+        doc.insert("block_id".to_owned(), block_id.into());
+        doc.insert("kind".to_owned(), update.kind.into());
+        doc.insert("out_msg_type".to_owned(), update.out_msg_type.into());
+        doc.insert(
+            "msg_env_hash".to_owned(),
+            update.msg_env_hash.as_hex_string().into(),
+        );
+        if let Some(msg_id) = update.msg_id {
+            doc.insert("msg_id".to_owned(), crate::hash_doc_id(&msg_id).into());
+        }
+        doc.insert("dst_workchain".to_owned(), update.dst_workchain.into());
+        doc.insert(
+            "dst_prefix".to_owned(),
+            crate::shard_to_string(update.dst_prefix).into(),
+        );
+        doc.insert("lt".to_owned(), crate::u64_to_string(update.lt).into());
+        if let Some(import_block_lt) = update.import_block_lt {
+            doc.insert(
+                "import_block_lt".to_owned(),
+                crate::u64_to_string(import_block_lt).into(),
+            );
+        }
+        insert_block_location(&mut doc, self.block_id);
+
+        // sharded by the destination like the queue itself
+        let partition = if self.sharding_depth > 0 {
+            Some((update.dst_prefix >> (64 - self.sharding_depth)) as u32)
+        } else {
+            None
+        };
+        ParsedEntry::reduced(doc, partition, self.config)
+    }
+}
+
+/// External and immediately processed messages don't touch the queue, so they give None.
+/// The `lt` is the message creation lt, `dequeueShort` keeps no message so its import block lt
+/// is used instead.
+fn read_queue_update(out_msg: &OutMsg) -> Result<Option<QueueUpdate>> {
+    let update = match out_msg {
+        OutMsg::New(msg) => {
+            QueueUpdate::with_envelope("enqueue", "outMsgNew", msg.read_out_message()?, None)?
+        }
+        OutMsg::Transit(msg) => {
+            QueueUpdate::with_envelope("enqueue", "transit", msg.read_out_message()?, None)?
+        }
+        OutMsg::TransitRequeued(msg) => QueueUpdate::with_envelope(
+            "enqueue",
+            "transitRequeued",
+            msg.read_out_message()?,
+            None,
+        )?,
+        OutMsg::Dequeue(msg) => QueueUpdate::with_envelope(
+            "dequeue",
+            "dequeue",
+            msg.read_out_message()?,
+            Some(msg.import_block_lt()),
+        )?,
+        OutMsg::DequeueImmediate(msg) => QueueUpdate::with_envelope(
+            "dequeue",
+            "dequeueImmediately",
+            msg.read_out_message()?,
+            None,
+        )?,
+        OutMsg::DequeueShort(msg) => QueueUpdate {
+            kind: "dequeue",
+            out_msg_type: "dequeueShort",
+            msg_env_hash: msg.msg_env_hash.clone(),
+            msg_id: None,
+            dst_workchain: msg.next_workchain,
+            dst_prefix: msg.next_addr_pfx,
+            lt: msg.import_block_lt,
+            import_block_lt: Some(msg.import_block_lt),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(update))
+}
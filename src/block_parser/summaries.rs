@@ -0,0 +1,94 @@
+use crate::block_parser::block::deliver;
+use crate::block_parser::entry::get_sharding_depth;
+use crate::block_parser::{get_partition, insert_block_location};
+use crate::{BlockParserConfig, EntryConfig, JsonReducer, ParsedEntry, ParsedEntrySink};
+use num::BigInt;
+use serde_json::Map;
+use std::collections::BTreeMap;
+use ton_dev_block::{AccountId, BlockIdExt, Result, TrComputePhase, Transaction, TransactionDescr};
+
+#[derive(Default)]
+struct AccountSummary {
+    tr_count: u32,
+    gas_used: u64,
+    total_fees: u128,
+    min_lt: u64,
+    max_lt: u64,
+}
+
+/// Totals of the account transactions in the block,
+/// collected while the transactions are walked for the other entries
+pub(crate) struct ParserAccountSummaries<'a, R: JsonReducer> {
+    block_id: &'a BlockIdExt,
+    config: &'a Option<EntryConfig<R>>,
+    sharding_depth: u32,
+    summaries: BTreeMap<AccountId, AccountSummary>,
+}
+
+impl<'a, R: JsonReducer> ParserAccountSummaries<'a, R> {
+    pub(crate) fn new(config: &'a BlockParserConfig<R>, block_id: &'a BlockIdExt) -> Self {
+        Self {
+            block_id,
+            config: &config.account_summaries,
+            sharding_depth: get_sharding_depth(&config.account_summaries),
+            summaries: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn add_transaction(&mut self, transaction: &Transaction, descr: &TransactionDescr) {
+        let lt = transaction.logical_time();
+        let summary = self
+            .summaries
+            .entry(transaction.account_id().clone())
+            .or_insert_with(|| AccountSummary {
+                min_lt: lt,
+                max_lt: lt,
+                ..Default::default()
+            });
+        summary.tr_count += 1;
+        summary.gas_used += compute_gas_used(descr);
+        summary.total_fees += transaction.total_fees().grams.as_u128();
+        summary.min_lt = summary.min_lt.min(lt);
+        summary.max_lt = summary.max_lt.max(lt);
+    }
+
+    pub(crate) fn insert_entries(self, sink: &mut impl ParsedEntrySink) -> Result<()> {
+        let workchain_id = self.block_id.shard().workchain_id();
+        for (account_id, summary) in self.summaries {
+            let mut doc = Map::new();
+            let address = crate::construct_address(workchain_id, account_id.clone())?;
+            doc.insert("id".to_owned(), crate::account_doc_id(&address).into());
+            doc.insert("block_id".to_owned(), crate::block_doc_id(self.block_id).into());
+            doc.insert("tr_count".to_owned(), summary.tr_count.into());
+            doc.insert("gas_used".to_owned(), crate::u64_to_string(summary.gas_used).into());
+            doc.insert("gas_used_dec".to_owned(), summary.gas_used.to_string().into());
+            doc.insert(
+                "total_fees".to_owned(),
+                crate::bigint_to_string(&BigInt::from(summary.total_fees)).into(),
+            );
+            doc.insert("total_fees_dec".to_owned(), summary.total_fees.to_string().into());
+            doc.insert("min_lt".to_owned(), crate::u64_to_string(summary.min_lt).into());
+            doc.insert("max_lt".to_owned(), crate::u64_to_string(summary.max_lt).into());
+            insert_block_location(&mut doc, self.block_id);
+
+            let partition = get_partition(self.sharding_depth, account_id)?;
+            let entry = ParsedEntry::reduced(doc, partition, self.config)?;
+            deliver("account summary", entry, |entry| sink.on_account_summary(entry))?;
+        }
+        Ok(())
+    }
+}
+
+fn compute_gas_used(descr: &TransactionDescr) -> u64 {
+    let compute_ph = match descr {
+        TransactionDescr::Ordinary(tr) => &tr.compute_ph,
+        TransactionDescr::TickTock(tr) => &tr.compute_ph,
+        TransactionDescr::SplitPrepare(tr) => &tr.compute_ph,
+        TransactionDescr::MergeInstall(tr) => &tr.compute_ph,
+        _ => return 0,
+    };
+    match compute_ph {
+        TrComputePhase::Vm(vm) => vm.gas_used.as_u64(),
+        TrComputePhase::Skipped(_) => 0,
+    }
+}
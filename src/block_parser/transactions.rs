@@ -1,15 +1,16 @@
-use crate::block_parser::entry::get_sharding_depth;
-use crate::block_parser::{get_partition, is_minter_address};
+use crate::block_parser::entry::{get_sharding_depth, raw_entry};
+use crate::block_parser::{get_partition, insert_block_location, is_minter_address};
 use crate::{
     BlockParserConfig, BlockParsingError, EntryConfig, JsonReducer, ParsedEntry, ParserTraceEvent,
     ParserTracer, ParsingBlock,
 };
 use serde_json::{Map, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::time::SystemTime;
 use ton_dev_block::{
-    CommonMsgInfo, Deserializable, Message, MessageProcessingStatus, MsgAddressExt, Transaction,
-    TransactionProcessingStatus,
+    BlockExtra, CommonMsgInfo, Deserializable, HashmapAugType, InMsg, Message,
+    MessageProcessingStatus, MsgAddressExt, OutMsg, Transaction, TransactionDescr,
+    TransactionProcessingStatus, TransactionTickTock, MASTERCHAIN_ID,
 };
 use ton_dev_block::{write_boc, Cell, Result, SliceData, UInt256};
 
@@ -49,7 +50,7 @@ impl PreparedMessage {
     ) {
         self.doc.insert(
             fields.transaction_id.to_owned(),
-            tr_id.as_hex_string().into(),
+            crate::hash_doc_id(tr_id).into(),
         );
         if let Some(tr_chain_order) = tr_chain_order {
             self.doc.insert(
@@ -64,6 +65,46 @@ impl PreparedMessage {
     }
 }
 
+/// Direction of the messages by the descriptor of the block they appear in: `in` for the
+/// in msg descr, `out` for the out msg descr and `transit` for the transit ones. Messages
+/// created and consumed in the block are listed in both descriptors and are `in`.
+#[derive(Default)]
+pub(crate) struct MessageDirections {
+    directions: HashMap<UInt256, &'static str>,
+    transit: Vec<Cell>,
+}
+
+impl MessageDirections {
+    pub(crate) fn read(extra: &BlockExtra) -> Result<Self> {
+        let mut directions = Self::default();
+        extra.read_in_msg_descr()?.iterate_objects(|in_msg| {
+            let cell = in_msg.message_cell()?;
+            let direction = match in_msg {
+                InMsg::Transit(_) | InMsg::DiscardedTransit(_) => {
+                    directions.transit.push(cell.clone());
+                    "transit"
+                }
+                _ => "in",
+            };
+            directions.directions.insert(cell.repr_hash(), direction);
+            Ok(true)
+        })?;
+        extra.read_out_msg_descr()?.iterate_objects(|out_msg| {
+            if let OutMsg::External(_) | OutMsg::New(_) | OutMsg::Immediate(_) = out_msg {
+                if let Some(cell) = out_msg.message_cell()? {
+                    directions.directions.entry(cell.repr_hash()).or_insert("out");
+                }
+            }
+            Ok(true)
+        })?;
+        Ok(directions)
+    }
+
+    pub(crate) fn get(&self, message_id: &UInt256) -> Option<&'static str> {
+        self.directions.get(message_id).copied()
+    }
+}
+
 pub(crate) struct ParserTransactions<'a, T: ParserTracer, R: JsonReducer> {
     parsing: &'a ParsingBlock<'a>,
     transactions_config: &'a Option<EntryConfig<R>>,
@@ -71,7 +112,12 @@ pub(crate) struct ParserTransactions<'a, T: ParserTracer, R: JsonReducer> {
     transactions_sharding_depth: u32,
     messages_sharding_depth: u32,
     with_proofs: bool,
+    with_transaction_proofs: bool,
     tracer: &'a Option<T>,
+    block_time: u32,
+    raw: bool,
+    special_accounts: &'a Option<HashSet<UInt256>>,
+    normalized_msg_hashes: bool,
 }
 
 impl<'a, T: ParserTracer, R: JsonReducer> ParserTransactions<'a, T, R> {
@@ -80,6 +126,7 @@ impl<'a, T: ParserTracer, R: JsonReducer> ParserTransactions<'a, T, R> {
         tracer: &'a Option<T>,
         parsing: &'a ParsingBlock,
         with_proofs: bool,
+        block_time: u32,
     ) -> Self {
         Self {
             parsing,
@@ -88,7 +135,12 @@ impl<'a, T: ParserTracer, R: JsonReducer> ParserTransactions<'a, T, R> {
             transactions_sharding_depth: get_sharding_depth(&config.transactions),
             messages_sharding_depth: get_sharding_depth(&config.messages),
             with_proofs,
+            with_transaction_proofs: with_proofs || config.transaction_proofs,
             tracer,
+            block_time,
+            raw: config.raw,
+            special_accounts: &config.special_accounts,
+            normalized_msg_hashes: config.normalized_msg_hashes,
         }
     }
 
@@ -98,8 +150,12 @@ impl<'a, T: ParserTracer, R: JsonReducer> ParserTransactions<'a, T, R> {
         transaction_id: UInt256,
         transaction_order: Option<&str>,
         code_hash: &Option<String>,
-        prepared_messages: &mut HashMap<UInt256, PreparedMessage>,
+        prepared_messages: &mut BTreeMap<UInt256, PreparedMessage>,
     ) -> Result<()> {
+        if self.raw {
+            return self.parse_raw_messages_from_transaction(transaction, prepared_messages);
+        }
+
         let now = std::time::Instant::now();
 
         if let Some(message_cell) = transaction.in_msg_cell() {
@@ -126,7 +182,8 @@ impl<'a, T: ParserTracer, R: JsonReducer> ParserTransactions<'a, T, R> {
                     prepared
                 } else {
                     let mut doc = Map::with_capacity(4);
-                    doc.insert("id".to_owned(), message_id.as_hex_string().into());
+                    doc.insert("id".to_owned(), crate::hash_doc_id(&message_id).into());
+                    doc.insert("msg_type_name".to_owned(), "internal".into());
                     PreparedMessage {
                         src_partition,
                         dst_partition,
@@ -141,6 +198,11 @@ impl<'a, T: ParserTracer, R: JsonReducer> ParserTransactions<'a, T, R> {
                 &transaction_order,
                 code_hash,
             );
+            // lt of the consuming transaction, `created_lt` is left as is
+            prepared_message.doc.insert(
+                "tr_lt".to_owned(),
+                crate::u64_to_string(transaction.logical_time()).into(),
+            );
             prepared_messages.insert(message_id, prepared_message);
         };
         log::debug!("TIME: prepare in messages {}ms", now.elapsed().as_millis());
@@ -172,18 +234,102 @@ impl<'a, T: ParserTracer, R: JsonReducer> ParserTransactions<'a, T, R> {
         Ok(())
     }
 
+    // the messages are the same in both transactions, so the first prepared one is kept
+    fn parse_raw_messages_from_transaction(
+        &self,
+        transaction: &Transaction,
+        prepared_messages: &mut BTreeMap<UInt256, PreparedMessage>,
+    ) -> Result<()> {
+        let mut cells = Vec::new();
+        cells.extend(transaction.in_msg_cell());
+        transaction.out_msgs.iterate_slices(|slice| {
+            cells.push(slice.reference(0)?);
+            Ok(true)
+        })?;
+        for message_cell in cells {
+            let message_id = message_cell.repr_hash();
+            if prepared_messages.contains_key(&message_id) {
+                continue;
+            }
+            let message = Message::construct_from_cell(message_cell.clone())?;
+            let (src_partition, dst_partition) =
+                get_message_partitions(self.messages_sharding_depth, &message)?;
+            let entry = raw_entry(
+                "message",
+                crate::hash_doc_id(&message_id),
+                self.parsing.id,
+                &write_boc(&message_cell)?,
+                None,
+            );
+            prepared_messages.insert(
+                message_id,
+                PreparedMessage {
+                    doc: entry.body,
+                    src_partition,
+                    dst_partition,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Transit messages have no transactions in the block, so they are prepared
+    /// from the in msg descr
+    pub(crate) fn parse_transit_messages(
+        &self,
+        directions: &MessageDirections,
+        prepared_messages: &mut BTreeMap<UInt256, PreparedMessage>,
+    ) -> Result<()> {
+        for message_cell in &directions.transit {
+            let message_id = message_cell.repr_hash();
+            if prepared_messages.contains_key(&message_id) {
+                continue;
+            }
+            let message = Message::construct_from_cell(message_cell.clone())?;
+            let prepared_message = if self.raw {
+                let (src_partition, dst_partition) =
+                    get_message_partitions(self.messages_sharding_depth, &message)?;
+                let entry = raw_entry(
+                    "message",
+                    crate::hash_doc_id(&message_id),
+                    self.parsing.id,
+                    &write_boc(message_cell)?,
+                    None,
+                );
+                PreparedMessage {
+                    doc: entry.body,
+                    src_partition,
+                    dst_partition,
+                }
+            } else {
+                self.prepare_message_entry(message_cell.clone(), message, None)?
+            };
+            prepared_messages.insert(message_id, prepared_message);
+        }
+        Ok(())
+    }
+
     pub fn finish_prepared_messages(
         &self,
-        prepared_messages: HashMap<UInt256, PreparedMessage>,
+        prepared_messages: BTreeMap<UInt256, PreparedMessage>,
+        directions: &MessageDirections,
     ) -> Result<Vec<ParsedEntry>> {
         let now = std::time::Instant::now();
         let mut messages = Vec::with_capacity(prepared_messages.len());
-        for (_, prepared_message) in prepared_messages {
+        for (message_id, prepared_message) in prepared_messages {
             let PreparedMessage {
-                doc,
+                mut doc,
                 src_partition,
                 dst_partition,
             } = prepared_message;
+            if self.raw {
+                messages.push(ParsedEntry::new(doc, src_partition.or(dst_partition))?);
+                continue;
+            }
+            if let Some(direction) = directions.get(&message_id) {
+                doc.insert("direction".to_owned(), direction.into());
+            }
+            insert_block_location(&mut doc, self.parsing.id);
 
             messages.push(ParsedEntry::reduced(
                 doc,
@@ -226,11 +372,21 @@ impl<'a, T: ParserTracer, R: JsonReducer> ParserTransactions<'a, T, R> {
             proof,
             transaction_now,
         };
-        let mut doc = crate::db_serialize_message("id", &set)?;
+        let mut doc = if self.normalized_msg_hashes {
+            crate::db_serialize_message_with_normalized_hash(
+                "id",
+                &set,
+                crate::SerializationMode::Standart,
+            )?
+        } else {
+            crate::db_serialize_message("id", &set)?
+        };
         doc.insert(
             "block_id".to_owned(),
-            self.parsing.id.root_hash().as_hex_string().into(),
+            crate::block_doc_id(self.parsing.id).into(),
         );
+        // time of the block the message is processed in, `created_at` is left as is
+        doc.insert("block_time".to_owned(), self.block_time.into());
 
         Ok(PreparedMessage {
             doc,
@@ -242,17 +398,38 @@ impl<'a, T: ParserTracer, R: JsonReducer> ParserTransactions<'a, T, R> {
         &self,
         cell: Cell,
         transaction: Transaction,
+        descr: &TransactionDescr,
         workchain_id: i32,
         transaction_order: Option<String>,
         code_hash: &Option<String>,
     ) -> Result<ParsedEntry> {
+        if self.raw {
+            let partition =
+                get_partition(self.transactions_sharding_depth, transaction.account_id().clone())?;
+            let id = crate::hash_doc_id(&cell.repr_hash());
+            return Ok(raw_entry(
+                "transaction",
+                id,
+                self.parsing.id,
+                &write_boc(&cell)?,
+                partition,
+            ));
+        }
         let boc = write_boc(&cell).unwrap();
-        let proof = if self.with_proofs {
+        let proof = if self.with_transaction_proofs {
             Some(write_boc(&transaction.prepare_proof(self.parsing.root)?)?)
         } else {
             None
         };
         let address = transaction.account_id().clone();
+        let tr_kind = transaction_kind(descr);
+        let is_special = match self.special_accounts {
+            Some(special_accounts) => Some(
+                workchain_id == MASTERCHAIN_ID
+                    && special_accounts.contains(&UInt256::construct_from(&mut address.clone())?),
+            ),
+            None => None,
+        };
         let set = crate::TransactionSerializationSet {
             transaction,
             id: cell.repr_hash(),
@@ -270,11 +447,33 @@ impl<'a, T: ParserTracer, R: JsonReducer> ParserTransactions<'a, T, R> {
         if let Some(code_hash) = code_hash {
             doc.insert("code_hash".to_owned(), code_hash.clone().into());
         }
+        doc.insert("tr_kind".to_owned(), tr_kind.into());
+        if let Some(is_special) = is_special {
+            doc.insert("is_special".to_owned(), is_special.into());
+        }
+        insert_block_location(&mut doc, self.parsing.id);
 
         ParsedEntry::reduced(doc, partition, self.transactions_config)
     }
 }
 
+/// `tr_kind` of the transaction entries in snake case, the numeric `tr_type` and
+/// the QServer `tr_type_name` in camel case are written by the serializer
+pub(crate) fn transaction_kind(descr: &TransactionDescr) -> &'static str {
+    match descr {
+        TransactionDescr::Ordinary(_) => "ordinary",
+        TransactionDescr::Storage(_) => "storage",
+        TransactionDescr::TickTock(tr) => match tr.tt {
+            TransactionTickTock::Tick => "tick",
+            TransactionTickTock::Tock => "tock",
+        },
+        TransactionDescr::SplitPrepare(_) => "split_prepare",
+        TransactionDescr::SplitInstall(_) => "split_install",
+        TransactionDescr::MergePrepare(_) => "merge_prepare",
+        TransactionDescr::MergeInstall(_) => "merge_install",
+    }
+}
+
 fn get_message_partitions(
     sharding_depth: u32,
     message: &Message,
@@ -0,0 +1,151 @@
+/*
+ * Copyright (C) ton.dev. All Rights Reserved.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.  You may obtain a copy of the
+ * License at:
+ *
+ * https://www.ever.dev/licenses
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific ton.dev software governing permissions and limitations
+ * under the License.
+ */
+
+//! `TryFrom` conversions between the JSON documents and the blockchain structures.
+//! Neither the trait nor the structures nor `Map` are of this crate, so the JSON side
+//! is wrapped into the `JsonConfig` and `JsonState` newtypes.
+
+use serde_json::{Map, Value};
+use ton_dev_block::{ConfigParams, Error, ShardStateUnsplit};
+
+/// Config in the form written by `serialize_config`: `config_addr`, the parameters object
+/// `config` and the raw `unknown_config` ones. The parameters are read by `parse_config`
+/// so the bare parameters object and the `master.config` wrapper are accepted too,
+/// `config_addr` is read if given. The `unknown_config` parameters are not read back
+///
+/// ```
+/// use ton_dev_block::ConfigParams;
+/// use ton_dev_block_json::JsonConfig;
+///
+/// let json = JsonConfig::try_from(&ConfigParams::default())?;
+/// let config: ConfigParams = json.try_into()?;
+/// assert_eq!(config, ConfigParams::default());
+/// # Ok::<(), ton_dev_block::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JsonConfig(pub Map<String, Value>);
+
+/// Shard state document in the form written by `db_serialize_shard_state`, read by `parse_state`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JsonState(pub Map<String, Value>);
+
+impl From<Map<String, Value>> for JsonConfig {
+    fn from(map: Map<String, Value>) -> Self {
+        Self(map)
+    }
+}
+
+impl From<JsonConfig> for Map<String, Value> {
+    fn from(json: JsonConfig) -> Self {
+        json.0
+    }
+}
+
+impl From<Map<String, Value>> for JsonState {
+    fn from(map: Map<String, Value>) -> Self {
+        Self(map)
+    }
+}
+
+impl From<JsonState> for Map<String, Value> {
+    fn from(json: JsonState) -> Self {
+        json.0
+    }
+}
+
+#[cfg(feature = "deserialize")]
+impl TryFrom<&JsonConfig> for ConfigParams {
+    type Error = Error;
+
+    fn try_from(json: &JsonConfig) -> Result<Self, Error> {
+        let mut config = crate::parse_config(&json.0)?;
+        let map = crate::PathMap::new(&json.0);
+        if map.has_field("config_addr") {
+            config.config_addr = map.get_uint256("config_addr")?;
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(feature = "deserialize")]
+impl TryFrom<JsonConfig> for ConfigParams {
+    type Error = Error;
+
+    fn try_from(json: JsonConfig) -> Result<Self, Error> {
+        Self::try_from(&json)
+    }
+}
+
+#[cfg(feature = "deserialize")]
+impl TryFrom<&JsonState> for ShardStateUnsplit {
+    type Error = Error;
+
+    fn try_from(json: &JsonState) -> Result<Self, Error> {
+        crate::parse_state(&json.0)
+    }
+}
+
+#[cfg(feature = "deserialize")]
+impl TryFrom<JsonState> for ShardStateUnsplit {
+    type Error = Error;
+
+    fn try_from(json: JsonState) -> Result<Self, Error> {
+        Self::try_from(&json)
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl TryFrom<&ConfigParams> for JsonConfig {
+    type Error = Error;
+
+    fn try_from(config: &ConfigParams) -> Result<Self, Error> {
+        let mut map = Map::new();
+        crate::serialize_config(&mut map, config, crate::SerializationMode::Standart)?;
+        Ok(Self(map))
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl TryFrom<ShardStateUnsplit> for JsonState {
+    type Error = Error;
+
+    /// The document id is the root hash of the state, it has no block id
+    fn try_from(state: ShardStateUnsplit) -> Result<Self, Error> {
+        use ton_dev_block::Serializable;
+        let cell = state.serialize()?;
+        let set = crate::ShardStateSerializationSet {
+            block_id: None,
+            workchain_id: state.shard().workchain_id(),
+            id: cell.repr_hash().as_hex_string(),
+            boc: ton_dev_block::write_boc(&cell)?,
+            state,
+        };
+        crate::db_serialize_shard_state("id", &set).map(Self)
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl TryFrom<&ShardStateUnsplit> for JsonState {
+    type Error = Error;
+
+    fn try_from(state: &ShardStateUnsplit) -> Result<Self, Error> {
+        Self::try_from(state.clone())
+    }
+}
+
+#[cfg(all(test, feature = "serialize", feature = "deserialize"))]
+#[path = "tests/test_convert.rs"]
+mod tests;
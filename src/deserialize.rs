@@ -15,12 +15,63 @@
  */
 
 use serde_json::{Map, Value};
-use std::{str::FromStr, convert::TryInto};
+use std::{collections::{HashMap, HashSet}, str::FromStr, convert::TryInto, io::Read};
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "remp")]
 use ton_api::{
-    ton::ton_node::{rempmessagestatus, RempMessageLevel, RempMessageStatus, RempReceipt},
+    ton::ton_node::{rempmessagestatus, RempMessageStatus, RempReceipt},
     IntoBoxed
 };
 use ton_dev_block::*;
+use crate::shared::{rfc3339_to_unix_time, COMPLAINT_PRICING_TAG, GLOBAL_CAPABILITIES, WORKCHAIN_FLAGS};
+#[cfg(feature = "remp")]
+use crate::shared::{remp_kind_to_status_template, RempStatusKind};
+
+/// Decodes both standard and URL-safe alphabets, padded or not
+fn base64_decode_any(input: &str) -> Result<Vec<u8>> {
+    let mut normalized = input.trim_end_matches('=').replace('-', "+").replace('_', "/");
+    while normalized.len() % 4 != 0 {
+        normalized.push('=');
+    }
+    base64_decode(normalized)
+}
+
+/// Accepts `1_000_000` and `+5` in decimal strings: drops a leading `+` and underscores
+/// placed between two digits. Any other underscore (`1__0`, `_1`, `1_`) leaves the text
+/// as is so the parsing fails on it.
+fn normalize_decimal(text: &str) -> std::borrow::Cow<str> {
+    let digits = match text.strip_prefix('+') {
+        Some(digits) if digits.starts_with(|c: char| c.is_ascii_digit()) => digits,
+        _ => text,
+    };
+    let bytes = digits.as_bytes();
+    let separated = (0..bytes.len()).all(|i| bytes[i] != b'_' || (
+        i > 0 && i + 1 < bytes.len() && bytes[i - 1].is_ascii_digit() && bytes[i + 1].is_ascii_digit()
+    ));
+    if !separated || (digits.len() == text.len() && !digits.contains('_')) {
+        std::borrow::Cow::Borrowed(text)
+    } else {
+        std::borrow::Cow::Owned(digits.replace('_', ""))
+    }
+}
+
+// 2^53, the integers above it are not exactly representable by f64
+const MAX_EXACT_FLOAT: f64 = 9007199254740992.0;
+
+/// Reads the integer written as the float, e.g. `1713043200.0` or `1.7130432e9`.
+/// Returns None if the value is not the float and fails if the float has the fractional
+/// part or is too big to be exact.
+fn float_as_integer(value: &Value) -> Result<Option<i64>> {
+    match value.as_f64() {
+        Some(v) if value.is_f64() => {
+            if v.fract() != 0.0 || v.abs() >= MAX_EXACT_FLOAT {
+                fail!("{} can't be converted to the integer without precision loss", value)
+            }
+            Ok(Some(v as i64))
+        }
+        _ => Ok(None)
+    }
+}
 
 #[allow(dead_code)]
 trait ParseJson {
@@ -37,9 +88,12 @@ impl ParseJson for Value {
         self.as_str().ok_or_else(|| error!("field is not str"))?.parse()
     }
     fn as_base64(&self) -> Result<Vec<u8>> {
-        base64_decode(self.as_str().ok_or_else(|| error!("field is not str"))?)
+        base64_decode_any(self.as_str().ok_or_else(|| error!("field is not str"))?)
     }
     fn as_int(&self) -> Result<i32> {
+        if let Some(v) = float_as_integer(self)? {
+            return Ok(v as i32)
+        }
         match self.as_i64() {
             Some(v) => Ok(v as i32),
             None => match self.as_str() {
@@ -49,6 +103,9 @@ impl ParseJson for Value {
         }
     }
     fn as_uint(&self) -> Result<u32> {
+        if let Some(v) = float_as_integer(self)? {
+            return Ok(v.try_into()?)
+        }
         match self.as_u64() {
             Some(v) => Ok(v as u32),
             None => match self.as_str() {
@@ -58,6 +115,9 @@ impl ParseJson for Value {
         }
     }
     fn as_long(&self) -> Result<i64> {
+        if let Some(v) = float_as_integer(self)? {
+            return Ok(v)
+        }
         match self.as_i64() {
             Some(v) => Ok(v),
             None => match self.as_str() {
@@ -67,6 +127,9 @@ impl ParseJson for Value {
         }
     }
     fn as_ulong(&self) -> Result<u64> {
+        if let Some(v) = float_as_integer(self)? {
+            return Ok(v.try_into()?)
+        }
         match self.as_u64() {
             Some(v) => Ok(v),
             None => match self.as_str() {
@@ -77,36 +140,208 @@ impl ParseJson for Value {
     }
 }
 
+/// Limits of the parsed document for the untrusted input, the parsing fails as soon as
+/// one of them is exceeded. The defaults are unlimited
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseLimits {
+    pub max_accounts: usize,
+    pub max_libraries: usize,
+    /// Decoded bytes of any base64 field, the inflated bytes of the deflated ones
+    pub max_boc_bytes: usize,
+    /// Values of the document including the containers and the root object
+    pub max_json_nodes: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_accounts: usize::MAX,
+            max_libraries: usize::MAX,
+            max_boc_bytes: usize::MAX,
+            max_json_nodes: usize::MAX,
+        }
+    }
+}
+
+impl ParseLimits {
+    fn check_json_nodes(&self, map: &Map<String, Value>) -> Result<()> {
+        self.count_json_nodes(1, map.values()).map(|_| ())
+    }
+
+    // counts the nodes without recursion and stops at the first one over the limit,
+    // `count` is the number of the nodes counted before
+    fn count_json_nodes<'v>(&self, mut count: usize, values: impl IntoIterator<Item = &'v Value>) -> Result<usize> {
+        if self.max_json_nodes == usize::MAX {
+            return Ok(count)
+        }
+        let mut pending = values.into_iter().collect::<Vec<_>>();
+        while let Some(value) = pending.pop() {
+            count += 1;
+            if count > self.max_json_nodes {
+                fail!("root exceeds the max_json_nodes limit of {}: {} nodes counted", self.max_json_nodes, count)
+            }
+            match value {
+                Value::Array(items) => pending.extend(items),
+                Value::Object(map) => pending.extend(map.values()),
+                _ => ()
+            }
+        }
+        Ok(count)
+    }
+
+    // the items are counted before any of them is decoded
+    fn check_items(map_path: &PathMap, name: &str, limit_name: &str, limit: usize, count: usize) -> Result<()> {
+        if count > limit {
+            fail!("{}/{} exceeds the {} limit of {}: {} items given", map_path.path.join("/"), name, limit_name, limit, count)
+        }
+        Ok(())
+    }
+}
+
+// keys read through the maps sharing the tracker and the nested maps entered,
+// both by the address of the map, see `PathMap::track`
+#[derive(Debug, Default)]
+struct VisitedKeys<'m> {
+    keys: HashSet<(usize, &'m str)>,
+    maps: HashSet<usize>,
+}
+
+fn map_address(map: &Map<String, Value>) -> usize {
+    map as *const Map<String, Value> as usize
+}
+
 #[derive(Debug)]
 pub struct PathMap<'m, 'a> {
     map: &'m Map<String, Value>,
-    path: Vec<&'a str>
+    path: Vec<&'a str>,
+    max_boc_bytes: usize,
+    decimal_grams: bool,
+    // shared by the nested maps, a mutex and not a cell as the accounts may be read in parallel
+    visited: Option<Arc<Mutex<VisitedKeys<'m>>>>,
 }
 
 impl<'m, 'a> PathMap<'m, 'a> {
     pub fn new(map: &'m Map<String, Value>) -> Self {
         Self {
             map,
-            path: vec!["root"]
+            path: vec!["root"],
+            max_boc_bytes: usize::MAX,
+            decimal_grams: false,
+            visited: None,
+        }
+    }
+    /// Same as `new` but remembers the keys read from the map and the nested maps,
+    /// see `unvisited_keys`
+    pub fn track(map: &'m Map<String, Value>) -> Self {
+        let mut visited = VisitedKeys::default();
+        visited.maps.insert(map_address(map));
+        Self {
+            visited: Some(Arc::new(Mutex::new(visited))),
+            ..Self::new(map)
+        }
+    }
+    /// Paths of the keys never read from the map and the nested maps it was read through,
+    /// e.g. `root/p15/extra`, the array items are addressed by index, e.g. `root/list/1/extra`.
+    /// The objects read as a whole value are not looked into.
+    /// Always empty for the map constructed without `track`
+    pub fn unvisited_keys(&self) -> Vec<String> {
+        fn collect(map: &Map<String, Value>, path: String, visited: &VisitedKeys, result: &mut Vec<String>) {
+            for (key, value) in map {
+                let path = format!("{}/{}", path, key);
+                if !visited.keys.contains(&(map_address(map), key.as_str())) {
+                    result.push(path);
+                    continue
+                }
+                match value {
+                    Value::Object(map) if visited.maps.contains(&map_address(map)) => {
+                        collect(map, path, visited, result)
+                    }
+                    Value::Array(items) => for (index, item) in items.iter().enumerate() {
+                        if let Value::Object(map) = item {
+                            if visited.maps.contains(&map_address(map)) {
+                                collect(map, format!("{}/{}", path, index), visited, result)
+                            }
+                        }
+                    }
+                    _ => ()
+                }
+            }
+        }
+        let mut result = Vec::new();
+        if let Some(visited) = &self.visited {
+            let visited = visited.lock().unwrap_or_else(|err| err.into_inner());
+            collect(self.map, self.path.join("/"), &visited, &mut result);
+        }
+        result
+    }
+    fn lookup(&self, name: &str) -> Option<&'m Value> {
+        let (key, value) = self.map.get_key_value(name)?;
+        if let Some(visited) = &self.visited {
+            visited.lock().unwrap_or_else(|err| err.into_inner())
+                .keys.insert((map_address(self.map), key.as_str()));
+        }
+        Some(value)
+    }
+    fn nested(&self, map: &'m Map<String, Value>, path: Vec<&'a str>) -> Self {
+        if let Some(visited) = &self.visited {
+            visited.lock().unwrap_or_else(|err| err.into_inner()).maps.insert(map_address(map));
+        }
+        Self {
+            map,
+            path,
+            max_boc_bytes: self.max_boc_bytes,
+            decimal_grams: self.decimal_grams,
+            visited: self.visited.clone(),
         }
     }
+    /// Fail reading the base64 fields decoding to more than `max_boc_bytes` bytes,
+    /// the nested objects inherit the limit
+    pub fn with_max_boc_bytes(mut self, max_boc_bytes: usize) -> Self {
+        self.max_boc_bytes = max_boc_bytes;
+        self
+    }
+    /// Read the grams strings with the decimal point as whole tokens of 9 decimal places,
+    /// e.g. `"1.5"` is 1500000000, the nested objects inherit the mode
+    pub fn with_decimal_grams(mut self, decimal_grams: bool) -> Self {
+        self.decimal_grams = decimal_grams;
+        self
+    }
     pub fn cont(prev: &Self, name: &'a str, value: &'m Value) -> Result<Self> {
         let map = value
             .as_object()
             .ok_or_else(|| error!("{}/{} must be the vector of objects", prev.path.join("/"), name))?;
         let mut path = prev.path.clone();
         path.push(name);
-        Ok(Self {
-            map,
-            path
-        })
+        Ok(prev.nested(map, path))
     }
     pub fn iter(&self) -> serde_json::map::Iter<'m> {
         self.map.iter()
     }
+    /// Null value is the same as the absent field, required fields fail with "must not be null"
     pub fn get_item(&self, name: &'a str) -> Result<&'m Value> {
-        let item = self.map.get(name).ok_or_else(|| error!("{} must have the field `{}`", self.path.join("/"), name))?;
-        Ok(item)
+        match self.lookup(name) {
+            None => fail!("{} must have the field `{}`", self.path.join("/"), name),
+            Some(Value::Null) => fail!("{}/{} must not be null", self.path.join("/"), name),
+            Some(item) => Ok(item)
+        }
+    }
+    /// Name of the document id field: `id`, `_key` or `_id`, see `DocIdField`
+    pub fn doc_id_field(&self) -> Result<&'static str> {
+        crate::DocIdField::ALL.into_iter()
+            .map(|field| field.name())
+            .find(|name| self.has_field(name))
+            .ok_or_else(|| error!("{} must have the field `id`, `_key` or `_id`", self.path.join("/")))
+    }
+
+    /// Returns false for the absent and the null fields
+    pub fn has_field(&self, name: &str) -> bool {
+        self.lookup(name).map_or(false, |item| !item.is_null())
+    }
+    fn fail_not_integer<T>(&self, name: &str) -> Result<T> {
+        if let Some(Value::Null) = self.lookup(name) {
+            fail!("{}/{} must not be null", self.path.join("/"), name)
+        }
+        fail!("{}/{} must be the integer or a string with the integer", self.path.join("/"), name)
     }
     pub fn get_obj(&self, name: &'a str) -> Result<Self> {
         let map = self.get_item(name)?
@@ -114,10 +349,34 @@ impl<'m, 'a> PathMap<'m, 'a> {
             .ok_or_else(|| error!("{}/{} must be the object", self.path.join("/"), name))?;
         let mut path = self.path.clone();
         path.push(name);
-        Ok(Self {
-            map,
-            path
-        })
+        Ok(self.nested(map, path))
+    }
+    /// Reads the object by the dotted path of nested object names, e.g. `master.config.p34`.
+    /// There is no escaping, so keys containing dots can't be addressed this way.
+    pub fn get_path_obj(&self, path: &'a str) -> Result<Self> {
+        self.with_full_path(path, self.get_path_parent(path).and_then(|(map, name)| map.get_obj(name)))
+    }
+    /// Reads the number by the dotted path, see [`Self::get_path_obj`]
+    pub fn get_path_num(&self, path: &'a str) -> Result<i64> {
+        self.with_full_path(path, self.get_path_parent(path).and_then(|(map, name)| map.get_num(name)))
+    }
+    /// Reads the string by the dotted path, see [`Self::get_path_obj`]
+    pub fn get_path_str(&self, path: &'a str) -> Result<&'m str> {
+        self.with_full_path(path, self.get_path_parent(path).and_then(|(map, name)| map.get_str(name)))
+    }
+    fn get_path_parent(&self, path: &'a str) -> Result<(Self, &'a str)> {
+        let (parents, name) = match path.rsplit_once('.') {
+            Some((parents, name)) => (Some(parents), name),
+            None => (None, path)
+        };
+        let mut map = self.nested(self.map, self.path.clone());
+        for name in parents.into_iter().flat_map(|parents| parents.split('.')) {
+            map = map.get_obj(name)?;
+        }
+        Ok((map, name))
+    }
+    fn with_full_path<T>(&self, path: &str, result: Result<T>) -> Result<T> {
+        result.map_err(|err| error!("{} (reading {}/{})", err, self.path.join("/"), path.replace('.', "/")))
     }
     pub fn get_vec(&self, name: &'a str) -> Result<&'m Vec<Value>> {
         self.get_item(name)?
@@ -135,20 +394,126 @@ impl<'m, 'a> PathMap<'m, 'a> {
                 self.path.join("/"), name, err))
     }
     pub fn get_base64(&self, name: &'a str) -> Result<Vec<u8>> {
-        base64_decode(self.get_str(name)?)
+        let text = self.get_str(name)?;
+        // checked before decoding, the padding and the whitespace don't count
+        let decoded_len = text.bytes().filter(|b| !b.is_ascii_whitespace() && *b != b'=').count() * 3 / 4;
+        self.check_boc_bytes(name, decoded_len)?;
+        base64_decode_any(text)
             .map_err(|err| error!("{}/{} must be the base64 : {}",
                 self.path.join("/"), name, err))
     }
+    fn check_boc_bytes(&self, name: &str, len: usize) -> Result<()> {
+        if len > self.max_boc_bytes {
+            fail!("{}/{} exceeds the max_boc_bytes limit of {}: {} bytes given",
+                self.path.join("/"), name, self.max_boc_bytes, len)
+        }
+        Ok(())
+    }
+    /// Reads the cell from base64 boc `name` or from `name_deflated` written by `deflate_boc_fields`
+    pub fn get_cell_maybe_deflated(&self, name: &'a str) -> Result<Cell> {
+        let deflated_name = format!("{}_deflated", name);
+        if !self.has_field(&deflated_name) {
+            return read_single_root_boc(self.get_base64(name)?)
+        }
+        let encoding_name = format!("{}_encoding", name);
+        if let Ok(encoding) = self.get_str(&encoding_name) {
+            if encoding != "deflate" {
+                fail!("{}/{} has unsupported encoding `{}`", self.path.join("/"), encoding_name, encoding)
+            }
+        }
+        let deflated = self.get_base64(&deflated_name)?;
+        let mut bytes = Vec::new();
+        // inflated up to one byte over the limit, so the small deflated field can't exhaust memory
+        let max_inflated = self.max_boc_bytes.saturating_add(1) as u64;
+        flate2::read::DeflateDecoder::new(deflated.as_slice()).take(max_inflated).read_to_end(&mut bytes)
+            .map_err(|err| error!("{}/{} must be the deflated boc : {}", self.path.join("/"), deflated_name, err))?;
+        self.check_boc_bytes(&deflated_name, bytes.len())?;
+        let length_name = format!("{}_length", name);
+        if let Some(length) = self.get_num_opt(&length_name)? {
+            if length as usize != bytes.len() {
+                fail!("{}/{} is {} but the inflated boc contains {} bytes",
+                    self.path.join("/"), length_name, length, bytes.len())
+            }
+        }
+        read_single_root_boc(bytes)
+    }
+
+    pub fn get_pubkey(&self, name: &'a str) -> Result<SigPubKey> {
+        let item = self.get_item(name)?;
+        let (encoding, bytes) = if item.is_object() {
+            ("keyring", self.get_obj(name)?.get_base64("pub_key")?)
+        } else if let Some(key) = item.as_str() {
+            if key.bytes().all(|b| b.is_ascii_hexdigit()) {
+                let bytes = hex::decode(key)
+                    .map_err(|err| error!("{}/{} must be the public key in hex format : {}",
+                        self.path.join("/"), name, err))?;
+                ("hex", bytes)
+            } else {
+                ("base64", self.get_base64(name)?)
+            }
+        } else {
+            fail!("{}/{} must be the public key string or keyring object", self.path.join("/"), name)
+        };
+        if bytes.len() != 32 {
+            fail!("{}/{} must be the 32 bytes public key but {} form contains {} bytes",
+                self.path.join("/"), name, encoding, bytes.len())
+        }
+        SigPubKey::from_bytes(&bytes)
+    }
+
+    /// Reads the signature given either as `name` string with r||s in hex or base64,
+    /// or as `name_r`/`name_s` or `r`/`s` pair of hex strings
+    pub fn get_signature(&self, name: &'a str) -> Result<CryptoSignature> {
+        let (encoding, bytes) = if let Ok(item) = self.get_item(name) {
+            let signature = item.as_str()
+                .ok_or_else(|| error!("{}/{} must be the signature string", self.path.join("/"), name))?;
+            if signature.bytes().all(|b| b.is_ascii_hexdigit()) {
+                let bytes = hex::decode(signature)
+                    .map_err(|err| error!("{}/{} must be the signature in hex format : {}",
+                        self.path.join("/"), name, err))?;
+                ("hex", bytes)
+            } else {
+                ("base64", self.get_base64(name)?)
+            }
+        } else {
+            let r_name = format!("{}_r", name);
+            let (r_name, s_name) = if self.has_field(&r_name) {
+                (r_name, format!("{}_s", name))
+            } else {
+                ("r".to_string(), "s".to_string())
+            };
+            let mut bytes = Vec::with_capacity(64);
+            for part in [&r_name, &s_name] {
+                let part_bytes = hex::decode(self.get_str(part)?)
+                    .map_err(|err| error!("{}/{} must be the signature part in hex format : {}",
+                        self.path.join("/"), part, err))?;
+                if part_bytes.len() != 32 {
+                    fail!("{}/{} must be the 32 bytes signature part but contains {} bytes",
+                        self.path.join("/"), part, part_bytes.len())
+                }
+                bytes.extend_from_slice(&part_bytes);
+            }
+            ("r/s", bytes)
+        };
+        if bytes.len() != 64 {
+            fail!("{}/{} must be the 64 bytes signature but {} form contains {} bytes",
+                self.path.join("/"), name, encoding, bytes.len())
+        }
+        CryptoSignature::from_r_s(&bytes[..32], &bytes[32..])
+    }
 
     pub fn get_num(&self, name: &'a str) -> Result<i64> {
         if let Ok(value) = self.get_item(name) {
             if let Some(v) = value.as_i64() {
                 return Ok(v);
             }
+            if let Some(v) = self.get_float_as_integer(name, value)? {
+                return Ok(v);
+            }
         }
         if let Ok(value) = self.get_item(&(name.to_string() + "_dec")) {
             if let Some(v) = value.as_str() {
-                return i64::from_str(v).map_err(|err| {
+                return i64::from_str(&normalize_decimal(v)).map_err(|err| {
                     error!(
                         "{}/{} must be the integer or a string with the integer {}: {}",
                         self.path.join("/"), name, v, err
@@ -166,7 +531,7 @@ impl<'m, 'a> PathMap<'m, 'a> {
                         )
                     });
                 } else {
-                    return i64::from_str(v).map_err(|err| {
+                    return i64::from_str(&normalize_decimal(v)).map_err(|err| {
                         error!(
                             "{}/{} must be the integer or a string with the integer {}: {}",
                             self.path.join("/"), name, v, err
@@ -175,10 +540,52 @@ impl<'m, 'a> PathMap<'m, 'a> {
                 }
             }
         }
-        fail!(
-            "{}/{} must be the integer or a string with the integer",
-            self.path.join("/"), name
-        )
+        self.fail_not_integer(name)
+    }
+
+    fn get_float_as_integer(&self, name: &str, value: &Value) -> Result<Option<i64>> {
+        float_as_integer(value).map_err(|err| {
+            error!("{}/{} must be the integer : {}", self.path.join("/"), name, err)
+        })
+    }
+
+    /// Reads u64 from the number, the decimal `name_dec` or `name` string or the `0x` hex `name`
+    /// string without the i64 conversion, so values up to u64::MAX are accepted
+    pub fn get_u64_radix(&self, name: &'a str) -> Result<u64> {
+        if let Ok(value) = self.get_item(name) {
+            if let Some(v) = value.as_u64() {
+                return Ok(v);
+            }
+            if value.is_i64() {
+                fail!("{}/{} must not be negative but {} given", self.path.join("/"), name, value)
+            }
+        }
+        if let Ok(value) = self.get_item(&(name.to_string() + "_dec")) {
+            if let Some(v) = value.as_str() {
+                return self.parse_u64_str(name, v, &normalize_decimal(v), 10);
+            }
+        }
+        if let Ok(value) = self.get_item(name) {
+            if let Some(v) = value.as_str() {
+                return match v.strip_prefix("0x") {
+                    Some(hex) => self.parse_u64_str(name, v, hex, 16),
+                    None => self.parse_u64_str(name, v, &normalize_decimal(v), 10),
+                };
+            }
+        }
+        self.fail_not_integer(name)
+    }
+
+    fn parse_u64_str(&self, name: &str, text: &str, digits: &str, radix: u32) -> Result<u64> {
+        u64::from_str_radix(digits, radix).map_err(|err| match err.kind() {
+            std::num::IntErrorKind::PosOverflow => error!(
+                "{}/{} must not exceed 2^64 - 1 but {} given", self.path.join("/"), name, text
+            ),
+            _ => error!(
+                "{}/{} must be the integer or a string with the integer {}: {}",
+                self.path.join("/"), name, text, err
+            ),
+        })
     }
 
     pub fn get_grams(&self, name: &'a str) -> Result<Grams> {
@@ -186,31 +593,76 @@ impl<'m, 'a> PathMap<'m, 'a> {
             if let Some(v) = value.as_u64() {
                 return Ok(v.into());
             }
+            if value.is_i64() {
+                fail!("{}/{} must not be negative but {} given", self.path.join("/"), name, value)
+            }
+            if let Some(v) = self.get_float_as_integer(name, value)? {
+                if v < 0 {
+                    fail!("{}/{} must not be negative but {} given", self.path.join("/"), name, value)
+                }
+                return Ok((v as u64).into());
+            }
         }
         if let Ok(value) = self.get_item(&(name.to_string() + "_dec")) {
             if let Some(v) = value.as_str() {
-                return Grams::from_str(v).map_err(|err| {
-                    error!(
-                        "{}/{} must be the integer or a string with the integer {}: {}",
-                        self.path.join("/"), name, v, err
-                    )
-                });
+                return self.parse_grams_str(name, v);
             }
         }
         if let Ok(value) = self.get_item(name) {
             if let Some(v) = value.as_str() {
-                return Grams::from_str(v).map_err(|err| {
-                    error!(
-                        "{}/{} must be the integer or a string with the integer {}: {}",
-                        self.path.join("/"), name, v, err
-                    )
-                });
+                return self.parse_grams_str(name, v);
             }
         }
-        fail!(
-            "{}/{} must be the integer or a string with the integer",
-            self.path.join("/"), name
-        )
+        self.fail_not_integer(name)
+    }
+
+    fn parse_grams_str(&self, name: &str, text: &str) -> Result<Grams> {
+        let v = &*normalize_decimal(text);
+        if v.trim_start().starts_with('-') {
+            fail!("{}/{} must not be negative but {} given", self.path.join("/"), name, text)
+        }
+        if let Some((tokens, fraction)) = v.split_once('.').filter(|_| self.decimal_grams) {
+            return self.parse_decimal_grams(name, text, tokens, fraction)
+        }
+        // Grams are stored in 120 bits at most
+        if !v.is_empty() && v.bytes().all(|b| b.is_ascii_digit()) {
+            match u128::from_str(v) {
+                Ok(value) if value >> 120 == 0 => (),
+                _ => fail!("{}/{} must not exceed 2^120 - 1 but {} given", self.path.join("/"), name, text)
+            }
+        }
+        Grams::from_str(v).map_err(|err| {
+            error!(
+                "{}/{} must be the integer or a string with the integer {}: {}",
+                self.path.join("/"), name, text, err
+            )
+        })
+    }
+
+    // "1.5" -> 1500000000, both parts must have digits
+    fn parse_decimal_grams(&self, name: &str, text: &str, tokens: &str, fraction: &str) -> Result<Grams> {
+        let is_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        if !is_digits(tokens) || !is_digits(fraction) {
+            fail!("{}/{} must be the integer or a string with the decimal tokens amount but {} given",
+                self.path.join("/"), name, text)
+        }
+        if fraction.len() > 9 {
+            fail!("{}/{} must have at most 9 digits after the decimal point but {} given",
+                self.path.join("/"), name, text)
+        }
+        let nanos = format!("{:0<9}", fraction).bytes().fold(0, |nanos, b| nanos * 10 + (b - b'0') as u128);
+        match u128::from_str(tokens).ok()
+            .and_then(|tokens| tokens.checked_mul(1_000_000_000))
+            .and_then(|value| value.checked_add(nanos))
+        {
+            Some(value) if value >> 120 == 0 => Grams::from_str(&value.to_string()),
+            _ => fail!("{}/{} must not exceed 2^120 - 1 but {} given", self.path.join("/"), name, text)
+        }
+    }
+
+    /// Same as `parse_currency_collection(self, name)`
+    pub fn get_cc(&self, name: &'a str) -> Result<CurrencyCollection> {
+        parse_currency_collection(self, name)
     }
 
     pub fn get_u32(&self, name: &'a str, value: &mut u32) {
@@ -228,6 +680,48 @@ impl<'m, 'a> PathMap<'m, 'a> {
             *value = new_value as u8;
         }
     }
+    pub fn get_capabilities_list(&self, name: &'a str) -> Result<u64> {
+        let mut capabilities = 0;
+        for item in self.get_vec(name)? {
+            let cap_name = item.as_str()
+                .ok_or_else(|| error!("{}/{} must be the vector of strings", self.path.join("/"), name))?;
+            match GLOBAL_CAPABILITIES.iter().find(|(known, _)| *known == cap_name) {
+                Some((_, bit)) => capabilities |= bit,
+                None => fail!("{}/{} contains unknown capability `{}`, valid ones are: {}",
+                    self.path.join("/"), name, cap_name,
+                    GLOBAL_CAPABILITIES.iter().map(|(known, _)| *known).collect::<Vec<_>>().join(", "))
+            }
+        }
+        Ok(capabilities)
+    }
+    pub fn get_workchain_flags(&self, name: &'a str) -> Result<u16> {
+        let mut flags = 0;
+        for item in self.get_vec(name)? {
+            let flag_name = item.as_str()
+                .ok_or_else(|| error!("{}/{} must be the vector of strings", self.path.join("/"), name))?;
+            match WORKCHAIN_FLAGS.iter().find(|(known, _)| *known == flag_name) {
+                Some((_, bit)) => flags |= bit,
+                None => fail!("{}/{} contains unknown flag `{}`, valid ones are: {}",
+                    self.path.join("/"), name, flag_name,
+                    WORKCHAIN_FLAGS.iter().map(|(known, _)| *known).collect::<Vec<_>>().join(", "))
+            }
+        }
+        Ok(flags)
+    }
+    /// Same as `get_num` but returns None if the field is absent or null
+    pub fn get_num_opt(&self, name: &'a str) -> Result<Option<i64>> {
+        match self.has_field(name) {
+            true => self.get_num(name).map(Some),
+            false => Ok(None)
+        }
+    }
+    /// Same as `get_bool` but returns None if the field is absent or null
+    pub fn get_bool_opt(&self, name: &'a str) -> Result<Option<bool>> {
+        match self.has_field(name) {
+            true => self.get_bool(name).map(Some),
+            false => Ok(None)
+        }
+    }
     pub fn get_num16(&self, name: &'a str) -> Result<u16> {
         Ok(self.get_num(name)? as u16)
     }
@@ -236,25 +730,78 @@ impl<'m, 'a> PathMap<'m, 'a> {
             .as_bool()
             .ok_or_else(|| error!("{}/{} must be boolean", self.path.join("/"), name))
     }
+    /// Same as `get_bool` but also accepts the strings "true" and "false" in any case
+    /// and the integers 0 and 1, as some SQL exports write the flags
+    pub fn get_bool_lenient(&self, name: &'a str) -> Result<bool> {
+        let value = self.get_item(name)?;
+        match value {
+            Value::Bool(v) => return Ok(*v),
+            Value::String(v) if v.eq_ignore_ascii_case("true") => return Ok(true),
+            Value::String(v) if v.eq_ignore_ascii_case("false") => return Ok(false),
+            Value::Number(v) if v.as_u64() == Some(1) => return Ok(true),
+            Value::Number(v) if v.as_u64() == Some(0) => return Ok(false),
+            _ => ()
+        }
+        fail!("{}/{} must be boolean, \"true\", \"false\", 0 or 1 but {} given", self.path.join("/"), name, value)
+    }
+    pub fn get_bool_lenient_opt(&self, name: &'a str) -> Result<Option<bool>> {
+        match self.has_field(name) {
+            true => self.get_bool_lenient(name).map(Some),
+            false => Ok(None)
+        }
+    }
 }
 
-struct StateParser {
+pub struct StateParser {
     state: ShardStateUnsplit,
     extra: McStateExtra,
-    mandatory_params: u64
+    mandatory_params: u64,
+    verify_signatures: bool,
+    check_economics: bool,
+    account_mismatch_as_warning: bool,
+    missing_special_accounts_as_error: bool,
+    p61_defaults: bool,
+    strict_fields: bool,
+    decimal_grams: bool,
+    global_balance_check: bool,
+    #[cfg(feature = "rayon")]
+    parallel_accounts: bool,
+    limits: ParseLimits,
+    warnings: Vec<crate::Diagnostic>,
+    // path of p0 given as `auto`, it is taken from `master.config_addr`
+    auto_config_addr: Option<String>,
+}
+
+impl Default for StateParser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StateParser {
 
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             state: ShardStateUnsplit::with_ident(ShardIdent::masterchain()),
             extra: McStateExtra::default(),
             mandatory_params: 0,
+            verify_signatures: false,
+            check_economics: false,
+            account_mismatch_as_warning: false,
+            missing_special_accounts_as_error: false,
+            p61_defaults: false,
+            strict_fields: false,
+            decimal_grams: false,
+            global_balance_check: false,
+            #[cfg(feature = "rayon")]
+            parallel_accounts: false,
+            limits: ParseLimits::default(),
+            warnings: Vec::new(),
+            auto_config_addr: None,
         }
     }
 
-    fn for_zero_state() -> Self {
+    pub fn for_zero_state() -> Self {
         // let mandatory_params = [0, 1, 2, 7, 8, 9, 10, 11, 12, 14, 15, 16, 17, 18,
         //     20, 21, 22, 23, 24, 25, 28, 29, 31, 34];
         // let mandatory_params = mandatory_params.iter().fold(0, |s, p| a |= 1 << p);
@@ -263,7 +810,108 @@ impl StateParser {
             state: ShardStateUnsplit::with_ident(ShardIdent::masterchain()),
             extra: McStateExtra::default(),
             mandatory_params: 0x0000_0004_B3F7_CF87,
+            verify_signatures: false,
+            check_economics: false,
+            account_mismatch_as_warning: false,
+            missing_special_accounts_as_error: false,
+            p61_defaults: false,
+            strict_fields: false,
+            decimal_grams: false,
+            global_balance_check: false,
+            #[cfg(feature = "rayon")]
+            parallel_accounts: false,
+            limits: ParseLimits::default(),
+            warnings: Vec::new(),
+            auto_config_addr: None,
+        }
+    }
+
+    pub fn with_mandatory_params(mut self, mandatories: &[u32]) -> Self {
+        self.mandatory_params = 0;
+        for mandatory in mandatories {
+            self.mandatory_params |= 1u64 << mandatory;
         }
+        self
+    }
+
+    /// Check signatures of the validator temp keys (p39) while parsing
+    pub fn with_signature_verification(mut self, verify: bool) -> Self {
+        self.verify_signatures = verify;
+        self
+    }
+
+    /// Fail the parsing if `check_config_economics` reports errors on the parsed config,
+    /// the warnings are put into the parse report
+    pub fn with_economics_check(mut self, check: bool) -> Self {
+        self.check_economics = check;
+        self
+    }
+
+    /// Put the accounts whose boc doesn't match the declared `id` or `address` into the parse
+    /// report instead of failing, such accounts are still inserted by the boc address
+    pub fn with_account_mismatch_as_warning(mut self, as_warning: bool) -> Self {
+        self.account_mismatch_as_warning = as_warning;
+        self
+    }
+
+    /// Fail the zero-state parsing if the config, elector or minter address of p0-p2 names
+    /// an account absent in `accounts` instead of putting it into the parse report
+    pub fn with_missing_special_accounts_as_error(mut self, as_error: bool) -> Self {
+        self.missing_special_accounts_as_error = as_error;
+        self
+    }
+
+    /// Put into the parse report the currencies whose `master.global_balance` is less than
+    /// `total_balance`, they are normally equal in the zero-state.
+    /// Only the masterchain states with the mandatory params and the parsed `master` are checked
+    pub fn with_global_balance_check(mut self, check: bool) -> Self {
+        self.global_balance_check = check;
+        self
+    }
+
+    /// Take the missing or malformed p61 fields from `FastFinalityConfig::default()`
+    /// instead of failing, the way p61 was parsed before
+    pub fn with_p61_defaults(mut self, p61_defaults: bool) -> Self {
+        self.p61_defaults = p61_defaults;
+        self
+    }
+
+    /// Fail the parsing if the document has fields the parser never read, e.g. misspelled
+    /// optional fields silently taken as absent. Meant for the hand-authored documents:
+    /// the serialized ones have the fields kept for the queries only, like `id` or `boc`
+    pub fn with_strict_fields(mut self, strict: bool) -> Self {
+        self.strict_fields = strict;
+        self
+    }
+
+    /// Accept the grams strings with the decimal point as whole tokens, e.g. `"min_stake": "10000.0"`
+    /// is 10000000000000 nanotokens and `"1.5"` is 1500000000, the strings without the point
+    /// are still nanotokens. At most 9 digits after the point are allowed,
+    /// the serializers never write this form
+    pub fn with_decimal_grams(mut self, decimal_grams: bool) -> Self {
+        self.decimal_grams = decimal_grams;
+        self
+    }
+
+    /// Decode the state accounts on the rayon thread pool, the resulting state is the same
+    #[cfg(feature = "rayon")]
+    pub fn with_parallel_accounts(mut self, parallel: bool) -> Self {
+        self.parallel_accounts = parallel;
+        self
+    }
+
+    /// Fail the parsing of the documents exceeding the limits, see `ParseLimits`
+    pub fn with_limits(mut self, limits: ParseLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    fn path_map<'m>(&self, map: &'m Map<String, Value>) -> PathMap<'m, 'static> {
+        let map = match self.strict_fields {
+            true => PathMap::track(map),
+            false => PathMap::new(map),
+        };
+        map.with_max_boc_bytes(self.limits.max_boc_bytes).with_decimal_grams(self.decimal_grams)
     }
 
     fn is_need(&self, num: i32) -> bool {
@@ -276,8 +924,8 @@ impl StateParser {
         num: i32,
         f: impl FnOnce(&PathMap) -> Result<ConfigParamEnum>
     ) -> Result<()> {
-        let p = format!("p{}", num);
-        match config.get_obj(&p) {
+        let p = config_param_key(num);
+        match config.get_obj(p) {
             Ok(p) => {
                 self.extra.config.set_config(f(&p)?)
                     .map_err(|err| error!("Can't set config for {} : {}", p.path.join("/"), err))
@@ -295,8 +943,8 @@ impl StateParser {
         num: i32,
         f: impl FnOnce(&Vec<Value>) -> Result<ConfigParamEnum>
     ) -> Result<()> {
-        let p = format!("p{}", num);
-        match config.get_vec(&p) {
+        let p = config_param_key(num);
+        match config.get_vec(p) {
             Ok(v) => {
                 self.extra.config.set_config(f(v)?)
                     .map_err(|err| error!("Can't set config for {} : {}", config.path.join("/"), err))
@@ -314,8 +962,8 @@ impl StateParser {
         num: i32,
         f: impl FnOnce(UInt256) -> Result<ConfigParamEnum>
     ) -> Result<()> {
-        let p = format!("p{}", num);
-        match config.get_uint256(&p) {
+        let p = config_param_key(num);
+        match config.get_uint256(p) {
             Ok(p) => {
                 self.extra.config.set_config(f(p)?)
                     .map_err(|err| error!("Can't set config for {} : {}", config.path.join("/"), err))
@@ -327,19 +975,24 @@ impl StateParser {
         }
     }
 
-    fn parse_param_set_params(&mut self, config: &PathMap, num: i32) -> Result<Option<MandatoryParams>> {
-        let p = format!("p{}", num);
-        match config.get_vec(&p) {
+    fn parse_param_set_params(
+        &mut self,
+        config: &PathMap,
+        num: i32,
+        f: impl FnOnce(MandatoryParams) -> ConfigParamEnum
+    ) -> Result<()> {
+        let p = config_param_key(num);
+        match config.get_vec(p) {
             Ok(vec) => {
                 let mut params = MandatoryParams::default();
                 vec.iter().try_for_each(|n| params.add_key(&n.as_uint()?))?;
-                Ok(Some(params))
+                self.extra.config.set_config(f(params))
             }
             Err(err) => {
                 if self.is_need(num) {
                     Err(err)
                 } else {
-                    Ok(None)
+                    Ok(())
                 }
             }
         }
@@ -361,11 +1014,6 @@ impl StateParser {
         ))
     }
 
-    fn parse_block_limits(&mut self, config: &PathMap) -> Result<()> {
-        self.parse_parameter(config, 22, |p| Ok(ConfigParamEnum::ConfigParam22(Self::parse_block_limits_struct(p)?)))?;
-        self.parse_parameter(config, 23, |p| Ok(ConfigParamEnum::ConfigParam23(Self::parse_block_limits_struct(p)?)))
-    }
-
     fn parse_msg_forward_prices_struct(param: &PathMap) -> Result<MsgForwardPrices> {
         Ok(MsgForwardPrices {
             lump_price:       param.get_num("lump_price")? as u64,
@@ -377,11 +1025,6 @@ impl StateParser {
         })
     }
 
-    fn parse_msg_forward_prices(&mut self, config: &PathMap) -> Result<()> {
-        self.parse_parameter(config, 24, |p| Ok(ConfigParamEnum::ConfigParam24(Self::parse_msg_forward_prices_struct(p)?)))?;
-        self.parse_parameter(config, 25, |p| Ok(ConfigParamEnum::ConfigParam25(Self::parse_msg_forward_prices_struct(p)?)))
-    }
-
     fn parse_gas_limits_struct(param: &PathMap) -> Result<GasLimitsPrices> {
         Ok(GasLimitsPrices {
             gas_price:         param.get_num("gas_price")? as u64,
@@ -397,19 +1040,14 @@ impl StateParser {
         })
     }
 
-    fn parse_gas_limits(&mut self, config: &PathMap) -> Result<()> {
-        self.parse_parameter(config, 20, |p| Ok(ConfigParamEnum::ConfigParam20(Self::parse_gas_limits_struct(p)?)))?;
-        self.parse_parameter(config, 21, |p| Ok(ConfigParamEnum::ConfigParam21(Self::parse_gas_limits_struct(p)?)))
-    }
-
-    fn parse_storage_prices(&mut self, config: &PathMap) -> Result<()> {
-        self.parse_array(config, 18, |p18| {
+    fn parse_storage_prices(&mut self, config: &PathMap, num: i32) -> Result<()> {
+        self.parse_array(config, num, |p18| {
             let mut map = ConfigParam18Map::default();
             let mut index = 0u32;
             p18.iter().try_for_each::<_, Result<_>>(|value| {
                 let p = PathMap::cont(config, "p18", value)?;
                 let p = StoragePrices {
-                    utime_since:      p.get_num("utime_since")? as u32,
+                    utime_since:      Self::parse_storage_prices_since(&p)?,
                     bit_price_ps:     p.get_num("bit_price_ps")? as u64,
                     cell_price_ps:    p.get_num("cell_price_ps")? as u64,
                     mc_bit_price_ps:  p.get_num("mc_bit_price_ps")? as u64,
@@ -423,8 +1061,36 @@ impl StateParser {
         })
     }
 
-    fn parse_mesh_config(&mut self, config: &PathMap) -> Result<()> {
-        self.parse_array(config, 58, |p58| {
+    // `utime_since` or its RFC 3339 form `utime_since_string` written for the reviews,
+    // both must give the same time
+    fn parse_storage_prices_since(p: &PathMap) -> Result<u32> {
+        let string = match p.has_field("utime_since_string") {
+            true => {
+                let string = p.get_str("utime_since_string")?;
+                let since = rfc3339_to_unix_time(string).ok_or_else(|| error!(
+                    "{}/utime_since_string must be the RFC 3339 time, e.g. `2023-03-01T12:00:00Z`",
+                    p.path.join("/")
+                ))?;
+                Some((string, since))
+            }
+            false => None
+        };
+        match string {
+            Some((string, since)) if p.has_field("utime_since") => {
+                let utime_since = p.get_num("utime_since")? as u32;
+                if utime_since != since {
+                    fail!("{}/utime_since_string {} doesn't match utime_since {}",
+                        p.path.join("/"), string, utime_since)
+                }
+                Ok(utime_since)
+            }
+            Some((_, since)) => Ok(since),
+            None => Ok(p.get_num("utime_since")? as u32)
+        }
+    }
+
+    fn parse_mesh_config(&mut self, config: &PathMap, num: i32) -> Result<()> {
+        self.parse_array(config, num, |p58| {
             let mut map = MeshConfig::default();
             p58.iter().try_for_each::<_, Result<_>>(|value| {
                 let p = PathMap::cont(config, "p58", value)?;
@@ -433,14 +1099,14 @@ impl StateParser {
                 if let Ok(vector) = p.get_vec("hardforks") {
                     for hf in vector {
                         let p = PathMap::cont(&p, "hardforks", hf)?;
-                        hardforks.push(parse_separated_block_id_ext(&p)?);
+                        hardforks.push(parse_nested_block_id_ext(&p)?);
                     }
                 }
                 let nw_cfg = ConnectedNwConfig {
-                    zerostate: parse_separated_block_id_ext(&p.get_obj("zerostate")?)?,
-                    is_active: p.get_bool("is_active")?,
+                    zerostate: parse_nested_block_id_ext(&p.get_obj("zerostate")?)?,
+                    is_active: p.get_bool_lenient("is_active")?,
                     currency_id: p.get_num("currency_id")? as u32,
-                    init_block: parse_separated_block_id_ext(&p.get_obj("init_block")?)?,
+                    init_block: parse_nested_block_id_ext(&p.get_obj("init_block")?)?,
                     emergency_guard_addr: p.get_uint256("emergency_guard_addr")?,
                     pull_addr: p.get_uint256("pull_addr")?,
                     minter_addr: p.get_uint256("minter_addr")?,
@@ -453,14 +1119,37 @@ impl StateParser {
         })
     }
 
-    fn parse_param_set(&mut self, config: &PathMap) -> Result<()> {
-        if let Some(mandatory_params) = self.parse_param_set_params(config, 9)? {
-            self.extra.config.set_config(ConfigParamEnum::ConfigParam9(ConfigParam9 {mandatory_params} ))?;
+    // either `masterchain_block_fee` and `basechain_block_fee` or the `workchain_fees` list;
+    // BlockCreateFees has no other workchains, so only -1 and 0 are accepted in the list
+    fn parse_block_create_fees(p14: &PathMap) -> Result<BlockCreateFees> {
+        if !p14.has_field("workchain_fees") {
+            return Ok(BlockCreateFees {
+                masterchain_block_fee: p14.get_grams("masterchain_block_fee")?,
+                basechain_block_fee: p14.get_grams("basechain_block_fee")?,
+            })
         }
-        if let Some(critical_params) = self.parse_param_set_params(config, 10)? {
-            self.extra.config.set_config(ConfigParamEnum::ConfigParam10(ConfigParam10 {critical_params} ))?;
+        if p14.has_field("masterchain_block_fee") || p14.has_field("basechain_block_fee") {
+            fail!("{} must not have both `workchain_fees` and the block fee fields", p14.path.join("/"))
         }
-        Ok(())
+        let mut fees = BlockCreateFees::default();
+        let mut seen = Vec::new();
+        for item in p14.get_vec("workchain_fees")? {
+            let item = PathMap::cont(p14, "workchain_fees", item)?;
+            let workchain_id = item.get_num("workchain_id")? as i32;
+            if seen.contains(&workchain_id) {
+                fail!("{}/workchain_id {} is duplicated", item.path.join("/"), workchain_id)
+            }
+            seen.push(workchain_id);
+            match workchain_id {
+                -1 => fees.masterchain_block_fee = item.get_grams("fee")?,
+                0 => fees.basechain_block_fee = item.get_grams("fee")?,
+                _ => fail!(
+                    "{}/workchain_id {} is not supported, the block create fees are set \
+                    for the masterchain and the basechain only", item.path.join("/"), workchain_id
+                )
+            }
+        }
+        Ok(fees)
     }
 
     fn parse_critical_params(params: &PathMap) -> Result<ConfigProposalSetup> {
@@ -476,8 +1165,8 @@ impl StateParser {
         })
     }
 
-    fn parse_p11(&mut self, config: &PathMap) -> Result<()> {
-        self.parse_parameter(config, 11, |p11| {
+    fn parse_p11(&mut self, config: &PathMap, num: i32) -> Result<()> {
+        self.parse_parameter(config, num, |p11| {
             let normal_params = Self::parse_critical_params(&p11.get_obj("normal_params")?)?;
             let critical_params = Self::parse_critical_params(&p11.get_obj("critical_params")?)?;
             let p11 = ConfigParam11::new(&normal_params, &critical_params)?;
@@ -485,42 +1174,13 @@ impl StateParser {
         })
     }
 
-    fn parse_p12(&mut self, config: &PathMap) -> Result<()> {
-        self.parse_array(config, 12, |p12| {
+    fn parse_p12(&mut self, config: &PathMap, num: i32) -> Result<()> {
+        self.parse_array(config, num, |p12| {
             let mut workchains = Workchains::default();
             p12.iter().try_for_each(|wc_info| {
                 let wc_info = PathMap::cont(config, "p12", wc_info)?;
-                let mut descr = WorkchainDescr::default();
-                let workchain_id = wc_info.get_num("workchain_id")? as u32;
-                descr.enabled_since = wc_info.get_num("enabled_since")? as u32;
-                descr.set_min_split(wc_info.get_num("min_split")? as u8)?;
-                descr.set_max_split(wc_info.get_num("max_split")? as u8)?;
-                descr.flags = wc_info.get_num("flags")? as u16;
-                descr.active = wc_info.get_bool("active")?;
-                descr.accept_msgs = wc_info.get_bool("accept_msgs")?;
-                descr.zerostate_root_hash = wc_info.get_uint256("zerostate_root_hash")?;
-                descr.zerostate_file_hash = wc_info.get_uint256("zerostate_file_hash")?;
-                descr.version = wc_info.get_num("version")? as u32;
-                // TODO: check here
-                descr.format = match wc_info.get_bool("basic")? {
-                    true => {
-                        let vm_version = wc_info.get_num("vm_version")? as i32;
-                        let vm_mode    = wc_info.get_num("vm_mode"   )? as u64;
-                        WorkchainFormat::Basic(WorkchainFormat1::with_params(vm_version, vm_mode))
-                    }
-                    false => {
-                        let min_addr_len      = wc_info.get_num("min_addr_len")? as u16;
-                        let max_addr_len      = wc_info.get_num("max_addr_len")? as u16;
-                        let addr_len_step     = wc_info.get_num("addr_len_step")? as u16;
-                        let workchain_type_id = wc_info.get_num("workchain_type_id")? as u32;
-                        WorkchainFormat::Extended(
-                            WorkchainFormat0::with_params(
-                                min_addr_len, max_addr_len, addr_len_step, workchain_type_id
-                            )?
-                        )
-                    }
-                };
-                workchains.set(&workchain_id, &descr)
+                let (workchain_id, descr) = parse_workchain_descr(&wc_info)?;
+                workchains.set(&(workchain_id as u32), &descr)
             })?;
             Ok(ConfigParamEnum::ConfigParam12(ConfigParam12 {workchains}))
         })
@@ -528,8 +1188,9 @@ impl StateParser {
 
     fn parse_catchain_config(p28: &PathMap) -> Result<ConfigParamEnum> {
         Ok(ConfigParamEnum::ConfigParam28(CatchainConfig {
-            shuffle_mc_validators:     p28.get_bool("shuffle_mc_validators")?,
-            isolate_mc_validators:     p28.get_bool("isolate_mc_validators").unwrap_or_default(),
+            shuffle_mc_validators:     p28.get_bool_lenient("shuffle_mc_validators")?,
+            // absent in the dumps of the older networks, but a malformed one is an error
+            isolate_mc_validators:     p28.get_bool_lenient_opt("isolate_mc_validators")?.unwrap_or_default(),
             mc_catchain_lifetime:      p28.get_num("mc_catchain_lifetime")? as u32,
             shard_catchain_lifetime:   p28.get_num("shard_catchain_lifetime")? as u32,
             shard_validators_lifetime: p28.get_num("shard_validators_lifetime")? as u32,
@@ -539,7 +1200,7 @@ impl StateParser {
 
     fn parse_consensus_config(p29: &PathMap) -> Result<ConfigParamEnum> {
         Ok(ConfigParamEnum::ConfigParam29(ConfigParam29 {consensus_config: ConsensusConfig {
-            new_catchain_ids:        p29.get_bool("new_catchain_ids")?,
+            new_catchain_ids:        p29.get_bool_lenient("new_catchain_ids")?,
             round_candidates:        p29.get_num("round_candidates")? as u32,
             next_candidate_delay_ms: p29.get_num("next_candidate_delay_ms")? as u32,
             consensus_timeout_ms:    p29.get_num("consensus_timeout_ms")? as u32,
@@ -559,6 +1220,53 @@ impl StateParser {
         }))
     }
 
+    fn parse_fast_finality_config(p61: &PathMap, defaults: bool) -> Result<ConfigParamEnum> {
+        if defaults {
+            let mut ff_config = FastFinalityConfig::default();
+            p61.get_u32("split_merge_interval", &mut ff_config.split_merge_interval);
+            p61.get_u32("collator_range_len", &mut ff_config.collator_range_len);
+            p61.get_u32("lost_collator_timeout", &mut ff_config.lost_collator_timeout);
+            p61.get_u32("mempool_validators_count", &mut ff_config.mempool_validators_count);
+            p61.get_u32("mempool_rotated_count", &mut ff_config.mempool_rotated_count);
+            p61.get_u16("unreliability_fine", &mut ff_config.unreliability_fine);
+            p61.get_u16("unreliability_weak_fading", &mut ff_config.unreliability_weak_fading);
+            p61.get_u16("unreliability_strong_fading", &mut ff_config.unreliability_strong_fading);
+            p61.get_u16("unreliability_max", &mut ff_config.unreliability_max);
+            p61.get_u16("unreliability_weight", &mut ff_config.unreliability_weight);
+            p61.get_u16("familiarity_collator_fine", &mut ff_config.familiarity_collator_fine);
+            p61.get_u16("familiarity_msgpool_fine", &mut ff_config.familiarity_msgpool_fine);
+            p61.get_u16("familiarity_fading", &mut ff_config.familiarity_fading);
+            p61.get_u16("familiarity_max", &mut ff_config.familiarity_max);
+            p61.get_u16("familiarity_weight", &mut ff_config.familiarity_weight);
+            p61.get_u16("busyness_collator_fine", &mut ff_config.busyness_collator_fine);
+            p61.get_u16("busyness_msgpool_fine", &mut ff_config.busyness_msgpool_fine);
+            p61.get_u16("busyness_weight", &mut ff_config.busyness_weight);
+            p61.get_u8("candidates_percentile", &mut ff_config.candidates_percentile);
+            return Ok(ConfigParamEnum::ConfigParam61(ff_config))
+        }
+        Ok(ConfigParamEnum::ConfigParam61(FastFinalityConfig {
+            split_merge_interval: p61.get_num("split_merge_interval")? as u32,
+            collator_range_len: p61.get_num("collator_range_len")? as u32,
+            lost_collator_timeout: p61.get_num("lost_collator_timeout")? as u32,
+            mempool_validators_count: p61.get_num("mempool_validators_count")? as u32,
+            mempool_rotated_count: p61.get_num("mempool_rotated_count")? as u32,
+            unreliability_fine: p61.get_num("unreliability_fine")? as u16,
+            unreliability_weak_fading: p61.get_num("unreliability_weak_fading")? as u16,
+            unreliability_strong_fading: p61.get_num("unreliability_strong_fading")? as u16,
+            unreliability_max: p61.get_num("unreliability_max")? as u16,
+            unreliability_weight: p61.get_num("unreliability_weight")? as u16,
+            familiarity_collator_fine: p61.get_num("familiarity_collator_fine")? as u16,
+            familiarity_msgpool_fine: p61.get_num("familiarity_msgpool_fine")? as u16,
+            familiarity_fading: p61.get_num("familiarity_fading")? as u16,
+            familiarity_max: p61.get_num("familiarity_max")? as u16,
+            familiarity_weight: p61.get_num("familiarity_weight")? as u16,
+            busyness_collator_fine: p61.get_num("busyness_collator_fine")? as u16,
+            busyness_msgpool_fine: p61.get_num("busyness_msgpool_fine")? as u16,
+            busyness_weight: p61.get_num("busyness_weight")? as u16,
+            candidates_percentile: p61.get_num("candidates_percentile")? as u8,
+        }))
+    }
+
     fn parse_smft_params(p62: &PathMap) -> Result<ConfigParamEnum> {
         Ok(ConfigParamEnum::ConfigParam62(SmftParams {
             min_forwarding_neighbours_count: p62.get_num("min_forwarding_neighbours_count")? as u32,
@@ -596,22 +1304,22 @@ impl StateParser {
         config.get_vec("list").and_then(|p| {
             p.iter().try_for_each::<_, Result<_>>(|p| {
                 let p = PathMap::cont(config, "p", p)?;
-                let public_key = hex::decode(p.get_str("public_key")?)?;
-                let weight = p.get_num("weight")? as u64;
-                let adnl_addr = if let Ok(adnl_addr) = p.get_uint256("adnl_addr") {
-                    Some(adnl_addr)
+                let public_key = p.get_pubkey("public_key")?;
+                let weight = p.get_u64_radix("weight")?;
+                let adnl_addr = if p.has_field("adnl_addr") {
+                    Some(p.get_uint256("adnl_addr")?)
                 } else {
                     None
                 };
-                let bls_public_key = if let Ok(bls_public_key) = p.get_str("bls_public_key") {
-                    let bls_public_key = hex::decode(bls_public_key)?;
+                let bls_public_key = if p.has_field("bls_public_key") {
+                    let bls_public_key = hex::decode(p.get_str("bls_public_key")?)?;
                     Some(bls_public_key.as_slice().try_into()?)
                 } else { 
                     None 
                 };
 
                 let descr = ValidatorDescr::with_params(
-                    SigPubKey::from_bytes(&public_key)?,
+                    public_key,
                     weight,
                     adnl_addr,
                     bls_public_key
@@ -627,248 +1335,349 @@ impl StateParser {
         Ok(validator_set)
     }
 
-    pub fn parse_config(&mut self, config: &PathMap) -> Result<()> {
-        self.parse_uint256(config, 0, |config_addr | Ok(ConfigParamEnum::ConfigParam0(ConfigParam0 {config_addr} )))?;
-        self.parse_uint256(config, 1, |elector_addr| Ok(ConfigParamEnum::ConfigParam1(ConfigParam1 {elector_addr} )))?;
-        self.parse_uint256(config, 2, |minter_addr | Ok(ConfigParamEnum::ConfigParam2(ConfigParam2 {minter_addr} )))?;
-        self.parse_uint256(config, 3, |fee_collector_addr | Ok(ConfigParamEnum::ConfigParam3(ConfigParam3 {fee_collector_addr} )))?;
-        self.parse_uint256(config, 4, |dns_root_addr | Ok(ConfigParamEnum::ConfigParam4(ConfigParam4 {dns_root_addr} )))?;
-        self.parse_uint256(config, 5, |owner_addr | Ok(ConfigParamEnum::ConfigParam5(ConfigParam5 {owner_addr} )))?;
-
-        self.parse_parameter(config, 6, |value| {
-            Ok(ConfigParamEnum::ConfigParam6(ConfigParam6 {
-                mint_new_price: value.get_grams("mint_new_price")?,
-                mint_add_price: value.get_grams("mint_add_price")?,
-            }))
-        })?;
+    // p32-p37 are given by the fields of the set or by the `boc` of the `ValidatorSet` cell,
+    // both forms must give the same cell
+    fn parse_validator_set_or_boc(
+        config: &PathMap,
+        parse: impl FnOnce(&PathMap) -> Result<ValidatorSet>,
+    ) -> Result<ValidatorSet> {
+        if config.has_field("boc") && !config.has_field("utime_since") && !config.has_field("list") {
+            return ValidatorSet::construct_from_cell(read_single_root_boc(config.get_base64("boc")?)?)
+        }
+        let validators = parse(config)?;
+        if config.has_field("boc") {
+            let boc_cell = read_single_root_boc(config.get_base64("boc")?)?;
+            if boc_cell.repr_hash() != validators.serialize()?.repr_hash() {
+                fail!("{}/boc doesn't match the validator set fields", config.path.join("/"))
+            }
+        }
+        Ok(validators)
+    }
 
-        self.parse_array(config, 7, |p7| {
-            let mut to_mint = ExtraCurrencyCollection::default();
-            p7.iter().try_for_each(|currency| {
-                let currency = PathMap::cont(config, "p7", currency)?;
-                let value = if let Ok(value) = currency.get_str("value_dec") {
-                    value.parse()?
-                } else {
-                    currency.get_str("value")?.parse()?
-                };
-                to_mint.set(
-                    &(currency.get_num("currency")? as u32),
-                    &value
-                )
-            })?;
-            Ok(ConfigParamEnum::ConfigParam7(ConfigParam7 {to_mint} ))
-        })?;
-
-        self.parse_parameter(config, 8, |p8| {
-            Ok(ConfigParamEnum::ConfigParam8(ConfigParam8 { global_version: GlobalVersion {
-                version: p8.get_num("version")? as u32,
-                capabilities: p8.get_num("capabilities")? as u64,
-            }}))
-        })?;
-
-        self.parse_param_set(config)?; // p9 p10
-        self.parse_p11(config)?;
-        self.parse_p12(config)?;
-
-        self.parse_parameter(config, 13, |p13| {
-            let cell = read_single_root_boc(p13.get_base64("boc")?)?;
-            Ok(ConfigParamEnum::ConfigParam13(ConfigParam13 { cell }))
-        })?;
-        self.parse_parameter(config, 14, |p14| {
-            Ok(ConfigParamEnum::ConfigParam14(ConfigParam14 {
-                block_create_fees: BlockCreateFees {
-                    masterchain_block_fee: p14.get_grams("masterchain_block_fee")?,
-                    basechain_block_fee: p14.get_grams("basechain_block_fee")?,
-            }}))
-        })?;
-
-        self.parse_parameter(config, 15, |p15| {
-            Ok(ConfigParamEnum::ConfigParam15(ConfigParam15 {
-                validators_elected_for: p15.get_num("validators_elected_for")? as u32,
-                elections_start_before: p15.get_num("elections_start_before")? as u32,
-                elections_end_before:   p15.get_num("elections_end_before")? as u32,
-                stake_held_for:         p15.get_num("stake_held_for")? as u32,
-            }))
-        })?;
-
-        self.parse_parameter(config, 16, |p16| {
-            Ok(ConfigParamEnum::ConfigParam16(ConfigParam16 {
-                min_validators:      p16.get_num16("min_validators")?.into(),
-                max_validators:      p16.get_num16("max_validators")?.into(),
-                max_main_validators: p16.get_num16("max_main_validators")?.into(),
-            }))
-        })?;
-
-        self.parse_parameter(config, 17, |p17| {
-            Ok(ConfigParamEnum::ConfigParam17(ConfigParam17 {
-                min_stake:        p17.get_grams("min_stake")?,
-                max_stake:        p17.get_grams("max_stake")?,
-                min_total_stake:  p17.get_grams("min_total_stake")?,
-                max_stake_factor: p17.get_num("max_stake_factor")? as u32,
-            }))
-        })?;
+    // the addresses are bare hex or `-1:hex` in any case, the same address written the same way
+    // is skipped, written differently is an error
+    fn parse_fundamental_smc_addresses(config: &PathMap, p31: &[Value]) -> Result<FundamentalSmcAddresses> {
+        let path = config.path.join("/");
+        let mut seen = HashMap::<UInt256, &str>::new();
+        let mut addresses = FundamentalSmcAddresses::default();
+        for item in p31 {
+            let text = item.as_str()
+                .ok_or_else(|| error!("{}/p31 must be the vector of addresses but {} given", path, item))?;
+            let hex = match text.split_once(':') {
+                Some(("-1", hex)) => hex,
+                Some((workchain_id, _)) => fail!(
+                    "{}/p31 must have the masterchain addresses but {} has the workchain {}", path, text, workchain_id
+                ),
+                None => text
+            };
+            let address = UInt256::from_str(hex)
+                .map_err(|err| error!("{}/p31 must have the addresses in hex format but {} given : {}", path, text, err))?;
+            match seen.get(&address) {
+                Some(prev) if *prev == text => continue,
+                Some(prev) => fail!("{}/p31 has the address {} twice as {} and {}", path, address.as_hex_string(), prev, text),
+                None => ()
+            }
+            addresses.add_key(&address)?;
+            seen.insert(address, text);
+        }
+        Ok(addresses)
+    }
 
-        self.parse_storage_prices(config)?;     // p18
-        self.parse_gas_limits(config)?;        // p20 p21
-        self.parse_block_limits(config)?;      // p22 p23
-        self.parse_msg_forward_prices(config)?;// p24 p25
-        self.parse_parameter(config, 28, Self::parse_catchain_config)?;
-        self.parse_parameter(config, 29, Self::parse_consensus_config)?;
-        self.parse_parameter(config, 30, Self::parse_delector_params)?;
-
-        self.parse_array(config, 31, |p31| {
-            let mut fundamental_smc_addr = FundamentalSmcAddresses::default();
-            p31.iter().try_for_each(|n| fundamental_smc_addr.add_key(&n.as_uint256()?))?;
-            Ok(ConfigParamEnum::ConfigParam31(ConfigParam31 {fundamental_smc_addr} ))
-        })?;
+    // p13 is given by `deposit`, `bit_price` and `cell_price` or by the `boc` of any cell,
+    // both forms must give the same cell
+    fn parse_complaint_pricing(p13: &PathMap) -> Result<Cell> {
+        if !p13.has_field("deposit") && !p13.has_field("deposit_dec") {
+            return read_single_root_boc(p13.get_base64("boc")?)
+        }
+        let mut builder = BuilderData::new();
+        builder.append_u8(COMPLAINT_PRICING_TAG)?;
+        p13.get_grams("deposit")?.write_to(&mut builder)?;
+        p13.get_grams("bit_price")?.write_to(&mut builder)?;
+        p13.get_grams("cell_price")?.write_to(&mut builder)?;
+        let cell = builder.into_cell()?;
+        if p13.has_field("boc") {
+            let boc_cell = read_single_root_boc(p13.get_base64("boc")?)?;
+            if boc_cell.repr_hash() != cell.repr_hash() {
+                fail!("{}/boc doesn't match deposit, bit_price and cell_price", p13.path.join("/"))
+            }
+        }
+        Ok(cell)
+    }
 
-        self.parse_parameter(config, 32, |p| Ok(ConfigParamEnum::ConfigParam32(ConfigParam32{prev_validators: Self::parse_validator_set(p)?})))?;
-        self.parse_parameter(config, 33, |p| Ok(ConfigParamEnum::ConfigParam33(ConfigParam33{prev_temp_validators: Self::parse_validator_set(p)?})))?;
+    pub fn parse_config(&mut self, config: &PathMap) -> Result<()> {
+        // one pass over the keys, then the present and the mandatory parameters are parsed
+        // in ascending order, the same order the errors were reported in when probing every key
+        let mut params = self.mandatory_params;
+        for (name, _) in config.iter() {
+            if let Some(num) = config_param_number(name) {
+                params |= 1u64 << num;
+            }
+        }
+        while params != 0 {
+            let num = params.trailing_zeros() as i32;
+            params &= params - 1;
+            self.dispatch_config_param(config, num)?;
+        }
+        Ok(())
+    }
 
-        self.parse_parameter(config, 34, |p34| {
-            let mut list = vec![];
-            p34.get_vec("list").and_then(|p| p.iter().try_for_each::<_, Result<()>>(|p| {
-                let p = PathMap::cont(config, "p34", p)?;
-                let bls_public_key = if let Ok(bls_public_key) = p.get_str("bls_public_key") {
-                    if bls_public_key.len() != 96 {
-                        fail!("Invalid BLS public key length {}", bls_public_key.len());
-                    }
-                    let bls_public_key = hex::decode(bls_public_key)?;
-                    Some(bls_public_key.as_slice().try_into()?)
-                } else {
-                    None
-                };
+    /// Installs the config params dictionary from `config_boc` as is, if `config` is given too
+    /// it is parsed and must give the same dictionary cell
+    fn parse_config_boc(&mut self, master: &PathMap) -> Result<()> {
+        let cell = read_single_root_boc(master.get_base64("config_boc")?)?;
+        if master.has_field("config") {
+            self.parse_config(&master.get_obj("config")?)?;
+            if self.extra.config.config_params.data().map(|data| data.repr_hash()) != Some(cell.repr_hash()) {
+                fail!("{}/config_boc doesn't match {}/config", master.path.join("/"), master.path.join("/"))
+            }
+        }
+        self.extra.config.config_params = HashmapE::with_hashmap(32, Some(cell));
+        let mut params = self.mandatory_params;
+        while params != 0 {
+            let num = params.trailing_zeros();
+            params &= params - 1;
+            if !self.extra.config.config_present(num)? {
+                fail!("{}/config_boc must have the parameter p{}", master.path.join("/"), num)
+            }
+        }
+        Ok(())
+    }
 
-                list.push(ValidatorDescr::with_params(
-                    p.get_str("public_key")?.parse()?,
-                    p.get_num("weight")? as u64,
-                    None,
-                    bls_public_key,
-                ));
+    fn dispatch_config_param(&mut self, config: &PathMap, num: i32) -> Result<()> {
+        match num {
+            // `auto` is resolved by parse_state_with_report from `master.config_addr`
+            0 if matches!(config.get_str("p0"), Ok("auto")) => {
+                self.auto_config_addr = Some(format!("{}/p0", config.path.join("/")));
                 Ok(())
-            }))?;
-            let cur_validators = ValidatorSet::new(
-                p34.get_num("utime_since")? as u32,
-                p34.get_num("utime_until")? as u32,
-                p34.get_num("main")? as u16,
-                list
-            )?;
-            Ok(ConfigParamEnum::ConfigParam34(ConfigParam34 {cur_validators}))
-        })?;
-
-        self.parse_parameter(config, 35, |p| Ok(ConfigParamEnum::ConfigParam35(ConfigParam35{cur_temp_validators: Self::parse_validator_set(p)?})))?;
-        self.parse_parameter(config, 36, |p| Ok(ConfigParamEnum::ConfigParam36(ConfigParam36{next_validators: Self::parse_validator_set(p)?})))?;
-        self.parse_parameter(config, 37, |p| Ok(ConfigParamEnum::ConfigParam37(ConfigParam37{next_temp_validators: Self::parse_validator_set(p)?})))?;
-
-        self.parse_array(config, 39, |p39| {
-            let mut validator_keys = ValidatorKeys::default();
-
-            p39.iter().try_for_each::<_, Result<()>>(|p| {
-                let p = PathMap::cont(config, "p39", p)?;
-
-                let key = p.get_uint256("map_key")?;
-                let adnl_addr = p.get_uint256("adnl_addr")?;
-                let temp_public_key = hex::decode(p.get_str("temp_public_key")?)?;
-                let seqno = p.get_num("seqno")? as u32;
-                let valid_until = p.get_num("valid_until")? as u32;
-                let signature_r = p.get_str("signature_r")?;
-                let signature_s = p.get_str("signature_s")?;
-
-                let pk = ValidatorTempKey::with_params(
-                    adnl_addr,
-                    SigPubKey::from_bytes(&temp_public_key)?,
-                    seqno,
-                    valid_until,
-                );
-                let sk = CryptoSignature::from_r_s_str(signature_r, signature_s)?;
-                validator_keys.set(&key, &ValidatorSignedTempKey::with_key_and_signature(pk, sk))?;
-                Ok(())
-            })?;
-
-            Ok(ConfigParamEnum::ConfigParam39(ConfigParam39 { validator_keys }))
-        })?;
+            }
+            0 => self.parse_uint256(config, 0, |config_addr | Ok(ConfigParamEnum::ConfigParam0(ConfigParam0 {config_addr} ))),
+            1 => self.parse_uint256(config, 1, |elector_addr| Ok(ConfigParamEnum::ConfigParam1(ConfigParam1 {elector_addr} ))),
+            2 => self.parse_uint256(config, 2, |minter_addr | Ok(ConfigParamEnum::ConfigParam2(ConfigParam2 {minter_addr} ))),
+            3 => self.parse_uint256(config, 3, |fee_collector_addr | Ok(ConfigParamEnum::ConfigParam3(ConfigParam3 {fee_collector_addr} ))),
+            4 => self.parse_uint256(config, 4, |dns_root_addr | Ok(ConfigParamEnum::ConfigParam4(ConfigParam4 {dns_root_addr} ))),
+            5 => self.parse_uint256(config, 5, |owner_addr | Ok(ConfigParamEnum::ConfigParam5(ConfigParam5 {owner_addr} ))),
+            6 => self.parse_parameter(config, 6, |value| {
+                Ok(ConfigParamEnum::ConfigParam6(ConfigParam6 {
+                    mint_new_price: value.get_grams("mint_new_price")?,
+                    mint_add_price: value.get_grams("mint_add_price")?,
+                }))
+            }),
+            // the array of `{currency, value}` or the object keyed by the currency ids
+            7 => match config.get_item("p7") {
+                Ok(_) => {
+                    let mut to_mint = ExtraCurrencyCollection::default();
+                    parse_extra_currencies(config, "p7", &mut to_mint)?;
+                    self.extra.config.set_config(ConfigParamEnum::ConfigParam7(ConfigParam7 {to_mint}))
+                        .map_err(|err| error!("Can't set config for {} : {}", config.path.join("/"), err))
+                }
+                Err(err) if self.is_need(7) => fail!("parameter p7 not found: {}", err),
+                Err(_) => Ok(())
+            },
+            8 => self.parse_parameter(config, 8, |p8| {
+                Ok(ConfigParamEnum::ConfigParam8(ConfigParam8 { global_version: parse_global_version(p8)? }))
+            }),
+            9 => self.parse_param_set_params(config, 9, |mandatory_params| ConfigParamEnum::ConfigParam9(ConfigParam9 {mandatory_params})),
+            10 => self.parse_param_set_params(config, 10, |critical_params| ConfigParamEnum::ConfigParam10(ConfigParam10 {critical_params})),
+            11 => self.parse_p11(config, 11),
+            12 => self.parse_p12(config, 12),
+            13 => self.parse_parameter(config, 13, |p13| {
+                Ok(ConfigParamEnum::ConfigParam13(ConfigParam13 { cell: Self::parse_complaint_pricing(p13)? }))
+            }),
+            14 => self.parse_parameter(config, 14, |p14| {
+                Ok(ConfigParamEnum::ConfigParam14(ConfigParam14 {
+                    block_create_fees: Self::parse_block_create_fees(p14)?
+                }))
+            }),
+            15 => self.parse_parameter(config, 15, |p15| {
+                Ok(ConfigParamEnum::ConfigParam15(ConfigParam15 {
+                    validators_elected_for: p15.get_num("validators_elected_for")? as u32,
+                    elections_start_before: p15.get_num("elections_start_before")? as u32,
+                    elections_end_before:   p15.get_num("elections_end_before")? as u32,
+                    stake_held_for:         p15.get_num("stake_held_for")? as u32,
+                }))
+            }),
+            16 => self.parse_parameter(config, 16, |p16| {
+                Ok(ConfigParamEnum::ConfigParam16(ConfigParam16 {
+                    min_validators:      p16.get_num16("min_validators")?.into(),
+                    max_validators:      p16.get_num16("max_validators")?.into(),
+                    max_main_validators: p16.get_num16("max_main_validators")?.into(),
+                }))
+            }),
+            17 => self.parse_parameter(config, 17, |p17| {
+                Ok(ConfigParamEnum::ConfigParam17(ConfigParam17 {
+                    min_stake:        p17.get_grams("min_stake")?,
+                    max_stake:        p17.get_grams("max_stake")?,
+                    min_total_stake:  p17.get_grams("min_total_stake")?,
+                    max_stake_factor: p17.get_num("max_stake_factor")? as u32,
+                }))
+            }),
+            18 => self.parse_storage_prices(config, 18),
+            20 => self.parse_parameter(config, 20, |p| Ok(ConfigParamEnum::ConfigParam20(Self::parse_gas_limits_struct(p)?))),
+            21 => self.parse_parameter(config, 21, |p| Ok(ConfigParamEnum::ConfigParam21(Self::parse_gas_limits_struct(p)?))),
+            22 => self.parse_parameter(config, 22, |p| Ok(ConfigParamEnum::ConfigParam22(Self::parse_block_limits_struct(p)?))),
+            23 => self.parse_parameter(config, 23, |p| Ok(ConfigParamEnum::ConfigParam23(Self::parse_block_limits_struct(p)?))),
+            24 => self.parse_parameter(config, 24, |p| Ok(ConfigParamEnum::ConfigParam24(Self::parse_msg_forward_prices_struct(p)?))),
+            25 => self.parse_parameter(config, 25, |p| Ok(ConfigParamEnum::ConfigParam25(Self::parse_msg_forward_prices_struct(p)?))),
+            28 => self.parse_parameter(config, 28, Self::parse_catchain_config),
+            29 => self.parse_parameter(config, 29, Self::parse_consensus_config),
+            30 => self.parse_parameter(config, 30, Self::parse_delector_params),
+            31 => self.parse_array(config, 31, |p31| {
+                let fundamental_smc_addr = Self::parse_fundamental_smc_addresses(config, p31)?;
+                Ok(ConfigParamEnum::ConfigParam31(ConfigParam31 {fundamental_smc_addr} ))
+            }),
+            32 => self.parse_parameter(config, 32, |p| Ok(ConfigParamEnum::ConfigParam32(ConfigParam32{prev_validators: Self::parse_validator_set_or_boc(p, Self::parse_validator_set)?}))),
+            33 => self.parse_parameter(config, 33, |p| Ok(ConfigParamEnum::ConfigParam33(ConfigParam33{prev_temp_validators: Self::parse_validator_set_or_boc(p, Self::parse_validator_set)?}))),
+            34 => self.parse_parameter(config, 34, |p34| {
+                let cur_validators = Self::parse_validator_set_or_boc(p34, |p34| {
+                    let mut list = vec![];
+                    p34.get_vec("list").and_then(|p| p.iter().try_for_each::<_, Result<()>>(|p| {
+                        let p = PathMap::cont(config, "p34", p)?;
+                        let bls_public_key = if p.has_field("bls_public_key") {
+                            let bls_public_key = p.get_str("bls_public_key")?;
+                            if bls_public_key.len() != 96 {
+                                fail!("Invalid BLS public key length {}", bls_public_key.len());
+                            }
+                            let bls_public_key = hex::decode(bls_public_key)?;
+                            Some(bls_public_key.as_slice().try_into()?)
+                        } else {
+                            None
+                        };
+
+                        list.push(ValidatorDescr::with_params(
+                            p.get_pubkey("public_key")?,
+                            p.get_u64_radix("weight")?,
+                            None,
+                            bls_public_key,
+                        ));
+                        Ok(())
+                    }))?;
+                    ValidatorSet::new(
+                        p34.get_num("utime_since")? as u32,
+                        p34.get_num("utime_until")? as u32,
+                        p34.get_num("main")? as u16,
+                        list
+                    )
+                })?;
+                Ok(ConfigParamEnum::ConfigParam34(ConfigParam34 {cur_validators}))
+            }),
+            35 => self.parse_parameter(config, 35, |p| Ok(ConfigParamEnum::ConfigParam35(ConfigParam35{cur_temp_validators: Self::parse_validator_set_or_boc(p, Self::parse_validator_set)?}))),
+            36 => self.parse_parameter(config, 36, |p| Ok(ConfigParamEnum::ConfigParam36(ConfigParam36{next_validators: Self::parse_validator_set_or_boc(p, Self::parse_validator_set)?}))),
+            37 => self.parse_parameter(config, 37, |p| Ok(ConfigParamEnum::ConfigParam37(ConfigParam37{next_temp_validators: Self::parse_validator_set_or_boc(p, Self::parse_validator_set)?}))),
+            39 => {
+                let verify_signatures = self.verify_signatures;
+                self.parse_array(config, 39, |p39| {
+                    let mut validator_keys = ValidatorKeys::default();
+
+                    p39.iter().try_for_each::<_, Result<()>>(|p| {
+                        let p = PathMap::cont(config, "p39", p)?;
+
+                        let key = p.get_uint256("map_key")?;
+                        let adnl_addr = p.get_uint256("adnl_addr")?;
+                        let temp_public_key = p.get_pubkey("temp_public_key")?;
+                        let seqno = p.get_num("seqno")? as u32;
+                        let valid_until = p.get_num("valid_until")? as u32;
+
+                        let pk = ValidatorTempKey::with_params(
+                            adnl_addr,
+                            temp_public_key,
+                            seqno,
+                            valid_until,
+                        );
+                        let sk = p.get_signature("signature")?;
+                        if verify_signatures {
+                            let hash = pk.serialize()?.repr_hash();
+                            if !pk.temp_public_key().verify_signature(hash.as_slice(), &sk) {
+                                fail!("{} has invalid signature for map_key {}", p.path.join("/"), key.as_hex_string())
+                            }
+                        }
+                        validator_keys.set(&key, &ValidatorSignedTempKey::with_key_and_signature(pk, sk))?;
+                        Ok(())
+                    })?;
 
-        let mut slashing_config = SlashingConfig::default();
-        if let Ok(p40) = config.get_obj("p40") {
-            p40.get_u32("slashing_period_mc_blocks_count", &mut slashing_config.slashing_period_mc_blocks_count);
-            p40.get_u32("resend_mc_blocks_count", &mut slashing_config.resend_mc_blocks_count);
-            p40.get_u32("min_samples_count", &mut slashing_config.min_samples_count);
-            p40.get_u32("collations_score_weight", &mut slashing_config.collations_score_weight);
-            p40.get_u32("signing_score_weight", &mut slashing_config.signing_score_weight);
-            p40.get_u32("min_slashing_protection_score", &mut slashing_config.min_slashing_protection_score);
-            p40.get_u32("z_param_numerator", &mut slashing_config.z_param_numerator);
-            p40.get_u32("z_param_denominator", &mut slashing_config.z_param_denominator);
-        }
-        self.extra.config.set_config(ConfigParamEnum::ConfigParam40(ConfigParam40 {slashing_config}))?;
-
-        self.parse_parameter(config, 42, |p42| {
-            let mut copyleft_config = ton_dev_block::ConfigCopyleft {
-                copyleft_reward_threshold: p42.get_grams("threshold")?,
-                ..Default::default() 
-            };
-            p42.get_vec("payouts").and_then(|p| {
-                p.iter().try_for_each::<_, Result<()>>(|p| {
-                    let p = PathMap::cont(config, "p42", p)?;
-                    let mut license_type = 0;
-                    p.get_u32("license_type", &mut license_type);
-                    let mut percent = 0;
-                    p.get_u32("payout_percent", &mut percent);
-                    copyleft_config.license_rates.set(&(license_type as u8), &(percent as u8))?;
-                    Ok(())
+                    Ok(ConfigParamEnum::ConfigParam39(ConfigParam39 { validator_keys }))
                 })
-            })?;
-            Ok(ConfigParamEnum::ConfigParam42(copyleft_config))
-        })?;
-
-        self.parse_array(config, 44, |p44| {
-            let mut suspended = SuspendedAddresses::new();
-
-            for address in p44 {
-                let address: MsgAddressInt = address
-                    .as_str()
-                    .ok_or_else(|| error!("address must be string"))?.parse()?;
-                suspended.add_suspended_address(
-                    address.get_workchain_id(),
-                    UInt256::construct_from(&mut address.address())?
-                )?;
             }
+            40 => self.parse_parameter(config, 40, |p40| {
+                let slashing_config = SlashingConfig {
+                    slashing_period_mc_blocks_count: p40.get_num("slashing_period_mc_blocks_count")? as u32,
+                    resend_mc_blocks_count: p40.get_num("resend_mc_blocks_count")? as u32,
+                    min_samples_count: p40.get_num("min_samples_count")? as u32,
+                    collations_score_weight: p40.get_num("collations_score_weight")? as u32,
+                    signing_score_weight: p40.get_num("signing_score_weight")? as u32,
+                    min_slashing_protection_score: p40.get_num("min_slashing_protection_score")? as u32,
+                    z_param_numerator: p40.get_num("z_param_numerator")? as u32,
+                    z_param_denominator: p40.get_num("z_param_denominator")? as u32,
+                };
+                Ok(ConfigParamEnum::ConfigParam40(ConfigParam40 {slashing_config}))
+            }),
+            42 => self.parse_parameter(config, 42, |p42| {
+                let mut copyleft_config = ton_dev_block::ConfigCopyleft {
+                    copyleft_reward_threshold: p42.get_grams("threshold")?,
+                    ..Default::default() 
+                };
+                p42.get_vec("payouts").and_then(|p| {
+                    p.iter().try_for_each::<_, Result<()>>(|p| {
+                        let p = PathMap::cont(config, "p42", p)?;
+                        let mut license_type = 0;
+                        p.get_u32("license_type", &mut license_type);
+                        let mut percent = 0;
+                        p.get_u32("payout_percent", &mut percent);
+                        copyleft_config.license_rates.set(&(license_type as u8), &(percent as u8))?;
+                        Ok(())
+                    })
+                })?;
+                Ok(ConfigParamEnum::ConfigParam42(copyleft_config))
+            }),
+            44 => self.parse_array(config, 44, |p44| {
+                let mut suspended = SuspendedAddresses::new();
+
+                for address in p44 {
+                    let address: MsgAddressInt = address
+                        .as_str()
+                        .ok_or_else(|| error!("address must be string"))?.parse()?;
+                    suspended.add_suspended_address(
+                        address.get_workchain_id(),
+                        UInt256::construct_from(&mut address.address())?
+                    )?;
+                }
 
-            Ok(ConfigParamEnum::ConfigParam44(suspended))
-        })?;
-
-        self.parse_mesh_config(config)?;     // p58
-
-        if let Ok(p61) = config.get_obj("p61") {
-            let mut ff_config = FastFinalityConfig::default();
-            p61.get_u32("split_merge_interval", &mut ff_config.split_merge_interval);
-            p61.get_u32("collator_range_len", &mut ff_config.collator_range_len);
-            p61.get_u32("lost_collator_timeout", &mut ff_config.lost_collator_timeout);
-            p61.get_u32("mempool_validators_count", &mut ff_config.mempool_validators_count);
-            p61.get_u32("mempool_rotated_count", &mut ff_config.mempool_rotated_count);
-            p61.get_u16("unreliability_fine", &mut ff_config.unreliability_fine);
-            p61.get_u16("unreliability_weak_fading", &mut ff_config.unreliability_weak_fading);
-            p61.get_u16("unreliability_strong_fading", &mut ff_config.unreliability_strong_fading);
-            p61.get_u16("unreliability_max", &mut ff_config.unreliability_max);
-            p61.get_u16("unreliability_weight", &mut ff_config.unreliability_weight);
-            p61.get_u16("familiarity_collator_fine", &mut ff_config.familiarity_collator_fine);
-            p61.get_u16("familiarity_msgpool_fine", &mut ff_config.familiarity_msgpool_fine);
-            p61.get_u16("familiarity_fading", &mut ff_config.familiarity_fading);
-            p61.get_u16("familiarity_max", &mut ff_config.familiarity_max);
-            p61.get_u16("familiarity_weight", &mut ff_config.familiarity_weight);
-            p61.get_u16("busyness_collator_fine", &mut ff_config.busyness_collator_fine);
-            p61.get_u16("busyness_msgpool_fine", &mut ff_config.busyness_msgpool_fine);
-            p61.get_u16("busyness_weight", &mut ff_config.busyness_weight);
-            p61.get_u8("candidates_percentile", &mut ff_config.candidates_percentile);
-            self.extra.config.set_config(ConfigParamEnum::ConfigParam61(ff_config))?;
+                Ok(ConfigParamEnum::ConfigParam44(suspended))
+            }),
+            58 => self.parse_mesh_config(config, 58),
+            61 => {
+                let defaults = self.p61_defaults;
+                self.parse_parameter(config, 61, |p61| Self::parse_fast_finality_config(p61, defaults))
+            }
+            62 => self.parse_parameter(config, 62, Self::parse_smft_params),
+            _ => Ok(())
         }
+    }
 
-        self.parse_parameter(config, 62, Self::parse_smft_params)?;
+    /// Parses the config parameters object, it may be wrapped as `{"config": {...}}`
+    /// or `{"master": {"config": {...}}}`, see `unwrap_config`
+    pub fn parse_config_params(mut self, config: &Map<String, Value>) -> Result<ConfigParams> {
+        self.limits.check_json_nodes(config)?;
+        let config = self.path_map(config);
+        self.parse_config(&unwrap_config(&config)?)?;
+        if let Some(path) = self.auto_config_addr {
+            fail!("{} is `auto` but it can be taken only from `master.config_addr` of the state", path)
+        }
+        // the tracker is shared with the unwrapped map, so the wrappers are checked too
+        check_unvisited_keys(&config)?;
+        Ok(self.extra.config)
+    }
 
-        Ok(())
+    pub fn parse_state_unchecked(self, map: &Map<String, Value>) -> Result<ShardStateUnsplit> {
+        self.parse_state_with_report(map).map(|(state, _)| state)
     }
 
-    fn parse_state_unchecked(mut self, map: &Map<String, Value>) -> Result<ShardStateUnsplit> {
-        let map_path = PathMap::new(map);
+    /// Same as `parse_state_unchecked` but also returns the warnings collected while parsing:
+    /// skipped accounts, mismatched account addresses and the economics check warnings
+    pub fn parse_state_with_report(
+        mut self, map: &Map<String, Value>
+    ) -> Result<(ShardStateUnsplit, Vec<crate::Diagnostic>)> {
+        self.limits.check_json_nodes(map)?;
+        let map_path = self.path_map(map);
 
         self.state.set_min_ref_mc_seqno(u32::MAX);
 
@@ -889,8 +1698,8 @@ impl StateParser {
             }
         }
 
-        match map_path.get_grams("total_balance") {
-            Ok(balance) => self.state.set_total_balance(CurrencyCollection::from_grams(balance)),
+        match parse_currency_collection(&map_path, "total_balance") {
+            Ok(balance) => self.state.set_total_balance(balance),
             Err(err) => {
                 if self.mandatory_params != 0 {
                     return Err(err)
@@ -898,85 +1707,140 @@ impl StateParser {
             }
         }
 
-        match map_path.get_obj("master") {
-            Ok(master) => {
-                let config = master.get_obj("config")?;
-                self.parse_config(&config)?;
-                match master.get_uint256("config_addr") {
-                    Ok(addr) => self.extra.config.config_addr = addr,
-                    Err(err) => {
-                        if self.mandatory_params != 0 {
-                            return Err(err)
-                        }
-                    }
+        // absent fields keep the defaults (0 and false) the existing states are hashed with
+        for name in ["overload_history", "underload_history"] {
+            if map_path.has_field(name) || map_path.has_field(&(name.to_string() + "_dec")) {
+                let history = map_path.get_u64_radix(name)?;
+                match name {
+                    "overload_history" => self.state.set_overload_history(history),
+                    _ => self.state.set_underload_history(history),
                 }
-                match master.get_num("validator_list_hash_short") {
-                    Ok(v) => self.extra.validator_info.validator_list_hash_short = v as u32,
-                    Err(err) => {
-                        if self.mandatory_params != 0 {
-                            return Err(err)
-                        }
+            }
+        }
+        if let Some(before_split) = map_path.get_bool_opt("before_split")? {
+            self.state.set_before_split(before_split);
+        }
+
+        let raw_master = ["master_boc", "custom"].into_iter().find(|name| map_path.get_item(name).is_ok());
+        if let Some(name) = raw_master {
+            if map_path.get_item("master").is_ok() {
+                fail!("{} must not have both fields `{}` and `master`", map_path.path.join("/"), name)
+            }
+            let cell = read_single_root_boc(map_path.get_base64(name)?)?;
+            self.extra = McStateExtra::construct_from_cell(cell)?;
+            self.state.write_custom(Some(&self.extra))?;
+        } else if self.mandatory_params != 0 || map_path.get_item("master").is_ok() {
+            let master = map_path.get_obj("master")?;
+            if master.has_field("config_boc") {
+                self.parse_config_boc(&master)?;
+            } else {
+                self.parse_config(&map_path.get_path_obj("master.config")?)?;
+            }
+            match master.get_uint256("config_addr") {
+                Ok(addr) => self.extra.config.config_addr = addr,
+                Err(err) => {
+                    if self.mandatory_params != 0 {
+                        return Err(err)
                     }
                 }
-                match master.get_num("catchain_seqno") {
-                    Ok(v) => self.extra.validator_info.catchain_seqno = v as u32,
-                    Err(err) => {
-                        if self.mandatory_params != 0 {
-                            return Err(err)
-                        }
-                    }
+            }
+            if let Some(path) = self.auto_config_addr.take() {
+                let config_addr = master.get_uint256("config_addr")
+                    .map_err(|err| error!("{} is `auto` but the config address can't be taken : {}", path, err))?;
+                self.extra.config.set_config(ConfigParamEnum::ConfigParam0(ConfigParam0 { config_addr }))?;
+            }
+            // the fields are read from `master` or the nested `master.validator_info` object,
+            // absent ones keep the defaults (0, 0 and false) unless the parameters are mandatory
+            let info = match master.has_field("validator_info") {
+                true => Some(master.get_obj("validator_info")?),
+                false => None
+            };
+            match get_validator_info_field(&master, info.as_ref(), "validator_list_hash_short", PathMap::get_num)? {
+                Some(v) => self.extra.validator_info.validator_list_hash_short = v as u32,
+                None => if self.mandatory_params != 0 {
+                    master.get_num("validator_list_hash_short")?;
                 }
-                match master.get_bool("nx_cc_updated") {
-                    Ok(v) => self.extra.validator_info.nx_cc_updated = v,
-                    Err(err) => {
-                        if self.mandatory_params != 0 {
-                            return Err(err)
-                        }
-                    }
+            }
+            match get_validator_info_field(&master, info.as_ref(), "catchain_seqno", PathMap::get_num)? {
+                Some(v) => self.extra.validator_info.catchain_seqno = v as u32,
+                None => if self.mandatory_params != 0 {
+                    master.get_num("catchain_seqno")?;
                 }
-                match master.get_grams("global_balance") {
-                    Ok(balance) => self.extra.global_balance.grams = balance,
-                    Err(err) => {
-                        if self.mandatory_params != 0 {
-                            return Err(err)
-                        }
-                    }
+            }
+            match get_validator_info_field(&master, info.as_ref(), "nx_cc_updated", PathMap::get_bool)? {
+                Some(v) => self.extra.validator_info.nx_cc_updated = v,
+                None => if self.mandatory_params != 0 {
+                    master.get_bool("nx_cc_updated")?;
                 }
-                self.extra.after_key_block = true;
-                self.state.write_custom(Some(&self.extra))?;
             }
-            Err(err) => {
-                if self.mandatory_params != 0 {
-                    return Err(err)
+            match parse_currency_collection(&master, "global_balance") {
+                Ok(balance) => self.extra.global_balance = balance,
+                Err(err) => {
+                    if self.mandatory_params != 0 {
+                        return Err(err)
+                    }
                 }
             }
+            // the zero-state is after the key block, intermediate states usually are not
+            let after_key_block = master.get_bool_opt("after_key_block")?;
+            self.extra.after_key_block = after_key_block.unwrap_or(self.mandatory_params != 0);
+            if master.has_field("last_key_block") {
+                let last_key_block = master.get_obj("last_key_block")?;
+                self.extra.last_key_block = Some(ExtBlkRef {
+                    end_lt: last_key_block.get_num("end_lt")? as u64,
+                    seq_no: last_key_block.get_num("seq_no")? as u32,
+                    root_hash: last_key_block.get_uint256("root_hash")?,
+                    file_hash: last_key_block.get_uint256("file_hash")?,
+                });
+            }
+            self.state.write_custom(Some(&self.extra))?;
         }
 
         if let Ok(accounts) = map_path.get_vec("accounts") {
+            ParseLimits::check_items(&map_path, "accounts", "max_accounts", self.limits.max_accounts, accounts.len())?;
+            // the accounts are decoded in parallel if asked, only the insertions are serial
+            let mismatch_as_warning = self.account_mismatch_as_warning;
+            let prepare = |(index, account): (usize, &Value)| {
+                prepare_state_account(&map_path, index, account, mismatch_as_warning)
+            };
+            #[cfg(feature = "rayon")]
+            let prepared = if self.parallel_accounts {
+                use rayon::prelude::*;
+                accounts.par_iter().enumerate().map(prepare).collect::<Result<Vec<_>>>()?
+            } else {
+                accounts.iter().enumerate().map(prepare).collect::<Result<Vec<_>>>()?
+            };
+            #[cfg(not(feature = "rayon"))]
+            let prepared = accounts.iter().enumerate().map(prepare).collect::<Result<Vec<_>>>()?;
+
             let mut shard_accounts = self.state.read_accounts()?;
-            accounts.iter().try_for_each::<_, Result<()>>(|account| {
-                let account = PathMap::cont(&map_path, "accounts", account)?;
-                let account = Account::construct_from_bytes(&account.get_base64("boc")?)?;
-                if let Some(account_id) = account.get_id() {
-                    let aug = account.aug()?;
-                    let account = ShardAccount::with_params(&account, UInt256::ZERO, 0)?;
-                    shard_accounts.set_builder_serialized(
-                        account_id,
-                        &account.write_to_new_cell()?,
-                        &aug
-                    )?;
+            for prepared in prepared {
+                self.warnings.extend(prepared.warnings);
+                if let Some((account_id, cell, aug)) = prepared.account {
+                    shard_accounts.set_builder_serialized(account_id, &cell, &aug)?;
                 }
-                Ok(())
-            })?;
+            }
             self.state.write_accounts(&shard_accounts)?;
         }
 
+        if self.mandatory_params != 0 && raw_master.is_none() {
+            self.check_special_accounts(&map_path)?;
+            if self.global_balance_check {
+                self.check_global_balance(&map_path)?;
+            }
+        }
+
         if let Ok(libraries) = map_path.get_vec("libraries") {
-            libraries.iter().try_for_each::<_, Result<()>>(|library| {
+            ParseLimits::check_items(&map_path, "libraries", "max_libraries", self.limits.max_libraries, libraries.len())?;
+            libraries.iter().enumerate().try_for_each::<_, Result<()>>(|(index, library)| {
                 let library = PathMap::cont(&map_path, "libraries", library)?;
                 let id = library.get_uint256("hash")?;
                 let lib = library.get_base64("lib")?;
                 let lib = read_single_root_boc(lib)?;
+                if lib.repr_hash() != id {
+                    fail!("{}/libraries item {} has the hash {} but the lib cell hash is {}",
+                        map_path.path.join("/"), index, id.as_hex_string(), lib.repr_hash().as_hex_string())
+                }
                 let mut lib = LibDescr::new(lib);
                 let publishers = library.get_vec("publishers")?;
                 publishers.iter().try_for_each::<_, Result<()>>(|publisher| {
@@ -987,23 +1851,425 @@ impl StateParser {
             })?;
         }
 
-        Ok(self.state)
+        if self.check_economics {
+            let mut errors = Vec::new();
+            for diagnostic in crate::check_config_economics(&self.extra.config) {
+                match diagnostic.severity {
+                    crate::Severity::Error => errors.push(format!("{} : {}", diagnostic.path, diagnostic.message)),
+                    crate::Severity::Warning => self.warnings.push(diagnostic),
+                }
+            }
+            if !errors.is_empty() {
+                fail!("config economics check failed: {}", errors.join("; "))
+            }
+        }
+
+        check_unvisited_keys(&map_path)?;
+        Ok((self.state, self.warnings))
+    }
+
+    // the global balance must cover the state accounts currency by currency
+    fn check_global_balance(&mut self, root: &PathMap) -> Result<()> {
+        let global = &self.extra.global_balance;
+        let total = self.state.total_balance();
+        let path = format!("{}/master/global_balance", root.path.join("/"));
+        let mut warnings = Vec::new();
+        if global.grams.as_u128() < total.grams.as_u128() {
+            warnings.push(crate::Diagnostic::warning(path.clone(), format!(
+                "global_balance {} is less than total_balance {}", global.grams.as_u128(), total.grams.as_u128()
+            )));
+        }
+        total.other.iterate_with_keys(|currency: u32, value: VarUInteger32| {
+            let global_value = global.other.get(&currency)?.unwrap_or_default();
+            if global_value.value() < value.value() {
+                warnings.push(crate::Diagnostic::warning(path.clone(), format!(
+                    "global_balance of the currency {} is {} and less than total_balance {}",
+                    currency, global_value.value(), value.value()
+                )));
+            }
+            Ok(true)
+        })?;
+        self.warnings.extend(warnings);
+        Ok(())
+    }
+
+    // the zero-state accounts of the config, elector and minter addresses must be provided
+    fn check_special_accounts(&mut self, root: &PathMap) -> Result<()> {
+        let accounts = self.state.read_accounts()?;
+        for num in 0..=2 {
+            let (name, address) = match self.extra.config.config(num)? {
+                Some(ConfigParamEnum::ConfigParam0(p0)) => ("config", p0.config_addr),
+                Some(ConfigParamEnum::ConfigParam1(p1)) => ("elector", p1.elector_addr),
+                Some(ConfigParamEnum::ConfigParam2(p2)) => ("minter", p2.minter_addr),
+                _ => continue,
+            };
+            if accounts.account(&AccountId::from(address.clone()))?.is_some() {
+                continue
+            }
+            let path = format!("{}/master/config/p{}", root.path.join("/"), num);
+            let message = format!("the {} address -1:{} has no account in {}/accounts",
+                name, address.as_hex_string(), root.path.join("/"));
+            if self.missing_special_accounts_as_error {
+                fail!("{} : {}", path, message)
+            }
+            self.warnings.push(crate::Diagnostic::warning(path, message).with_params(&[num]));
+        }
+        Ok(())
+    }
+
+    /// Same as `parse_state_unchecked` but reads the document from the JSON text without building
+    /// the whole `Value` tree: the `accounts` items are decoded and inserted one by one as they are
+    /// read, only the other fields are kept in memory. The accounts are decoded serially
+    pub fn parse_state_streaming(self, reader: impl Read) -> Result<ShardStateUnsplit> {
+        self.parse_state_streaming_with_report(reader).map(|(state, _)| state)
+    }
+
+    /// Same as `parse_state_with_report` but streaming, see `parse_state_streaming`
+    pub fn parse_state_streaming_with_report(
+        mut self, reader: impl Read
+    ) -> Result<(ShardStateUnsplit, Vec<crate::Diagnostic>)> {
+        let mut deserializer = serde_json::Deserializer::from_reader(std::io::BufReader::new(reader));
+        let streamed = serde::Deserializer::deserialize_map(&mut deserializer, StreamingStateVisitor { parser: &self })
+            .and_then(|streamed| deserializer.end().map(|_| streamed))
+            .map_err(|err| match err.classify() {
+                serde_json::error::Category::Data => error!("{}", err),
+                _ => error!("root must be the JSON object : {}", err),
+            })?;
+        // the accounts are counted while streaming
+        self.limits.count_json_nodes(streamed.nodes, streamed.fields.values())?;
+        self.limits.max_json_nodes = usize::MAX;
+
+        // written before the fields are parsed for the zero-state checks to see them
+        if let Some(accounts) = streamed.accounts {
+            self.state.write_accounts(&accounts)?;
+        }
+        let (state, warnings) = self.parse_state_with_report(&streamed.fields)?;
+        let mut all_warnings = streamed.warnings;
+        all_warnings.extend(warnings);
+        Ok((state, all_warnings))
+    }
+
+    fn insert_streamed_account(
+        &self,
+        root: &PathMap,
+        index: usize,
+        account: &Value,
+        streamed: &mut StreamedState,
+    ) -> Result<()> {
+        ParseLimits::check_items(root, "accounts", "max_accounts", self.limits.max_accounts, index + 1)?;
+        streamed.nodes = self.limits.count_json_nodes(streamed.nodes, [account])?;
+        let prepared = prepare_state_account(root, index, account, self.account_mismatch_as_warning)?;
+        streamed.warnings.extend(prepared.warnings);
+        if let Some((account_id, cell, aug)) = prepared.account {
+            streamed.accounts.get_or_insert_with(ShardAccounts::default)
+                .set_builder_serialized(account_id, &cell, &aug)?;
+        }
+        Ok(())
     }
 }
 
-pub fn parse_config_with_mandatory_params(config: &Map<String, Value>, mandatories: &[u32]) -> Result<ConfigParams> {
-    let config = PathMap::new(config);
-    let mut parser = StateParser::new();
-    if !mandatories.is_empty() {
-        parser.mandatory_params = 0;
-        for mandatory in mandatories {
-            parser.mandatory_params |= 1u64 << mandatory;
+// the state document read by `StateParser::parse_state_streaming`: the inserted accounts
+// and the other top level fields
+struct StreamedState {
+    fields: Map<String, Value>,
+    accounts: Option<ShardAccounts>,
+    warnings: Vec<crate::Diagnostic>,
+    // JSON nodes of the root and the accounts
+    nodes: usize,
+}
+
+struct StreamingStateVisitor<'p> {
+    parser: &'p StateParser,
+}
+
+impl<'de, 'p> serde::de::Visitor<'de> for StreamingStateVisitor<'p> {
+    type Value = StreamedState;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("the state object")
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> std::result::Result<StreamedState, A::Error> {
+        let mut streamed = StreamedState { fields: Map::new(), accounts: None, warnings: Vec::new(), nodes: 1 };
+        let mut has_accounts = false;
+        while let Some(name) = map.next_key::<String>()? {
+            if name == "accounts" {
+                if has_accounts {
+                    return Err(serde::de::Error::custom("root must have the field `accounts` once"))
+                }
+                has_accounts = true;
+                map.next_value_seed(StreamingAccountsSeed { parser: self.parser, streamed: &mut streamed })?;
+            } else {
+                let value = map.next_value::<Value>()?;
+                streamed.fields.insert(name, value);
+            }
+        }
+        Ok(streamed)
+    }
+}
+
+// the `accounts` array, the items are dropped as soon as they are inserted
+struct StreamingAccountsSeed<'p, 's> {
+    parser: &'p StateParser,
+    streamed: &'s mut StreamedState,
+}
+
+impl<'de, 'p, 's> serde::de::DeserializeSeed<'de> for StreamingAccountsSeed<'p, 's> {
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 'p, 's> serde::de::Visitor<'de> for StreamingAccountsSeed<'p, 's> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("the array of the accounts")
+    }
+
+    // the same as the absent field like in `parse_state_with_report`
+    fn visit_unit<E: serde::de::Error>(self) -> std::result::Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        let root = Map::new();
+        let root = PathMap::new(&root)
+            .with_max_boc_bytes(self.parser.limits.max_boc_bytes)
+            .with_decimal_grams(self.parser.decimal_grams);
+        self.streamed.nodes += 1;
+        let mut index = 0;
+        while let Some(account) = seq.next_element::<Value>()? {
+            self.parser.insert_streamed_account(&root, index, &account, self.streamed)
+                .map_err(serde::de::Error::custom)?;
+            index += 1;
+        }
+        Ok(())
+    }
+}
+
+// Reads the field from `master` or from `validator_info`, fails if both have it with different values
+fn get_validator_info_field<'m, 'a, T: PartialEq + std::fmt::Display>(
+    master: &PathMap<'m, 'a>,
+    info: Option<&PathMap<'m, 'a>>,
+    name: &'a str,
+    read: impl Fn(&PathMap<'m, 'a>, &'a str) -> Result<T>,
+) -> Result<Option<T>> {
+    let flat = match master.has_field(name) {
+        true => Some(read(master, name)?),
+        false => None
+    };
+    let nested = match info {
+        Some(info) if info.has_field(name) => Some(read(info, name)?),
+        _ => None
+    };
+    match (flat, nested) {
+        (Some(flat), Some(nested)) if flat != nested => fail!(
+            "{}/validator_info/{} is {} but {}/{} is {}",
+            master.path.join("/"), name, nested, master.path.join("/"), name, flat
+        ),
+        (Some(value), _) | (None, Some(value)) => Ok(Some(value)),
+        (None, None) => Ok(None)
+    }
+}
+
+// the `accounts` item of the state ready for the insertion, None for AccountNone
+struct PreparedStateAccount {
+    account: Option<(AccountId, BuilderData, DepthBalanceInfo)>,
+    warnings: Vec<crate::Diagnostic>,
+}
+
+fn prepare_state_account<'m>(
+    map_path: &PathMap<'m, '_>,
+    index: usize,
+    account: &'m Value,
+    mismatch_as_warning: bool,
+) -> Result<PreparedStateAccount> {
+    let account_map = PathMap::cont(map_path, "accounts", account)?;
+    let account = if account_map.has_field("boc") {
+        Account::construct_from_bytes(&account_map.get_base64("boc")?)?
+    } else {
+        parse_expanded_account(&account_map)?
+    };
+    let path = format!("{}/accounts", map_path.path.join("/"));
+    let Some(account_id) = account.get_id() else {
+        return Ok(PreparedStateAccount {
+            account: None,
+            warnings: vec![crate::Diagnostic::warning(path, format!("item {} is AccountNone and is skipped", index))],
+        })
+    };
+    let mut warnings = Vec::new();
+    for name in ["id", "_key", "_id", "address"] {
+        if !account_map.has_field(name) {
+            continue
+        }
+        let declared = account_map.get_str(name)?;
+        if !declared_address_matches(declared, &account)? {
+            let message = format!(
+                "item {} declares {} {} but the account has the address {}",
+                index, name, declared, account.get_addr().map(|addr| addr.to_string()).unwrap_or_default()
+            );
+            if !mismatch_as_warning {
+                fail!("{} {}", path, message)
+            }
+            warnings.push(crate::Diagnostic::warning(path.clone(), message));
+        }
+    }
+    // the last transaction of the shard account, the zero one by default
+    let last_trans_hash = match account_map.has_field("last_trans_hash") {
+        true => account_map.get_uint256("last_trans_hash")?,
+        false => UInt256::ZERO
+    };
+    let last_trans_lt = match account_map.has_field("shard_last_trans_lt")
+        || account_map.has_field("shard_last_trans_lt_dec")
+    {
+        true => account_map.get_num("shard_last_trans_lt")? as u64,
+        false => 0
+    };
+    let aug = account.aug()?;
+    let account = ShardAccount::with_params(&account, last_trans_hash, last_trans_lt)?;
+    // the account cell hash computed upstream, catches the corrupted bocs
+    for name in ["hash", "expected_hash"] {
+        if !account_map.has_field(name) {
+            continue
+        }
+        let expected = account_map.get_uint256(name)?;
+        let actual = account.account_cell().repr_hash();
+        if expected != actual {
+            fail!("{} item {} has the {} {} but the account cell hash is {}",
+                path, index, name, expected.as_hex_string(), actual.as_hex_string())
+        }
+    }
+    Ok(PreparedStateAccount {
+        account: Some((account_id, account.write_to_new_cell()?, aug)),
+        warnings,
+    })
+}
+
+// `declared` is the full `workchain:hex` address or the hex account id
+fn declared_address_matches(declared: &str, account: &Account) -> Result<bool> {
+    if declared.contains(':') {
+        let declared = MsgAddressInt::from_str(declared)?;
+        Ok(account.get_addr() == Some(&declared))
+    } else {
+        let declared = UInt256::from_str(declared)?;
+        let account_id = account.get_id().map(|mut id| UInt256::construct_from(&mut id)).transpose()?;
+        Ok(account_id == Some(declared))
+    }
+}
+
+/// Builds the config parameters one by one from JSON fragments and native values.
+/// The fragments are parsed by the same functions `parse_config` uses,
+/// so `set_from_json(15, &value)` accepts the value of the `p15` config field.
+#[derive(Default)]
+pub struct ConfigBuilder {
+    parser: StateParser,
+    mandatory_params: Vec<u32>,
+}
+
+impl ConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the JSON value of the parameter `num`, replaces the already set one
+    pub fn set_from_json(mut self, num: u32, value: &Value) -> Result<Self> {
+        if !is_supported_config_param(num) {
+            fail!("parameter p{} is not supported", num)
         }
+        let mut config = Map::new();
+        config.insert(config_param_key(num as i32).to_string(), value.clone());
+        self.parser.dispatch_config_param(&PathMap::new(&config), num as i32)?;
+        Ok(self)
+    }
+
+    pub fn set_param(mut self, param: ConfigParamEnum) -> Result<Self> {
+        self.parser.extra.config.set_config(param)?;
+        Ok(self)
+    }
+
+    /// Parameters `build` fails without
+    pub fn mandatory(mut self, mandatories: &[u32]) -> Self {
+        self.mandatory_params = mandatories.to_vec();
+        self
+    }
+
+    pub fn build(self) -> Result<ConfigParams> {
+        let config = self.parser.extra.config;
+        for num in self.mandatory_params {
+            if config.config(num)?.is_none() {
+                fail!("parameter p{} not found", num)
+            }
+        }
+        Ok(config)
+    }
+}
+
+pub fn parse_config_with_mandatory_params(config: &Map<String, Value>, mandatories: &[u32]) -> Result<ConfigParams> {
+    StateParser::new().with_mandatory_params(mandatories).parse_config_params(config)
+}
+
+// keys of the config parameters StateParser::parse_config can dispatch by number
+const CONFIG_PARAM_KEYS: [&str; 64] = [
+    "p0", "p1", "p2", "p3", "p4", "p5", "p6", "p7", "p8", "p9", "p10", "p11", "p12", "p13", "p14", "p15",
+    "p16", "p17", "p18", "p19", "p20", "p21", "p22", "p23", "p24", "p25", "p26", "p27", "p28", "p29", "p30", "p31",
+    "p32", "p33", "p34", "p35", "p36", "p37", "p38", "p39", "p40", "p41", "p42", "p43", "p44", "p45", "p46", "p47",
+    "p48", "p49", "p50", "p51", "p52", "p53", "p54", "p55", "p56", "p57", "p58", "p59", "p60", "p61", "p62", "p63",
+];
+
+fn config_param_key(num: i32) -> &'static str {
+    CONFIG_PARAM_KEYS[num as usize]
+}
+
+// "p12" -> 12, keys like "p012" or "p64" are not config parameters
+fn config_param_number(key: &str) -> Option<u32> {
+    let num = key.strip_prefix('p')?.parse::<usize>().ok()?;
+    (*CONFIG_PARAM_KEYS.get(num)? == key).then_some(num as u32)
+}
+
+// the config of the state dumps is taken as is or from the `config` or `master.config` wrapper,
+// the wrapper stays in the error paths, e.g. `root/master/config/p34 must ...`
+fn unwrap_config<'m, 'a>(config: &PathMap<'m, 'a>) -> Result<PathMap<'m, 'a>> {
+    let has_params = config.iter().any(|(name, _)| config_param_number(name).is_some());
+    let wrapper = ["config", "master.config"].into_iter().find(|path| config.get_path_obj(path).is_ok());
+    match (has_params, wrapper) {
+        (true, Some(path)) => fail!(
+            "{} must have either the config parameters or the `{}` object but not both",
+            config.path.join("/"), path
+        ),
+        (false, Some(path)) => config.get_path_obj(path),
+        _ => Ok(config.nested(config.map, config.path.clone()))
     }
-    parser.parse_config(&config)?;
-    Ok(parser.extra.config)
 }
 
+// fails listing the fields never read through the tracked map, see `StateParser::with_strict_fields`
+fn check_unvisited_keys(map: &PathMap) -> Result<()> {
+    let unvisited = map.unvisited_keys();
+    if !unvisited.is_empty() {
+        fail!("the fields are not read by the parser: {}", unvisited.join(", "))
+    }
+    Ok(())
+}
+
+// config parameters read by StateParser::parse_config, keep in sync with it
+const SUPPORTED_CONFIG_PARAMS: [u32; 42] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18,
+    20, 21, 22, 23, 24, 25, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37,
+    39, 40, 42, 44, 58, 61, 62
+];
+
+/// Numbers of the config parameters `parse_config` reads, all the others are ignored
+pub fn supported_config_params() -> &'static [u32] {
+    &SUPPORTED_CONFIG_PARAMS
+}
+
+pub fn is_supported_config_param(number: u32) -> bool {
+    SUPPORTED_CONFIG_PARAMS.contains(&number)
+}
+
+/// Parses the config parameters object, the `config` and `master.config` wrappers are unwrapped
 pub fn parse_config(config: &Map<String, Value>) -> Result<ConfigParams> {
     parse_config_with_mandatory_params(config, &[])
 }
@@ -1016,6 +2282,413 @@ pub fn parse_state_unchecked(map: &Map<String, Value>) -> Result<ShardStateUnspl
     StateParser::new().parse_state_unchecked(map)
 }
 
+/// Same as `parse_state` but streaming from the JSON text, see `StateParser::parse_state_streaming`
+pub fn parse_state_streaming(reader: impl Read) -> Result<ShardStateUnsplit> {
+    StateParser::for_zero_state().parse_state_streaming(reader)
+}
+
+fn parse_state_split_side(map_path: &PathMap, name: &'static str) -> Result<ShardStateUnsplit> {
+    let side = map_path.get_obj(name)?;
+    if side.has_field("boc") {
+        return ShardStateUnsplit::construct_from_bytes(&side.get_base64("boc")?)
+    }
+    let mut state = StateParser::new().parse_state_unchecked(side.map)?;
+    state.set_shard(parse_shard_ident(&side)?);
+    if let Some(seq_no) = side.get_num_opt("seq_no")? {
+        state.set_seq_no(seq_no as u32);
+    }
+    Ok(state)
+}
+
+/// Reads `left` and `right` halves of the split state, each one is either a state document
+/// with the `boc` field or a state in the `parse_state_unchecked` schema with `workchain_id` and `shard`
+pub fn parse_state_split(map: &Map<String, Value>) -> Result<ShardStateSplit> {
+    let map_path = PathMap::new(map);
+    let left = parse_state_split_side(&map_path, "left")?;
+    let right = parse_state_split_side(&map_path, "right")?;
+    let siblings = left.shard().merge().and_then(|parent| parent.split()).ok();
+    if siblings != Some((left.shard().clone(), right.shard().clone())) {
+        fail!("root/left {} and root/right {} must be the left and the right halves of one shard",
+            left.shard(), right.shard())
+    }
+    Ok(ShardStateSplit { left, right })
+}
+
+/// Builds the outbound message queue from the `out_queue`, `proc_info` and the optional
+/// `ihr_pending` arrays in the form written by `serialize_out_msg_queue`.
+/// The queued message is either the `envelope` boc or the `msg` boc with the optional
+/// envelope fields `cur_addr`, `next_addr` and `fwd_fee_remaining`, `lt` is the enqueued lt
+pub fn parse_out_msg_queue(map: &Map<String, Value>) -> Result<OutMsgQueueInfo> {
+    let map_path = PathMap::new(map);
+    let mut out_queue = OutMsgQueue::default();
+    for item in map_path.get_vec("out_queue")? {
+        let item = PathMap::cont(&map_path, "out_queue", item)?;
+        let env = parse_queued_envelope(&item)?;
+        let enqueued_lt = item.get_u64_radix("lt")?;
+        let (_, next_prefix) = env.calc_cur_next_prefix()?;
+        let created_lt = env.read_message()?.lt().unwrap_or_default();
+        let key = OutMsgQueueKey::with_workchain_id_and_prefix(
+            next_prefix.workchain_id, next_prefix.prefix, env.message_cell().repr_hash()
+        );
+        out_queue.set(&key, &EnqueuedMsg::with_param(enqueued_lt, &env)?, &created_lt)?;
+    }
+
+    let mut proc_info = ProcessedInfo::default();
+    for item in map_path.get_vec("proc_info")? {
+        let item = PathMap::cont(&map_path, "proc_info", item)?;
+        let key = ProcessedInfoKey::with_params(
+            parse_shard_prefix(&item, "shard")?,
+            item.get_num("mc_seqno")? as u32,
+        );
+        let value = ProcessedUpto::with_params(
+            item.get_u64_radix("last_msg_lt")?,
+            item.get_uint256("last_msg_hash")?,
+        );
+        proc_info.set(&key, &value)?;
+    }
+
+    let mut ihr_pending = IhrPendingInfo::default();
+    if map_path.has_field("ihr_pending") {
+        for item in map_path.get_vec("ihr_pending")? {
+            let item = PathMap::cont(&map_path, "ihr_pending", item)?;
+            let mut key = BuilderData::new();
+            key.append_u64(parse_shard_prefix(&item, "dest_addr_prefix")?)?;
+            key.append_raw(item.get_uint256("msg_id")?.as_slice(), 256)?;
+            let value = IhrPendingSince::with_import_lt(item.get_u64_radix("import_lt")?);
+            ihr_pending.set_builder_serialized(SliceData::load_builder(key)?, &value.write_to_new_cell()?)?;
+        }
+    }
+
+    Ok(OutMsgQueueInfo::with_params(out_queue, proc_info, ihr_pending))
+}
+
+fn parse_queued_envelope(item: &PathMap) -> Result<MsgEnvelope> {
+    match (item.has_field("envelope"), item.has_field("msg")) {
+        (true, true) => fail!("{} must not have both fields `envelope` and `msg`", item.path.join("/")),
+        (true, false) => MsgEnvelope::construct_from_cell(read_single_root_boc(item.get_base64("envelope")?)?),
+        (false, true) => {
+            let msg_cell = read_single_root_boc(item.get_base64("msg")?)?;
+            let fwd_fee_remaining = match item.has_field("fwd_fee_remaining")
+                || item.has_field("fwd_fee_remaining_dec")
+            {
+                true => item.get_grams("fwd_fee_remaining")?,
+                false => Grams::zero()
+            };
+            Ok(MsgEnvelope::with_routing(
+                msg_cell,
+                fwd_fee_remaining,
+                parse_intermediate_address(item, "cur_addr")?,
+                parse_intermediate_address(item, "next_addr")?,
+            ))
+        }
+        (false, false) => fail!("{} must have the field `envelope` or `msg`", item.path.join("/")),
+    }
+}
+
+/// Reads the routing address written by the serializer: the count of the source address bits
+/// or `workchain:prefix` in hex. Absent address is the zero count of bits
+fn parse_intermediate_address<'m, 'a>(item: &PathMap<'m, 'a>, name: &'a str) -> Result<IntermediateAddress> {
+    if !item.has_field(name) {
+        return Ok(IntermediateAddress::default())
+    }
+    let text = item.get_str(name)?;
+    let invalid = |err: String| error!("{}/{} must be the count of bits or `workchain:prefix` in hex {}: {}",
+        item.path.join("/"), name, text, err);
+    match text.split_once(':') {
+        None => {
+            let bits = u8::from_str(text).map_err(|err| invalid(err.to_string()))?;
+            Ok(IntermediateAddress::Regular(
+                IntermediateAddressRegular::with_use_src_bits(bits).map_err(|err| invalid(err.to_string()))?
+            ))
+        }
+        Some((workchain_id, prefix)) => {
+            let workchain_id = i32::from_str(workchain_id).map_err(|err| invalid(err.to_string()))?;
+            let prefix = u64::from_str_radix(prefix, 16).map_err(|err| invalid(err.to_string()))?;
+            Ok(match i8::try_from(workchain_id) {
+                Ok(workchain_id) => IntermediateAddress::Simple(
+                    IntermediateAddressSimple::with_addr(workchain_id, prefix)
+                ),
+                Err(_) => IntermediateAddress::Ext(IntermediateAddressExt::with_addr(workchain_id, prefix)),
+            })
+        }
+    }
+}
+
+fn parse_shard_prefix<'m, 'a>(item: &PathMap<'m, 'a>, name: &'a str) -> Result<u64> {
+    u64::from_str_radix(item.get_str(name)?, 16)
+        .map_err(|err| error!("{}/{} must be the shard prefix in hex format : {}",
+            item.path.join("/"), name, err))
+}
+
+/// Reads the block references of the block document into the block info: `prev_refs` with
+/// one prev block or the two merged ones, `after_merge`, `after_split` and the optional `master_ref`.
+/// The `after_merge` flag must agree with the count of the prev blocks
+pub fn parse_block_info_refs(map: &Map<String, Value>, info: &mut BlockInfo) -> Result<()> {
+    let map_path = PathMap::new(map);
+    let mut prev_refs = Vec::new();
+    for item in map_path.get_vec("prev_refs")? {
+        prev_refs.push(parse_ext_blk_ref(&PathMap::cont(&map_path, "prev_refs", item)?)?);
+    }
+    let after_merge = map_path.get_bool_opt("after_merge")?.unwrap_or_default();
+    match (after_merge, prev_refs.len()) {
+        (false, 1) | (true, 2) => (),
+        (false, count) => fail!("root/prev_refs must have 1 item for the block not after merge but {} given", count),
+        (true, count) => fail!("root/prev_refs must have 2 items for the block after merge but {} given", count),
+    }
+    info.set_prev_stuff(after_merge, &BlkPrevInfo::new(prev_refs)?)?;
+    info.set_after_split(map_path.get_bool_opt("after_split")?.unwrap_or_default());
+    if map_path.has_field("master_ref") {
+        let master = parse_ext_blk_ref(&map_path.get_obj("master_ref")?)?;
+        info.write_master_ref(Some(&BlkMasterInfo { master }))?;
+    } else {
+        info.write_master_ref(None)?;
+    }
+    Ok(())
+}
+
+// the end_lt is written as the number, the `_dec` string or the `0x` string depending on the mode
+fn parse_ext_blk_ref(item: &PathMap) -> Result<ExtBlkRef> {
+    Ok(ExtBlkRef {
+        end_lt: item.get_u64_radix("end_lt")?,
+        seq_no: item.get_num("seq_no")? as u32,
+        root_hash: item.get_uint256("root_hash")?,
+        file_hash: item.get_uint256("file_hash")?,
+    })
+}
+
+/// Builds the active account from the serialized fields when there is no `boc`,
+/// the storage stat is recalculated
+fn parse_expanded_account(account: &PathMap) -> Result<Account> {
+    let acc_type = account.get_num("acc_type")?;
+    if acc_type != 1 {
+        fail!("{}/acc_type must be 1 (active) for the account without boc but {} given",
+            account.path.join("/"), acc_type)
+    }
+    let id_field = account.doc_id_field()?;
+    let addr_str = account.get_str(id_field)?;
+    let addr = MsgAddressInt::from_str(addr_str)
+        .map_err(|err| error!("{}/{} must be the account address : {}", account.path.join("/"), id_field, err))?;
+    let mut state_init = StateInit::default();
+    if let Some(split_depth) = account.get_num_opt("split_depth")? {
+        state_init.split_depth = Some(Number5::new(split_depth as u32)?);
+    }
+    let tick = account.get_bool_opt("tick")?;
+    let tock = account.get_bool_opt("tock")?;
+    if tick.is_some() || tock.is_some() {
+        state_init.special = Some(TickTock {
+            tick: tick.unwrap_or_default(),
+            tock: tock.unwrap_or_default(),
+        });
+    }
+    if account.has_field("code") {
+        state_init.code = Some(account.get_cell_maybe_deflated("code")?);
+    }
+    if account.has_field("data") {
+        state_init.data = Some(account.get_cell_maybe_deflated("data")?);
+    }
+    if account.has_field("library") {
+        state_init.library = StateInitLib::with_hashmap(Some(account.get_cell_maybe_deflated("library")?));
+    }
+    let init_code_hash = match account.has_field("init_code_hash") {
+        true => {
+            let init_code_hash = account.get_uint256("init_code_hash")?;
+            // the documents without the cells (see `omit_body_fields`) have nothing to check against
+            if let Some(code_hash) = state_init.code.as_ref().map(|code| code.repr_hash()) {
+                if init_code_hash != code_hash {
+                    fail!("{}/init_code_hash {} doesn't match the code hash {}",
+                        account.path.join("/"), init_code_hash.as_hex_string(), code_hash.as_hex_string())
+                }
+            }
+            true
+        }
+        false => false
+    };
+    let storage = AccountStorage::active_by_init_code_hash(
+        account.get_num_opt("last_trans_lt")?.unwrap_or_default() as u64,
+        parse_currency_collection(account, "balance")?,
+        state_init,
+        init_code_hash,
+    );
+    let due_payment = if account.has_field("due_payment") || account.has_field("due_payment_dec") {
+        Some(account.get_grams("due_payment")?)
+    } else {
+        None
+    };
+    let last_paid = account.get_num_opt("last_paid")?.unwrap_or_default() as u32;
+    let mut parsed = Account::with_storage(&addr, &StorageInfo::with_values(last_paid, due_payment), &storage);
+    parsed.update_storage_stat()?;
+    Ok(parsed)
+}
+
+pub fn parse_workchain_descr(wc_info: &PathMap) -> Result<(i32, WorkchainDescr)> {
+    let mut descr = WorkchainDescr::default();
+    let workchain_id = wc_info.get_num("workchain_id")? as i32;
+    descr.enabled_since = wc_info.get_num_opt("enabled_since")?.unwrap_or(0) as u32;
+    descr.set_min_split(wc_info.get_num("min_split")? as u8)?;
+    descr.set_max_split(wc_info.get_num("max_split")? as u8)?;
+    descr.flags = match wc_info.get_item("flags") {
+        Ok(Value::Array(_)) => wc_info.get_workchain_flags("flags")?,
+        Ok(_) => wc_info.get_num("flags")? as u16,
+        Err(_) if wc_info.has_field("flags_list") => wc_info.get_workchain_flags("flags_list")?,
+        Err(_) => 0
+    };
+    descr.active = wc_info.get_bool_lenient_opt("active")?.unwrap_or(true);
+    descr.accept_msgs = wc_info.get_bool_lenient_opt("accept_msgs")?.unwrap_or(true);
+    descr.zerostate_root_hash = wc_info.get_uint256("zerostate_root_hash")?;
+    descr.zerostate_file_hash = wc_info.get_uint256("zerostate_file_hash")?;
+    descr.version = wc_info.get_num_opt("version")?.unwrap_or(0) as u32;
+    // TODO: check here
+    descr.format = match wc_info.get_bool_lenient_opt("basic")?.unwrap_or(true) {
+        true => {
+            // the conventional values for the basic workchain
+            let vm_version = wc_info.get_num_opt("vm_version")?.unwrap_or(-1) as i32;
+            let vm_mode    = match wc_info.has_field("vm_mode") {
+                true => wc_info.get_u64_radix("vm_mode")?,
+                false => 0
+            };
+            WorkchainFormat::Basic(WorkchainFormat1::with_params(vm_version, vm_mode))
+        }
+        false => {
+            let min_addr_len      = wc_info.get_num("min_addr_len")? as u16;
+            let max_addr_len      = wc_info.get_num("max_addr_len")? as u16;
+            let addr_len_step     = wc_info.get_num("addr_len_step")? as u16;
+            let workchain_type_id = wc_info.get_num("workchain_type_id")? as u32;
+            WorkchainFormat::Extended(
+                WorkchainFormat0::with_params(
+                    min_addr_len, max_addr_len, addr_len_step, workchain_type_id
+                )?
+            )
+        }
+    };
+    Ok((workchain_id, descr))
+}
+
+/// Reads the currency collection written either as the `name` grams (number, string or `name_dec`)
+/// with the `name_other` extra currencies, or as the `name` object with `grams` and `other` fields
+/// as `serialize_currency_collection` writes it. The extra currencies are the array of
+/// `{currency, value}` objects or the object with the values keyed by the currency id,
+/// e.g. `{"239": "1000"}`.
+pub fn parse_currency_collection(map_path: &PathMap, name: &str) -> Result<CurrencyCollection> {
+    if let Some(Value::Object(_)) = map_path.lookup(name) {
+        let cc = map_path.get_obj(name)?;
+        let mut result = CurrencyCollection::from_grams(cc.get_grams("grams")?);
+        parse_extra_currencies(&cc, "other", &mut result.other)?;
+        return Ok(result)
+    }
+    let mut result = CurrencyCollection::from_grams(map_path.get_grams(name)?);
+    parse_extra_currencies(map_path, &format!("{}_other", name), &mut result.other)?;
+    Ok(result)
+}
+
+/// Reads the array of `{currency, value}` or the object keyed by the currency ids with the integer
+/// or the decimal string values, the `239_dec` keys are the same as `239`. A currency given twice fails
+fn parse_extra_currencies(map_path: &PathMap, name: &str, other: &mut ExtraCurrencyCollection) -> Result<()> {
+    let mut set = |currency: u32, value: VarUInteger32| {
+        if other.get(&currency)?.is_some() {
+            fail!("{}/{} has the currency {} twice", map_path.path.join("/"), name, currency)
+        }
+        other.set(&currency, &value)
+    };
+    match map_path.lookup(name) {
+        None | Some(Value::Null) => Ok(()),
+        Some(Value::Array(items)) => items.iter().try_for_each(|item| {
+            let item = PathMap::cont(map_path, name, item)?;
+            let (currency, value) = parse_extra_currency(&item)?;
+            set(currency, value)
+        }),
+        Some(Value::Object(items)) => items.iter().try_for_each(|(key, value)| {
+            let currency: u32 = key.strip_suffix("_dec").unwrap_or(key).parse().map_err(|err| {
+                error!("{}/{} keys must be the currency ids but {} given : {}", map_path.path.join("/"), name, key, err)
+            })?;
+            let value: VarUInteger32 = match value {
+                Value::String(value) => normalize_decimal(value).parse()?,
+                Value::Number(value) => value.to_string().parse()?,
+                _ => fail!("{}/{}/{} must be the integer or a string with the integer", map_path.path.join("/"), name, key)
+            };
+            set(currency, value)
+        }),
+        Some(_) => fail!("{}/{} must be the array or the object", map_path.path.join("/"), name)
+    }
+}
+
+fn parse_extra_currency(map_path: &PathMap) -> Result<(u32, VarUInteger32)> {
+    let value = if let Ok(value) = map_path.get_str("value_dec") {
+        value.parse()?
+    } else {
+        map_path.get_str("value")?.parse()?
+    };
+    let currency = map_path.get_num("currency")?;
+    let currency = u32::try_from(currency).map_err(|_| {
+        error!("{}/currency must be the currency id in the u32 range but {} given", map_path.path.join("/"), currency)
+    })?;
+    Ok((currency, value))
+}
+
+/// Reads `{version, capabilities}` of p8 or the block `gen_software`,
+/// the capabilities may be given by names in `capabilities_list` instead
+pub fn parse_global_version(map_path: &PathMap) -> Result<GlobalVersion> {
+    Ok(GlobalVersion {
+        version: map_path.get_num("version")? as u32,
+        capabilities: match map_path.get_item("capabilities") {
+            Ok(_) => map_path.get_u64_radix("capabilities")?,
+            Err(_) => map_path.get_capabilities_list("capabilities_list")?
+        },
+    })
+}
+
+pub fn parse_value_flow(map_path: &PathMap) -> Result<ValueFlow> {
+    Ok(ValueFlow {
+        from_prev_blk: parse_currency_collection(map_path, "from_prev_blk")?,
+        to_next_blk: parse_currency_collection(map_path, "to_next_blk")?,
+        imported: parse_currency_collection(map_path, "imported")?,
+        exported: parse_currency_collection(map_path, "exported")?,
+        fees_collected: parse_currency_collection(map_path, "fees_collected")?,
+        fees_imported: parse_currency_collection(map_path, "fees_imported")?,
+        recovered: parse_currency_collection(map_path, "recovered")?,
+        created: parse_currency_collection(map_path, "created")?,
+        minted: parse_currency_collection(map_path, "minted")?,
+        ..Default::default()
+    })
+}
+
+/// Reads the shard from `shard` with the workchain in `wc`, `workchain_id` or `workchain`,
+/// or from the composite `"wc,shard"` string like `"-1,8000000000000000"` in `shard` alone
+pub fn parse_shard_ident(map_path: &PathMap) -> Result<ShardIdent> {
+    let shard = map_path.get_str("shard")?;
+    let mut workchain_id = None;
+    let shard = match shard.split_once(',') {
+        Some((workchain, shard)) => {
+            let value = i32::from_str(workchain.trim())
+                .map_err(|err| error!("{}/shard must be the workchain id and the shard prefix \
+                    separated by comma : {}", map_path.path.join("/"), err))?;
+            workchain_id = Some(("shard", value));
+            shard.trim()
+        }
+        None => shard
+    };
+    for name in ["wc", "workchain_id", "workchain"] {
+        if map_path.get_item(name).is_err() {
+            continue
+        }
+        let value = map_path.get_num(name)? as i32;
+        match workchain_id {
+            Some((prev_name, prev_value)) if prev_value != value => {
+                fail!("{} has conflicting fields `{}` = {} and `{}` = {}",
+                    map_path.path.join("/"), prev_name, prev_value, name, value)
+            }
+            Some(_) => (),
+            None => workchain_id = Some((name, value))
+        }
+    }
+    let (_, workchain_id) = workchain_id
+        .ok_or_else(|| error!("{} must have the field `wc` or `workchain_id`", map_path.path.join("/")))?;
+    let shard = u64::from_str_radix(shard, 16)
+        .map_err(|err| error!("{}/shard must be the shard prefix in hex format : {}",
+            map_path.path.join("/"), err))?;
+    ShardIdent::with_tagged_prefix(workchain_id, shard)
+}
+
+#[cfg(feature = "remp")]
 fn parse_block_id_ext(map_path: &PathMap, mc: bool) -> Result<BlockIdExt> {
     if mc {
         Ok(BlockIdExt::with_params(
@@ -1026,10 +2699,7 @@ fn parse_block_id_ext(map_path: &PathMap, mc: bool) -> Result<BlockIdExt> {
         ))
     } else {
         Ok(BlockIdExt::with_params(
-            ton_dev_block::ShardIdent::with_tagged_prefix(
-                map_path.get_num("wc")? as i32,
-                u64::from_str_radix(map_path.get_str("shard")?, 16)?
-            )?,
+            parse_shard_ident(map_path)?,
             map_path.get_num("block_seqno")? as u32,
             map_path.get_uint256("block_id")?,
             map_path.get_uint256("block_file_hash")?,
@@ -1037,40 +2707,52 @@ fn parse_block_id_ext(map_path: &PathMap, mc: bool) -> Result<BlockIdExt> {
     }
 }
 
-fn parse_separated_block_id_ext(map_path: &PathMap) -> Result<BlockIdExt> {
+/// Reads the block id of the nested layout `{wc, shard, seqno, root_hash, file_hash}`
+/// written by `serialize_block_id_ext` with `nested` set
+pub fn parse_separated_block_id_ext(map: &Map<String, Value>) -> Result<BlockIdExt> {
+    parse_nested_block_id_ext(&PathMap::new(map))
+}
+
+fn parse_nested_block_id_ext(map_path: &PathMap) -> Result<BlockIdExt> {
     Ok(BlockIdExt::with_params(
-        ShardIdent::with_tagged_prefix(
-            map_path.get_num("wc")? as i32,
-            u64::from_str_radix(map_path.get_str("shard")?, 16)?
-        )?,
+        parse_shard_ident(map_path)?,
         map_path.get_num("seqno")? as u32,
         map_path.get_uint256("root_hash")?,
         map_path.get_uint256("file_hash")?,
     ))
 }
 
+#[cfg(feature = "remp")]
 pub fn parse_remp_status(map: &Map<String, Value>)
     -> Result<(RempReceipt, Vec<u8>)> {
+    parse_remp_status_with_defaults(map, None, false)
+}
+
+/// Same as `parse_remp_status` but missing `timestamp` is taken from `default_timestamp`
+/// and missing `source_id` is set to zero if `allow_zero_source_id` is set
+#[cfg(feature = "remp")]
+pub fn parse_remp_status_with_defaults(
+    map: &Map<String, Value>,
+    default_timestamp: Option<i64>,
+    allow_zero_source_id: bool,
+) -> Result<(RempReceipt, Vec<u8>)> {
 
     let map_path = PathMap::new(map);
 
-    let source_id = map_path.get_uint256("source_id")?;
+    let source_id = match map_path.get_item("source_id") {
+        Err(_) if allow_zero_source_id => UInt256::default(),
+        _ => map_path.get_uint256("source_id")?
+    };
     let signature = map_path.get_base64("signature")?;
 
-    let timestamp = map_path.get_num("timestamp")?;
+    let timestamp = match (map_path.get_item("timestamp"), default_timestamp) {
+        (Err(_), Some(timestamp)) => timestamp,
+        _ => map_path.get_num("timestamp")?
+    };
     let message_id = map_path.get_uint256("message_id")?;
 
-    let status = match map_path.get_str("kind")? {
-        // RempMessageStatus::TonNode_RempAccepted
-        s @ ("IncludedIntoBlock" | "AcceptedByFullnode" | "Finalized" | "AcceptedByQueue" | "IncludedIntoAcceptedBlock") => {
-            let level = match s {
-                "IncludedIntoBlock" => RempMessageLevel::TonNode_RempCollator,
-                "AcceptedByFullnode" => RempMessageLevel::TonNode_RempFullnode,
-                "Finalized" => RempMessageLevel::TonNode_RempMasterchain,
-                "AcceptedByQueue" => RempMessageLevel::TonNode_RempQueue,
-                "IncludedIntoAcceptedBlock" => RempMessageLevel::TonNode_RempShardchain,
-                s => fail!("Unknown status: {}", s)
-            };
+    let status = match remp_kind_to_status_template(map_path.get_str("kind")?)? {
+        RempStatusKind::Accepted(level) => {
             RempMessageStatus::TonNode_RempAccepted (
                 rempmessagestatus::RempAccepted {
                     level,
@@ -1079,22 +2761,14 @@ pub fn parse_remp_status(map: &Map<String, Value>)
                 }
             )
         }
-        "Duplicate" => {
+        RempStatusKind::Duplicate => {
             RempMessageStatus::TonNode_RempDuplicate (
                 rempmessagestatus::RempDuplicate {
                     block_id: parse_block_id_ext(&map_path, false)?,
                 }
             )
         }
-        s @ ("IgnoredByCollator" | "IgnoredByFullNode" | "IgnoredByMasterchain" | "IgnoredByQueue" | "IgnoredByShardchain") => {
-            let level = match s {
-                "IgnoredByCollator" => RempMessageLevel::TonNode_RempCollator,
-                "IgnoredByFullNode" => RempMessageLevel::TonNode_RempFullnode,
-                "IgnoredByMasterchain" => RempMessageLevel::TonNode_RempMasterchain,
-                "IgnoredByQueue" => RempMessageLevel::TonNode_RempQueue,
-                "IgnoredByShardchain" => RempMessageLevel::TonNode_RempShardchain,
-                s => fail!("Unknown status: {}", s)
-            };
+        RempStatusKind::Ignored(level) => {
             RempMessageStatus::TonNode_RempIgnored (
                 rempmessagestatus::RempIgnored {
                     level,
@@ -1102,20 +2776,10 @@ pub fn parse_remp_status(map: &Map<String, Value>)
                 }
             )
         }
-        // RempMessageStatus::TonNode_RempNew
-        "PutIntoQueue" => {
+        RempStatusKind::New => {
             RempMessageStatus::TonNode_RempNew
         }
-        // RempMessageStatus::TonNode_RempRejected
-        s @ ("RejectedByCollator" | "RejectedByFullnode" | "RejectedByMasterchain" | "RejectedByQueue" | "RejectedByShardchain") => {
-            let level = match s {
-                "RejectedByCollator" => RempMessageLevel::TonNode_RempCollator,
-                "RejectedByFullnode" => RempMessageLevel::TonNode_RempFullnode,
-                "RejectedByMasterchain" => RempMessageLevel::TonNode_RempMasterchain,
-                "RejectedByQueue" => RempMessageLevel::TonNode_RempQueue,
-                "RejectedByShardchain" => RempMessageLevel::TonNode_RempShardchain,
-                s => fail!("Unknown status: {}", s)
-            };
+        RempStatusKind::Rejected(level) => {
             RempMessageStatus::TonNode_RempRejected (
                 rempmessagestatus::RempRejected {
                     level,
@@ -1124,8 +2788,7 @@ pub fn parse_remp_status(map: &Map<String, Value>)
                 }
             )
         }
-        // RempMessageStatus::TonNode_RempSentToValidators
-        "SentToValidators" => {
+        RempStatusKind::SentToValidators => {
             RempMessageStatus::TonNode_RempSentToValidators (
                 rempmessagestatus::RempSentToValidators {
                     sent_to: map_path.get_num("sent_to")? as i32,
@@ -1133,11 +2796,9 @@ pub fn parse_remp_status(map: &Map<String, Value>)
                 }
             )
         }
-        // RempMessageStatus::TonNode_RempTimeout
-        "Timeout" => {
+        RempStatusKind::Timeout => {
             RempMessageStatus::TonNode_RempTimeout
         }
-        s => fail!("Unknown status: {}", s)
     };
 
     let receipt = ton_api::ton::ton_node::rempreceipt::RempReceipt {
@@ -1150,9 +2811,56 @@ pub fn parse_remp_status(map: &Map<String, Value>)
     Ok((receipt, signature))
 }
 
+/// Same as `parse_block_proof` but takes the block file hash from the `file_hash` field
+/// or calculates it from the `block_boc` field, `block_file_hash` is used only if both are absent.
+/// All the given sources must agree.
+pub fn parse_block_proof_auto(
+    map: &Map<String, Value>, block_file_hash: Option<UInt256>
+) -> Result<ton_dev_block::BlockProof> {
+    let map_path = PathMap::new(map);
+    let mut sources = Vec::new();
+    if map_path.has_field("block_boc") {
+        sources.push(("block_boc", UInt256::calc_file_hash(&map_path.get_base64("block_boc")?)));
+    }
+    if map_path.has_field("file_hash") {
+        sources.push(("file_hash", map_path.get_uint256("file_hash")?));
+    }
+    if let Some(block_file_hash) = block_file_hash {
+        sources.push(("argument", block_file_hash));
+    }
+    let Some((first_source, file_hash)) = sources.first().cloned() else {
+        fail!("root must have the field `file_hash` or `block_boc` if no block file hash is given")
+    };
+    for (source, other_hash) in &sources[1..] {
+        if *other_hash != file_hash {
+            fail!("root has disagreeing block file hashes: {} from {} and {} from {}",
+                file_hash.as_hex_string(), first_source, other_hash.as_hex_string(), source)
+        }
+    }
+    parse_block_proof(map, file_hash)
+}
+
 pub fn parse_block_proof(
     map: &Map<String, Value>, block_file_hash: UInt256
 ) -> Result<ton_dev_block::BlockProof> {
+    parse_block_proof_with_validators(map, block_file_hash, None)
+}
+
+/// Same as `parse_block_proof` but the signatures may reference the validators by
+/// `validator_index` in the given validator set instead of `node_id`
+pub fn parse_block_proof_with_validators(
+    map: &Map<String, Value>, block_file_hash: UInt256, validators: Option<&ValidatorSet>
+) -> Result<ton_dev_block::BlockProof> {
+    parse_block_proof_resolving(map, block_file_hash, |_| Ok(validators.cloned()))
+}
+
+// `validators` gives the set resolving `validator_index` of the signatures by the `gen_utime`
+// of the proved block, it is called only if the proof has signatures
+fn parse_block_proof_resolving(
+    map: &Map<String, Value>,
+    block_file_hash: UInt256,
+    validators: impl FnOnce(u32) -> Result<Option<ValidatorSet>>,
+) -> Result<ton_dev_block::BlockProof> {
 
     let map_path = PathMap::new(map);
 
@@ -1173,17 +2881,16 @@ pub fn parse_block_proof(
         block_file_hash,
     );
 
-    let signatures = if let Ok(signatures) = map_path.get_vec("signatures") {
+    let signatures = if map_path.has_field("signatures") {
+        let signatures = map_path.get_vec("signatures")?;
+        let validators = validators(block_info.gen_utime().as_u32())?;
         let mut pure_signatures = ton_dev_block::BlockSignaturesPure::new();
         pure_signatures.set_weight(map_path.get_num("sig_weight")? as u64);
         for signature in signatures {
             let signature = PathMap::cont(&map_path, "signatures", signature)?;
             pure_signatures.add_sigpair(ton_dev_block::CryptoSignaturePair {
-                node_id_short: signature.get_uint256("node_id")?,
-                sign: ton_dev_block::CryptoSignature::from_r_s_str(
-                    signature.get_str("r")?,
-                    signature.get_str("s")?,
-                )?
+                node_id_short: parse_signature_node_id(&signature, validators.as_ref())?,
+                sign: signature.get_signature("signature")?,
             });
         }
         Some(ton_dev_block::BlockSignatures::with_params(
@@ -1200,6 +2907,95 @@ pub fn parse_block_proof(
     Ok(ton_dev_block::BlockProof::with_params(proof_for, root, signatures))
 }
 
+fn parse_signature_node_id(signature: &PathMap, validators: Option<&ValidatorSet>) -> Result<UInt256> {
+    let path = signature.path.join("/");
+    match (signature.has_field("node_id"), signature.has_field("validator_index")) {
+        (true, true) => fail!("{} must have either `node_id` or `validator_index` but not both", path),
+        (false, true) => {
+            let index = signature.get_num("validator_index")?;
+            let validators = validators.ok_or_else(|| {
+                error!("{}/validator_index can't be resolved without the validator set", path)
+            })?;
+            let validator = usize::try_from(index).ok()
+                .and_then(|index| validators.list().get(index))
+                .ok_or_else(|| error!(
+                    "{}/validator_index {} is out of the validator set of {} validators",
+                    path, index, validators.list().len()
+                ))?;
+            Ok(validator.compute_node_id_short())
+        }
+        _ => signature.get_uint256("node_id")
+    }
+}
+
+/// Computes the validator subset of the shard for the catchain `cc_seqno` the same way the node does.
+/// The validator set is the one of p34, p32 or p36 active at the `at` unix time, the subset is
+/// chosen by the catchain config p28. Returns the subset and its short hash which is
+/// `validator_list_hash_short` of the block signatures.
+pub fn compute_validator_subset(
+    config: &ConfigParams, shard: &ShardIdent, cc_seqno: u32, at: u32
+) -> Result<(Vec<ValidatorDescr>, u32)> {
+    let vset = active_validator_set(config, at)?;
+    let cc_config = config.catchain_config()?;
+    vset.calc_subset(&cc_config, shard.shard_prefix_with_tag(), shard.workchain_id(), cc_seqno, at.into())
+}
+
+// the one of p34, p32 or p36 active at the `at` unix time
+fn active_validator_set(config: &ConfigParams, at: u32) -> Result<ValidatorSet> {
+    let is_active = |set: &ValidatorSet| set.utime_since() <= at && at < set.utime_until();
+    let mut vset = config.validator_set()?;
+    if !is_active(&vset) {
+        let mut found = None;
+        for number in [32, 36] {
+            let set = match config.config(number)? {
+                Some(ConfigParamEnum::ConfigParam32(p32)) => p32.prev_validators,
+                Some(ConfigParamEnum::ConfigParam36(p36)) => p36.next_validators,
+                _ => continue
+            };
+            if is_active(&set) {
+                found = Some(set);
+                break
+            }
+        }
+        vset = found.ok_or_else(|| error!("config has no validator set active at {}", at))?;
+    }
+    Ok(vset)
+}
+
+/// Same as `parse_block_proof` but also checks the signatures of the proof against
+/// the validator subset computed from the config of the key block preceding the proved block.
+/// `validator_index` of the signatures refers to the validator set the subset is chosen from.
+pub fn parse_block_proof_checked(
+    map: &Map<String, Value>, block_file_hash: UInt256, config: &ConfigParams
+) -> Result<ton_dev_block::BlockProof> {
+    let proof = parse_block_proof_resolving(
+        map, block_file_hash, |gen_utime| active_validator_set(config, gen_utime).map(Some)
+    )?;
+    let signatures = proof.signatures.as_ref()
+        .ok_or_else(|| error!("root must have the field `signatures` to check the proof"))?;
+
+    let merkle_proof = ton_dev_block::MerkleProof::construct_from_cell(proof.root.clone())?;
+    let block = ton_dev_block::Block::construct_from_cell(merkle_proof.proof.virtualize(1))?;
+    let gen_utime = block.read_info()?.gen_utime().as_u32();
+
+    let (validators, hash_short) = compute_validator_subset(
+        config, proof.proof_for.shard(), signatures.validator_info.catchain_seqno, gen_utime
+    )?;
+    if hash_short != signatures.validator_info.validator_list_hash_short {
+        fail!(
+            "root/validator_list_hash_short {} doesn't match the validator subset {} computed from the config",
+            signatures.validator_info.validator_list_hash_short, hash_short
+        )
+    }
+    let data = ton_dev_block::Block::build_data_for_sign(proof.proof_for.root_hash(), proof.proof_for.file_hash());
+    let weight = signatures.pure_signatures.check_signatures(&validators, &data)?;
+    let total_weight = validators.iter().map(|v| v.weight).sum::<u64>();
+    if (weight as u128) * 3 <= (total_weight as u128) * 2 {
+        fail!("root/signatures have the weight {} of {} which is not more than 2/3", weight, total_weight)
+    }
+    Ok(proof)
+}
+
 #[cfg(test)]
 #[path = "tests/test_deserialize.rs"]
 mod tests;
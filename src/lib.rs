@@ -14,12 +14,48 @@
  * under the License.
  */
 
+#[cfg(any(feature = "serialize", feature = "deserialize"))]
+mod shared;
+#[cfg(any(feature = "serialize", feature = "deserialize"))]
+pub use self::shared::DocIdField;
+#[cfg(all(feature = "remp", any(feature = "serialize", feature = "deserialize")))]
+pub use self::shared::{remp_kind_to_status_template, remp_status_kind_string, RempStatusKind};
+#[cfg(any(feature = "serialize", feature = "deserialize"))]
+mod convert;
+#[cfg(any(feature = "serialize", feature = "deserialize"))]
+pub use self::convert::{JsonConfig, JsonState};
+#[cfg(feature = "serialize")]
 mod serialize;
+#[cfg(feature = "serialize")]
 pub use self::serialize::*;
+#[cfg(feature = "serialize")]
 mod block_parser;
+#[cfg(feature = "deserialize")]
 mod deserialize;
+#[cfg(feature = "deserialize")]
+mod location;
+#[cfg(feature = "serialize")]
+mod schema;
+#[cfg(feature = "deserialize")]
+mod validate;
+#[cfg(all(any(test, feature = "test-helpers"), feature = "deserialize"))]
+mod arbitrary;
+// the deserializer alone, it is the wasm32 build, see scripts/check_wasm.sh
+#[cfg(all(test, feature = "deserialize"))]
+#[path = "tests/test_wasm.rs"]
+mod test_wasm;
 
+#[cfg(feature = "deserialize")]
 pub use self::deserialize::*;
+#[cfg(feature = "deserialize")]
+pub use self::location::*;
+#[cfg(feature = "serialize")]
+pub use self::schema::*;
+#[cfg(feature = "deserialize")]
+pub use self::validate::*;
+#[cfg(all(any(test, feature = "test-helpers"), feature = "deserialize"))]
+pub use self::arbitrary::*;
+#[cfg(feature = "serialize")]
 pub use block_parser::*;
 
 include!("../common/src/info.rs");
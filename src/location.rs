@@ -0,0 +1,193 @@
+/*
+ * Copyright (C) ton.dev. All Rights Reserved.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.  You may obtain a copy of the
+ * License at:
+ *
+ * https://www.ever.dev/licenses
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific ton.dev software governing permissions and limitations
+ * under the License.
+ */
+
+use serde_json::{Map, Value};
+use ton_dev_block::{error, Result, ShardStateUnsplit};
+
+use crate::parse_state;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextPosition {
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column in chars
+    pub column: usize,
+}
+
+/// Same as `parse_state` but reads the state from the JSON text and appends the approximate
+/// position of the failing node to the errors, e.g. `... (line 282, column 11)`
+pub fn parse_state_from_str(text: &str) -> Result<ShardStateUnsplit> {
+    let map: Map<String, Value> = serde_json::from_str(text)
+        .map_err(|err| error!("root must be the JSON object : {}", err))?;
+    parse_state(&map).map_err(|err| {
+        match locate_error(text, &err.to_string()) {
+            Some(position) => error!("{} (line {}, column {})", err, position.line, position.column),
+            None => err
+        }
+    })
+}
+
+/// Finds the node of the `root/...` path from the parsing error message in the JSON text.
+/// The paths don't have array indices, so the first array item having the next path field
+/// is taken, for `must have the field` errors it is the first item without the field.
+/// Returns None if the message has no path.
+pub fn locate_error(text: &str, message: &str) -> Option<TextPosition> {
+    let path = message.split_whitespace().find(|token| *token == "root" || token.starts_with("root/"))?;
+    let path = path.split('/').skip(1).collect::<Vec<_>>();
+    let missing = message.split_once("must have the field `")
+        .and_then(|(_, rest)| rest.split_once('`'))
+        .map(|(field, _)| field);
+    let scanner = Scanner { text: text.as_bytes() };
+    let root = scanner.skip_ws(0);
+    let offset = scanner.find(root, &path, missing).unwrap_or(root).min(text.len());
+    let before = text.get(..offset).unwrap_or(text);
+    let line_start = before.rfind('\n').map_or(0, |pos| pos + 1);
+    Some(TextPosition {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    })
+}
+
+// Walks the already validated JSON text by offsets without building the values,
+// keys are compared raw so escaped keys are not matched.
+// The offsets never exceed the text length, so truncated texts are walked to the end
+struct Scanner<'t> {
+    text: &'t [u8],
+}
+
+impl<'t> Scanner<'t> {
+    fn byte(&self, pos: usize) -> u8 {
+        self.text.get(pos).copied().unwrap_or(0)
+    }
+
+    fn skip_ws(&self, mut pos: usize) -> usize {
+        while self.byte(pos).is_ascii_whitespace() {
+            pos += 1;
+        }
+        pos.min(self.text.len())
+    }
+
+    // `pos` is at the opening quote, returns the offset after the closing one
+    fn string_end(&self, mut pos: usize) -> usize {
+        pos += 1;
+        while let Some(byte) = self.text.get(pos) {
+            match byte {
+                b'\\' => pos += 2,
+                b'"' => return pos + 1,
+                _ => pos += 1,
+            }
+        }
+        self.text.len()
+    }
+
+    fn value_end(&self, pos: usize) -> usize {
+        match self.byte(pos) {
+            b'"' => self.string_end(pos),
+            b'{' | b'[' => {
+                let mut pos = pos + 1;
+                loop {
+                    pos = self.skip_ws(pos);
+                    match self.byte(pos) {
+                        b'}' | b']' => return pos + 1,
+                        _ if pos >= self.text.len() => return self.text.len(),
+                        b',' | b':' => pos += 1,
+                        _ => pos = self.value_end(pos),
+                    }
+                }
+            }
+            _ => {
+                let mut pos = pos;
+                while pos < self.text.len()
+                    && !matches!(self.byte(pos), b',' | b'}' | b']')
+                    && !self.byte(pos).is_ascii_whitespace()
+                {
+                    pos += 1;
+                }
+                pos
+            }
+        }
+    }
+
+    // (key offset, raw key, value offset) of the object members
+    fn members(&self, pos: usize) -> Vec<(usize, &'t str, usize)> {
+        let mut members = Vec::new();
+        let mut pos = pos + 1;
+        loop {
+            pos = self.skip_ws(pos);
+            match self.byte(pos) {
+                b',' => pos += 1,
+                b'"' => {
+                    let key_end = self.string_end(pos);
+                    // an unterminated key runs up to the end of the text
+                    let raw_end = if key_end > pos + 1 && self.byte(key_end - 1) == b'"' { key_end - 1 } else { key_end };
+                    let key = self.text.get(pos + 1..raw_end)
+                        .and_then(|key| std::str::from_utf8(key).ok())
+                        .unwrap_or_default();
+                    let value = self.skip_ws(self.skip_ws(key_end) + 1);
+                    members.push((pos, key, value));
+                    pos = self.value_end(value);
+                }
+                _ => return members
+            }
+        }
+    }
+
+    fn elements(&self, pos: usize) -> Vec<usize> {
+        let mut elements = Vec::new();
+        let mut pos = pos + 1;
+        loop {
+            pos = self.skip_ws(pos);
+            match self.byte(pos) {
+                b',' => pos += 1,
+                b']' | b'}' | 0 => return elements,
+                _ => {
+                    elements.push(pos);
+                    pos = self.value_end(pos);
+                }
+            }
+        }
+    }
+
+    fn find(&self, pos: usize, path: &[&str], missing: Option<&str>) -> Option<usize> {
+        match (self.byte(pos), path.split_first()) {
+            (b'{', None) => {
+                let missing = missing?;
+                if self.members(pos).iter().any(|(_, key, _)| *key == missing) {
+                    None
+                } else {
+                    Some(pos)
+                }
+            }
+            (b'{', Some((name, rest))) => {
+                let members = self.members(pos);
+                if let Some((key_pos, _, value)) = members.iter().find(|(_, key, _)| key == name) {
+                    return self.find(*value, rest, missing).or(Some(*key_pos))
+                }
+                // array items are named by the parsers, e.g. `p34/p`, instead of their field names
+                members.iter()
+                    .filter(|(_, _, value)| self.byte(*value) == b'[')
+                    .flat_map(|(_, _, value)| self.elements(*value))
+                    .find_map(|item| self.find(item, rest, missing))
+            }
+            (b'[', _) => self.elements(pos).into_iter().find_map(|item| self.find(item, path, missing)),
+            _ => None
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/test_location.rs"]
+mod tests;
@@ -0,0 +1,605 @@
+/*
+ * Copyright (C) ton.dev. All Rights Reserved.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.  You may obtain a copy of the
+ * License at:
+ *
+ * https://www.ever.dev/licenses
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific ton.dev software governing permissions and limitations
+ * under the License.
+ */
+
+//! JSON Schemas (draft 2020-12) of the documents written by the `db_serialize_*` functions.
+//! The schemas list the fields every serializer can write with the mode-dependent number
+//! conventions, the fields written only for some blockchain objects are not required.
+//! The objects are left open so the schemas describe the documents without pinning them.
+
+use serde_json::{json, Map, Value};
+
+use crate::SerializationMode;
+use crate::serialize::VERSION;
+
+const DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Any,
+    Bool,
+    Int,
+    UInt,
+    Hash,
+    Hashes,
+    Base64,
+    Str,
+    Shard,
+    Array,
+    Object,
+    // `*_name` companions written in QServer and Debug modes only
+    Name,
+    Grams,
+    U64,
+    Lt,
+    BigInt,
+    Cc,
+    SignedCc,
+    ConfigParams,
+    Nested(&'static [Field]),
+    ArrayOf(&'static [Field]),
+}
+
+// name, kind, required
+type Field = (&'static str, Kind, bool);
+
+use Kind::*;
+
+const BLOCK_REF_FIELDS: &[Field] = &[
+    ("end_lt", Lt, true),
+    ("seq_no", UInt, true),
+    ("key", Bool, false),
+    ("root_hash", Hash, true),
+    ("file_hash", Hash, true),
+];
+
+const SIGNATURE_FIELDS: &[Field] = &[
+    ("node_id", Hash, true),
+    ("r", Hash, true),
+    ("s", Hash, true),
+];
+
+const COPYLEFT_REWARD_FIELDS: &[Field] = &[
+    ("account", Str, true),
+    ("reward", Grams, true),
+];
+
+const VALUE_FLOW_FIELDS: &[Field] = &[
+    ("from_prev_blk", Cc, true),
+    ("to_next_blk", Cc, true),
+    ("imported", Cc, true),
+    ("exported", Cc, true),
+    ("fees_collected", Cc, true),
+    ("fees_imported", Cc, true),
+    ("recovered", Cc, true),
+    ("created", Cc, true),
+    ("minted", Cc, true),
+    ("copyleft_rewards", ArrayOf(COPYLEFT_REWARD_FIELDS), true),
+];
+
+const ACCOUNT_BLOCK_TRANSACTION_FIELDS: &[Field] = &[
+    ("lt", Lt, true),
+    ("transaction_id", Hash, true),
+    ("total_fees", Cc, true),
+];
+
+const ACCOUNT_BLOCK_FIELDS: &[Field] = &[
+    ("account_addr", Str, true),
+    ("transactions", ArrayOf(ACCOUNT_BLOCK_TRANSACTION_FIELDS), true),
+    ("old_hash", Hash, true),
+    ("new_hash", Hash, true),
+    ("tr_count", UInt, true),
+];
+
+const CONFIG_FIELDS: &[Field] = &[
+    ("config_addr", Hash, true),
+    ("config", ConfigParams, true),
+    ("unknown_config", Array, false),
+];
+
+// the config is written into the masterchain block extra of key blocks only
+const MC_BLOCK_EXTRA_FIELDS: &[Field] = &[
+    ("shard_hashes", Array, false),
+    ("min_shard_gen_utime", UInt, false),
+    ("max_shard_gen_utime", UInt, false),
+    ("shard_fees", Array, false),
+    ("prev_blk_signatures", ArrayOf(SIGNATURE_FIELDS), true),
+    ("recover_create_msg", Object, false),
+    ("mint_msg", Object, false),
+    ("config_addr", Hash, false),
+    ("config", ConfigParams, false),
+    ("unknown_config", Array, false),
+];
+
+const BLOCK_FIELDS: &[Field] = &[
+    ("file_hash", Hash, true),
+    ("status", UInt, true),
+    ("status_name", Name, true),
+    ("boc", Base64, true),
+    ("global_id", Int, true),
+    ("version", UInt, true),
+    ("after_merge", Bool, true),
+    ("before_split", Bool, true),
+    ("after_split", Bool, true),
+    ("want_split", Bool, true),
+    ("want_merge", Bool, true),
+    ("key_block", Bool, true),
+    ("vert_seqno_incr", UInt, true),
+    ("seq_no", UInt, true),
+    ("vert_seq_no", UInt, true),
+    ("gen_utime", UInt, true),
+    ("gen_utime_ms", UInt, false),
+    ("start_lt", Lt, true),
+    ("end_lt", Lt, true),
+    ("gen_validator_list_hash_short", UInt, true),
+    ("gen_catchain_seqno", UInt, true),
+    ("min_ref_mc_seqno", UInt, true),
+    ("prev_key_block_seqno", UInt, true),
+    ("workchain_id", Int, true),
+    ("shard", Shard, true),
+    ("pack_info", Object, false),
+    ("gen_software_version", UInt, false),
+    ("gen_software_capabilities", U64, false),
+    ("prev_seq_no", UInt, true),
+    ("master_ref", Nested(BLOCK_REF_FIELDS), false),
+    ("prev_ref", Nested(BLOCK_REF_FIELDS), true),
+    ("prev_alt_ref", Nested(BLOCK_REF_FIELDS), false),
+    ("prev_vert_ref", Nested(BLOCK_REF_FIELDS), false),
+    ("prev_vert_alt_ref", Nested(BLOCK_REF_FIELDS), false),
+    ("prev_refs", ArrayOf(BLOCK_REF_FIELDS), true),
+    ("value_flow", Nested(VALUE_FLOW_FIELDS), true),
+    ("old_hash", Hash, true),
+    ("new_hash", Hash, true),
+    ("old_depth", UInt, true),
+    ("new_depth", UInt, true),
+    ("in_msg_descr", Array, true),
+    ("out_msg_descr", Array, true),
+    ("ref_shard_blocks", Array, false),
+    ("account_blocks", ArrayOf(ACCOUNT_BLOCK_FIELDS), false),
+    ("tr_count", UInt, true),
+    ("rand_seed", Hash, true),
+    ("created_by", Hash, true),
+    ("master", Nested(MC_BLOCK_EXTRA_FIELDS), false),
+];
+
+const STORAGE_PHASE_FIELDS: &[Field] = &[
+    ("storage_fees_collected", Grams, true),
+    ("storage_fees_due", Grams, false),
+    ("status_change", UInt, true),
+    ("status_change_name", Name, true),
+];
+
+const CREDIT_PHASE_FIELDS: &[Field] = &[
+    ("due_fees_collected", Grams, false),
+    ("credit", Cc, true),
+];
+
+// the skipped phase has only the reason, the other fields belong to the executed one
+const COMPUTE_PHASE_FIELDS: &[Field] = &[
+    ("compute_type", UInt, true),
+    ("compute_type_name", Name, true),
+    ("skipped_reason", UInt, false),
+    ("skipped_reason_name", Name, false),
+    ("success", Bool, false),
+    ("msg_state_used", Bool, false),
+    ("account_activated", Bool, false),
+    ("gas_fees", Grams, false),
+    ("gas_used", UInt, false),
+    ("gas_limit", UInt, false),
+    ("gas_credit", UInt, false),
+    ("mode", Int, false),
+    ("exit_code", Int, false),
+    ("exit_arg", Int, false),
+    ("vm_steps", UInt, false),
+    ("vm_init_state_hash", Hash, false),
+    ("vm_final_state_hash", Hash, false),
+];
+
+const ACTION_PHASE_FIELDS: &[Field] = &[
+    ("success", Bool, true),
+    ("valid", Bool, true),
+    ("no_funds", Bool, true),
+    ("status_change", UInt, true),
+    ("total_fwd_fees", Grams, false),
+    ("total_action_fees", Grams, false),
+    ("result_code", Int, true),
+    ("result_arg", Int, false),
+    ("tot_actions", UInt, true),
+    ("spec_actions", UInt, true),
+    ("skipped_actions", UInt, true),
+    ("msgs_created", UInt, true),
+    ("action_list_hash", Hash, true),
+    ("tot_msg_size_cells", UInt, true),
+    ("tot_msg_size_bits", UInt, true),
+];
+
+const BOUNCE_PHASE_FIELDS: &[Field] = &[
+    ("bounce_type", UInt, true),
+    ("bounce_type_name", Name, true),
+    ("msg_size_cells", UInt, false),
+    ("msg_size_bits", UInt, false),
+    ("req_fwd_fees", Grams, false),
+    ("msg_fees", Grams, false),
+    ("fwd_fees", Grams, false),
+];
+
+// the phases and flags depend on the transaction type, the account is written
+// either as `account_addr` with `workchain_id` or as `account_id`
+const TRANSACTION_FIELDS: &[Field] = &[
+    ("block_id", Hash, false),
+    ("proof", Base64, false),
+    ("boc", Base64, true),
+    ("status", UInt, true),
+    ("status_name", Name, true),
+    ("storage", Nested(STORAGE_PHASE_FIELDS), false),
+    ("credit", Nested(CREDIT_PHASE_FIELDS), false),
+    ("compute", Nested(COMPUTE_PHASE_FIELDS), false),
+    ("action", Nested(ACTION_PHASE_FIELDS), false),
+    ("bounce", Nested(BOUNCE_PHASE_FIELDS), false),
+    ("credit_first", Bool, false),
+    ("aborted", Bool, false),
+    ("destroyed", Bool, false),
+    ("cur_shard_pfx_len", UInt, false),
+    ("acc_split_depth", UInt, false),
+    ("this_addr", Hash, false),
+    ("sibling_addr", Hash, false),
+    ("prepare_transaction", Hash, false),
+    ("installed", Bool, false),
+    ("tr_type", UInt, true),
+    ("tr_type_name", Name, true),
+    ("lt", Lt, true),
+    ("prev_trans_hash", Hash, true),
+    ("prev_trans_lt", Lt, true),
+    ("now", UInt, true),
+    ("outmsg_cnt", UInt, true),
+    ("orig_status", UInt, true),
+    ("orig_status_name", Name, true),
+    ("end_status", UInt, true),
+    ("end_status_name", Name, true),
+    ("in_msg", Hash, false),
+    ("ext_in_msg_fee", Grams, false),
+    ("out_msgs", Hashes, true),
+    ("account_addr", Str, false),
+    ("workchain_id", Int, false),
+    ("account_id", Hash, false),
+    ("total_fees", Cc, true),
+    ("balance_delta", SignedCc, true),
+    ("old_hash", Hash, true),
+    ("new_hash", Hash, true),
+];
+
+// the header fields depend on the message type
+const MESSAGE_FIELDS: &[Field] = &[
+    ("transaction_id", Hash, false),
+    ("proof", Base64, false),
+    ("boc", Base64, true),
+    ("status", UInt, true),
+    ("status_name", Name, true),
+    ("split_depth", UInt, false),
+    ("tick", Bool, false),
+    ("tock", Bool, false),
+    ("code", Base64, false),
+    ("code_hash", Hash, false),
+    ("data", Base64, false),
+    ("data_hash", Hash, false),
+    ("library", Base64, false),
+    ("library_hash", Hash, false),
+    ("body", Base64, false),
+    ("body_hash", Hash, false),
+    ("msg_type", UInt, true),
+    ("msg_type_name", Str, true),
+    ("src", Str, true),
+    ("src_workchain_id", Int, false),
+    ("dst", Str, true),
+    ("dst_workchain_id", Int, false),
+    ("ihr_disabled", Bool, false),
+    ("ihr_fee", Grams, false),
+    ("fwd_fee", Grams, false),
+    ("import_fee", Grams, false),
+    ("bounce", Bool, false),
+    ("bounced", Bool, false),
+    ("value", Cc, false),
+    ("created_lt", Lt, false),
+    ("created_at", UInt, false),
+    ("msg_hash_normalized", Hash, false),
+];
+
+// the account state fields depend on the account status
+const ACCOUNT_FIELDS: &[Field] = &[
+    ("workchain_id", Int, true),
+    ("boc", Base64, true),
+    ("boc1", Base64, false),
+    ("init_code_hash", Hash, false),
+    ("last_paid", UInt, false),
+    ("bits", U64, false),
+    ("cells", U64, false),
+    ("public_cells", U64, false),
+    ("due_payment", Grams, false),
+    ("last_trans_lt", Lt, true),
+    ("balance", Cc, false),
+    ("split_depth", UInt, false),
+    ("tick", Bool, false),
+    ("tock", Bool, false),
+    ("code", Base64, false),
+    ("code_hash", Hash, false),
+    ("code_label", Str, false),
+    ("data", Base64, false),
+    ("data_hash", Hash, false),
+    ("library", Base64, false),
+    ("library_hash", Hash, false),
+    ("state_hash", Hash, false),
+    ("proof", Base64, false),
+    ("acc_type", UInt, true),
+    ("acc_type_name", Name, true),
+    ("prev_code_hash", Hash, false),
+];
+
+// the signatures are written for the proofs of the masterchain blocks only
+const BLOCK_PROOF_FIELDS: &[Field] = &[
+    ("gen_utime", UInt, true),
+    ("gen_utime_ms", UInt, false),
+    ("seq_no", UInt, true),
+    ("workchain_id", Int, true),
+    ("shard", Shard, true),
+    ("proof", Base64, true),
+    ("validator_list_hash_short", UInt, false),
+    ("catchain_seqno", UInt, false),
+    ("sig_weight", U64, false),
+    ("signatures", ArrayOf(SIGNATURE_FIELDS), false),
+];
+
+const OUT_MSG_QUEUE_INFO_FIELDS: &[Field] = &[
+    ("out_queue", Array, true),
+    ("proc_info", Array, true),
+    ("ihr_pending", Array, true),
+];
+
+const VALIDATOR_INFO_FIELDS: &[Field] = &[
+    ("validator_list_hash_short", UInt, true),
+    ("catchain_seqno", UInt, true),
+    ("nx_cc_updated", Bool, true),
+];
+
+const MC_STATE_EXTRA_FIELDS: &[Field] = &[
+    ("shard_hashes", Array, false),
+    ("min_shard_gen_utime", UInt, false),
+    ("max_shard_gen_utime", UInt, false),
+    ("config_addr", Hash, true),
+    ("config", ConfigParams, true),
+    ("unknown_config", Array, false),
+    ("validator_info", Nested(VALIDATOR_INFO_FIELDS), true),
+    ("after_key_block", Bool, true),
+    ("last_key_block", Nested(BLOCK_REF_FIELDS), false),
+    ("block_create_stats", Array, false),
+    ("global_balance", Cc, true),
+    ("state_copyleft_rewards", ArrayOf(COPYLEFT_REWARD_FIELDS), true),
+    ("validators_unreliability", Any, false),
+];
+
+// `accounts` are added separately, they are account documents without `json_version`
+const STATE_FIELDS: &[Field] = &[
+    ("root_hash", Hash, true),
+    ("file_hash", Hash, true),
+    ("block_id", Hash, false),
+    ("workchain_id", Int, true),
+    ("boc", Base64, true),
+    ("global_id", Int, true),
+    ("shard", Shard, true),
+    ("seq_no", UInt, true),
+    ("vert_seq_no", UInt, true),
+    ("gen_utime", UInt, true),
+    ("gen_utime_ms", UInt, false),
+    ("gen_lt", Lt, true),
+    ("min_ref_mc_seqno", UInt, true),
+    ("before_split", Bool, true),
+    ("overload_history", U64, true),
+    ("underload_history", U64, true),
+    ("total_balance", Cc, true),
+    ("total_validator_fees", Cc, true),
+    ("master_ref", Nested(BLOCK_REF_FIELDS), false),
+    ("master", Nested(MC_STATE_EXTRA_FIELDS), false),
+    ("libraries", Array, true),
+    ("out_msg_queue_info", Nested(OUT_MSG_QUEUE_INFO_FIELDS), true),
+];
+
+fn string_pattern(pattern: &str) -> Value {
+    json!({ "type": "string", "pattern": pattern })
+}
+
+fn decimal_string(signed: bool) -> Value {
+    string_pattern(if signed { "^-?[0-9]+$" } else { "^[0-9]+$" })
+}
+
+// Standart numbers are hex with the length prefix (two digits for grams and big integers),
+// QServer ones are `0x` hex and Debug ones are decimal with lt written as `{lt / 10^6}_{lt % 10^6}`
+fn number_string(kind: Kind, mode: SerializationMode) -> Value {
+    match (mode, kind) {
+        (SerializationMode::Standart, Grams) => string_pattern("^[0-9a-f]{3,}$"),
+        (SerializationMode::Standart, BigInt) => string_pattern("^-?[0-9a-f]{3,}$"),
+        (SerializationMode::Standart, _) => string_pattern("^[0-9a-f]{2,}$"),
+        (SerializationMode::QServer, BigInt) => string_pattern("^-?0x[0-9a-f]+$"),
+        (SerializationMode::QServer, _) => string_pattern("^0x[0-9a-f]+$"),
+        (SerializationMode::Debug, Lt) => string_pattern("^[0-9]+_[0-9]+$"),
+        (SerializationMode::Debug, BigInt) => decimal_string(true),
+        (SerializationMode::Debug, _) => decimal_string(false),
+    }
+}
+
+struct ObjectSchema {
+    mode: SerializationMode,
+    properties: Map<String, Value>,
+    required: Vec<Value>,
+}
+
+impl ObjectSchema {
+    fn new(mode: SerializationMode) -> Self {
+        Self { mode, properties: Map::new(), required: Vec::new() }
+    }
+
+    fn property(&mut self, name: &str, schema: Value, required: bool) {
+        self.properties.insert(name.to_string(), schema);
+        if required {
+            self.required.push(name.into());
+        }
+    }
+
+    // numbers get the `_dec` companion in Standart mode
+    fn number(&mut self, name: &str, kind: Kind, required: bool) {
+        if self.mode.is_standart() {
+            let signed = matches!(kind, BigInt);
+            self.property(&format!("{}_dec", name), decimal_string(signed), required);
+        }
+        self.property(name, number_string(kind, self.mode), required);
+    }
+
+    fn currency_collection(&mut self, name: &str, grams: Kind, required: bool) {
+        self.number(name, grams, required);
+        let mut other = ObjectSchema::new(self.mode);
+        other.field("currency", UInt, true);
+        other.number("value", BigInt, true);
+        let other = json!({ "type": "array", "items": other.build() });
+        self.property(&format!("{}_other", name), other, false);
+    }
+
+    fn field(&mut self, name: &str, kind: Kind, required: bool) {
+        let schema = match kind {
+            Any => json!({}),
+            Bool => json!({ "type": "boolean" }),
+            Int => json!({ "type": "integer" }),
+            UInt => json!({ "type": "integer", "minimum": 0 }),
+            Hash => string_pattern("^[0-9a-f]{64}$"),
+            Hashes => json!({ "type": "array", "items": string_pattern("^[0-9a-f]{64}$") }),
+            Base64 => string_pattern("^[A-Za-z0-9+/=_-]*$"),
+            Str => json!({ "type": "string" }),
+            Shard => string_pattern("^[0-9a-f]{16}$"),
+            Array => json!({ "type": "array" }),
+            Object => json!({ "type": "object" }),
+            Name => {
+                if self.mode.is_q_server() {
+                    self.property(name, json!({ "type": "string" }), required);
+                }
+                return
+            }
+            Grams | U64 | Lt | BigInt => return self.number(name, kind, required),
+            Cc => return self.currency_collection(name, Grams, required),
+            SignedCc => return self.currency_collection(name, BigInt, required),
+            ConfigParams => config_params_schema(),
+            Nested(fields) => ObjectSchema::new(self.mode).fields(fields).build(),
+            ArrayOf(fields) => json!({
+                "type": "array",
+                "items": ObjectSchema::new(self.mode).fields(fields).build()
+            }),
+        };
+        self.property(name, schema, required)
+    }
+
+    fn fields(mut self, fields: &[Field]) -> Self {
+        for (name, kind, required) in fields {
+            self.field(name, *kind, *required);
+        }
+        self
+    }
+
+    fn build(self) -> Value {
+        json!({
+            "type": "object",
+            "properties": self.properties,
+            "required": self.required,
+        })
+    }
+}
+
+// p0..p5 are account ids, the other parameters are written as objects or lists
+fn config_params_schema() -> Value {
+    let mut properties = Map::new();
+    for number in 0..=5 {
+        properties.insert(format!("p{}", number), string_pattern("^[0-9a-f]{64}$"));
+    }
+    json!({
+        "type": "object",
+        "propertyNames": { "pattern": "^p[0-9]+$" },
+        "properties": properties,
+    })
+}
+
+fn document_schema(
+    title: &str,
+    id_str: &str,
+    id_kind: Kind,
+    fields: &[Field],
+    mode: SerializationMode
+) -> Value {
+    let mut schema = ObjectSchema::new(mode);
+    schema.property("json_version", json!({ "type": "integer", "const": VERSION }), true);
+    schema.field(id_str, id_kind, true);
+    with_title(schema.fields(fields).build(), title)
+}
+
+fn with_title(mut schema: Value, title: &str) -> Value {
+    if let Value::Object(map) = &mut schema {
+        map.insert("$schema".to_string(), DRAFT_2020_12.into());
+        map.insert("title".to_string(), title.into());
+    }
+    schema
+}
+
+/// Schema of the document written by `db_serialize_block_ex` with the same `id_str` and `mode`
+pub fn block_schema(id_str: &str, mode: SerializationMode) -> Value {
+    document_schema("Block", id_str, Hash, BLOCK_FIELDS, mode)
+}
+
+/// Schema of the document written by `db_serialize_transaction_ex`
+pub fn transaction_schema(id_str: &str, mode: SerializationMode) -> Value {
+    document_schema("Transaction", id_str, Hash, TRANSACTION_FIELDS, mode)
+}
+
+/// Schema of the document written by `db_serialize_message_ex`
+pub fn message_schema(id_str: &str, mode: SerializationMode) -> Value {
+    document_schema("Message", id_str, Hash, MESSAGE_FIELDS, mode)
+}
+
+/// Schema of the document written by `db_serialize_account_ex`, the id is the account address
+pub fn account_schema(id_str: &str, mode: SerializationMode) -> Value {
+    document_schema("Account", id_str, Str, ACCOUNT_FIELDS, mode)
+}
+
+/// Schema of the document written by `db_serialize_block_proof_ex`
+pub fn block_proof_schema(id_str: &str, mode: SerializationMode) -> Value {
+    document_schema("Block proof", id_str, Hash, BLOCK_PROOF_FIELDS, mode)
+}
+
+/// Schema of the config written by `serialize_config`: `config_addr`, the known parameters
+/// as `config` and the raw unknown ones as `unknown_config`
+pub fn config_schema(mode: SerializationMode) -> Value {
+    with_title(ObjectSchema::new(mode).fields(CONFIG_FIELDS).build(), "Config")
+}
+
+/// Schema of the document written by `db_serialize_shard_state_ex`
+pub fn state_schema(id_str: &str, mode: SerializationMode) -> Value {
+    let mut account = ObjectSchema::new(mode);
+    account.field("id", Str, false);
+    account.field("last_trans_hash", Hash, false);
+    account.field("shard_last_trans_lt", Lt, false);
+    let account = account.fields(ACCOUNT_FIELDS).build();
+    let mut schema = ObjectSchema::new(mode);
+    schema.property("json_version", json!({ "type": "integer", "const": VERSION }), true);
+    schema.field(id_str, Str, true);
+    schema.property("accounts", json!({ "type": "array", "items": account }), true);
+    with_title(schema.fields(STATE_FIELDS).build(), "Shard state")
+}
+
+#[cfg(test)]
+#[path = "tests/test_schema.rs"]
+mod tests;
@@ -15,13 +15,17 @@
  */
 
 use ton_dev_block::*;
-use ton_api::ton::ton_node::{RempMessageLevel, RempMessageStatus, RempReceipt};
+#[cfg(feature = "remp")]
+use ton_api::ton::ton_node::{RempMessageStatus, RempReceipt};
+#[cfg(feature = "remp")]
+use crate::shared::remp_status_kind_string;
 use num::BigInt;
 use num_traits::sign::Signed;
 use serde_json::{Map, Value};
 use std::collections::HashMap;
+use crate::shared::{unix_time_to_rfc3339, COMPLAINT_PRICING_TAG, GLOBAL_CAPABILITIES, WORKCHAIN_FLAGS};
 
-const VERSION: u32 = 8;
+pub(crate) const VERSION: u32 = 8;
 // Version changes
 // 2 - fix var account addresses tag in block (`8_` postfix)
 // 3 - `balance_delta` added to transaction
@@ -33,7 +37,7 @@ const VERSION: u32 = 8;
 
 const STD_ACCOUNT_ID_LENGTH: usize = 256;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum SerializationMode {
     Standart,
     QServer,
@@ -50,6 +54,19 @@ impl SerializationMode {
     }
 }
 
+/// Alternative forms of some fields for the interop with other exporters,
+/// the default options give the same output as the functions without options
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SerializationOptions {
+    /// Writes the `wc` and `shard` pairs of the block ids (in p58, remp statuses) as single
+    /// composite strings like `"shard": "-1,8000000000000000"`, `parse_shard_ident` reads both forms
+    pub composite_shards: bool,
+    /// Writes p7 as the object keyed by the currency ids with the decimal values,
+    /// e.g. `"p7": {"239": "1000000"}`, instead of the array of `{currency, value}`.
+    /// `parse_config` reads both forms
+    pub p7_as_map: bool,
+}
+
 struct SignedCurrencyCollection {
     pub grams: BigInt,
     pub other: HashMap<u32, BigInt>
@@ -247,7 +264,37 @@ pub fn shard_to_string(value: u64) -> String {
     format!("{:016x}", value)
 }
 
-fn construct_address(workchain_id: i32, account_id: AccountId) -> Result<MsgAddressInt> {
+pub fn shard_ident_to_json(shard: &ShardIdent) -> (i32, String) {
+    (shard.workchain_id(), shard_to_string(shard.shard_prefix_with_tag()))
+}
+
+/// Lowercase hex of the hash the block, transaction and message document ids are made of,
+/// used directly where the cell hash is known to avoid serializing the cell again
+pub(crate) fn hash_doc_id(hash: &UInt256) -> String {
+    hash.as_hex_string()
+}
+
+/// Id of the block document: lowercase hex of the root hash
+pub fn block_doc_id(block_id: &BlockIdExt) -> String {
+    hash_doc_id(block_id.root_hash())
+}
+
+/// Id of the transaction document: lowercase hex of the transaction cell hash
+pub fn transaction_doc_id(transaction: &Transaction) -> Result<String> {
+    Ok(hash_doc_id(&transaction.serialize()?.repr_hash()))
+}
+
+/// Id of the message document: lowercase hex of the message cell hash
+pub fn message_doc_id(message: &Message) -> Result<String> {
+    Ok(hash_doc_id(&message.serialize()?.repr_hash()))
+}
+
+/// Id of the account document: the `wc:hex` address with the lowercase account id
+pub fn account_doc_id(address: &MsgAddressInt) -> String {
+    address.to_string()
+}
+
+pub(crate) fn construct_address(workchain_id: i32, account_id: AccountId) -> Result<MsgAddressInt> {
     if (-128..=127).contains(&workchain_id)
         && account_id.remaining_bits() == STD_ACCOUNT_ID_LENGTH
     {
@@ -257,6 +304,164 @@ fn construct_address(workchain_id: i32, account_id: AccountId) -> Result<MsgAddr
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Base64Format {
+    Standard,
+    StandardUnpadded,
+    UrlSafe,
+    UrlSafeUnpadded,
+}
+
+pub fn base64_encode_as(data: impl AsRef<[u8]>, format: Base64Format) -> String {
+    convert_base64(base64_encode(data), format)
+}
+
+fn convert_base64(mut string: String, format: Base64Format) -> String {
+    if matches!(format, Base64Format::UrlSafe | Base64Format::UrlSafeUnpadded) {
+        string = string.replace('+', "-").replace('/', "_");
+    }
+    if matches!(format, Base64Format::StandardUnpadded | Base64Format::UrlSafeUnpadded) {
+        string.truncate(string.trim_end_matches('=').len());
+    }
+    string
+}
+
+// fields the serializers write in base64
+const BASE64_FIELDS: [&str; 13] = [
+    "boc", "boc1", "body", "code", "data", "lib", "library", "master_boc", "msg",
+    "proof", "proof_created", "proof_delivered", "signature",
+];
+
+/// Re-encodes all base64 fields of the serialized document into the given format.
+/// The serializers always write the standard padded alphabet.
+pub fn convert_base64_fields(value: &mut Value, format: Base64Format) {
+    match value {
+        Value::Object(map) => {
+            for (name, value) in map.iter_mut() {
+                if let Value::String(string) = value {
+                    if BASE64_FIELDS.contains(&name.as_str()) {
+                        *string = convert_base64(std::mem::take(string), format);
+                    }
+                } else {
+                    convert_base64_fields(value, format);
+                }
+            }
+        }
+        Value::Array(array) => array.iter_mut().for_each(|value| convert_base64_fields(value, format)),
+        _ => ()
+    }
+}
+
+/// Kind of the serialized document, selects the fields [`skip_default_fields`] may remove
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentKind {
+    /// `after_merge`, `before_split`, `after_split`, `want_split`, `want_merge`, `key_block`
+    /// if false, `vert_seqno_incr`, `vert_seq_no` if zero, `in_msg_descr`, `out_msg_descr`
+    /// if empty
+    Block,
+    /// `aborted`, `destroyed` if false, `ext_in_msg_fee` if zero, `out_msgs` if empty
+    Transaction,
+    /// `bounce`, `bounced` if false, `value`, `ihr_fee`, `fwd_fee`, `import_fee` if zero
+    Message,
+    /// `tick`, `tock` if false, `due_payment` if zero
+    Account,
+    /// `flags` if zero and `flags_list` if empty in the workchain descriptions of p12
+    State,
+}
+
+struct DefaultFields {
+    falsy: &'static [&'static str],
+    zero: &'static [&'static str],
+    empty: &'static [&'static str],
+}
+
+const BLOCK_DEFAULT_FIELDS: DefaultFields = DefaultFields {
+    falsy: &["after_merge", "before_split", "after_split", "want_split", "want_merge", "key_block"],
+    zero: &["vert_seqno_incr", "vert_seq_no"],
+    empty: &["in_msg_descr", "out_msg_descr"],
+};
+
+const TRANSACTION_DEFAULT_FIELDS: DefaultFields = DefaultFields {
+    falsy: &["aborted", "destroyed"],
+    zero: &["ext_in_msg_fee"],
+    empty: &["out_msgs"],
+};
+
+const MESSAGE_DEFAULT_FIELDS: DefaultFields = DefaultFields {
+    falsy: &["bounce", "bounced"],
+    zero: &["value", "ihr_fee", "fwd_fee", "import_fee"],
+    empty: &[],
+};
+
+const ACCOUNT_DEFAULT_FIELDS: DefaultFields = DefaultFields {
+    falsy: &["tick", "tock"],
+    zero: &["due_payment"],
+    empty: &[],
+};
+
+const STATE_DEFAULT_FIELDS: DefaultFields = DefaultFields {
+    falsy: &[],
+    zero: &["flags"],
+    empty: &["flags_list"],
+};
+
+impl DocumentKind {
+    fn default_fields(self) -> &'static DefaultFields {
+        match self {
+            DocumentKind::Block => &BLOCK_DEFAULT_FIELDS,
+            DocumentKind::Transaction => &TRANSACTION_DEFAULT_FIELDS,
+            DocumentKind::Message => &MESSAGE_DEFAULT_FIELDS,
+            DocumentKind::Account => &ACCOUNT_DEFAULT_FIELDS,
+            DocumentKind::State => &STATE_DEFAULT_FIELDS,
+        }
+    }
+}
+
+fn is_zero_value(value: &Value) -> bool {
+    match value {
+        Value::Number(number) => number.as_u64() == Some(0),
+        // zero grams and numbers in all serialization modes
+        Value::String(string) => matches!(string.as_str(), "0" | "00" | "000" | "0x0"),
+        _ => false
+    }
+}
+
+/// Removes the fields equal to the default value of their type from the serialized document
+/// and its nested objects. The fields removed for every kind are listed in [`DocumentKind`],
+/// besides them empty extra currency collections (`*_other`) are removed for all kinds.
+/// The parsers treat the absent fields as default, so the documents re-parse into equal structures.
+pub fn skip_default_fields(value: &mut Value, kind: DocumentKind) {
+    skip_default_fields_impl(value, kind.default_fields());
+}
+
+fn skip_default_fields_impl(value: &mut Value, fields: &DefaultFields) {
+    match value {
+        Value::Object(map) => {
+            let mut skipped = Vec::new();
+            for (name, value) in map.iter() {
+                let name = name.as_str();
+                let is_default = match value {
+                    Value::Bool(false) => fields.falsy.contains(&name),
+                    Value::Array(array) if array.is_empty() => {
+                        fields.empty.contains(&name) || name.ends_with("_other")
+                    }
+                    value => fields.zero.contains(&name) && is_zero_value(value),
+                };
+                if is_default {
+                    skipped.push(name.to_owned());
+                }
+            }
+            for name in skipped {
+                map.remove(&format!("{}_dec", name));
+                map.remove(&name);
+            }
+            map.values_mut().for_each(|value| skip_default_fields_impl(value, fields));
+        }
+        Value::Array(array) => array.iter_mut().for_each(|value| skip_default_fields_impl(value, fields)),
+        _ => ()
+    }
+}
+
 fn serialize_cell(
     map: &mut Map<String, Value>,
     id_str: &'static str,
@@ -472,13 +677,31 @@ fn serialize_bounce_phase<'a>(map: &mut Map<String, Value>, ph: Option<&'a TrBou
     fees
 }
 
+// the documents keep the collection flat: `grams` and `grams_dec` become `{prefix}` and `{prefix}_dec`,
+// `other` becomes `{prefix}_other`
 fn serialize_cc(map: &mut Map<String, Value>, prefix: &'static str, cc: &CurrencyCollection, mode: SerializationMode) -> Result<()> {
-    serialize_grams(map,  prefix, &cc.grams, mode);
+    if let Value::Object(cc) = serialize_currency_collection(cc, mode)? {
+        for (key, value) in cc {
+            let key = match key.strip_prefix("grams") {
+                Some(suffix) => format!("{}{}", prefix, suffix),
+                None => format!("{}_{}", prefix, key),
+            };
+            map.insert(key, value);
+        }
+    }
+    Ok(())
+}
+
+/// Serializes the currency collection as the object with `grams` and the `other` extra currencies,
+/// `parse_currency_collection` reads it back
+pub fn serialize_currency_collection(cc: &CurrencyCollection, mode: SerializationMode) -> Result<Value> {
+    let mut map = Map::new();
+    serialize_grams(&mut map, "grams", &cc.grams, mode);
     let other = serialize_ecc(&cc.other, mode)?;
     if !other.is_empty() {
-        map.insert(format!("{}_other", prefix), other.into());
+        map.insert("other".to_string(), other.into());
     }
-    Ok(())
+    Ok(map.into())
 }
 
 fn serialize_ecc(ecc: &ExtraCurrencyCollection, mode: SerializationMode) -> Result<Vec<Map<String, Value>>> {
@@ -493,6 +716,15 @@ fn serialize_ecc(ecc: &ExtraCurrencyCollection, mode: SerializationMode) -> Resu
     Ok(other)
 }
 
+fn serialize_ecc_map(ecc: &ExtraCurrencyCollection) -> Result<Map<String, Value>> {
+    let mut map = Map::new();
+    ecc.iterate_with_keys(|key: u32, value| -> Result<bool> {
+        map.insert(key.to_string(), value.value().to_string().into());
+        Ok(true)
+    })?;
+    Ok(map)
+}
+
 fn serialize_scc(
     map: &mut Map<String, Value>,
     prefix: &'static str,
@@ -608,6 +840,16 @@ fn serialize_in_msg(msg: &InMsg, mode: SerializationMode) -> Result<Value> {
     Ok(map.into())
 }
 
+// recover_create_msg and mint_msg are not listed in the block's in_msg_descr,
+// so the message itself is attached to the envelope
+fn serialize_mc_special_msg(msg: &InMsg, mode: SerializationMode) -> Result<Value> {
+    let mut value = serialize_in_msg(msg, mode)?;
+    if let Some(Value::Object(envelope)) = value.get_mut("in_msg") {
+        serialize_cell(envelope, "boc", Some(&msg.message_cell()?), false)?;
+    }
+    Ok(value)
+}
+
 fn serialize_out_msg(msg: &OutMsg, mode: SerializationMode) -> Result<Value> {
     let mut map = Map::new();
     let (type_, type_name) = match msg {
@@ -666,6 +908,83 @@ fn serialize_out_msg(msg: &OutMsg, mode: SerializationMode) -> Result<Value> {
     Ok(map.into())
 }
 
+fn serialize_envelope_routing(env: &MsgEnvelope, mode: SerializationMode) -> Result<Value> {
+    let mut map = Map::new();
+    let (cur_prefix, next_prefix) = env.calc_cur_next_prefix()?;
+    serialize_intermidiate_address(&mut map, "cur_addr",  env.cur_addr());
+    serialize_intermidiate_address(&mut map, "next_addr", env.next_addr());
+    map.insert("cur_prefix".to_string(),  format!("{}", cur_prefix).into());
+    map.insert("next_prefix".to_string(), format!("{}", next_prefix).into());
+    serialize_grams(&mut map, "fwd_fee_remaining", env.fwd_fee_remaining(), mode);
+    Ok(map.into())
+}
+
+// TL-B constructor name, routing of the envelopes and the fees of the in msg descriptor
+fn serialize_in_msg_envelope(msg: &InMsg, mode: SerializationMode) -> Result<Map<String, Value>> {
+    let mut map = Map::new();
+    let tag = match msg {
+        InMsg::External(_) => "msg_import_ext",
+        InMsg::IHR(msg) => {
+            serialize_grams(&mut map, "ihr_fee", msg.ihr_fee(), mode);
+            "msg_import_ihr"
+        }
+        InMsg::Immediate(msg) => {
+            map.insert("in".to_string(), serialize_envelope_routing(&msg.read_envelope_message()?, mode)?);
+            serialize_grams(&mut map, "fwd_fee", &msg.fwd_fee, mode);
+            "msg_import_imm"
+        }
+        InMsg::Final(msg) => {
+            map.insert("in".to_string(), serialize_envelope_routing(&msg.read_envelope_message()?, mode)?);
+            serialize_grams(&mut map, "fwd_fee", &msg.fwd_fee, mode);
+            "msg_import_fin"
+        }
+        InMsg::Transit(msg) => {
+            map.insert("in".to_string(), serialize_envelope_routing(&msg.read_in_message()?, mode)?);
+            map.insert("out".to_string(), serialize_envelope_routing(&msg.read_out_message()?, mode)?);
+            serialize_grams(&mut map, "transit_fee", &msg.transit_fee, mode);
+            "msg_import_tr"
+        }
+        InMsg::DiscardedFinal(msg) => {
+            map.insert("in".to_string(), serialize_envelope_routing(&msg.read_envelope_message()?, mode)?);
+            serialize_grams(&mut map, "fwd_fee", &msg.fwd_fee, mode);
+            "msg_discard_fin"
+        }
+        InMsg::DiscardedTransit(msg) => {
+            map.insert("in".to_string(), serialize_envelope_routing(&msg.read_envelope_message()?, mode)?);
+            serialize_grams(&mut map, "fwd_fee", msg.fwd_fee(), mode);
+            "msg_discard_tr"
+        }
+        _ => "none"
+    };
+    map.insert("tag".to_string(), tag.into());
+    Ok(map)
+}
+
+// TL-B constructor name and routing of the out msg descriptor
+fn serialize_out_msg_envelope(msg: &OutMsg, mode: SerializationMode) -> Result<Map<String, Value>> {
+    let mut map = Map::new();
+    let (tag, env) = match msg {
+        OutMsg::External(_) => ("msg_export_ext", None),
+        OutMsg::Immediate(msg) => ("msg_export_imm", Some(msg.read_out_message()?)),
+        OutMsg::New(msg) => ("msg_export_new", Some(msg.read_out_message()?)),
+        OutMsg::Transit(msg) => ("msg_export_tr", Some(msg.read_out_message()?)),
+        OutMsg::DequeueImmediate(msg) => ("msg_export_deq_imm", Some(msg.read_out_message()?)),
+        OutMsg::Dequeue(msg) => ("msg_export_deq", Some(msg.read_out_message()?)),
+        OutMsg::TransitRequeued(msg) => ("msg_export_tr_req", Some(msg.read_out_message()?)),
+        OutMsg::DequeueShort(msg) => {
+            map.insert("next_prefix".to_string(),
+                format!("{}:{}", msg.next_workchain, shard_to_string(msg.next_addr_pfx)).into());
+            ("msg_export_deq_short", None)
+        }
+        _ => ("none", None)
+    };
+    if let Some(env) = env {
+        map.insert("out".to_string(), serialize_envelope_routing(&env, mode)?);
+    }
+    map.insert("tag".to_string(), tag.into());
+    Ok(map)
+}
+
 fn serialise_shard_block_ref(block_id: &BlockIdExt, end_lt: u64, mode: SerializationMode) -> Result<Value> {
     let mut map = Map::new();
     map.insert("workchain".to_string(), block_id.shard().workchain_id().into());
@@ -759,6 +1078,19 @@ fn serialize_pack_info(pack_info: &MsgPackProcessingInfo) -> Result<Value> {
     Ok(pack_map.into())
 }
 
+// deposit, bit_price and cell_price if the p13 cell is ComplaintPricing without extra data
+fn read_complaint_pricing(cell: &Cell) -> Option<(Grams, Grams, Grams)> {
+    let mut slice = SliceData::load_cell(cell.clone()).ok()?;
+    if slice.get_next_byte().ok()? != COMPLAINT_PRICING_TAG {
+        return None
+    }
+    let deposit = Grams::construct_from(&mut slice).ok()?;
+    let bit_price = Grams::construct_from(&mut slice).ok()?;
+    let cell_price = Grams::construct_from(&mut slice).ok()?;
+    (slice.remaining_bits() == 0 && slice.remaining_references() == 0)
+        .then_some((deposit, bit_price, cell_price))
+}
+
 fn serialize_config_proposal_setup(cps: &ConfigProposalSetup) -> Result<Value> {
     let mut map = Map::new();
     serialize_field(&mut map, "min_tot_rounds", cps.min_tot_rounds);
@@ -781,36 +1113,45 @@ fn serialize_mandatory_params(mp: &MandatoryParams) -> Result<Value> {
     Ok(vector.into())
 }
 
+pub fn serialize_workchain_descr(workchain_id: i32, wc: &WorkchainDescr) -> Value {
+    let mut map = Map::new();
+    serialize_field(&mut map, "workchain_id", workchain_id);
+    serialize_field(&mut map, "enabled_since", wc.enabled_since);
+    serialize_field(&mut map, "actual_min_split", wc.actual_min_split());
+    serialize_field(&mut map, "min_split", wc.min_split());
+    serialize_field(&mut map, "max_split", wc.max_split());
+    serialize_field(&mut map, "active", wc.active);
+    serialize_field(&mut map, "accept_msgs", wc.accept_msgs);
+    serialize_field(&mut map, "flags", wc.flags);
+    let flags = WORKCHAIN_FLAGS.iter()
+        .filter(|(_, bit)| wc.flags & bit != 0)
+        .map(|(name, _)| Value::from(*name))
+        .collect::<Vec<_>>();
+    serialize_field(&mut map, "flags_list", flags);
+    serialize_uint256(&mut map, "zerostate_root_hash", &wc.zerostate_root_hash);
+    serialize_uint256(&mut map, "zerostate_file_hash", &wc.zerostate_file_hash);
+    serialize_field(&mut map, "version", wc.version);
+    match &wc.format {
+        WorkchainFormat::Basic(f) => {
+            serialize_field(&mut map, "basic", true);
+            serialize_field(&mut map, "vm_version" , f.vm_version);
+            serialize_field(&mut map, "vm_mode" , f.vm_mode);
+        },
+        WorkchainFormat::Extended(f) => {
+            serialize_field(&mut map, "basic", false);
+            serialize_field(&mut map, "min_addr_len", f.min_addr_len());
+            serialize_field(&mut map, "max_addr_len", f.max_addr_len());
+            serialize_field(&mut map, "addr_len_step", f.addr_len_step());
+            serialize_field(&mut map, "workchain_type_id", f.workchain_type_id());
+        }
+    }
+    map.into()
+}
+
 fn serialize_workchains(wcs: &Workchains) -> Result<Value> {
     let mut vector = Vec::new();
     wcs.iterate_with_keys(|key: u32, wc: WorkchainDescr| -> Result<bool> {
-        let mut map = Map::new();
-        serialize_field(&mut map, "workchain_id", key);
-        serialize_field(&mut map, "enabled_since", wc.enabled_since);
-        serialize_field(&mut map, "actual_min_split", wc.actual_min_split());
-        serialize_field(&mut map, "min_split", wc.min_split());
-        serialize_field(&mut map, "max_split", wc.max_split());
-        serialize_field(&mut map, "active", wc.active);
-        serialize_field(&mut map, "accept_msgs", wc.accept_msgs);
-        serialize_field(&mut map, "flags", wc.flags);
-        serialize_uint256(&mut map, "zerostate_root_hash", &wc.zerostate_root_hash);
-        serialize_uint256(&mut map, "zerostate_file_hash", &wc.zerostate_file_hash);
-        serialize_field(&mut map, "version", wc.version);
-        match wc.format {
-            WorkchainFormat::Basic(f) => {
-                serialize_field(&mut map, "basic", true);
-                serialize_field(&mut map, "vm_version" , f.vm_version);
-                serialize_field(&mut map, "vm_mode" , f.vm_mode);
-            },
-            WorkchainFormat::Extended(f) => {
-                serialize_field(&mut map, "basic", false);
-                serialize_field(&mut map, "min_addr_len", f.min_addr_len());
-                serialize_field(&mut map, "max_addr_len", f.max_addr_len());
-                serialize_field(&mut map, "addr_len_step", f.addr_len_step());
-                serialize_field(&mut map, "workchain_type_id", f.workchain_type_id());
-            }
-        }
-        vector.push(Value::from(map));
+        vector.push(serialize_workchain_descr(key as i32, &wc));
         Ok(true)
     })?;
     Ok(vector.into())
@@ -821,6 +1162,7 @@ fn serialize_storage_prices(wcs: &ConfigParam18Map, mode: SerializationMode) ->
     wcs.iterate(|val| {
         let mut map = Map::new();
         serialize_field(&mut map, "utime_since", val.utime_since);
+        serialize_field(&mut map, "utime_since_string", unix_time_to_rfc3339(val.utime_since));
         serialize_u64(&mut map, "bit_price_ps", &val.bit_price_ps, mode);
         serialize_u64(&mut map, "cell_price_ps", &val.cell_price_ps, mode);
         serialize_u64(&mut map, "mc_bit_price_ps", &val.mc_bit_price_ps, mode);
@@ -883,21 +1225,34 @@ fn serialize_validators_set(map: &mut Map<String, Value>, set: &ValidatorSet, mo
     serialize_field(map, "total", set.total());
     serialize_field(map, "main", set.main());
     serialize_u64(map, "total_weight", &set.total_weight(), mode);
-    let mut vector = Vec::<Value>::new();
-    for v in set.list() {
-        let mut map = Map::new();
-        serialize_field(&mut map, "public_key", hex::encode(v.public_key.as_slice()));
-        serialize_u64(&mut map, "weight", &v.weight, mode);
-        serialize_id(&mut map, "adnl_addr", v.adnl_addr.as_ref());
-        if let Some(bls_public_key) = v.bls_public_key {
-            serialize_field(&mut map, "bls_public_key", hex::encode(bls_public_key));
-        }
-        vector.push(map.into());
-    };
+    let vector = set.list().iter().map(|v| serialize_validator_descr(v, mode).into()).collect::<Vec<Value>>();
     serialize_field(map, "list", Value::from(vector));
     Ok(())
 }
 
+fn serialize_validator_descr(v: &ValidatorDescr, mode: SerializationMode) -> Map<String, Value> {
+    let mut map = Map::new();
+    serialize_field(&mut map, "public_key", hex::encode(v.public_key.as_slice()));
+    serialize_u64(&mut map, "weight", &v.weight, mode);
+    serialize_id(&mut map, "adnl_addr", v.adnl_addr.as_ref());
+    if let Some(bls_public_key) = v.bls_public_key {
+        serialize_field(&mut map, "bls_public_key", hex::encode(bls_public_key));
+    }
+    map
+}
+
+/// Serializes the shard validator subset given by `compute_validator_subset`,
+/// the list items have the same fields as the items of the validator set config params
+pub fn serialize_validator_subset(
+    validators: &[ValidatorDescr], hash_short: u32, mode: SerializationMode
+) -> Map<String, Value> {
+    let mut map = Map::new();
+    serialize_field(&mut map, "validator_list_hash_short", hash_short);
+    let list = validators.iter().map(|v| serialize_validator_descr(v, mode).into()).collect::<Vec<Value>>();
+    serialize_field(&mut map, "list", list);
+    map
+}
+
 fn serialize_validator_signed_temp_keys(stk: &ValidatorKeys) -> Result<Value> {
     let mut vector = Vec::<Value>::new();
     stk.iterate_with_keys(|key: UInt256, val| -> Result<bool> {
@@ -939,22 +1294,22 @@ fn serialize_suspended_addresses(sa: &SuspendedAddresses) -> Result<Value> {
     Ok(vector.into())
 }
 
-fn serialize_mesh_config(mc: &MeshConfig) -> Result<Value> {
+fn serialize_mesh_config(mc: &MeshConfig, options: SerializationOptions) -> Result<Value> {
     let mut vector = Vec::new();
     mc.iterate_with_keys(|k: i32, v| {
         let mut map = Map::new();
         serialize_field(&mut map, "network_id", k);
-        serialize_field(&mut map, "zerostate", serialize_separated_block_id_ext(&v.zerostate)?);
+        serialize_field(&mut map, "zerostate", block_id_ext_fields(&v.zerostate, true, options));
         serialize_field(&mut map, "is_active", v.is_active);
         serialize_field(&mut map, "currency_id", v.currency_id);
-        serialize_field(&mut map, "init_block", serialize_separated_block_id_ext(&v.init_block)?);
+        serialize_field(&mut map, "init_block", block_id_ext_fields(&v.init_block, true, options));
         serialize_uint256(&mut map, "emergency_guard_addr", &v.emergency_guard_addr);
         serialize_uint256(&mut map, "pull_addr", &v.pull_addr);
         serialize_uint256(&mut map, "minter_addr", &v.minter_addr);
         if !v.hardforks.is_empty() {
             let mut hardforks: Vec<Value> = Vec::new();
             for hf in &v.hardforks {
-                hardforks.push(serialize_separated_block_id_ext(hf)?)
+                hardforks.push(block_id_ext_fields(hf, true, options).into())
             }
             serialize_field(&mut map, "hardforks", hardforks);
         }
@@ -964,6 +1319,20 @@ fn serialize_mesh_config(mc: &MeshConfig) -> Result<Value> {
     Ok(vector.into())
 }
 
+/// Writes p8 or the block `gen_software` as `{version, capabilities, capabilities_list}`,
+/// `parse_global_version` reads it back
+pub fn serialize_global_version(global_version: &GlobalVersion, mode: SerializationMode) -> Map<String, Value> {
+    let mut map = Map::new();
+    serialize_field(&mut map, "version", global_version.version);
+    serialize_u64(&mut map, "capabilities", &global_version.capabilities, mode);
+    let capabilities = GLOBAL_CAPABILITIES.iter()
+        .filter(|(_, bit)| global_version.capabilities & bit != 0)
+        .map(|(name, _)| Value::from(*name))
+        .collect::<Vec<_>>();
+    serialize_field(&mut map, "capabilities_list", capabilities);
+    map
+}
+
 fn serialize_crypto_signature(s: &CryptoSignaturePair) -> Result<Value> {
     let mut map = Map::new();
     serialize_uint256(&mut map, "node_id", &s.node_id_short);
@@ -974,6 +1343,15 @@ fn serialize_crypto_signature(s: &CryptoSignaturePair) -> Result<Value> {
 }
 
 pub fn serialize_known_config_param(number: u32, param: &mut SliceData, mode: SerializationMode) -> Result<Option<Value>> {
+    serialize_known_config_param_with_options(number, param, mode, SerializationOptions::default())
+}
+
+pub fn serialize_known_config_param_with_options(
+    number: u32,
+    param: &mut SliceData,
+    mode: SerializationMode,
+    options: SerializationOptions,
+) -> Result<Option<Value>> {
     let mut map = Map::new();
 
     match ConfigParamEnum::construct_from_slice_and_number(param, number)? {
@@ -999,12 +1377,14 @@ pub fn serialize_known_config_param(number: u32, param: &mut SliceData, mode: Se
             serialize_grams(&mut map, "mint_new_price", &c.mint_new_price, mode);
             serialize_grams(&mut map, "mint_add_price", &c.mint_add_price, mode);
         },
+        ConfigParamEnum::ConfigParam7(ref c) if options.p7_as_map => {
+            return Ok(Some(serialize_ecc_map(&c.to_mint)?.into()));
+        },
         ConfigParamEnum::ConfigParam7(ref c) => {
             return Ok(Some(serialize_ecc(&c.to_mint, mode)?.into()));
         },
         ConfigParamEnum::ConfigParam8(ref c) => {
-            serialize_field(&mut map, "version", c.global_version.version);
-            serialize_u64(&mut map, "capabilities", &c.global_version.capabilities, mode);
+            return Ok(Some(serialize_global_version(&c.global_version, mode).into()));
         },
         ConfigParamEnum::ConfigParam9(ref c) => {
             return Ok(Some(serialize_mandatory_params(&c.mandatory_params)?));
@@ -1022,6 +1402,11 @@ pub fn serialize_known_config_param(number: u32, param: &mut SliceData, mode: Se
             return Ok(Some(serialize_workchains(&c.workchains)?));
         },
         ConfigParamEnum::ConfigParam13(ref c) => {
+            if let Some((deposit, bit_price, cell_price)) = read_complaint_pricing(&c.cell) {
+                serialize_grams(&mut map, "deposit", &deposit, mode);
+                serialize_grams(&mut map, "bit_price", &bit_price, mode);
+                serialize_grams(&mut map, "cell_price", &cell_price, mode);
+            }
             let boc = write_boc(&c.cell)?;
             serialize_field(&mut map, "boc", base64_encode(boc));
         },
@@ -1178,7 +1563,7 @@ pub fn serialize_known_config_param(number: u32, param: &mut SliceData, mode: Se
             serialize_field(&mut map, "use_debug_bls_keys", c.use_debug_bls_keys);
         },
         ConfigParamEnum::ConfigParam58(ref c) => {
-            return Ok(Some(serialize_mesh_config(c)?));
+            return Ok(Some(serialize_mesh_config(c, options)?));
         },
         _ => {
             return Ok(None)
@@ -1197,7 +1582,7 @@ fn serialize_unknown_config_param(number: u32, param: &mut SliceData) -> Result<
     Ok(map.into())
 }
 
-fn serialize_block_ref(blk_ref: &ExtBlkRef, key: Option<bool>, mode: SerializationMode) -> Value {
+pub(crate) fn serialize_block_ref(blk_ref: &ExtBlkRef, key: Option<bool>, mode: SerializationMode) -> Value {
     let mut blk_ref_map = Map::new();
     serialize_lt(&mut blk_ref_map, "end_lt", &blk_ref.end_lt, mode);
     blk_ref_map.insert("seq_no".to_string(), blk_ref.seq_no.into());
@@ -1216,10 +1601,10 @@ fn serialize_shard_hashes(map: &mut Map<String, Value>, id_str: &str, hashes: &S
     hashes.iterate_with_keys(&mut |key: i32, InRefValue(tree): InRefValue<BinTree<ShardDescr>>| {
         tree.iterate(&mut |shard: SliceData, descr| {
             if let Ok(descr) = serialize_shard_descr(&descr, mode) {
-                let shard_ident = ShardIdent::with_prefix_slice(key, shard)?;
+                let (workchain_id, shard) = shard_ident_to_json(&ShardIdent::with_prefix_slice(key, shard)?);
                 shard_hashes.push(serde_json::json!({
-                    "workchain_id": key,
-                    "shard": shard_ident.shard_prefix_as_str_with_tag(),
+                    "workchain_id": workchain_id,
+                    "shard": shard,
                     "descr": descr,
                 }));
             }
@@ -1238,6 +1623,15 @@ fn serialize_shard_hashes(map: &mut Map<String, Value>, id_str: &str, hashes: &S
 }
 
 pub fn serialize_config(map: &mut Map<String, Value>, config: &ConfigParams, mode: SerializationMode) -> Result<()> {
+    serialize_config_with_options(map, config, mode, SerializationOptions::default())
+}
+
+pub fn serialize_config_with_options(
+    map: &mut Map<String, Value>,
+    config: &ConfigParams,
+    mode: SerializationMode,
+    options: SerializationOptions,
+) -> Result<()> {
     serialize_id(map, "config_addr", Some(&config.config_addr));
     let mut known_cp_map = Map::new();
     let mut unknown_cp_vec = Vec::new();
@@ -1245,7 +1639,7 @@ pub fn serialize_config(map: &mut Map<String, Value>, config: &ConfigParams, mod
             //println!("key {}", num);
             let num = num.get_next_u32()?;
             let mut cp = SliceData::load_cell(cp_ref.checked_drain_reference()?)?;
-            if let Some(cp) = serialize_known_config_param(num, &mut cp.clone(), mode)? {
+            if let Some(cp) = serialize_known_config_param_with_options(num, &mut cp.clone(), mode, options)? {
                 known_cp_map.insert(format!("p{}", num), cp);
             } else {
                 unknown_cp_vec.push(serialize_unknown_config_param(num, &mut cp)?);
@@ -1259,6 +1653,104 @@ pub fn serialize_config(map: &mut Map<String, Value>, config: &ConfigParams, mod
     Ok(())
 }
 
+/// Change of one config field found by `diff_configs`, the added and removed parameters
+/// come as a single change with the empty `field_path` and the missing side set to None
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigChange {
+    pub param: u32,
+    /// Slash separated path inside the parameter, array items are named by their indices
+    pub field_path: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+impl std::fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "p{}", self.param)?;
+        if !self.field_path.is_empty() {
+            write!(f, "/{}", self.field_path)?;
+        }
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(f, ": {} -> {}", old, new),
+            (None, Some(new)) => write!(f, ": added {}", new),
+            (Some(old), None) => write!(f, ": removed {}", old),
+            (None, None) => Ok(())
+        }
+    }
+}
+
+fn serialize_config_params_by_number(config: &ConfigParams) -> Result<std::collections::BTreeMap<u32, Value>> {
+    let mut map = Map::new();
+    serialize_config(&mut map, config, SerializationMode::Debug)?;
+    let mut params = std::collections::BTreeMap::new();
+    if let Some(Value::Object(known)) = map.remove("config") {
+        for (name, value) in known {
+            let number = name.trim_start_matches('p').parse::<u32>()
+                .map_err(|err| error!("wrong config param name {} : {}", name, err))?;
+            params.insert(number, value);
+        }
+    }
+    if let Some(Value::Array(unknown)) = map.remove("unknown_config") {
+        for value in unknown {
+            let number = value["number"].as_u64().unwrap_or_default() as u32;
+            params.insert(number, value);
+        }
+    }
+    Ok(params)
+}
+
+fn diff_values(param: u32, path: &str, old: Option<&Value>, new: Option<&Value>, changes: &mut Vec<ConfigChange>) {
+    let nested = |name: &str| match path {
+        "" => name.to_string(),
+        _ => format!("{}/{}", path, name)
+    };
+    match (old, new) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            for (name, old_value) in old {
+                diff_values(param, &nested(name), Some(old_value), new.get(name), changes);
+            }
+            for (name, new_value) in new.iter().filter(|(name, _)| !old.contains_key(*name)) {
+                diff_values(param, &nested(name), None, Some(new_value), changes);
+            }
+        }
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            for index in 0..old.len().max(new.len()) {
+                diff_values(param, &nested(&index.to_string()), old.get(index), new.get(index), changes);
+            }
+        }
+        (old, new) if old != new => changes.push(ConfigChange {
+            param,
+            field_path: path.to_string(),
+            old: old.cloned(),
+            new: new.cloned(),
+        }),
+        _ => ()
+    }
+}
+
+/// Field level differences between two configs, both are serialized in the debug mode
+/// and the values are compared recursively. The changes are ordered by the parameter number.
+pub fn diff_configs(old: &ConfigParams, new: &ConfigParams) -> Result<Vec<ConfigChange>> {
+    let old = serialize_config_params_by_number(old)?;
+    let new = serialize_config_params_by_number(new)?;
+    let mut numbers = old.keys().chain(new.keys()).copied().collect::<Vec<_>>();
+    numbers.sort_unstable();
+    numbers.dedup();
+    let mut changes = Vec::new();
+    for number in numbers {
+        match (old.get(&number), new.get(&number)) {
+            (Some(old), Some(new)) => diff_values(number, "", Some(old), Some(new), &mut changes),
+            (old, new) => changes.push(ConfigChange {
+                param: number,
+                field_path: String::new(),
+                old: old.cloned(),
+                new: new.cloned(),
+            }),
+        }
+    }
+    Ok(changes)
+}
+
 fn serialize_counters(counters: &Counters, mode: SerializationMode) -> Value {
     let mut map = Map::new();
     map.insert("valid".to_string(), counters.is_valid().into());
@@ -1304,6 +1796,12 @@ fn serialize_shard_accounts(map: &mut Map<String, Value>, id_str: &str, shard_ac
         };
         let mut account = db_serialize_account_ex("id", &account_set, mode)?;
         account.remove("json_version");
+        // the last transaction of the shard account, `last_trans_lt` is the one of the account
+        // storage. The zero ones are the parser default, so they are not written
+        if value.last_trans_lt() != 0 || value.last_trans_hash() != &UInt256::ZERO {
+            serialize_id(&mut account, "last_trans_hash", Some(value.last_trans_hash()));
+            serialize_lt(&mut account, "shard_last_trans_lt", &value.last_trans_lt(), mode);
+        }
         accounts.push(account);
         Ok(true)
     })?;
@@ -1321,11 +1819,12 @@ fn serialize_libraries(map: &mut Map<String, Value>, id_str: &str, libraries: &L
             Ok(true)
         })?;
 
-        libraries_vec.push(serde_json::json!({
-            "hash": key.as_hex_string(),
-            "publishers": publishers,
-            "lib": base64_encode(write_boc(value.lib())?)
-        }));
+        let mut library = Map::new();
+        serialize_field(&mut library, "hash", key.as_hex_string());
+        serialize_field(&mut library, "publishers", publishers);
+        // written as the account code and data, the pruned cells of the proofs have no boc
+        serialize_cell(&mut library, "lib", Some(value.lib()), false)?;
+        libraries_vec.push(library);
         Ok(true)
     })?;
     map.insert(id_str.to_string(), libraries_vec.into());
@@ -1343,45 +1842,75 @@ fn serialize_out_msg_queue_info(map: &mut Map<String, Value>, id_str: &str, info
         Ok(true)
     })?;
 
-    let mut proc_info = Vec::new();
-    info.proc_info().iterate_slices_with_keys(&mut |mut key: SliceData, mut value: SliceData| -> Result<bool> {
+    map.insert(id_str.to_string(), serde_json::json!({
+        "out_queue": out_queue,
+        "proc_info": serialize_proc_info(info.proc_info(), mode)?,
+        "ihr_pending": serialize_ihr_pending(info.ihr_pending(), mode)?,
+    }));
+
+    Ok(())
+}
+
+/// Writes the queue with the message bocs, so it can be read back by `parse_out_msg_queue`.
+/// The lts are read back from `Standart` and `QServer` modes only
+pub fn serialize_out_msg_queue(info: &OutMsgQueueInfo, mode: SerializationMode) -> Result<Map<String, Value>> {
+    let mut out_queue = Vec::new();
+    info.out_queue().iterate_with_keys(&mut |key: OutMsgQueueKey, value: EnqueuedMsg| -> Result<bool> {
+        let env = value.read_out_msg()?;
+        let mut msg_map = serialize_envelope_msg(&env, mode);
+        msg_map.insert("msg".to_string(), base64_encode(write_boc(&env.message_cell())?).into());
+        serialize_lt(&mut msg_map, "lt", &value.enqueued_lt(), mode);
+        msg_map.insert("dest_workchain".to_string(), key.workchain_id.into());
+        msg_map.insert("dest_addr_prefix".to_string(), shard_to_string(key.prefix).into());
+        out_queue.push(msg_map);
+        Ok(true)
+    })?;
+
+    let mut map = Map::new();
+    map.insert("out_queue".to_string(), out_queue.into());
+    map.insert("proc_info".to_string(), serialize_proc_info(info.proc_info(), mode)?.into());
+    map.insert("ihr_pending".to_string(), serialize_ihr_pending(info.ihr_pending(), mode)?.into());
+    Ok(map)
+}
+
+fn serialize_proc_info(proc_info: &ProcessedInfo, mode: SerializationMode) -> Result<Vec<Map<String, Value>>> {
+    let mut result = Vec::new();
+    proc_info.iterate_slices_with_keys(&mut |mut key: SliceData, mut value: SliceData| -> Result<bool> {
         let mut processed_map = Map::new();
         let value = ProcessedUpto::construct_from(&mut value)?;
         processed_map.insert("shard".to_string(), shard_to_string(key.get_next_u64()?).into());
         processed_map.insert("mc_seqno".to_string(), key.get_next_u32()?.into());
         serialize_lt(&mut processed_map, "last_msg_lt", &value.last_msg_lt, mode);
         processed_map.insert("last_msg_hash".to_string(), value.last_msg_hash.as_hex_string().into());
-        proc_info.push(processed_map);
+        result.push(processed_map);
         Ok(true)
     })?;
+    Ok(result)
+}
 
-    let mut ihr_pending = Vec::new();
-    info.ihr_pending().iterate_slices_with_keys(|ref mut key, ref mut value| -> Result<bool> {
+fn serialize_ihr_pending(ihr_pending: &IhrPendingInfo, mode: SerializationMode) -> Result<Vec<Map<String, Value>>> {
+    let mut result = Vec::new();
+    ihr_pending.iterate_slices_with_keys(|ref mut key, ref mut value| -> Result<bool> {
         let value = IhrPendingSince::construct_from(value)?;
         let mut ihr_map = Map::new();
         ihr_map.insert("dest_addr_prefix".to_string(), shard_to_string(key.get_next_u64()?).into());
         ihr_map.insert("msg_id".to_string(), format!("{:x}", key).into());
         serialize_lt(&mut ihr_map, "import_lt", &value.import_lt(), mode);
-        ihr_pending.push(ihr_map);
+        result.push(ihr_map);
         Ok(true)
     })?;
-
-    map.insert(id_str.to_string(), serde_json::json!({
-        "out_queue": out_queue,
-        "proc_info": proc_info,
-        "ihr_pending": ihr_pending,
-    }));
-
-    Ok(())
+    Ok(result)
 }
 
 fn serialize_mc_state_extra(map: &mut Map<String, Value>, id_str: &str, extra: &McStateExtra, mode: SerializationMode) -> Result<()> {
     let mut extra_map = Map::new();
     serialize_shard_hashes(&mut extra_map, "shard_hashes", extra.shards(), mode)?;
     serialize_config(&mut extra_map, &extra.config, mode)?;
-    serialize_field(&mut extra_map, "validator_list_hash_short", extra.validator_info.validator_list_hash_short);
-    serialize_field(&mut extra_map, "catchain_seqno", extra.validator_info.catchain_seqno);
-    serialize_field(&mut extra_map, "nx_cc_updated", extra.validator_info.nx_cc_updated);
+    let mut info_map = Map::new();
+    serialize_field(&mut info_map, "validator_list_hash_short", extra.validator_info.validator_list_hash_short);
+    serialize_field(&mut info_map, "catchain_seqno", extra.validator_info.catchain_seqno);
+    serialize_field(&mut info_map, "nx_cc_updated", extra.validator_info.nx_cc_updated);
+    serialize_field(&mut extra_map, "validator_info", info_map);
     // `prev_blocks` field is quite huge and not useful. Don't need to serialize it
     //serialize_field(&mut master_map, "prev_blocks", serialize_old_mc_blocks_info(&master.prev_blocks, mode)?);
     serialize_field(&mut extra_map, "after_key_block", extra.after_key_block);
@@ -1460,6 +1989,51 @@ pub fn debug_block_map(block: Block) -> Result<Map<String, Value>> {
     db_serialize_block_ex("id", &set, SerializationMode::Debug)
 }
 
+/// Writes JSON with sorted object keys and without whitespaces, so equal documents
+/// always produce equal bytes. Float numbers are not allowed.
+pub fn write_canonical(value: &Value, writer: &mut impl std::io::Write) -> Result<()> {
+    match value {
+        Value::Null | Value::Bool(_) | Value::String(_) => serde_json::to_writer(&mut *writer, value)?,
+        Value::Number(number) => {
+            if number.is_f64() {
+                fail!("float number {} can not be written in canonical form", number)
+            }
+            serde_json::to_writer(&mut *writer, number)?
+        }
+        Value::Array(array) => {
+            writer.write_all(b"[")?;
+            for (i, item) in array.iter().enumerate() {
+                if i != 0 {
+                    writer.write_all(b",")?;
+                }
+                write_canonical(item, writer)?;
+            }
+            writer.write_all(b"]")?;
+        }
+        Value::Object(map) => {
+            let mut keys = map.keys().collect::<Vec<_>>();
+            keys.sort();
+            writer.write_all(b"{")?;
+            for (i, key) in keys.into_iter().enumerate() {
+                if i != 0 {
+                    writer.write_all(b",")?;
+                }
+                serde_json::to_writer(&mut *writer, key)?;
+                writer.write_all(b":")?;
+                write_canonical(&map[key], writer)?;
+            }
+            writer.write_all(b"}")?;
+        }
+    }
+    Ok(())
+}
+
+pub fn to_canonical_string(value: &Value) -> Result<String> {
+    let mut bytes = Vec::new();
+    write_canonical(value, &mut bytes)?;
+    Ok(String::from_utf8(bytes)?)
+}
+
 pub fn debug_block(block: Block) -> Result<String> {
     let map = debug_block_map(block)?;
     Ok(format!("{:#}", serde_json::json!(map)))
@@ -1521,6 +2095,14 @@ pub fn db_serialize_block<'a>(
     db_serialize_block_ex(id_str, set, SerializationMode::Standart)
 }
 
+pub fn db_serialize_block_canonical<'a>(
+    id_str: &'static str,
+    set:  impl Into<BlockSerializationSetFH<'a>>,
+    mode: SerializationMode
+) -> Result<String> {
+    to_canonical_string(&db_serialize_block_ex(id_str, set, mode)?.into())
+}
+
 pub fn db_serialize_block_ex<'a>(
     id_str: &'static str,
     set:  impl Into<BlockSerializationSetFH<'a>>,
@@ -1529,7 +2111,7 @@ pub fn db_serialize_block_ex<'a>(
     let set: BlockSerializationSetFH = set.into();
     let mut map = Map::new();
     serialize_field(&mut map, "json_version", VERSION);
-    serialize_id(&mut map, id_str, Some(set.id));
+    serialize_field(&mut map, id_str, hash_doc_id(set.id));
     serialize_file_hash(&mut map, set.file_hash, set.boc);
     serialize_field(&mut map, "status", set.status as u8);
     if mode.is_q_server() {
@@ -1563,8 +2145,9 @@ pub fn db_serialize_block_ex<'a>(
     map.insert("gen_catchain_seqno".to_string(), block_info.gen_catchain_seqno().into());
     map.insert("min_ref_mc_seqno".to_string(), block_info.min_ref_mc_seqno().into());
     map.insert("prev_key_block_seqno".to_string(), block_info.prev_key_block_seqno().into());
-    map.insert("workchain_id".to_string(), block_info.shard().workchain_id().into());
-    map.insert("shard".to_string(), block_info.shard().shard_prefix_as_str_with_tag().into());
+    let (workchain_id, shard) = shard_ident_to_json(block_info.shard());
+    map.insert("workchain_id".to_string(), workchain_id.into());
+    map.insert("shard".to_string(), shard.into());
     if let Some(pack_info) = &block_info.read_pack_info()? {
         map.insert("pack_info".to_string(), serialize_pack_info(pack_info)?);
     }
@@ -1589,6 +2172,12 @@ pub fn db_serialize_block_ex<'a>(
     ].iter().for_each(|(id_str, blk_ref)| if let Some(blk_ref) = blk_ref {
         map.insert(id_str.to_string(), serialize_block_ref(blk_ref, None, mode));
     });
+    // the same prev blocks as a list, the after merge block has two of them
+    let prev_refs = [Some(prev_block_ref.prev1()?), prev_block_ref.prev2()?].iter()
+        .flatten()
+        .map(|blk_ref| serialize_block_ref(blk_ref, None, mode))
+        .collect::<Vec<_>>();
+    map.insert("prev_refs".to_string(), prev_refs.into());
     let value_flow = set.block.read_value_flow()?;
     let mut value_map = Map::new();
     serialize_cc(&mut value_map, "from_prev_blk",  &value_flow.from_prev_blk, mode)?;
@@ -1693,10 +2282,10 @@ pub fn db_serialize_block_ex<'a>(
         })?;
         master_map.insert("prev_blk_signatures".to_string(), crypto_signs.into());
         if let Some(msg) = &master.read_recover_create_msg()? {
-            master_map.insert("recover_create_msg".to_string(), serialize_in_msg(msg, mode)?);
+            master_map.insert("recover_create_msg".to_string(), serialize_mc_special_msg(msg, mode)?);
         }
         if let Some(msg) = &master.read_mint_msg()? {
-            master_map.insert("mint_msg".to_string(), serialize_in_msg(msg, mode)?);
+            master_map.insert("mint_msg".to_string(), serialize_mc_special_msg(msg, mode)?);
         }
         if let Some(config) = master.config() {
             serialize_config(&mut master_map, config, mode)?;
@@ -1771,7 +2360,7 @@ pub fn db_serialize_transaction_ex<'a>(
     let set: TransactionSerializationSetEx = set.into();
     let mut map = Map::new();
     serialize_field(&mut map, "json_version", VERSION);
-    serialize_id(&mut map, id_str, Some(set.id));
+    serialize_field(&mut map, id_str, hash_doc_id(set.id));
     serialize_id(&mut map, "block_id", set.block_id);
     if let Some(proof) = &set.proof {
         serialize_field(&mut map, "proof", base64_encode(proof));
@@ -1929,6 +2518,36 @@ pub fn db_serialize_transaction_ex<'a>(
     Ok(map)
 }
 
+// (phase, field, top level name) of the phase fields copied by the flat transaction serialization
+const FLAT_TRANSACTION_FIELDS: [(&str, &str, &str); 8] = [
+    ("storage", "storage_fees_collected_dec", "storage_fees_collected_dec"),
+    ("storage", "storage_fees_collected", "storage_fees_collected"),
+    ("compute", "gas_used", "compute_gas_used"),
+    ("compute", "exit_code", "compute_exit_code"),
+    ("compute", "success", "compute_success"),
+    ("action", "result_code", "action_result_code"),
+    ("action", "total_fwd_fees_dec", "action_total_fwd_fees_dec"),
+    ("action", "total_fwd_fees", "action_total_fwd_fees"),
+];
+
+/// Serializes the transaction as `db_serialize_transaction_ex` and copies the most queried
+/// phase fields to the top level (`compute_gas_used`, `action_result_code`, etc.).
+/// The nested phases are kept, the fields of absent or skipped phases are not written.
+pub fn db_serialize_transaction_flat<'a>(
+    id_str: &'static str,
+    set: impl Into<TransactionSerializationSetEx<'a>>,
+    mode: SerializationMode
+) -> Result<Map<String, Value>> {
+    let mut map = db_serialize_transaction_ex(id_str, set, mode)?;
+    for (phase, field, name) in FLAT_TRANSACTION_FIELDS {
+        let value = map.get(phase).and_then(|phase| phase.get(field)).cloned();
+        if let Some(value) = value {
+            map.insert(name.to_string(), value);
+        }
+    }
+    Ok(map)
+}
+
 fn serialize_account_status(
     map: &mut Map<String, Value>,
     name: &'static str,
@@ -1985,11 +2604,31 @@ pub fn db_serialize_account_ex(
     id_str: &'static str,
     set: &AccountSerializationSet,
     mode: SerializationMode
+) -> Result<Map<String, Value>> {
+    serialize_account(id_str, set, mode, None)
+}
+
+/// Serializes the account adding `code_label` if its code hash is one of the `code_labels` keys
+#[cfg(feature = "code_label")]
+pub fn db_serialize_account_with_code_labels(
+    id_str: &'static str,
+    set: &AccountSerializationSet,
+    mode: SerializationMode,
+    code_labels: &HashMap<UInt256, String>,
+) -> Result<Map<String, Value>> {
+    serialize_account(id_str, set, mode, Some(code_labels))
+}
+
+fn serialize_account(
+    id_str: &'static str,
+    set: &AccountSerializationSet,
+    mode: SerializationMode,
+    code_labels: Option<&HashMap<UInt256, String>>,
 ) -> Result<Map<String, Value>> {
     let mut map = Map::new();
     serialize_field(&mut map, "json_version", VERSION);
     if let Some(addr) = set.account.get_addr() {
-        serialize_field(&mut map, id_str, addr.to_string());
+        serialize_field(&mut map, id_str, account_doc_id(addr));
         serialize_field(&mut map, "workchain_id", addr.get_workchain_id());
     }
     serialize_field(&mut map, "boc", base64_encode(&set.boc));
@@ -2019,6 +2658,12 @@ pub fn db_serialize_account_ex(
                     serialize_field(&mut map, "tock", special.tock);
                 };
                 serialize_cell(&mut map, "code", state.code(), true)?;
+                if let (Some(code_labels), Some(code)) = (code_labels, state.code()) {
+                    // the hash is calculated once and kept in the cell
+                    if let Some(label) = code_labels.get(&code.repr_hash()) {
+                        serialize_field(&mut map, "code_label", label.as_str());
+                    }
+                }
                 serialize_cell(&mut map, "data", state.data(), true)?;
                 serialize_cell(&mut map, "library", state.libraries().root(), true)?;
             }
@@ -2058,10 +2703,11 @@ pub fn db_serialize_deleted_account_ex(
     let mut map = Map::new();
     serialize_field(&mut map, "json_version", VERSION);
     let address = construct_address(set.workchain_id, set.account_id.clone())?;
-    serialize_field(&mut map, id_str, address.to_string());
+    serialize_field(&mut map, id_str, account_doc_id(&address));
     serialize_field(&mut map, "workchain_id", set.workchain_id);
     serialize_account_status(&mut map, "acc_type", &AccountStatus::AccStateNonexist, mode);
     serialize_id(&mut map, "prev_code_hash", set.prev_code_hash.as_ref());
+    serialize_field(&mut map, "deleted", true);
 
     Ok(map)
 }
@@ -2098,10 +2744,19 @@ pub fn db_serialize_message(id_str: &'static str, set: &MessageSerializationSet)
     db_serialize_message_ex(id_str, set, SerializationMode::Standart)
 }
 
+/// `msg_type_name` of the serialized message: `internal`, `extIn` or `extOut`
+pub fn msg_type_name(header: &CommonMsgInfo) -> &'static str {
+    match header {
+        CommonMsgInfo::IntMsgInfo(_) => "internal",
+        CommonMsgInfo::ExtInMsgInfo(_) => "extIn",
+        CommonMsgInfo::ExtOutMsgInfo(_) => "extOut",
+    }
+}
+
 pub fn db_serialize_message_ex(id_str: &'static str, set: &MessageSerializationSet, mode: SerializationMode) -> Result<Map<String, Value>> {
     let mut map = Map::new();
     serialize_field(&mut map, "json_version", VERSION);
-    serialize_id(&mut map, id_str, Some(&set.id));
+    serialize_field(&mut map, id_str, hash_doc_id(&set.id));
     // isn't needed there - because message should be fully immutable from source block to destination one
     //serialize_id(&mut map, "block_id", set.block_id.as_ref());
     serialize_id(&mut map, "transaction_id", set.transaction_id.as_ref());
@@ -2139,9 +2794,7 @@ pub fn db_serialize_message_ex(id_str: &'static str, set: &MessageSerializationS
     match set.message.header() {
         CommonMsgInfo::IntMsgInfo(ref header) => {
             serialize_field(&mut map, "msg_type", 0);
-            if mode.is_q_server() {
-                serialize_field(&mut map, "msg_type_name", "internal");
-            }
+            serialize_field(&mut map, "msg_type_name", msg_type_name(set.message.header()));
             serialize_field(&mut map, "src", header.src.to_string());
             if let Some(src_addr) = header.src_ref() {
                 serialize_field(&mut map, "src_workchain_id", src_addr.get_workchain_id());
@@ -2159,9 +2812,7 @@ pub fn db_serialize_message_ex(id_str: &'static str, set: &MessageSerializationS
         }
         CommonMsgInfo::ExtInMsgInfo(ref header) => {
             serialize_field(&mut map, "msg_type", 1);
-            if mode.is_q_server() {
-                serialize_field(&mut map, "msg_type_name", "extIn");
-            }
+            serialize_field(&mut map, "msg_type_name", msg_type_name(set.message.header()));
             serialize_field(&mut map, "src", header.src.to_string());
             serialize_field(&mut map, "dst", header.dst.to_string());
             serialize_field(&mut map, "dst_workchain_id", header.dst.get_workchain_id());
@@ -2172,9 +2823,7 @@ pub fn db_serialize_message_ex(id_str: &'static str, set: &MessageSerializationS
         }
         CommonMsgInfo::ExtOutMsgInfo(ref header) => {
             serialize_field(&mut map, "msg_type", 2);
-            if mode.is_q_server() {
-                serialize_field(&mut map, "msg_type_name", "extOut");
-            }
+            serialize_field(&mut map, "msg_type_name", msg_type_name(set.message.header()));
             serialize_field(&mut map, "src", header.src.to_string());
             if let Some(src_addr) = header.src() {
                 serialize_field(&mut map, "src_workchain_id", src_addr.get_workchain_id());
@@ -2187,6 +2836,134 @@ pub fn db_serialize_message_ex(id_str: &'static str, set: &MessageSerializationS
     Ok(map)
 }
 
+/// Hash of the external inbound message with the signature zeroed, the wallet SDKs identify
+/// the sent messages by it. The signature is the first 512 bits of the body in the standard
+/// wallet layout, the rest of the message is kept as is. `None` for the other messages
+/// and the bodies shorter than the signature
+pub fn normalized_message_hash(message: &Message) -> Result<Option<UInt256>> {
+    if !message.is_inbound_external() {
+        return Ok(None)
+    }
+    let mut body = match message.body() {
+        Some(body) if body.remaining_bits() >= 512 => body,
+        _ => return Ok(None)
+    };
+    body.move_by(512)?;
+    let mut builder = BuilderData::new();
+    builder.append_raw(&[0; 64], 512)?;
+    builder.checked_append_references_and_data(&body)?;
+    let mut normalized = message.clone();
+    normalized.set_body(SliceData::load_builder(builder)?);
+    Ok(Some(normalized.serialize()?.repr_hash()))
+}
+
+/// Same as `db_serialize_message_ex` but the external inbound messages with the signed body
+/// get `msg_hash_normalized`, see `normalized_message_hash`
+pub fn db_serialize_message_with_normalized_hash(
+    id_str: &'static str,
+    set: &MessageSerializationSet,
+    mode: SerializationMode,
+) -> Result<Map<String, Value>> {
+    let mut map = db_serialize_message_ex(id_str, set, mode)?;
+    if let Some(hash) = normalized_message_hash(&set.message)? {
+        serialize_uint256(&mut map, "msg_hash_normalized", &hash);
+    }
+    Ok(map)
+}
+
+/// Shards the internal messages are routed to, see `db_serialize_message_with_routing`
+pub struct MessageRouting {
+    shards: Vec<ShardIdent>,
+}
+
+impl MessageRouting {
+    pub fn with_shards(shards: Vec<ShardIdent>) -> Self {
+        Self { shards }
+    }
+
+    /// Takes the workchain shards of the masterchain block, the masterchain itself is added
+    pub fn with_shard_hashes(shard_hashes: &ShardHashes) -> Result<Self> {
+        let mut shards = vec![ShardIdent::masterchain()];
+        shard_hashes.iterate_shards(|shard, _descr| {
+            shards.push(shard);
+            Ok(true)
+        })?;
+        Ok(Self { shards })
+    }
+
+    pub fn find_shard(&self, address: &MsgAddressInt) -> Result<Option<&ShardIdent>> {
+        for shard in &self.shards {
+            if shard.workchain_id() == address.get_workchain_id() && shard.contains_account(address.address())? {
+                return Ok(Some(shard))
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Same as `db_serialize_message_ex` but adds `dst_shard` and `dst_workchain` of the shard
+/// the internal message is routed to, both are null if no shard contains the destination
+pub fn db_serialize_message_with_routing(
+    id_str: &'static str,
+    set: &MessageSerializationSet,
+    mode: SerializationMode,
+    routing: &MessageRouting,
+) -> Result<Map<String, Value>> {
+    let mut map = db_serialize_message_ex(id_str, set, mode)?;
+    if let Some(header) = set.message.int_header() {
+        match routing.find_shard(&header.dst)? {
+            Some(shard) => {
+                serialize_field(&mut map, "dst_shard", shard_to_string(shard.shard_prefix_with_tag()));
+                serialize_field(&mut map, "dst_workchain", shard.workchain_id());
+            }
+            None => {
+                serialize_field(&mut map, "dst_shard", Value::Null);
+                serialize_field(&mut map, "dst_workchain", Value::Null);
+            }
+        }
+    }
+    Ok(map)
+}
+
+/// Same as `db_serialize_block_ex` but every `in_msg_descr` and `out_msg_descr` entry gets
+/// the `envelope` object: the descriptor TL-B tag (`msg_import_ext`, `msg_export_new`, ...),
+/// `in` and `out` envelope routing (transit addresses and prefixes, `fwd_fee_remaining`)
+/// and the descriptor fees
+pub fn db_serialize_block_with_envelopes<'a>(
+    id_str: &'static str,
+    set:  impl Into<BlockSerializationSetFH<'a>>,
+    mode: SerializationMode
+) -> Result<Map<String, Value>> {
+    let set: BlockSerializationSetFH = set.into();
+    let block = set.block;
+    let mut map = db_serialize_block_ex(id_str, set, mode)?;
+    let extra = block.read_extra()?;
+    let mut envelopes = vec![];
+    extra.read_in_msg_descr()?.iterate_objects(|ref msg| {
+        envelopes.push(serialize_in_msg_envelope(msg, mode)?);
+        Ok(true)
+    })?;
+    insert_envelopes(&mut map, "in_msg_descr", envelopes);
+    let mut envelopes = vec![];
+    extra.read_out_msg_descr()?.iterate_objects(|ref msg| {
+        envelopes.push(serialize_out_msg_envelope(msg, mode)?);
+        Ok(true)
+    })?;
+    insert_envelopes(&mut map, "out_msg_descr", envelopes);
+    Ok(map)
+}
+
+// the descriptors are iterated in the same order as for the block itself
+fn insert_envelopes(map: &mut Map<String, Value>, name: &str, envelopes: Vec<Map<String, Value>>) {
+    if let Some(Value::Array(entries)) = map.get_mut(name) {
+        for (entry, envelope) in entries.iter_mut().zip(envelopes) {
+            if let Value::Object(entry) = entry {
+                entry.insert("envelope".to_string(), envelope.into());
+            }
+        }
+    }
+}
+
 pub fn db_serialize_block_signatures(
     id_str: &'static str,
     block_id: &UInt256,
@@ -2232,8 +3009,9 @@ pub fn db_serialize_block_proof_ex(
         map.insert("gen_utime_ms".to_string(), block_info.gen_utime_ms().into());
     }
     map.insert("seq_no".to_string(), block_info.seq_no().into());
-    map.insert("workchain_id".to_string(), block_info.shard().workchain_id().into());
-    map.insert("shard".to_string(), block_info.shard().shard_prefix_as_str_with_tag().into());
+    let (workchain_id, shard) = shard_ident_to_json(block_info.shard());
+    map.insert("workchain_id".to_string(), workchain_id.into());
+    map.insert("shard".to_string(), shard.into());
     serialize_cell(&mut map, "proof", Some(&proof.root), false)?;
 
     if let Some(signatures) = proof.signatures.as_ref() {
@@ -2284,7 +3062,7 @@ pub fn db_serialize_shard_state_ex(id_str: &'static str, set: &ShardStateSeriali
     serialize_field(&mut map, "workchain_id", set.workchain_id);
     serialize_field(&mut map, "boc", base64_encode(&set.boc));
     serialize_field(&mut map, "global_id", set.state.global_id());
-    serialize_field(&mut map, "shard", set.state.shard().shard_prefix_as_str_with_tag());
+    serialize_field(&mut map, "shard", shard_ident_to_json(set.state.shard()).1);
     serialize_field(&mut map, "seq_no", set.state.seq_no());
     serialize_field(&mut map, "vert_seq_no", set.state.vert_seq_no());
     serialize_field(&mut map, "gen_utime", set.state.gen_time());
@@ -2310,6 +3088,37 @@ pub fn db_serialize_shard_state_ex(id_str: &'static str, set: &ShardStateSeriali
     Ok(map)
 }
 
+/// Same as `db_serialize_shard_state_ex` but masterchain extra is written verbatim
+/// as `master_boc` instead of the structured `master` object.
+pub fn db_serialize_shard_state_with_raw_master(
+    id_str: &'static str,
+    set: &ShardStateSerializationSet,
+    mode: SerializationMode
+) -> Result<Map<String, Value>> {
+    let mut map = db_serialize_shard_state_ex(id_str, set, mode)?;
+    if let Some(master) = set.state.read_custom()? {
+        map.remove("master");
+        serialize_field(&mut map, "master_boc", base64_encode(write_boc(&master.serialize()?)?));
+    }
+    Ok(map)
+}
+
+/// Writes both halves of the split state as `left` and `right` shard state documents
+pub fn serialize_state_split(split: &ShardStateSplit, mode: SerializationMode) -> Result<Map<String, Value>> {
+    let mut map = Map::new();
+    for (name, state) in [("left", &split.left), ("right", &split.right)] {
+        let set = ShardStateSerializationSet {
+            block_id: None,
+            workchain_id: state.shard().workchain_id(),
+            id: format!("{}", state.shard()),
+            boc: state.write_to_bytes()?,
+            state: state.clone(),
+        };
+        map.insert(name.to_string(), db_serialize_shard_state_ex("id", &set, mode)?.into());
+    }
+    Ok(map)
+}
+
 pub fn debug_state(mut state: ShardStateUnsplit) -> Result<String> {
     state.write_accounts(&Default::default())?;
     let set = ShardStateSerializationSet {
@@ -2335,33 +3144,60 @@ pub fn debug_state_full(state: ShardStateUnsplit) -> Result<String> {
     Ok(format!("{:#}", serde_json::json!(map)))
 }
 
-fn serialize_block_id_ext(map: &mut Map<String, Value>, id: &BlockIdExt, mc: bool) {
-    if mc {
-        serialize_uint256(map, "mc_block_id", id.root_hash());
-        serialize_uint256(map, "mc_block_file_hash", id.file_hash());
-        serialize_field(map, "mc_block_seqno", id.seq_no());
+#[cfg(feature = "remp")]
+fn serialize_mc_block_id_ext(map: &mut Map<String, Value>, id: &BlockIdExt) {
+    serialize_uint256(map, "mc_block_id", id.root_hash());
+    serialize_uint256(map, "mc_block_file_hash", id.file_hash());
+    serialize_field(map, "mc_block_seqno", id.seq_no());
+}
+
+fn block_id_ext_fields(id: &BlockIdExt, nested: bool, options: SerializationOptions) -> Map<String, Value> {
+    let mut map = Map::new();
+    let (workchain_id, shard) = shard_ident_to_json(id.shard());
+    let (workchain_id, shard) = match options.composite_shards {
+        true => (None, format!("{},{}", workchain_id, shard)),
+        false => (Some(workchain_id), shard),
+    };
+    if nested {
+        if let Some(workchain_id) = workchain_id {
+            serialize_field(&mut map, "wc", workchain_id);
+        }
+        serialize_field(&mut map, "shard", shard);
+        serialize_field(&mut map, "seqno", id.seq_no());
+        serialize_uint256(&mut map, "root_hash", id.root_hash());
+        serialize_uint256(&mut map, "file_hash", id.file_hash());
     } else {
-        serialize_uint256(map, "block_id", id.root_hash());
-        serialize_uint256(map, "block_file_hash", id.file_hash());
-        serialize_field(map, "block_seqno", id.seq_no());
-        serialize_field(map, "shard", id.shard().shard_prefix_as_str_with_tag());
-        serialize_field(map, "wc", id.shard().workchain_id());
+        serialize_uint256(&mut map, "block_id", id.root_hash());
+        serialize_uint256(&mut map, "block_file_hash", id.file_hash());
+        serialize_field(&mut map, "block_seqno", id.seq_no());
+        serialize_field(&mut map, "shard", shard);
+        if let Some(workchain_id) = workchain_id {
+            serialize_field(&mut map, "wc", workchain_id);
+        }
     }
+    map
 }
 
-fn serialize_separated_block_id_ext(id: &BlockIdExt) -> Result<Value>{
-    let mut map = Map::new();
-    serialize_field(&mut map, "wc", id.shard().workchain_id());
-    serialize_field(&mut map, "shard", id.shard().shard_prefix_as_str_with_tag());
-    serialize_field(&mut map, "seqno", id.seq_no());
-    serialize_uint256(&mut map, "root_hash", id.root_hash());
-    serialize_uint256(&mut map, "file_hash", id.file_hash());
-    Ok(map.into())
+/// Block id in one of the two layouts read by the crate: the nested object
+/// `{wc, shard, seqno, root_hash, file_hash}` of the config p58 (read by `parse_separated_block_id_ext`)
+/// or the flat fields `block_id`, `block_file_hash`, `block_seqno`, `shard` and `wc` of the REMP receipts
+pub fn serialize_block_id_ext(id: &BlockIdExt, nested: bool) -> Value {
+    block_id_ext_fields(id, nested, SerializationOptions::default()).into()
 }
 
+#[cfg(feature = "remp")]
 pub fn db_serialize_remp_status(
     status: &RempReceipt,
     signature: &[u8]
+) -> Result<Map<String, Value>> {
+    db_serialize_remp_status_with_options(status, signature, SerializationOptions::default())
+}
+
+#[cfg(feature = "remp")]
+pub fn db_serialize_remp_status_with_options(
+    status: &RempReceipt,
+    signature: &[u8],
+    options: SerializationOptions,
 ) -> Result<Map<String, Value>> {
     let mut map = Map::new();
 
@@ -2370,61 +3206,31 @@ pub fn db_serialize_remp_status(
     serialize_uint256(&mut map, "source_id", status.source_id());
     serialize_field(&mut map, "signature", base64_encode(signature));
 
+    serialize_field(&mut map, "kind", remp_status_kind_string(status.status()));
     match status.status() {
         RempMessageStatus::TonNode_RempAccepted(acc) => {
-            let kind = match acc.level {
-                RempMessageLevel::TonNode_RempCollator => "IncludedIntoBlock",
-                RempMessageLevel::TonNode_RempFullnode => "AcceptedByFullnode", // impossible
-                RempMessageLevel::TonNode_RempMasterchain => "Finalized",
-                RempMessageLevel::TonNode_RempQueue => "AcceptedByQueue", // impossible
-                RempMessageLevel::TonNode_RempShardchain => "IncludedIntoAcceptedBlock",
-            };
-            serialize_field(&mut map, "kind", kind);
-            serialize_block_id_ext(&mut map, &acc.block_id, false);
+            map.extend(block_id_ext_fields(&acc.block_id, false, options));
             if acc.master_id.seq_no() != 0 {
-                serialize_block_id_ext(&mut map, &acc.master_id, true);
+                serialize_mc_block_id_ext(&mut map, &acc.master_id);
             }
         },
         RempMessageStatus::TonNode_RempDuplicate(dup) => {
-            serialize_field(&mut map, "kind", "Duplicate");
-            serialize_block_id_ext(&mut map, &dup.block_id, false);
+            map.extend(block_id_ext_fields(&dup.block_id, false, options));
         },
         RempMessageStatus::TonNode_RempIgnored(ign) => {
-            let kind = match ign.level {
-                RempMessageLevel::TonNode_RempCollator => "IgnoredByCollator",
-                RempMessageLevel::TonNode_RempFullnode => "IgnoredByFullNode",
-                RempMessageLevel::TonNode_RempMasterchain => "IgnoredByMasterchain",
-                RempMessageLevel::TonNode_RempQueue => "IgnoredByQueue",
-                RempMessageLevel::TonNode_RempShardchain => "IgnoredByShardchain",
-            };
-            serialize_field(&mut map, "kind", kind);
-            serialize_block_id_ext(&mut map, &ign.block_id, false);
-        },
-        RempMessageStatus::TonNode_RempNew => {
-            serialize_field(&mut map, "kind", "PutIntoQueue");
+            map.extend(block_id_ext_fields(&ign.block_id, false, options));
         },
         RempMessageStatus::TonNode_RempRejected(rj) => {
-            let kind = match rj.level {
-                RempMessageLevel::TonNode_RempCollator => "RejectedByCollator",
-                RempMessageLevel::TonNode_RempFullnode => "RejectedByFullnode",
-                RempMessageLevel::TonNode_RempMasterchain => "RejectedByMasterchain",
-                RempMessageLevel::TonNode_RempQueue => "RejectedByQueue",
-                RempMessageLevel::TonNode_RempShardchain => "RejectedByShardchain",
-            };
-            serialize_field(&mut map, "kind", kind);
             if rj.block_id.seq_no() != 0 {
-                serialize_block_id_ext(&mut map, &rj.block_id, false);
+                map.extend(block_id_ext_fields(&rj.block_id, false, options));
             }
             serialize_field(&mut map, "error", rj.error.clone());
         },
         RempMessageStatus::TonNode_RempSentToValidators(stv) => {
-            serialize_field(&mut map, "kind", "SentToValidators");
             serialize_field(&mut map, "sent_to", stv.sent_to);
             serialize_field(&mut map, "total_validators", stv.total_validators);
         },
-        RempMessageStatus::TonNode_RempTimeout => {
-            serialize_field(&mut map, "kind", "Timeout");
-        },
+        RempMessageStatus::TonNode_RempNew | RempMessageStatus::TonNode_RempTimeout => (),
     }
 
     Ok(map)
@@ -0,0 +1,288 @@
+/*
+ * Copyright (C) ton.dev. All Rights Reserved.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.  You may obtain a copy of the
+ * License at:
+ *
+ * https://www.ever.dev/licenses
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific ton.dev software governing permissions and limitations
+ * under the License.
+ */
+
+// Constants of both the serializer and the parser, kept apart so each of them builds alone
+
+use ton_dev_block::GlobalCapabilities;
+#[cfg(feature = "remp")]
+use ton_api::ton::ton_node::{RempMessageLevel, RempMessageStatus};
+#[cfg(feature = "remp")]
+use ton_dev_block::{fail, Result};
+
+// complaint_prices#1a deposit:Grams bit_price:Grams cell_price:Grams = ComplaintPricing (p13)
+pub(crate) const COMPLAINT_PRICING_TAG: u8 = 0x1a;
+
+// Names of the global capabilities bits used in p8
+pub(crate) const GLOBAL_CAPABILITIES: &[(&str, u64)] = &[
+    ("CapIhrEnabled", GlobalCapabilities::CapIhrEnabled as u64),
+    ("CapCreateStatsEnabled", GlobalCapabilities::CapCreateStatsEnabled as u64),
+    ("CapBounceMsgBody", GlobalCapabilities::CapBounceMsgBody as u64),
+    ("CapReportVersion", GlobalCapabilities::CapReportVersion as u64),
+    ("CapSplitMergeTransactions", GlobalCapabilities::CapSplitMergeTransactions as u64),
+    ("CapShortDequeue", GlobalCapabilities::CapShortDequeue as u64),
+    ("CapMbppEnabled", GlobalCapabilities::CapMbppEnabled as u64),
+    ("CapFastStorageStat", GlobalCapabilities::CapFastStorageStat as u64),
+    ("CapInitCodeHash", GlobalCapabilities::CapInitCodeHash as u64),
+    ("CapOffHypercube", GlobalCapabilities::CapOffHypercube as u64),
+    ("CapMycode", GlobalCapabilities::CapMycode as u64),
+    ("CapSetLibCode", GlobalCapabilities::CapSetLibCode as u64),
+    ("CapFixTupleIndexBug", GlobalCapabilities::CapFixTupleIndexBug as u64),
+    ("CapRemp", GlobalCapabilities::CapRemp as u64),
+    ("CapDelections", GlobalCapabilities::CapDelections as u64),
+    ("CapFullBodyInBounced", GlobalCapabilities::CapFullBodyInBounced as u64),
+    ("CapStorageFeeToTvm", GlobalCapabilities::CapStorageFeeToTvm as u64),
+    ("CapCopyleft", GlobalCapabilities::CapCopyleft as u64),
+    ("CapIndexAccounts", GlobalCapabilities::CapIndexAccounts as u64),
+    ("CapDiff", GlobalCapabilities::CapDiff as u64),
+    ("CapsTvmBugfixes2022", GlobalCapabilities::CapsTvmBugfixes2022 as u64),
+    ("CapWorkchains", GlobalCapabilities::CapWorkchains as u64),
+    ("CapStcontNewFormat", GlobalCapabilities::CapStcontNewFormat as u64),
+    ("CapFastStorageStatBugfix", GlobalCapabilities::CapFastStorageStatBugfix as u64),
+    ("CapResolveMerkleCell", GlobalCapabilities::CapResolveMerkleCell as u64),
+    ("CapSignatureWithId", GlobalCapabilities::CapSignatureWithId as u64),
+    ("CapBounceAfterFailedAction", GlobalCapabilities::CapBounceAfterFailedAction as u64),
+    ("CapGroth16", GlobalCapabilities::CapGroth16 as u64),
+    ("CapFeeInGasUnits", GlobalCapabilities::CapFeeInGasUnits as u64),
+    ("CapBigCells", GlobalCapabilities::CapBigCells as u64),
+    ("CapSuspendedList", GlobalCapabilities::CapSuspendedList as u64),
+    ("CapFastFinality", GlobalCapabilities::CapFastFinality as u64),
+    ("CapTvmV19", GlobalCapabilities::CapTvmV19 as u64),
+    ("CapSmft", GlobalCapabilities::CapSmft as u64),
+    ("CapNoSplitOutQueue", GlobalCapabilities::CapNoSplitOutQueue as u64),
+    ("CapUndeletableAccounts", GlobalCapabilities::CapUndeletableAccounts as u64),
+    ("CapTvmV20", GlobalCapabilities::CapTvmV20 as u64),
+    ("CapDuePaymentFix", GlobalCapabilities::CapDuePaymentFix as u64),
+    ("CapCommonMessage", GlobalCapabilities::CapCommonMessage as u64),
+];
+
+// Names of the workchain descriptor flag bits used in p12. The schema declares the field
+// as `flags:(## 13) { flags = 0 }`, so all the 13 bits are reserved and named by position,
+// a bit given a meaning gets its own name here
+pub(crate) const WORKCHAIN_FLAGS: &[(&str, u16)] = &[
+    ("bit0", 1 << 0), ("bit1", 1 << 1), ("bit2", 1 << 2), ("bit3", 1 << 3),
+    ("bit4", 1 << 4), ("bit5", 1 << 5), ("bit6", 1 << 6), ("bit7", 1 << 7),
+    ("bit8", 1 << 8), ("bit9", 1 << 9), ("bit10", 1 << 10), ("bit11", 1 << 11),
+    ("bit12", 1 << 12),
+];
+
+// Days since 1970-01-01 of the proleptic Gregorian date and back, the algorithms of
+// http://howardhinnant.github.io/date_algorithms.html
+#[cfg(feature = "deserialize")]
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(feature = "serialize")]
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// unix time as RFC 3339 UTC, e.g. `2023-03-01T12:00:00Z`
+#[cfg(feature = "serialize")]
+pub(crate) fn unix_time_to_rfc3339(time: u32) -> String {
+    let (days, seconds) = (time as i64 / 86400, time % 86400);
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+// RFC 3339 time with the `Z` or numeric offset and no fraction of second, e.g.
+// `2023-03-01T12:00:00Z` or `2023-03-01T15:00:00+03:00`, as unix time
+#[cfg(feature = "deserialize")]
+pub(crate) fn rfc3339_to_unix_time(text: &str) -> Option<u32> {
+    fn number(text: &str, range: std::ops::RangeInclusive<u32>) -> Option<u32> {
+        if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+            return None
+        }
+        text.parse().ok().filter(|value| range.contains(value))
+    }
+    let (date, time) = text.split_once(['T', 't'])?;
+    let mut date = date.split('-');
+    let (year, month, day) = (date.next()?, date.next()?, date.next()?);
+    if date.next().is_some() || year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None
+    }
+    let (year, month) = (number(year, 0..=9999)? as i64, number(month, 1..=12)?);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    let day = number(day, 1..=month_days)?;
+    let (time, offset) = match time.strip_suffix(['Z', 'z']) {
+        Some(time) => (time, 0),
+        None => {
+            let split = time.len().checked_sub(6)?;
+            let (time, offset) = (time.get(..split)?, time.get(split..)?);
+            let sign = match offset.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None
+            };
+            let (hours, minutes) = offset[1..].split_once(':')?;
+            let (hours, minutes) = (number(hours, 0..=23)?, number(minutes, 0..=59)?);
+            (time, sign * (hours * 3600 + minutes * 60) as i64)
+        }
+    };
+    let mut time = time.split(':');
+    let (hours, minutes, seconds) = (time.next()?, time.next()?, time.next()?);
+    if time.next().is_some() || hours.len() != 2 || minutes.len() != 2 || seconds.len() != 2 {
+        return None
+    }
+    let seconds = number(hours, 0..=23)? * 3600 + number(minutes, 0..=59)? * 60 + number(seconds, 0..=59)?;
+    let time = days_from_civil(year, month, day) * 86400 + seconds as i64 - offset;
+    u32::try_from(time).ok()
+}
+
+/// Name of the document id field. The serializers take it as `id_str`,
+/// e.g. `db_serialize_block(DocIdField::Key.name(), set)`, the block parser as
+/// `BlockParserConfig::doc_id_field`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DocIdField {
+    /// `id`
+    #[default]
+    Id,
+    /// `_key` of ArangoDB
+    Key,
+    /// `_id` of Elastic
+    UnderscoreId,
+}
+
+impl DocIdField {
+    pub const ALL: [DocIdField; 3] = [DocIdField::Id, DocIdField::Key, DocIdField::UnderscoreId];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DocIdField::Id => "id",
+            DocIdField::Key => "_key",
+            DocIdField::UnderscoreId => "_id",
+        }
+    }
+}
+
+/// REMP status without its data: the variant and the level of the accepted,
+/// ignored and rejected statuses. Each kind has its own `kind` string in the documents
+#[cfg(feature = "remp")]
+#[derive(Clone, Debug, PartialEq)]
+pub enum RempStatusKind {
+    Accepted(RempMessageLevel),
+    Duplicate,
+    Ignored(RempMessageLevel),
+    New,
+    Rejected(RempMessageLevel),
+    SentToValidators,
+    Timeout,
+}
+
+#[cfg(feature = "remp")]
+impl RempStatusKind {
+    pub const ALL: [RempStatusKind; 19] = [
+        RempStatusKind::Accepted(RempMessageLevel::TonNode_RempCollator),
+        RempStatusKind::Accepted(RempMessageLevel::TonNode_RempFullnode),
+        RempStatusKind::Accepted(RempMessageLevel::TonNode_RempMasterchain),
+        RempStatusKind::Accepted(RempMessageLevel::TonNode_RempQueue),
+        RempStatusKind::Accepted(RempMessageLevel::TonNode_RempShardchain),
+        RempStatusKind::Duplicate,
+        RempStatusKind::Ignored(RempMessageLevel::TonNode_RempCollator),
+        RempStatusKind::Ignored(RempMessageLevel::TonNode_RempFullnode),
+        RempStatusKind::Ignored(RempMessageLevel::TonNode_RempMasterchain),
+        RempStatusKind::Ignored(RempMessageLevel::TonNode_RempQueue),
+        RempStatusKind::Ignored(RempMessageLevel::TonNode_RempShardchain),
+        RempStatusKind::New,
+        RempStatusKind::Rejected(RempMessageLevel::TonNode_RempCollator),
+        RempStatusKind::Rejected(RempMessageLevel::TonNode_RempFullnode),
+        RempStatusKind::Rejected(RempMessageLevel::TonNode_RempMasterchain),
+        RempStatusKind::Rejected(RempMessageLevel::TonNode_RempQueue),
+        RempStatusKind::Rejected(RempMessageLevel::TonNode_RempShardchain),
+        RempStatusKind::SentToValidators,
+        RempStatusKind::Timeout,
+    ];
+
+    pub fn of(status: &RempMessageStatus) -> Self {
+        match status {
+            RempMessageStatus::TonNode_RempAccepted(acc) => RempStatusKind::Accepted(acc.level.clone()),
+            RempMessageStatus::TonNode_RempDuplicate(_) => RempStatusKind::Duplicate,
+            RempMessageStatus::TonNode_RempIgnored(ign) => RempStatusKind::Ignored(ign.level.clone()),
+            RempMessageStatus::TonNode_RempNew => RempStatusKind::New,
+            RempMessageStatus::TonNode_RempRejected(rj) => RempStatusKind::Rejected(rj.level.clone()),
+            RempMessageStatus::TonNode_RempSentToValidators(_) => RempStatusKind::SentToValidators,
+            RempMessageStatus::TonNode_RempTimeout => RempStatusKind::Timeout,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RempStatusKind::Accepted(level) => match level {
+                RempMessageLevel::TonNode_RempCollator => "IncludedIntoBlock",
+                RempMessageLevel::TonNode_RempFullnode => "AcceptedByFullnode", // impossible
+                RempMessageLevel::TonNode_RempMasterchain => "Finalized",
+                RempMessageLevel::TonNode_RempQueue => "AcceptedByQueue", // impossible
+                RempMessageLevel::TonNode_RempShardchain => "IncludedIntoAcceptedBlock",
+            },
+            RempStatusKind::Duplicate => "Duplicate",
+            RempStatusKind::Ignored(level) => match level {
+                RempMessageLevel::TonNode_RempCollator => "IgnoredByCollator",
+                RempMessageLevel::TonNode_RempFullnode => "IgnoredByFullNode",
+                RempMessageLevel::TonNode_RempMasterchain => "IgnoredByMasterchain",
+                RempMessageLevel::TonNode_RempQueue => "IgnoredByQueue",
+                RempMessageLevel::TonNode_RempShardchain => "IgnoredByShardchain",
+            },
+            RempStatusKind::New => "PutIntoQueue",
+            RempStatusKind::Rejected(level) => match level {
+                RempMessageLevel::TonNode_RempCollator => "RejectedByCollator",
+                RempMessageLevel::TonNode_RempFullnode => "RejectedByFullnode",
+                RempMessageLevel::TonNode_RempMasterchain => "RejectedByMasterchain",
+                RempMessageLevel::TonNode_RempQueue => "RejectedByQueue",
+                RempMessageLevel::TonNode_RempShardchain => "RejectedByShardchain",
+            },
+            RempStatusKind::SentToValidators => "SentToValidators",
+            RempStatusKind::Timeout => "Timeout",
+        }
+    }
+}
+
+/// Kind of the REMP status by the `kind` string of the documents,
+/// the parser fills the status data of the kind from the other fields
+#[cfg(feature = "remp")]
+pub fn remp_kind_to_status_template(kind: &str) -> Result<RempStatusKind> {
+    match RempStatusKind::ALL.into_iter().find(|status_kind| status_kind.name() == kind) {
+        Some(status_kind) => Ok(status_kind),
+        None => fail!("Unknown status: {}", kind)
+    }
+}
+
+/// The `kind` string of the REMP status in the documents
+#[cfg(feature = "remp")]
+pub fn remp_status_kind_string(status: &RempMessageStatus) -> &'static str {
+    RempStatusKind::of(status).name()
+}
@@ -0,0 +1,107 @@
+/*
+ * Copyright (C) ton.dev. All Rights Reserved.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.  You may obtain a copy of the
+ * License at:
+ *
+ * https://www.ever.dev/licenses
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific ton.dev software governing permissions and limitations
+ * under the License.
+ */
+
+use super::*;
+use crate::{serialize_config, SerializationMode};
+use serde_json::Map;
+
+fn serialize(config: &ConfigParams, mode: SerializationMode) -> Map<String, Value> {
+    let mut json = Map::new();
+    serialize_config(&mut json, config, mode).unwrap();
+    json["config"].as_object().unwrap().clone()
+}
+
+#[test]
+fn test_arbitrary_config_is_reproducible() {
+    let json = serialize(&arbitrary_config(1), SerializationMode::Standart);
+    assert_eq!(json, serialize(&arbitrary_config(1), SerializationMode::Standart));
+    assert_ne!(json, serialize(&arbitrary_config(2), SerializationMode::Standart));
+}
+
+#[test]
+fn test_config_round_trip_fixpoint() {
+    for seed in 0..64 {
+        let config = arbitrary_config(seed);
+        for mode in [SerializationMode::Standart, SerializationMode::QServer, SerializationMode::Debug] {
+            let json = serialize(&config, mode);
+            let parsed = parse_config(&json)
+                .unwrap_or_else(|err| panic!("seed {} mode {:?}: {}", seed, mode, err));
+            assert_eq!(serialize(&parsed, mode), json, "seed {} mode {:?}", seed, mode);
+        }
+    }
+}
+
+fn mutations(value: &Value) -> Vec<Value> {
+    let replacements = [
+        Value::Null,
+        Value::Bool(true),
+        serde_json::json!(-1),
+        serde_json::json!(u64::MAX),
+        serde_json::json!(1.5),
+        serde_json::json!("x"),
+        serde_json::json!("0xffffffffffffffffffff"),
+        serde_json::json!([]),
+        serde_json::json!({}),
+    ];
+    let mut result = Vec::new();
+    match value {
+        Value::Object(map) => {
+            for (name, item) in map {
+                for replacement in replacements.iter().cloned().chain(mutations(item)) {
+                    let mut mutated = map.clone();
+                    mutated.insert(name.clone(), replacement);
+                    result.push(Value::Object(mutated));
+                }
+                let mut removed = map.clone();
+                removed.remove(name);
+                result.push(Value::Object(removed));
+            }
+        }
+        Value::Array(array) if !array.is_empty() => {
+            for replacement in replacements.iter().cloned().chain(mutations(&array[0])) {
+                let mut mutated = array.clone();
+                mutated[0] = replacement;
+                result.push(Value::Array(mutated));
+            }
+        }
+        _ => ()
+    }
+    result
+}
+
+#[test]
+fn test_fuzz_parse_config_corpus() {
+    let seed = std::fs::read("fuzz/corpus/fuzz_parse_config/zerostate-p12-p18-p34.json").unwrap();
+    fuzz_parse_config(&seed);
+    let config: Map<String, Value> = serde_json::from_slice(&seed).unwrap();
+    parse_config(&config).unwrap();
+
+    // every single field broken in every possible way must not panic
+    let mut count = 0;
+    for (name, param) in &config {
+        for mutated in mutations(param) {
+            let mut config = config.clone();
+            config.insert(name.clone(), mutated);
+            fuzz_parse_config(&serde_json::to_vec(&config).unwrap());
+            count += 1;
+        }
+    }
+    assert!(count > 100, "{}", count);
+
+    for data in [&b""[..], b"null", b"[]", b"{", b"{\"config\": 1}", b"{\"p12\": {}}", &seed[..seed.len() / 2]] {
+        fuzz_parse_config(data);
+    }
+}
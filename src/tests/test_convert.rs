@@ -0,0 +1,78 @@
+/*
+ * Copyright (C) ton.dev. All Rights Reserved.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.  You may obtain a copy of the
+ * License at:
+ *
+ * https://www.ever.dev/licenses
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific ton.dev software governing permissions and limitations
+ * under the License.
+ */
+
+use super::*;
+use ton_dev_block::UInt256;
+
+fn read_zerostate() -> Map<String, Value> {
+    let text = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    serde_json::from_str(&text).unwrap()
+}
+
+// generic code sees only the traits
+fn from_json<J, T>(json: J) -> T
+where
+    T: TryFrom<J, Error = Error>,
+{
+    T::try_from(json).unwrap()
+}
+
+fn to_json<'t, T, J>(value: &'t T) -> J
+where
+    J: TryFrom<&'t T, Error = Error>,
+{
+    J::try_from(value).unwrap()
+}
+
+#[test]
+fn test_config_round_trip() {
+    let state = crate::parse_state(&read_zerostate()).unwrap();
+    let config = state.read_custom().unwrap().unwrap().config;
+
+    assert_ne!(config.config_addr, UInt256::ZERO);
+
+    let json: JsonConfig = to_json(&config);
+    assert!(json.0["config"].as_object().unwrap().contains_key("p34"));
+    assert_eq!(json.0["config_addr"], config.config_addr.as_hex_string());
+    let parsed: ConfigParams = from_json(json.clone());
+    assert_eq!(parsed.config_addr, config.config_addr);
+    assert_eq!(parsed, config);
+    let parsed: ConfigParams = (&json).try_into().unwrap();
+    assert_eq!(parsed, config);
+
+    // the wrappers accepted by parse_config are accepted too, the address is taken if given
+    let wrapped = JsonConfig::from(serde_json::json!({ "master": { "config": json.0["config"] } }).as_object().unwrap().clone());
+    let parsed = ConfigParams::try_from(wrapped).unwrap();
+    assert_eq!(parsed.config_addr, UInt256::ZERO);
+    assert_eq!(parsed.config_params, config.config_params);
+
+    let broken = JsonConfig::from(serde_json::json!({ "p12": "soon" }).as_object().unwrap().clone());
+    assert!(ConfigParams::try_from(broken).is_err());
+}
+
+#[test]
+fn test_state_round_trip() {
+    let state: ShardStateUnsplit = from_json(JsonState(read_zerostate()));
+
+    let json: JsonState = to_json(&state);
+    assert_eq!(json.0["workchain_id"], -1);
+    assert!(json.0.get("block_id").is_none());
+    let parsed: ShardStateUnsplit = from_json(json.clone());
+    assert_eq!(parsed, state);
+
+    let map: Map<String, Value> = json.into();
+    assert_eq!(ShardStateUnsplit::try_from(JsonState::from(map)).unwrap(), state);
+}
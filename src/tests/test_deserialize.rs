@@ -15,16 +15,23 @@
  */
 
 use super::*;
-use crate::{serialize_config, SerializationMode, serialize_config_param};
+#[cfg(feature = "serialize")]
+use crate::{serialize_config, serialize_config_with_options, SerializationMode, SerializationOptions, serialize_config_param};
 use ton_dev_block::{
-    ConfigParam3, ConfigParam32, ConfigParam33, ConfigParam35, ConfigParam36, ConfigParam37,
-    ConfigParam39, ConfigParam4, ConfigParam6, ConfigVotingSetup, DelectorParams, Number16,
-    SigPubKey, VarUInteger32, ConfigCopyleft, SmftParams,
+    ConfigParam32, ConfigParam36, ConfigParam39, ConfigVotingSetup, Number16,
+    SigPubKey, VarUInteger32, ConfigCopyleft,
+};
+// the round trip tests need the serializer
+#[cfg(feature = "serialize")]
+use ton_dev_block::{
+    BuilderData, ConfigParam3, ConfigParam33, ConfigParam35, ConfigParam37, ConfigParam4,
+    ConfigParam6, DelectorParams, IBitstring, SmftParams,
 };
-use ton_dev_block::{BuilderData, IBitstring};
 
+#[cfg(feature = "serialize")]
 include!("./test_common.rs");
 
+#[cfg(feature = "serialize")]
 #[test]
 fn test_parse_zerostate() {
     let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
@@ -342,6 +349,7 @@ fn get_validator_set() -> ValidatorSet {
     ValidatorSet::new(0, 100, 1, list).unwrap()
 }
 
+#[cfg(feature = "serialize")]
 fn prepare_config_params() -> ConfigParams {
     let mut cp = ConfigParams::new();
 
@@ -486,7 +494,14 @@ fn prepare_config_params() -> ConfigParams {
     let c44 = get_config_param44();
     cp.set_config(ConfigParamEnum::ConfigParam44(c44)).unwrap();
 
-    let c61 = FastFinalityConfig::default();
+    let c61 = FastFinalityConfig {
+        split_merge_interval: 100,
+        collator_range_len: 1000,
+        mempool_validators_count: 7,
+        familiarity_max: 300,
+        candidates_percentile: 80,
+        ..Default::default()
+    };
     cp.set_config(ConfigParamEnum::ConfigParam61(c61)).unwrap();
 
     let c62 = ConfigParamEnum::ConfigParam62(SmftParams::new());
@@ -495,13 +510,14 @@ fn prepare_config_params() -> ConfigParams {
     cp
 }
 
+#[cfg(feature = "serialize")]
 #[test]
 fn test_config_params() {
 
     let cp = prepare_config_params();
 
     let check_params = |old: &ConfigParams, new: &ConfigParams| {
-        for i in 0..45 {
+        for i in 0..64 {
             println!("Iteration {}", i);
             if old.config_present(i).unwrap() {
                 let old_conf = old.config(i).unwrap().unwrap();
@@ -524,11 +540,126 @@ fn test_config_params() {
     check_params(&cp, &parsed_config);
 }
 
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_p7_forms() {
+    let mut cp = ConfigParams::new();
+    cp.set_config(ConfigParamEnum::ConfigParam7(get_config_param7())).unwrap();
+    let expected = get_config_param7().to_mint;
+    let read_p7 = |config: &Value| match parse_config(config.as_object().unwrap()).unwrap().config(7).unwrap() {
+        Some(ConfigParamEnum::ConfigParam7(p7)) => p7.to_mint,
+        _ => panic!("no p7"),
+    };
+
+    for mode in [SerializationMode::Standart, SerializationMode::QServer, SerializationMode::Debug] {
+        let mut json = Map::new();
+        serialize_config(&mut json, &cp, mode).unwrap();
+        let config = json["config"].clone();
+        assert!(config["p7"].is_array());
+        assert_eq!(read_p7(&config), expected);
+
+        let mut json = Map::new();
+        let options = SerializationOptions { p7_as_map: true, ..Default::default() };
+        serialize_config_with_options(&mut json, &cp, mode, options).unwrap();
+        let config = json["config"].clone();
+        let p7 = config["p7"].as_object().unwrap();
+        assert_eq!(p7.len(), 99);
+        assert_eq!(p7["1"], VarUInteger32::from_two_u128(100, 205).unwrap().value().to_string());
+        assert_eq!(read_p7(&config), expected);
+    }
+
+    let map_form = serde_json::json!({ "p7": { "239": "1_000_000", "4294967279": 500 } });
+    let array_form = serde_json::json!({ "p7": [
+        { "currency": 239, "value": "1000000" },
+        { "currency": 4294967279u32, "value_dec": "500" },
+    ] });
+    assert_eq!(read_p7(&map_form), read_p7(&array_form));
+    let dec_keys = serde_json::json!({ "p7": { "239_dec": "1000000", "4294967279": "500" } });
+    assert_eq!(read_p7(&dec_keys), read_p7(&array_form));
+
+    let config = serde_json::json!({ "p7": { "239": "1", "239_dec": "1" } });
+    check_err(parse_config(config.as_object().unwrap()), "root/p7 has the currency 239 twice");
+    let config = serde_json::json!({ "p7": [{ "currency": 7, "value": "1" }, { "currency": 7, "value": "2" }] });
+    check_err(parse_config(config.as_object().unwrap()), "root/p7 has the currency 7 twice");
+    let config = serde_json::json!({ "p7": { "4294967296": "1" } });
+    check_err(parse_config(config.as_object().unwrap()), "root/p7 keys must be the currency ids but 4294967296 given");
+    let config = serde_json::json!({ "p7": [{ "currency": 4294967296u64, "value": "1" }] });
+    check_err(
+        parse_config(config.as_object().unwrap()),
+        "root/p7/currency must be the currency id in the u32 range but 4294967296 given",
+    );
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_wrapped_config() {
+    let cp = prepare_config_params();
+    let mut json = serde_json::Map::<String, Value>::new();
+    serialize_config(&mut json, &cp, SerializationMode::Standart).unwrap();
+    let mut config = json["config"].clone();
+    let expected = parse_config(config.as_object().unwrap()).unwrap();
+
+    let wrapped = serde_json::json!({ "config": config.clone() });
+    assert_eq!(parse_config(wrapped.as_object().unwrap()).unwrap(), expected);
+    let wrapped = serde_json::json!({ "master": { "config": config.clone() } });
+    assert_eq!(parse_config(wrapped.as_object().unwrap()).unwrap(), expected);
+
+    // the wrapper is kept in the error paths
+    config.as_object_mut().unwrap().remove("p34");
+    let wrapped = serde_json::json!({ "master": { "config": config.clone() } });
+    check_err(
+        parse_config_with_mandatory_params(wrapped.as_object().unwrap(), &[34]),
+        "parameter p34 not found: root/master/config must have the field `p34`"
+    );
+    let wrapped = serde_json::json!({ "config": config, "p34": json["config"]["p34"].clone() });
+    check_err(
+        parse_config(wrapped.as_object().unwrap()),
+        "root must have either the config parameters or the `config` object but not both"
+    );
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_fast_finality_config() {
+    let cp = prepare_config_params();
+    let mut json = serde_json::Map::<String, Value>::new();
+    serialize_config(&mut json, &cp, SerializationMode::Standart).unwrap();
+    let mut config = json["config"].as_object().unwrap().clone();
+    assert!(config["p62"].as_object().unwrap().contains_key("use_debug_bls_keys"));
+
+    config["p61"].as_object_mut().unwrap().remove("collator_range_len");
+    let p61 = serde_json::json!({ "p61": config["p61"].clone() });
+    check_err(
+        parse_config(p61.as_object().unwrap()),
+        "root/p61 must have the field `collator_range_len`"
+    );
+
+    // the old lenient parsing takes the missing fields from the default
+    let parsed = StateParser::new().with_p61_defaults(true)
+        .parse_config_params(p61.as_object().unwrap()).unwrap();
+    let expected = FastFinalityConfig {
+        collator_range_len: FastFinalityConfig::default().collator_range_len,
+        ..match cp.config(61).unwrap() {
+            Some(ConfigParamEnum::ConfigParam61(c61)) => c61,
+            other => panic!("{:?}", other)
+        }
+    };
+    assert_eq!(parsed.config(61).unwrap(), Some(ConfigParamEnum::ConfigParam61(expected)));
+
+    for num in [61, 62] {
+        check_err(
+            parse_config_with_mandatory_params(&serde_json::Map::new(), &[num]),
+            &format!("parameter p{} not found", num)
+        );
+    }
+}
+
+#[cfg(feature = "serialize")]
 #[test]
 fn test_parse_config_params() {
     let cp = prepare_config_params();
 
-    for index in 0..45 {
+    for index in 0..64 {
         if let Ok(param) = serialize_config_param(&cp, index) {
             println!("{}: {}", index, param);
             let config = serde_json::from_str(&param).unwrap();
@@ -549,3 +680,2026 @@ fn test_parse_block_proof() {
     assert_eq!(ethalon_proof, parsed_proof);
     assert_eq!(boc.as_slice(), &parsed_proof.write_to_bytes().unwrap());
 }
+
+#[test]
+fn test_parse_pubkey_forms() {
+    let bytes = [0x5a; 32];
+    let json = serde_json::json!({
+        "hex": hex::encode(bytes),
+        "base64": base64_encode(bytes),
+        "keyring": {
+            "type_id": 1209251014,
+            "pub_key": base64_encode(bytes),
+        },
+        "short_hex": hex::encode([0x5a; 31]),
+        "short_base64": base64_encode([0x5a; 31]),
+        "number": 123,
+    });
+    let map = PathMap::new(json.as_object().unwrap());
+    let ethalon = SigPubKey::from_bytes(&bytes).unwrap();
+    assert_eq!(map.get_pubkey("hex").unwrap(), ethalon);
+    assert_eq!(map.get_pubkey("base64").unwrap(), ethalon);
+    assert_eq!(map.get_pubkey("keyring").unwrap(), ethalon);
+
+    check_err(map.get_pubkey("short_hex"), "root/short_hex must be the 32 bytes public key but hex form contains 31 bytes");
+    check_err(map.get_pubkey("short_base64"), "root/short_base64 must be the 32 bytes public key but base64 form contains 31 bytes");
+    check_err(map.get_pubkey("number"), "root/number must be the public key string or keyring object");
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_libraries_round_trip() {
+    let mut state = ShardStateUnsplit::default();
+    for (value, publishers) in [(1u32, 1u8), (2, 2)] {
+        let mut builder = BuilderData::new();
+        builder.append_u32(value).unwrap();
+        let cell = builder.into_cell().unwrap();
+        let mut lib = LibDescr::new(cell.clone());
+        for publisher in 0..publishers {
+            lib.publishers_mut().add_key(&UInt256::from([publisher + 1; 32])).unwrap();
+        }
+        state.libraries_mut().set(&cell.repr_hash(), &lib).unwrap();
+    }
+
+    let json = crate::debug_state_full(state.clone()).unwrap();
+    let map = serde_json::from_str::<Map<String, Value>>(&json).unwrap();
+    let libraries = map["libraries"].as_array().unwrap();
+    assert_eq!(libraries.len(), 2);
+    assert!(libraries[0]["hash"].as_str().unwrap() < libraries[1]["hash"].as_str().unwrap());
+    let publishers = libraries.iter().map(|lib| lib["publishers"].as_array().unwrap().len()).collect::<Vec<_>>();
+    assert_eq!(publishers.iter().sum::<usize>(), 3);
+
+    let parsed = parse_state_unchecked(&map).unwrap();
+    assert_eq!(state.libraries().root(), parsed.libraries().root());
+}
+
+#[test]
+fn test_parse_capabilities_list() {
+    let json = serde_json::json!({
+        "p8": {
+            "version": 5,
+            "capabilities_list": ["CapCreateStatsEnabled", "CapBounceMsgBody", "CapReportVersion"]
+        }
+    });
+    let config = parse_config_with_mandatory_params(json.as_object().unwrap(), &[8]).unwrap();
+    let ConfigParamEnum::ConfigParam8(p8) = config.config(8).unwrap().unwrap() else {
+        panic!("p8 must be parsed")
+    };
+    assert_eq!(p8.global_version.capabilities, 0x0e);
+
+    let json = serde_json::json!({
+        "p8": {
+            "version": 5,
+            "capabilities_list": ["CapCreateStatsEnabled", "CapBounceMsgBodi"]
+        }
+    });
+    check_err(
+        parse_config_with_mandatory_params(json.as_object().unwrap(), &[8]),
+        "root/p8/capabilities_list contains unknown capability `CapBounceMsgBodi`, valid ones are: CapIhrEnabled,"
+    );
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_shard_ident() {
+    let ethalon = ShardIdent::with_tagged_prefix(0, 0x6800_0000_0000_0000).unwrap();
+    for name in ["wc", "workchain_id", "workchain"] {
+        let json = serde_json::json!({
+            name: 0,
+            "shard": "6800000000000000",
+        });
+        let map = PathMap::new(json.as_object().unwrap());
+        assert_eq!(parse_shard_ident(&map).unwrap(), ethalon);
+    }
+
+    let json = serde_json::json!({
+        "wc": 0,
+        "workchain_id": "0",
+        "shard": "6800000000000000",
+    });
+    assert_eq!(parse_shard_ident(&PathMap::new(json.as_object().unwrap())).unwrap(), ethalon);
+
+    let json = serde_json::json!({
+        "wc": 0,
+        "workchain_id": -1,
+        "shard": "6800000000000000",
+    });
+    check_err(
+        parse_shard_ident(&PathMap::new(json.as_object().unwrap())),
+        "root has conflicting fields `wc` = 0 and `workchain_id` = -1"
+    );
+
+    assert_eq!(crate::shard_ident_to_json(&ethalon), (0, "6800000000000000".to_string()));
+}
+
+#[test]
+fn test_parse_composite_shard_ident() {
+    let parse = |json: Value| parse_shard_ident(&PathMap::new(json.as_object().unwrap()));
+    assert_eq!(
+        parse(serde_json::json!({ "shard": "-1,8000000000000000" })).unwrap(),
+        ShardIdent::masterchain()
+    );
+    // the same workchain may be repeated in the separate field
+    assert_eq!(
+        parse(serde_json::json!({ "wc": 0, "shard": "0, 6800000000000000" })).unwrap(),
+        ShardIdent::with_tagged_prefix(0, 0x6800_0000_0000_0000).unwrap()
+    );
+    check_err(
+        parse(serde_json::json!({ "workchain_id": 0, "shard": "-1,8000000000000000" })),
+        "root has conflicting fields `shard` = -1 and `workchain_id` = 0"
+    );
+    check_err(
+        parse(serde_json::json!({ "shard": "master,8000000000000000" })),
+        "root/shard must be the workchain id and the shard prefix separated by comma"
+    );
+    check_err(
+        parse(serde_json::json!({ "shard": "-1,80000000000000zz" })),
+        "root/shard must be the shard prefix in hex format"
+    );
+}
+
+#[test]
+fn test_parse_p39_signature_verification() {
+    let private_key = ton_dev_block::ed25519_create_private_key(&[7; 32]).unwrap();
+    let temp_public_key = SigPubKey::from_bytes(&private_key.verifying_key()).unwrap();
+    let temp_key = ValidatorTempKey::with_params(UInt256::from([2; 32]), temp_public_key, 10, 1000);
+    let signature = private_key.sign(temp_key.serialize().unwrap().repr_hash().as_slice());
+
+    let p39 = |signature: &[u8]| serde_json::json!({
+        "p39": [{
+            "map_key": UInt256::from([1; 32]).as_hex_string(),
+            "adnl_addr": UInt256::from([2; 32]).as_hex_string(),
+            "temp_public_key": hex::encode(private_key.verifying_key()),
+            "seqno": 10,
+            "valid_until": 1000,
+            "signature_r": hex::encode(&signature[..32]),
+            "signature_s": hex::encode(&signature[32..]),
+        }]
+    });
+
+    let json = p39(&signature);
+    let config = StateParser::new()
+        .with_signature_verification(true)
+        .parse_config_params(json.as_object().unwrap())
+        .unwrap();
+    assert!(config.config_present(39).unwrap());
+
+    let mut broken = signature;
+    broken[5] ^= 1;
+    let json = p39(&broken);
+    check_err(
+        StateParser::new()
+            .with_signature_verification(true)
+            .parse_config_params(json.as_object().unwrap()),
+        "root/p39 has invalid signature for map_key 0101"
+    );
+    // verification is opt-in
+    StateParser::new().parse_config_params(json.as_object().unwrap()).unwrap();
+}
+
+#[test]
+fn test_parse_state_validator_info_layouts() {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let nested = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let read_info = |map: &Map<String, Value>| {
+        let state = parse_state(map).unwrap();
+        state.read_custom().unwrap().unwrap().validator_info
+    };
+    let expected = read_info(&nested);
+    assert_eq!(expected.validator_list_hash_short, 2129871690);
+    assert!(expected.nx_cc_updated);
+
+    let mut flat = nested.clone();
+    let master = flat["master"].as_object_mut().unwrap();
+    let info = master.remove("validator_info").unwrap();
+    master.extend(info.as_object().unwrap().clone());
+    assert_eq!(read_info(&flat), expected);
+
+    // both layouts agreeing
+    let mut both = flat.clone();
+    both["master"]["validator_info"] = nested["master"]["validator_info"].clone();
+    assert_eq!(read_info(&both), expected);
+
+    both["master"]["validator_info"]["catchain_seqno"] = 5.into();
+    check_err(
+        parse_state(&both),
+        "root/master/validator_info/catchain_seqno is 5 but root/master/catchain_seqno is 0"
+    );
+    both["master"]["validator_info"]["catchain_seqno"] = 0.into();
+    both["master"]["validator_info"]["nx_cc_updated"] = false.into();
+    check_err(
+        parse_state(&both),
+        "root/master/validator_info/nx_cc_updated is false but root/master/nx_cc_updated is true"
+    );
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_state_with_raw_master() {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let state = parse_state(&map).unwrap();
+    let set = crate::ShardStateSerializationSet {
+        block_id: None,
+        workchain_id: state.shard().workchain_id(),
+        id: format!("{}", state.shard()),
+        boc: state.write_to_bytes().unwrap(),
+        state,
+    };
+    let map = crate::db_serialize_shard_state_with_raw_master("id", &set, SerializationMode::Debug).unwrap();
+    assert!(map.get("master").is_none());
+
+    let extra_cell = read_single_root_boc(base64_decode(map["master_boc"].as_str().unwrap()).unwrap()).unwrap();
+    let parsed = parse_state_unchecked(&map).unwrap();
+    let parsed_extra = parsed.read_custom().unwrap().unwrap();
+    assert_eq!(parsed_extra.serialize().unwrap().repr_hash(), extra_cell.repr_hash());
+
+    let mut map = map;
+    map.insert("master".to_string(), serde_json::json!({}));
+    check_err(parse_state_unchecked(&map), "root must not have both fields `master_boc` and `master`");
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_value_flow_with_extra_currencies() {
+    let boc = std::fs::read("src/tests/data/89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc").unwrap();
+    let block = Block::construct_from_bytes(&boc).unwrap();
+    let value_flow = block.read_value_flow().unwrap();
+    assert!(!value_flow.from_prev_blk.other.is_empty());
+
+    for mode in [SerializationMode::Standart, SerializationMode::QServer] {
+        let set = crate::BlockSerializationSet {
+            id: block.hash().unwrap(),
+            block: block.clone(),
+            status: BlockProcessingStatus::Finalized,
+            boc: boc.clone(),
+        };
+        let json = crate::db_serialize_block_ex("id", &set, mode).unwrap();
+        let map = PathMap::new(&json);
+        let parsed = parse_value_flow(&map.get_obj("value_flow").unwrap()).unwrap();
+        assert_eq!(parsed.from_prev_blk, value_flow.from_prev_blk);
+        assert_eq!(parsed.to_next_blk, value_flow.to_next_blk);
+        assert_eq!(parsed.minted, value_flow.minted);
+        assert_eq!(parsed.created, value_flow.created);
+
+        let mut income = parsed.from_prev_blk.clone();
+        for cc in [&parsed.imported, &parsed.fees_imported, &parsed.recovered, &parsed.created, &parsed.minted] {
+            income.add(cc).unwrap();
+        }
+        let mut outcome = parsed.to_next_blk.clone();
+        for cc in [&parsed.exported, &parsed.fees_collected] {
+            outcome.add(cc).unwrap();
+        }
+        assert_eq!(income, outcome);
+    }
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_workchain_descr_standalone() {
+    let basic = get_workchain_desc();
+    let mut extended = get_workchain_desc();
+    extended.format = WorkchainFormat::Extended(WorkchainFormat0::with_params(64, 1023, 8, 1).unwrap());
+
+    for (workchain_id, descr) in [(0, basic), (7, extended)] {
+        let json = crate::serialize_workchain_descr(workchain_id, &descr);
+        assert_eq!(json["workchain_id"], workchain_id);
+        assert_eq!(json["basic"], matches!(descr.format, WorkchainFormat::Basic(_)));
+        let map = PathMap::new(json.as_object().unwrap());
+        let (parsed_id, parsed) = parse_workchain_descr(&map).unwrap();
+        assert_eq!(parsed_id, workchain_id);
+        assert_eq!(parsed, descr);
+    }
+
+    let json = serde_json::json!({ "workchain_id": 0, "basic": true });
+    check_err(parse_workchain_descr(&PathMap::new(json.as_object().unwrap())), "root must have the field `min_split`");
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_base64_alphabets() {
+    let mut builder = BuilderData::new();
+    builder.append_raw(&[0xfb, 0xff, 0xbf, 0xfe, 0xef], 40).unwrap();
+    let cell = builder.into_cell().unwrap();
+    let boc = ton_dev_block::write_boc(&cell).unwrap();
+
+    let mut json = serde_json::Map::new();
+    for format in [
+        crate::Base64Format::Standard,
+        crate::Base64Format::StandardUnpadded,
+        crate::Base64Format::UrlSafe,
+        crate::Base64Format::UrlSafeUnpadded,
+    ] {
+        json.insert(format!("{:?}", format), crate::base64_encode_as(&boc, format).into());
+    }
+    json.insert("raw".to_string(), crate::base64_encode_as([0xfb, 0xff], crate::Base64Format::UrlSafeUnpadded).into());
+    json.insert("invalid".to_string(), "+/-_*AAA".into());
+    let map = PathMap::new(&json);
+    for name in ["Standard", "StandardUnpadded", "UrlSafe", "UrlSafeUnpadded"] {
+        let parsed = read_single_root_boc(map.get_base64(name).unwrap()).unwrap();
+        assert_eq!(parsed, cell, "{}", name);
+    }
+    assert_eq!(map.get_str("raw").unwrap(), "-_8");
+    assert_eq!(map.get_base64("raw").unwrap(), vec![0xfb, 0xff]);
+    check_err(map.get_base64("invalid"), "root/invalid must be the base64");
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_p40() {
+    let p40 = serde_json::json!({
+        "slashing_period_mc_blocks_count": 100,
+        "resend_mc_blocks_count": 4,
+        "min_samples_count": 30,
+        "collations_score_weight": 0,
+        "signing_score_weight": 1,
+        "min_slashing_protection_score": 70,
+        "z_param_numerator": 2326,
+        "z_param_denominator": 1000
+    });
+
+    let config = parse_config(serde_json::json!({}).as_object().unwrap()).unwrap();
+    assert!(!config.config_present(40).unwrap());
+    check_err(
+        parse_config_with_mandatory_params(serde_json::json!({}).as_object().unwrap(), &[40]),
+        "parameter p40 not found"
+    );
+
+    let config = parse_config(serde_json::json!({ "p40": p40 }).as_object().unwrap()).unwrap();
+    let param = serialize_config_param(&config, 40).unwrap();
+    assert_eq!(serde_json::from_str::<Value>(&param).unwrap()["p40"], p40);
+
+    let mut misspelled = p40.clone();
+    let value = misspelled.as_object_mut().unwrap().remove("min_samples_count").unwrap();
+    misspelled.as_object_mut().unwrap().insert("min_sample_count".to_string(), value);
+    check_err(
+        parse_config(serde_json::json!({ "p40": misspelled }).as_object().unwrap()),
+        "root/p40 must have the field `min_samples_count`"
+    );
+}
+
+#[test]
+fn test_get_grams_limits() {
+    let json = serde_json::json!({
+        "big": "18446744073709551616",
+        "max": ((1u128 << 120) - 1).to_string(),
+        "too_big": (1u128 << 120).to_string(),
+        "negative": "-5",
+        "negative_num": -5,
+        "float": 1.5,
+        "float_big": 18446744073709551616.0,
+    });
+    let map = PathMap::new(json.as_object().unwrap());
+    assert_eq!(map.get_grams("big").unwrap().as_u128(), u64::MAX as u128 + 1);
+    assert_eq!(map.get_grams("max").unwrap().as_u128(), (1u128 << 120) - 1);
+    check_err(map.get_grams("too_big"), "root/too_big must not exceed 2^120 - 1");
+    check_err(map.get_grams("negative"), "root/negative must not be negative");
+    check_err(map.get_grams("negative_num"), "root/negative_num must not be negative");
+    check_err(map.get_grams("float"), "root/float must be the integer");
+    check_err(map.get_grams("float_big"), "root/float_big must be the integer");
+}
+
+#[test]
+fn test_get_decimal_grams() {
+    let json = serde_json::json!({
+        "tokens": "1.5",
+        "nano": "0.000000001",
+        "separated": "1_000.25",
+        "too_precise": "1.1234567891",
+        "no_fraction": "1.",
+        "max": format!("{}.{:09}", ((1u128 << 120) - 1) / 1_000_000_000, ((1u128 << 120) - 1) % 1_000_000_000),
+        "too_big": format!("{}.0", (1u128 << 120) / 1_000_000_000 + 1),
+    });
+    let map = PathMap::new(json.as_object().unwrap()).with_decimal_grams(true);
+    assert_eq!(map.get_grams("tokens").unwrap().as_u128(), 1_500_000_000);
+    assert_eq!(map.get_grams("nano").unwrap().as_u128(), 1);
+    assert_eq!(map.get_grams("separated").unwrap().as_u128(), 1_000_250_000_000);
+    assert_eq!(map.get_grams("max").unwrap().as_u128(), (1u128 << 120) - 1);
+    check_err(
+        map.get_grams("too_precise"),
+        "root/too_precise must have at most 9 digits after the decimal point but 1.1234567891 given"
+    );
+    check_err(map.get_grams("no_fraction"), "root/no_fraction must be the integer or a string with the decimal");
+    check_err(map.get_grams("too_big"), "root/too_big must not exceed 2^120 - 1");
+
+    check_err(PathMap::new(json.as_object().unwrap()).get_grams("tokens"), "root/tokens must be the integer");
+
+    let config = serde_json::json!({
+        "p17": {
+            "min_stake": "10000",
+            "max_stake": "10000000.5",
+            "min_total_stake": "100000",
+            "max_stake_factor": 196608,
+        }
+    });
+    check_err(StateParser::new().parse_config_params(config.as_object().unwrap()), "root/p17/max_stake must be the integer");
+    let config = StateParser::new().with_decimal_grams(true).parse_config_params(config.as_object().unwrap()).unwrap();
+    match config.config(17).unwrap() {
+        Some(ConfigParamEnum::ConfigParam17(p17)) => {
+            assert_eq!(p17.min_stake.as_u128(), 10000);
+            assert_eq!(p17.max_stake.as_u128(), 10_000_000_500_000_000);
+        }
+        _ => panic!("p17 must be parsed")
+    }
+}
+
+#[test]
+fn test_get_u64_radix() {
+    let json = serde_json::json!({
+        "weight": "0xffffffffffffffff",
+        "weight_num": u64::MAX,
+        "capabilities": "0x2e",
+        "vm_mode": "7",
+        "big": "18446744073709551616",
+        "big_hex": "0x10000000000000000",
+        "negative": -1,
+        "wrong": "0xzz",
+    });
+    let map = PathMap::new(json.as_object().unwrap());
+    assert_eq!(map.get_u64_radix("weight").unwrap(), u64::MAX);
+    assert_eq!(map.get_u64_radix("weight_num").unwrap(), u64::MAX);
+    assert_eq!(map.get_u64_radix("capabilities").unwrap(), 0x2e);
+    assert_eq!(map.get_u64_radix("vm_mode").unwrap(), 7);
+    check_err(map.get_u64_radix("big"), "root/big must not exceed 2^64 - 1");
+    check_err(map.get_u64_radix("big_hex"), "root/big_hex must not exceed 2^64 - 1");
+    check_err(map.get_u64_radix("negative"), "root/negative must not be negative");
+    check_err(map.get_u64_radix("wrong"), "root/wrong must be the integer");
+    check_err(map.get_u64_radix("absent"), "root/absent must be the integer");
+}
+
+#[test]
+fn test_integer_floats() {
+    let json: Value = serde_json::from_str(r#"{
+        "gen_utime": 1713043200.0,
+        "gen_utime_exp": 1.7130432e9,
+        "fraction": 1.5,
+        "huge": 1e20,
+        "negative": -5.0
+    }"#).unwrap();
+    let map = PathMap::new(json.as_object().unwrap());
+    assert_eq!(map.get_num("gen_utime").unwrap(), 1713043200);
+    assert_eq!(map.get_num("gen_utime_exp").unwrap(), 1713043200);
+    assert_eq!(map.get_num("negative").unwrap(), -5);
+    check_err(map.get_num("fraction"), "root/fraction must be the integer : 1.5 can't be converted to the integer without precision loss");
+    check_err(map.get_num("huge"), "root/huge must be the integer : 1e20 can't be converted to the integer without precision loss");
+
+    assert_eq!(map.get_grams("gen_utime").unwrap().as_u128(), 1713043200);
+    check_err(map.get_grams("fraction"), "root/fraction must be the integer");
+    check_err(map.get_grams("huge"), "root/huge must be the integer");
+    check_err(map.get_grams("negative"), "root/negative must not be negative");
+
+    assert_eq!(json["gen_utime"].as_uint().unwrap(), 1713043200);
+    assert_eq!(json["negative"].as_long().unwrap(), -5);
+    assert!(json["fraction"].as_int().is_err());
+    assert!(json["huge"].as_ulong().is_err());
+    assert!(json["negative"].as_ulong().is_err());
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_currency_collection_forms() {
+    let mut expected = CurrencyCollection::with_grams(1000);
+    expected.set_other(239, 5000).unwrap();
+    expected.set_other(1, 7).unwrap();
+    let other_array = serde_json::json!([
+        { "currency": 239, "value": "5000" },
+        { "currency": 1, "value_dec": "7" },
+    ]);
+    for (name, json) in [
+        ("number", serde_json::json!({ "balance": 1000, "balance_other": other_array })),
+        ("string", serde_json::json!({ "balance": "1000", "balance_other": other_array })),
+        ("dec", serde_json::json!({ "balance": "03e8", "balance_dec": "1000", "balance_other": other_array })),
+        ("keyed", serde_json::json!({ "balance": 1000, "balance_other": { "239": "5000", "1": 7 } })),
+        ("object", serde_json::json!({ "balance": { "grams": 1000, "other": other_array } })),
+        ("object keyed", serde_json::json!({ "balance": { "grams_dec": "1000", "other": { "239": 5000, "1": "7" } } })),
+    ] {
+        let map = PathMap::new(json.as_object().unwrap());
+        assert_eq!(parse_currency_collection(&map, "balance").unwrap(), expected, "{}", name);
+    }
+
+    let json = serde_json::json!({ "balance": 1000 });
+    let map = PathMap::new(json.as_object().unwrap());
+    assert_eq!(parse_currency_collection(&map, "balance").unwrap(), CurrencyCollection::with_grams(1000));
+
+    let json = serde_json::json!({
+        "bad_key": 1, "bad_key_other": { "usd": "1" },
+        "bad_kind": 1, "bad_kind_other": "1",
+    });
+    let map = PathMap::new(json.as_object().unwrap());
+    check_err(parse_currency_collection(&map, "bad_key"), "root/bad_key_other keys must be the currency ids but usd given");
+    check_err(parse_currency_collection(&map, "bad_kind"), "root/bad_kind_other must be the array or the object");
+
+    for mode in [SerializationMode::Standart, SerializationMode::QServer, SerializationMode::Debug] {
+        let json = serde_json::json!({ "balance": crate::serialize_currency_collection(&expected, mode).unwrap() });
+        let map = PathMap::new(json.as_object().unwrap());
+        assert_eq!(parse_currency_collection(&map, "balance").unwrap(), expected);
+    }
+}
+
+#[test]
+fn test_parse_signature_forms() {
+    let bytes: Vec<u8> = (0..64).collect();
+    let json = serde_json::json!({
+        "pair": { "r": hex::encode(&bytes[..32]), "s": hex::encode(&bytes[32..]) },
+        "prefixed": { "signature_r": hex::encode(&bytes[..32]), "signature_s": hex::encode(&bytes[32..]) },
+        "hex": { "signature": hex::encode(&bytes) },
+        "base64": { "signature": base64_encode(&bytes) },
+        "short_hex": { "signature": hex::encode(&bytes[..63]) },
+        "short_base64": { "signature": base64_encode(&bytes[..48]) },
+        "short_pair": { "r": hex::encode(&bytes[..31]), "s": hex::encode(&bytes[32..]) },
+    });
+    let map = PathMap::new(json.as_object().unwrap());
+    for name in ["pair", "prefixed", "hex", "base64"] {
+        let signature = map.get_obj(name).unwrap().get_signature("signature").unwrap();
+        assert_eq!(signature.to_bytes().as_slice(), bytes.as_slice(), "{}", name);
+    }
+    check_err(
+        map.get_obj("short_hex").unwrap().get_signature("signature"),
+        "root/short_hex/signature must be the 64 bytes signature but hex form contains 63 bytes"
+    );
+    check_err(
+        map.get_obj("short_base64").unwrap().get_signature("signature"),
+        "root/short_base64/signature must be the 64 bytes signature but base64 form contains 48 bytes"
+    );
+    check_err(
+        map.get_obj("short_pair").unwrap().get_signature("signature"),
+        "root/short_pair/r must be the 32 bytes signature part but contains 31 bytes"
+    );
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_workchain_descr_defaults_and_flags() {
+    let json = serde_json::json!({
+        "workchain_id": 0,
+        "min_split": 2,
+        "max_split": 8,
+        "zerostate_root_hash": "55b13f6d0e1d0c34c9c2160f6f918e92d82bf9ddcf8de2e4c94a3fdf39d15446",
+        "zerostate_file_hash": "ee0bedfe4b32761fb35e9e1d8818ea720cad1a0e7b4d2ed673c488e72e910342",
+    });
+    let (_, descr) = parse_workchain_descr(&PathMap::new(json.as_object().unwrap())).unwrap();
+    assert_eq!(descr.format, WorkchainFormat::Basic(WorkchainFormat1::with_params(-1, 0)));
+    assert_eq!(descr.flags, 0);
+    assert!(descr.active && descr.accept_msgs);
+
+    let mut json = json;
+    json["flags"] = serde_json::json!(["bit0", "bit3"]);
+    let (_, descr) = parse_workchain_descr(&PathMap::new(json.as_object().unwrap())).unwrap();
+    assert_eq!(descr.flags, 0b1001);
+    let serialized = crate::serialize_workchain_descr(0, &descr);
+    assert_eq!(serialized["flags"], 9);
+    assert_eq!(serialized["flags_list"], serde_json::json!(["bit0", "bit3"]));
+    let (_, parsed) = parse_workchain_descr(&PathMap::new(serialized.as_object().unwrap())).unwrap();
+    assert_eq!(parsed, descr);
+
+    json["flags"] = serde_json::json!(["bit0", "split"]);
+    check_err(
+        parse_workchain_descr(&PathMap::new(json.as_object().unwrap())),
+        "root/flags contains unknown flag `split`"
+    );
+    json["flags"] = serde_json::json!(0);
+    json["vm_mode"] = serde_json::json!("zero");
+    check_err(
+        parse_workchain_descr(&PathMap::new(json.as_object().unwrap())),
+        "root/vm_mode must be the integer"
+    );
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_supported_config_params_in_sync() {
+    let mut cp = prepare_config_params();
+    let mut mesh_config = MeshConfig::default();
+    let block_id = BlockIdExt {
+        shard_id: ShardIdent::masterchain(),
+        seq_no: 0,
+        root_hash: UInt256::from([1; 32]),
+        file_hash: UInt256::from([2; 32]),
+    };
+    mesh_config.set(&1, &ConnectedNwConfig {
+        zerostate: block_id.clone(),
+        is_active: true,
+        currency_id: 1,
+        init_block: block_id,
+        emergency_guard_addr: UInt256::from([3; 32]),
+        pull_addr: UInt256::from([4; 32]),
+        minter_addr: UInt256::from([5; 32]),
+        hardforks: vec![],
+    }).unwrap();
+    cp.set_config(ConfigParamEnum::ConfigParam58(mesh_config)).unwrap();
+
+    let mut json = serde_json::Map::<String, Value>::new();
+    serialize_config(&mut json, &cp, SerializationMode::Standart).unwrap();
+    let config = json["config"].as_object().unwrap();
+    for number in supported_config_params() {
+        assert!(config.contains_key(&format!("p{}", number)), "p{} is missing in the synthetic config", number);
+    }
+
+    let parsed = parse_config(config).unwrap();
+    for number in 0..64 {
+        assert_eq!(
+            parsed.config_present(number).unwrap(),
+            is_supported_config_param(number),
+            "p{} support is out of sync", number
+        );
+    }
+}
+
+#[test]
+fn test_unsupported_config_params_are_not_modelled() {
+    // neither the mesh parameters next to p58 nor the collator parameters past p62 are
+    // ConfigParamEnum variants, there is nothing to parse or serialize besides the raw
+    // `unknown_config` entries
+    for number in (45..256).filter(|number| !is_supported_config_param(*number)) {
+        match ConfigParamEnum::construct_from_slice_and_number(&mut SliceData::default(), number).unwrap() {
+            ConfigParamEnum::ConfigParamAny(..) => (),
+            _ => panic!("p{} is modelled by ton_dev_block", number),
+        }
+    }
+}
+
+#[test]
+fn test_parse_block_proof_validator_index() {
+    let boc = include_bytes!("data/block_proof");
+    let file_hash = ton_dev_block::BlockProof::construct_from_bytes(boc).unwrap().proof_for.file_hash;
+    let mut json: Map<String, Value> = serde_json::from_str(include_str!("data/proof-ethalon.json")).unwrap();
+    let vset = get_validator_set();
+
+    let signatures = json["signatures"].as_array_mut().unwrap();
+    signatures[0].as_object_mut().unwrap().remove("node_id");
+    signatures[0]["validator_index"] = 1.into();
+    signatures[1].as_object_mut().unwrap().remove("node_id");
+    signatures[1]["validator_index"] = "0".into();
+    let parsed = parse_block_proof_with_validators(&json, file_hash.clone(), Some(&vset)).unwrap();
+
+    let mut by_node_id = json.clone();
+    let signatures = by_node_id["signatures"].as_array_mut().unwrap();
+    for (signature, index) in signatures.iter_mut().zip([1, 0]) {
+        let signature = signature.as_object_mut().unwrap();
+        signature.remove("validator_index");
+        signature.insert("node_id".to_string(), vset.list()[index].compute_node_id_short().as_hex_string().into());
+    }
+    assert_eq!(parse_block_proof(&by_node_id, file_hash.clone()).unwrap(), parsed);
+
+    check_err(
+        parse_block_proof(&json, file_hash.clone()),
+        "root/signatures/validator_index can't be resolved without the validator set"
+    );
+    json["signatures"][0]["validator_index"] = 2.into();
+    check_err(
+        parse_block_proof_with_validators(&json, file_hash.clone(), Some(&vset)),
+        "root/signatures/validator_index 2 is out of the validator set of 2 validators"
+    );
+    json["signatures"][0]["node_id"] = by_node_id["signatures"][0]["node_id"].clone();
+    check_err(
+        parse_block_proof_with_validators(&json, file_hash, Some(&vset)),
+        "root/signatures must have either `node_id` or `validator_index` but not both"
+    );
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_compute_validator_subset() {
+    let ethalon: Value = serde_json::from_str(include_str!(
+        "data/9C9906A80D020952E0192DC60C0B2BF1F55FE9A9E065606E8FE25C08BD1AA6B2-ethalon.json"
+    )).unwrap();
+    let config = parse_config(ethalon["master"]["config"].as_object().unwrap()).unwrap();
+    let vset = config.validator_set().unwrap();
+    let at = vset.utime_since();
+
+    // mc validators are not shuffled by p28 so the main ones are taken in order
+    let (validators, hash_short) = compute_validator_subset(&config, &ShardIdent::masterchain(), 1, at).unwrap();
+    assert_eq!(validators, vset.list()[..vset.main() as usize]);
+    let (_, other_hash_short) = compute_validator_subset(&config, &ShardIdent::masterchain(), 2, at).unwrap();
+    assert_ne!(hash_short, other_hash_short);
+
+    let shard = ShardIdent::with_tagged_prefix(0, 0x8000_0000_0000_0000).unwrap();
+    let (validators, hash_short) = compute_validator_subset(&config, &shard, 1, at).unwrap();
+    assert_eq!(validators.len(), 7);
+    let json = crate::serialize_validator_subset(&validators, hash_short, SerializationMode::Standart);
+    assert_eq!(json["validator_list_hash_short"], hash_short);
+    assert_eq!(json["list"].as_array().unwrap().len(), 7);
+
+    check_err(
+        compute_validator_subset(&config, &shard, 1, vset.utime_until()),
+        &format!("config has no validator set active at {}", vset.utime_until())
+    );
+
+    // the proof is signed by a later validator set
+    let boc = include_bytes!("data/block_proof");
+    let ethalon_proof = ton_dev_block::BlockProof::construct_from_bytes(boc).unwrap();
+    let json: Map<String, Value> = serde_json::from_str(include_str!("data/proof-ethalon.json")).unwrap();
+    check_err(
+        parse_block_proof_checked(&json, ethalon_proof.proof_for.file_hash.clone(), &config),
+        "config has no validator set active at 1586880800"
+    );
+
+    // the same proof signed by the subset of the validator set active at the block time
+    let gen_utime = json["gen_utime"].as_u64().unwrap() as u32;
+    let keys = (1..=4u8).map(|i| ton_dev_block::ed25519_create_private_key(&[i; 32]).unwrap()).collect::<Vec<_>>();
+    let list = keys.iter()
+        .map(|key| ValidatorDescr::with_params(SigPubKey::from_bytes(&key.verifying_key()).unwrap(), 10, None, None))
+        .collect::<Vec<_>>();
+    let mut config = config;
+    config.set_config(ConfigParamEnum::ConfigParam34(ton_dev_block::ConfigParam34 {
+        cur_validators: ValidatorSet::new(gen_utime - 100, gen_utime + 1000, 3, list).unwrap(),
+    })).unwrap();
+    let catchain_seqno = json["catchain_seqno"].as_u64().unwrap() as u32;
+    let (validators, hash_short) = compute_validator_subset(
+        &config, ethalon_proof.proof_for.shard(), catchain_seqno, gen_utime
+    ).unwrap();
+    assert_eq!(validators.len(), 3);
+
+    let data = ton_dev_block::Block::build_data_for_sign(
+        ethalon_proof.proof_for.root_hash(), ethalon_proof.proof_for.file_hash()
+    );
+    let signatures = validators.iter().map(|validator| {
+        let key = keys.iter()
+            .find(|key| key.verifying_key() == *validator.public_key.as_slice())
+            .unwrap();
+        let signature = key.sign(&data);
+        serde_json::json!({
+            "node_id": validator.compute_node_id_short().as_hex_string(),
+            "r": hex::encode(&signature[..32]),
+            "s": hex::encode(&signature[32..]),
+        })
+    }).collect::<Vec<_>>();
+    let mut json = json;
+    json.remove("sig_weight_dec");
+    json.insert("sig_weight".to_string(), 30.into());
+    json.insert("validator_list_hash_short".to_string(), hash_short.into());
+    json.insert("signatures".to_string(), signatures.clone().into());
+
+    let parsed = parse_block_proof_checked(&json, ethalon_proof.proof_for.file_hash.clone(), &config).unwrap();
+    assert_eq!(parsed.proof_for, ethalon_proof.proof_for);
+    assert_eq!(parsed.root, ethalon_proof.root);
+    let parsed_signatures = parsed.signatures.unwrap();
+    assert_eq!(parsed_signatures.validator_info.validator_list_hash_short, hash_short);
+    assert_eq!(parsed_signatures.validator_info.catchain_seqno, catchain_seqno);
+    assert_eq!(parsed_signatures.pure_signatures.count(), 3);
+
+    // two signatures of three are exactly 2/3 of the weight
+    json["signatures"].as_array_mut().unwrap().pop();
+    check_err(
+        parse_block_proof_checked(&json, ethalon_proof.proof_for.file_hash.clone(), &config),
+        "root/signatures have the weight 20 of 30 which is not more than 2/3"
+    );
+
+    // the set signing the block is p32 as p34 is not active yet,
+    // so `validator_index` refers to the validators of p32
+    let set = config.validator_set().unwrap();
+    let mut prev_config = config;
+    prev_config.set_config(ConfigParamEnum::ConfigParam32(ton_dev_block::ConfigParam32 {
+        prev_validators: set.clone(),
+    })).unwrap();
+    prev_config.set_config(ConfigParamEnum::ConfigParam34(ton_dev_block::ConfigParam34 {
+        cur_validators: ValidatorSet::new(gen_utime + 1000, gen_utime + 2000, 1, set.list()[3..].to_vec()).unwrap(),
+    })).unwrap();
+    let signatures = validators.iter().zip(signatures).map(|(validator, mut signature)| {
+        let index = set.list().iter().position(|v| v == validator).unwrap();
+        let signature = signature.as_object_mut().unwrap();
+        signature.remove("node_id");
+        signature.insert("validator_index".to_string(), index.into());
+        signature.clone()
+    }).collect::<Vec<_>>();
+    json.insert("signatures".to_string(), signatures.into());
+    let parsed = parse_block_proof_checked(&json, ethalon_proof.proof_for.file_hash.clone(), &prev_config).unwrap();
+    assert_eq!(parsed.signatures.unwrap().pure_signatures.count(), 3);
+}
+
+#[test]
+fn test_parse_block_proof_file_hash_sources() {
+    let boc = include_bytes!("data/block_proof");
+    let ethalon_proof = ton_dev_block::BlockProof::construct_from_bytes(boc).unwrap();
+    let file_hash = ethalon_proof.proof_for.file_hash.clone();
+    let json: Map<String, Value> = serde_json::from_str(include_str!("data/proof-ethalon.json")).unwrap();
+
+    // explicit argument
+    let parsed = parse_block_proof_auto(&json, Some(file_hash.clone())).unwrap();
+    assert_eq!(parsed, ethalon_proof);
+
+    // field in the document
+    let mut with_field = json.clone();
+    with_field.insert("file_hash".to_string(), file_hash.as_hex_string().into());
+    assert_eq!(parse_block_proof_auto(&with_field, None).unwrap(), ethalon_proof);
+    assert_eq!(parse_block_proof_auto(&with_field, Some(file_hash.clone())).unwrap(), ethalon_proof);
+
+    // block boc in the document
+    let block_boc = std::fs::read("src/tests/data/3FAFAAB7B5D17E439CD9DDEF7EEC3C3BC9D28E7C181BA374218E13599F8F6657.boc").unwrap();
+    let mut with_boc = json.clone();
+    with_boc.insert("block_boc".to_string(), base64_encode(&block_boc).into());
+    let parsed = parse_block_proof_auto(&with_boc, None).unwrap();
+    assert_eq!(parsed.proof_for.file_hash, UInt256::calc_file_hash(&block_boc));
+
+    check_err(
+        parse_block_proof_auto(&with_field, Some(UInt256::from([1; 32]))),
+        "root has disagreeing block file hashes"
+    );
+    with_boc.insert("file_hash".to_string(), file_hash.as_hex_string().into());
+    check_err(parse_block_proof_auto(&with_boc, None), "root has disagreeing block file hashes");
+    check_err(parse_block_proof_auto(&json, None), "root must have the field `file_hash` or `block_boc`");
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_state_with_skipped_defaults() {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let state = parse_state(&map).unwrap();
+
+    let mut value = Value::Object(map);
+    crate::skip_default_fields(&mut value, crate::DocumentKind::State);
+    let p12 = &value["master"]["config"]["p12"][0];
+    assert!(p12.get("flags").is_none() && p12.get("flags_list").is_none(), "{}", p12);
+    assert!(value["total_balance_other"].as_array().map_or(true, |other| !other.is_empty()));
+
+    let parsed = parse_state(value.as_object().unwrap()).unwrap();
+    assert_eq!(parsed, state);
+}
+
+#[test]
+fn test_dotted_path_reads() {
+    let json = serde_json::json!({
+        "master": {
+            "config": {
+                "p34": { "utime_until": 1000, "list": [] },
+                "name": "config",
+            },
+            "number": 5,
+        }
+    });
+    let map = PathMap::new(json.as_object().unwrap());
+    assert_eq!(map.get_path_num("master.config.p34.utime_until").unwrap(), 1000);
+    assert_eq!(map.get_path_num("master.number").unwrap(), 5);
+    assert_eq!(map.get_path_str("master.config.name").unwrap(), "config");
+    let p34 = map.get_path_obj("master.config.p34").unwrap();
+    assert_eq!(p34.get_num("utime_until").unwrap(), 1000);
+    check_err(p34.get_num("utime_since"), "root/master/config/p34/utime_since must be the integer");
+
+    check_err(
+        map.get_path_num("master.state.p34.utime_until"),
+        "root/master must have the field `state` (reading root/master/state/p34/utime_until)"
+    );
+    check_err(
+        map.get_path_num("master.number.value"),
+        "root/master/number must be the object (reading root/master/number/value)"
+    );
+    check_err(
+        map.get_path_obj("master.config.p34.list"),
+        "root/master/config/p34/list must be the object (reading root/master/config/p34/list)"
+    );
+    check_err(
+        map.get_path_str("master.config.p34.utime_until"),
+        "root/master/config/p34/utime_until must be the string"
+    );
+}
+
+#[test]
+fn test_null_is_absence() {
+    // optional workchain fields
+    let mut p12: Map<String, Value> = serde_json::from_str(include_str!("data/p12-config-param.json")).unwrap();
+    let ethalon = parse_config(&p12).unwrap();
+    let wc = p12["p12"][0].as_object_mut().unwrap();
+    for name in ["flags", "flags_list", "version", "active", "vm_mode"] {
+        wc.insert(name.to_string(), Value::Null);
+    }
+    assert_eq!(parse_config(&p12).unwrap(), ethalon);
+
+    // optional validator fields
+    let mut set = serde_json::json!({
+        "utime_since": 0, "utime_until": 100, "main": 1,
+        "list": [{
+            "public_key": "39MLqLIVrzLqPCHCFpbn1/jILSbfNMtnr/7zOkKE1Ds=",
+            "weight": 4,
+            "adnl_addr": null,
+            "bls_public_key": null,
+        }]
+    });
+    let config = serde_json::json!({ "p32": set.clone(), "p61": null });
+    let parsed = parse_config(config.as_object().unwrap()).unwrap();
+    assert!(parsed.config(61).unwrap().is_none());
+    let p32 = match parsed.config(32).unwrap() {
+        Some(ConfigParamEnum::ConfigParam32(p32)) => p32,
+        other => panic!("{:?}", other)
+    };
+    assert_eq!(p32.prev_validators.list()[0].adnl_addr, None);
+
+    // required fields
+    set["utime_since"] = Value::Null;
+    check_err(
+        parse_config(serde_json::json!({ "p32": set }).as_object().unwrap()),
+        "root/p32/utime_since must not be null"
+    );
+    let json = serde_json::json!({ "name": null, "grams": null });
+    let map = PathMap::new(json.as_object().unwrap());
+    check_err(map.get_str("name"), "root/name must not be null");
+    check_err(map.get_grams("grams"), "root/grams must not be null");
+    check_err(map.get_str("other"), "root must have the field `other`");
+    assert!(!map.has_field("name") && !map.has_field("other"));
+
+    // proof without signatures
+    let boc = include_bytes!("data/block_proof");
+    let ethalon_proof = ton_dev_block::BlockProof::construct_from_bytes(boc).unwrap();
+    let mut json: Map<String, Value> = serde_json::from_str(include_str!("data/proof-ethalon.json")).unwrap();
+    json.insert("signatures".to_string(), Value::Null);
+    let parsed = parse_block_proof(&json, ethalon_proof.proof_for.file_hash.clone()).unwrap();
+    assert!(parsed.signatures.is_none());
+    assert_eq!(parsed.proof_for, ethalon_proof.proof_for);
+
+    // raw master given as null is ignored
+    let mut state: Map<String, Value> = serde_json::from_str(
+        &std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap()
+    ).unwrap();
+    let ethalon_state = parse_state(&state).unwrap();
+    state.insert("master_boc".to_string(), Value::Null);
+    assert_eq!(parse_state(&state).unwrap(), ethalon_state);
+}
+
+#[test]
+fn test_parse_validator_set_boc() {
+    let set = serde_json::json!({
+        "utime_since": 10, "utime_until": 100, "main": 1,
+        "list": [{
+            "public_key": "39MLqLIVrzLqPCHCFpbn1/jILSbfNMtnr/7zOkKE1Ds=",
+            "weight": 4,
+        }]
+    });
+    let parsed = parse_config(serde_json::json!({ "p36": set.clone() }).as_object().unwrap()).unwrap();
+    let validators = match parsed.config(36).unwrap() {
+        Some(ConfigParamEnum::ConfigParam36(p36)) => p36.next_validators,
+        other => panic!("{:?}", other)
+    };
+    let boc = base64_encode(ton_dev_block::write_boc(&validators.serialize().unwrap()).unwrap());
+
+    for name in ["p32", "p33", "p34", "p35", "p36", "p37"] {
+        let config = serde_json::json!({ name: { "boc": boc } });
+        let from_boc = parse_config(config.as_object().unwrap()).unwrap();
+        let mut both = set.clone();
+        both["boc"] = boc.clone().into();
+        let config = serde_json::json!({ name: both });
+        let from_both = parse_config(config.as_object().unwrap()).unwrap();
+        assert_eq!(from_boc, from_both, "{}", name);
+        let from_fields = parse_config(serde_json::json!({ name: set }).as_object().unwrap()).unwrap();
+        assert_eq!(from_boc, from_fields, "{}", name);
+    }
+
+    let mut mismatch = set;
+    mismatch["utime_until"] = 200.into();
+    mismatch["boc"] = boc.into();
+    check_err(
+        parse_config(serde_json::json!({ "p34": mismatch }).as_object().unwrap()),
+        "root/p34/boc doesn't match the validator set fields"
+    );
+}
+
+#[cfg(feature = "serialize")]
+fn split_half(shard: u64, seq_no: u32) -> ShardStateUnsplit {
+    let mut state = ShardStateUnsplit::with_ident(ShardIdent::with_tagged_prefix(0, shard).unwrap());
+    state.set_seq_no(seq_no);
+    state.set_global_id(42);
+    state.set_gen_time(1_600_000_000);
+    state
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_state_split() {
+    let split = ShardStateSplit {
+        left: split_half(0x4000_0000_0000_0000, 10),
+        right: split_half(0xc000_0000_0000_0000, 11),
+    };
+    let json = crate::serialize_state_split(&split, SerializationMode::Standart).unwrap();
+    assert_eq!(json["left"]["shard"], "4000000000000000");
+    assert_eq!(json["right"]["shard"], "c000000000000000");
+    assert_eq!(parse_state_split(&json).unwrap(), split);
+
+    // the schema of parse_state_unchecked without bocs
+    let mut json = json;
+    for side in ["left", "right"] {
+        json[side].as_object_mut().unwrap().remove("boc");
+    }
+    let parsed = parse_state_split(&json).unwrap();
+    assert_eq!(parsed.left.shard(), split.left.shard());
+    assert_eq!(parsed.right.shard(), split.right.shard());
+    assert_eq!(parsed.right.seq_no(), 11);
+    assert_eq!(parsed.left.global_id(), 42);
+
+    let not_siblings = ShardStateSplit {
+        left: split_half(0x2000_0000_0000_0000, 10),
+        right: split_half(0xc000_0000_0000_0000, 11),
+    };
+    let json = crate::serialize_state_split(&not_siblings, SerializationMode::Standart).unwrap();
+    let err = parse_state_split(&json).expect_err("halves of different shards").to_string();
+    assert!(err.contains(&not_siblings.left.shard().to_string()), "{}", err);
+    assert!(err.contains(&not_siblings.right.shard().to_string()), "{}", err);
+
+    let swapped = ShardStateSplit { left: split.right.clone(), right: split.left.clone() };
+    let json = crate::serialize_state_split(&swapped, SerializationMode::Standart).unwrap();
+    check_err(parse_state_split(&json), "root/left");
+}
+
+#[test]
+fn test_separated_and_signed_numbers() {
+    let json = serde_json::json!({
+        "million": "1_000_000",
+        "plus": "+5",
+        "dec": "0x10",
+        "dec_dec": "+1_024",
+        "hex": "0x1_0",
+        "double": "1__0",
+        "trailing": "10_",
+        "plus_minus": "+-5",
+        "grams": "+1_000_000_000",
+    });
+    let map = PathMap::new(json.as_object().unwrap());
+    assert_eq!(map.get_num("million").unwrap(), 1_000_000);
+    assert_eq!(map.get_num("plus").unwrap(), 5);
+    assert_eq!(map.get_num("dec").unwrap(), 1024);
+    assert_eq!(map.get_grams("grams").unwrap().as_u128(), 1_000_000_000);
+    // underscores are not allowed in hex and must separate two digits
+    check_err(map.get_num("hex"), "root/hex must be the integer or a string with the integer 1_0");
+    check_err(map.get_num("double"), "root/double must be the integer or a string with the integer 1__0");
+    check_err(map.get_num("trailing"), "root/trailing must be the integer or a string with the integer 10_");
+    check_err(map.get_num("plus_minus"), "root/plus_minus must be the integer or a string with the integer +-5");
+    check_err(map.get_grams("double"), "root/double must be the integer or a string with the integer 1__0");
+}
+
+fn zerostate_config() -> Map<String, Value> {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    map["master"]["config"].as_object().unwrap().clone()
+}
+
+#[test]
+fn test_parse_config_one_pass_dispatch() {
+    let full = zerostate_config();
+    let ethalon = parse_config(&full).unwrap();
+
+    // the same parameters as set one by one from the full config
+    let mut three = Map::new();
+    let mut expected = ConfigParams::default();
+    for name in ["p1", "p15", "p34"] {
+        three.insert(name.to_string(), full[name].clone());
+        let num = name[1..].parse().unwrap();
+        expected.set_config(ethalon.config(num).unwrap().unwrap()).unwrap();
+    }
+    assert_eq!(parse_config(&three).unwrap(), expected);
+
+    // unknown and malformed keys are skipped
+    three.insert("p19".to_string(), serde_json::json!({}));
+    three.insert("p015".to_string(), serde_json::json!("garbage"));
+    three.insert("p64".to_string(), serde_json::json!("garbage"));
+    three.insert("other".to_string(), serde_json::json!("garbage"));
+    assert_eq!(parse_config(&three).unwrap(), expected);
+
+    // missing mandatory parameters are still reported in ascending order
+    check_err(parse_config_with_mandatory_params(&three, &[34, 2, 0]), "parameter p0 not found");
+    check_err(parse_config_with_mandatory_params(&three, &[34, 20, 15]), "parameter p20 not found");
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_p14_workchain_fees() {
+    let fees = |p14: Value| -> Result<ConfigParam14> {
+        let config = parse_config(serde_json::json!({ "p14": p14 }).as_object().unwrap())?;
+        match config.config(14)? {
+            Some(ConfigParamEnum::ConfigParam14(p14)) => Ok(p14),
+            other => panic!("{:?}", other)
+        }
+    };
+
+    // two field form, zero fee included
+    let p14 = fees(serde_json::json!({ "masterchain_block_fee": "1000", "basechain_block_fee": 0 })).unwrap();
+    assert_eq!(p14.block_create_fees.masterchain_block_fee, Grams::from(1000u64));
+    assert_eq!(p14.block_create_fees.basechain_block_fee, Grams::from(0u64));
+    let mut json = Map::new();
+    let mut cp = ConfigParams::default();
+    cp.set_config(ConfigParamEnum::ConfigParam14(p14.clone())).unwrap();
+    serialize_config(&mut json, &cp, SerializationMode::Standart).unwrap();
+    assert_eq!(fees(json["config"]["p14"].clone()).unwrap(), p14);
+
+    // workchain list form gives the same parameter
+    let listed = fees(serde_json::json!({ "workchain_fees": [
+        { "workchain_id": 0, "fee": 0 },
+        { "workchain_id": -1, "fee_dec": "1000" },
+    ]})).unwrap();
+    assert_eq!(listed, p14);
+
+    check_err(
+        fees(serde_json::json!({ "workchain_fees": [
+            { "workchain_id": 0, "fee": 1 }, { "workchain_id": 0, "fee": 2 },
+        ]})),
+        "root/p14/workchain_fees/workchain_id 0 is duplicated"
+    );
+    check_err(
+        fees(serde_json::json!({ "workchain_fees": [{ "workchain_id": 1, "fee": 1 }]})),
+        "root/p14/workchain_fees/workchain_id 1 is not supported"
+    );
+    check_err(
+        fees(serde_json::json!({ "workchain_fees": [], "basechain_block_fee": 1 })),
+        "root/p14 must not have both"
+    );
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_after_key_block() {
+    let after_key_block = |state: ShardStateUnsplit| state.read_custom().unwrap().unwrap().after_key_block;
+
+    // zero-state defaults to true
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    map["master"].as_object_mut().unwrap().remove("after_key_block");
+    assert!(after_key_block(parse_state(&map).unwrap()));
+    map["master"]["after_key_block"] = false.into();
+    assert!(!after_key_block(parse_state(&map).unwrap()));
+
+    // other states default to false
+    let mut map = serde_json::json!({ "master": { "config": {} } });
+    assert!(!after_key_block(parse_state_unchecked(map.as_object().unwrap()).unwrap()));
+    map["master"]["after_key_block"] = true.into();
+    assert!(after_key_block(parse_state_unchecked(map.as_object().unwrap()).unwrap()));
+
+    let last_key_block = ExtBlkRef {
+        end_lt: 1_000_001,
+        seq_no: 12,
+        root_hash: UInt256::from([1; 32]),
+        file_hash: UInt256::from([2; 32]),
+    };
+    map["master"]["last_key_block"] = crate::serialize::serialize_block_ref(
+        &last_key_block, None, SerializationMode::Standart
+    );
+    let state = parse_state_unchecked(map.as_object().unwrap()).unwrap();
+    assert_eq!(state.read_custom().unwrap().unwrap().last_key_block, Some(last_key_block));
+
+    // both are written back by the serializer
+    let json = crate::debug_state_full(state.clone()).unwrap();
+    let mut map = serde_json::from_str::<Map<String, Value>>(&json).unwrap();
+    assert_eq!(map["master"]["after_key_block"], true);
+    let parsed = parse_state_unchecked(&map).unwrap();
+    assert_eq!(parsed.read_custom().unwrap(), state.read_custom().unwrap());
+
+    map["master"]["last_key_block"]["seq_no"] = "twelve".into();
+    check_err(parse_state_unchecked(&map), "root/master/last_key_block/seq_no must be the integer");
+}
+
+#[test]
+fn test_config_builder() {
+    let config = ConfigBuilder::new()
+        .set_from_json(1, &serde_json::json!("3333333333333333333333333333333333333333333333333333333333333333")).unwrap()
+        .set_from_json(8, &serde_json::json!({"version": 5, "capabilities": "46"})).unwrap()
+        .set_from_json(14, &serde_json::json!({
+            "masterchain_block_fee": "1700000000",
+            "basechain_block_fee": "1000000000"
+        })).unwrap()
+        .set_param(ConfigParamEnum::ConfigParam0(get_config_param0())).unwrap()
+        .set_param(ConfigParamEnum::ConfigParam15(get_config_param15())).unwrap()
+        .mandatory(&[0, 1, 15])
+        .build()
+        .unwrap();
+
+    match config.config(0).unwrap() {
+        Some(ConfigParamEnum::ConfigParam0(p0)) => assert_eq!(p0, get_config_param0()),
+        other => panic!("{:?}", other)
+    }
+    match config.config(1).unwrap() {
+        Some(ConfigParamEnum::ConfigParam1(p1)) => assert_eq!(p1.elector_addr, UInt256::from([0x33; 32])),
+        other => panic!("{:?}", other)
+    }
+    match config.config(8).unwrap() {
+        Some(ConfigParamEnum::ConfigParam8(p8)) => assert_eq!(p8.global_version.version, 5),
+        other => panic!("{:?}", other)
+    }
+    match config.config(14).unwrap() {
+        Some(ConfigParamEnum::ConfigParam14(p14)) => {
+            assert_eq!(p14.block_create_fees.masterchain_block_fee, Grams::from(1700000000u64));
+        }
+        other => panic!("{:?}", other)
+    }
+    match config.config(15).unwrap() {
+        Some(ConfigParamEnum::ConfigParam15(p15)) => assert_eq!(p15, get_config_param15()),
+        other => panic!("{:?}", other)
+    }
+
+    // the same parameters as parse_config gives
+    let json = serde_json::json!({
+        "p1": "3333333333333333333333333333333333333333333333333333333333333333",
+        "p8": {"version": 5, "capabilities": "46"},
+    });
+    let parsed = parse_config(json.as_object().unwrap()).unwrap();
+    assert_eq!(parsed.config(8).unwrap(), config.config(8).unwrap());
+
+    check_err(
+        ConfigBuilder::new()
+            .set_param(ConfigParamEnum::ConfigParam0(get_config_param0())).unwrap()
+            .mandatory(&[0, 34])
+            .build(),
+        "parameter p34 not found"
+    );
+    check_err(
+        ConfigBuilder::new().set_from_json(15, &serde_json::json!({"validators_elected_for": 10})).map(|_| ()),
+        "root/p15 must have the field `elections_start_before`"
+    );
+    check_err(
+        ConfigBuilder::new().set_from_json(19, &serde_json::json!(1)).map(|_| ()),
+        "parameter p19 is not supported"
+    );
+}
+
+#[test]
+fn test_parse_state_account_address_mismatch() {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let (_, warnings) = StateParser::for_zero_state().parse_state_with_report(&map).unwrap();
+    assert!(warnings.is_empty(), "{:?}", warnings);
+
+    let accounts = map.get_mut("accounts").unwrap().as_array_mut().unwrap();
+    let other_id = accounts[0]["id"].clone();
+    let actual_id = accounts[1]["id"].as_str().unwrap().to_owned();
+    accounts[1]["id"] = other_id.clone();
+    check_err(
+        StateParser::for_zero_state().parse_state_unchecked(&map),
+        &format!("root/accounts item 1 declares id {} but the account has the address {}", other_id.as_str().unwrap(), actual_id)
+    );
+
+    let (state, warnings) = StateParser::for_zero_state()
+        .with_account_mismatch_as_warning(true)
+        .parse_state_with_report(&map)
+        .unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].path, "root/accounts");
+    assert!(warnings[0].message.starts_with("item 1 declares id"));
+    assert_eq!(state.read_accounts().unwrap().len().unwrap(), 4);
+
+    // the account id without the workchain is accepted too
+    let accounts = map.get_mut("accounts").unwrap().as_array_mut().unwrap();
+    accounts[1]["id"] = actual_id.split_once(':').unwrap().1.into();
+    accounts[1]["address"] = actual_id.clone().into();
+    accounts.push(serde_json::json!({ "boc": base64_encode(Account::default().write_to_bytes().unwrap()) }));
+    let (state, warnings) = StateParser::for_zero_state().parse_state_with_report(&map).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].message, "item 4 is AccountNone and is skipped");
+    assert_eq!(state.read_accounts().unwrap().len().unwrap(), 4);
+}
+
+#[test]
+fn test_parse_state_special_accounts() {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let expected = parse_state(&map).unwrap();
+
+    // p0 is taken from master.config_addr
+    map["master"]["config"]["p0"] = "auto".into();
+    assert_eq!(parse_state(&map).unwrap(), expected);
+    assert!(!crate::validate_state_json(&map).iter().any(|d| d.path == "root/master/config/p0"));
+    check_err(
+        parse_config(map["master"]["config"].as_object().unwrap()),
+        "root/p0 is `auto` but it can be taken only from `master.config_addr` of the state"
+    );
+    map["master"].as_object_mut().unwrap().remove("config_addr");
+    check_err(
+        StateParser::new().parse_state_unchecked(&map),
+        "root/master/config/p0 is `auto` but the config address can't be taken : root/master must have the field `config_addr`"
+    );
+
+    // the minter account is missing
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let minter = format!("-1:{}", "0".repeat(64));
+    map["accounts"].as_array_mut().unwrap().retain(|account| account["id"] != minter.as_str());
+    let (_, warnings) = StateParser::for_zero_state().parse_state_with_report(&map).unwrap();
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert_eq!(warnings[0].path, "root/master/config/p2");
+    assert_eq!(warnings[0].message, format!("the minter address {} has no account in root/accounts", minter));
+    assert_eq!(warnings[0].params, vec![2]);
+    check_err(
+        StateParser::for_zero_state()
+            .with_missing_special_accounts_as_error(true)
+            .parse_state_unchecked(&map),
+        "root/master/config/p2 : the minter address -1:0000"
+    );
+    // the states other than the zero-state are not checked
+    let (_, warnings) = StateParser::new().parse_state_with_report(&map).unwrap();
+    assert!(warnings.is_empty(), "{:?}", warnings);
+
+    // the streamed accounts are checked the same way
+    let (_, streamed) = StateParser::for_zero_state()
+        .parse_state_streaming_with_report(serde_json::to_string(&map).unwrap().as_bytes())
+        .unwrap();
+    assert_eq!(streamed.len(), 1, "{:?}", streamed);
+    assert_eq!(streamed[0].path, "root/master/config/p2");
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_state_shard_account_last_transaction() {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let account = map["accounts"][1].as_object_mut().unwrap();
+    let account_id = account["id"].as_str().unwrap().split_once(':').unwrap().1.parse::<UInt256>().unwrap();
+    account.insert("last_trans_hash".to_owned(), UInt256::from([7; 32]).as_hex_string().into());
+    account.insert("shard_last_trans_lt_dec".to_owned(), "1602000001".into());
+
+    let read_shard_account = |state: &ShardStateUnsplit| {
+        state.read_accounts().unwrap().account(&AccountId::from(account_id.clone())).unwrap().unwrap()
+    };
+    let state = parse_state(&map).unwrap();
+    let shard_account = read_shard_account(&state);
+    assert_eq!(shard_account.last_trans_hash(), &UInt256::from([7; 32]));
+    assert_eq!(shard_account.last_trans_lt(), 1602000001);
+
+    for mode in [SerializationMode::Standart, SerializationMode::QServer] {
+        let set = crate::ShardStateSerializationSet {
+            block_id: None,
+            workchain_id: state.shard().workchain_id(),
+            id: format!("{}", state.shard()),
+            boc: state.write_to_bytes().unwrap(),
+            state: state.clone(),
+        };
+        let json = crate::db_serialize_shard_state_ex("id", &set, mode).unwrap();
+        let parsed = parse_state(&json).unwrap();
+        assert_eq!(read_shard_account(&parsed), shard_account);
+        // the zero last transactions are not written
+        let accounts = json["accounts"].as_array().unwrap();
+        assert_eq!(accounts.iter().filter(|account| account.get("last_trans_hash").is_some()).count(), 1);
+    }
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_state_split_history() {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let default_state = parse_state(&map).unwrap();
+    assert_eq!(default_state.overload_history(), 0);
+    assert_eq!(default_state.underload_history(), 0);
+    assert!(!default_state.before_split());
+
+    map.insert("overload_history".to_owned(), "0xf000000000000001".into());
+    map.insert("underload_history_dec".to_owned(), "12345678901234567890".into());
+    map.insert("before_split".to_owned(), true.into());
+    let state = parse_state(&map).unwrap();
+    assert_eq!(state.overload_history(), 0xf000000000000001);
+    assert_eq!(state.underload_history(), 12345678901234567890);
+    assert!(state.before_split());
+
+    for mode in [SerializationMode::Standart, SerializationMode::QServer, SerializationMode::Debug] {
+        let set = crate::ShardStateSerializationSet {
+            block_id: None,
+            workchain_id: state.shard().workchain_id(),
+            id: format!("{}", state.shard()),
+            boc: state.write_to_bytes().unwrap(),
+            state: state.clone(),
+        };
+        let json = crate::db_serialize_shard_state_ex("id", &set, mode).unwrap();
+        let parsed = parse_state(&json).unwrap();
+        assert_eq!(parsed.overload_history(), state.overload_history());
+        assert_eq!(parsed.underload_history(), state.underload_history());
+        assert_eq!(parsed.before_split(), state.before_split());
+    }
+
+    map.insert("before_split".to_owned(), "yes".into());
+    check_err(parse_state(&map), "root/before_split must be");
+}
+
+#[test]
+fn test_parse_state_expected_hashes() {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let account_hash = |index: usize, map: &Map<String, Value>| {
+        let boc = base64_decode(map["accounts"][index]["boc"].as_str().unwrap()).unwrap();
+        read_single_root_boc(&boc).unwrap().repr_hash()
+    };
+    let (hash1, hash2) = (account_hash(1, &map), account_hash(2, &map));
+    map["accounts"][1]["expected_hash"] = hash1.as_hex_string().into();
+    map["accounts"][2]["hash"] = hash2.as_hex_string().to_uppercase().into();
+    let state = parse_state(&map).unwrap();
+    let expected = parse_state(&serde_json::from_str(&ethalon).unwrap()).unwrap();
+    assert_eq!(state.write_to_bytes().unwrap(), expected.write_to_bytes().unwrap());
+
+    // the hash of the other account stands for the corrupted boc
+    map["accounts"][1]["expected_hash"] = hash2.as_hex_string().into();
+    check_err(
+        parse_state(&map),
+        &format!(
+            "root/accounts item 1 has the expected_hash {} but the account cell hash is {}",
+            hash2.as_hex_string(), hash1.as_hex_string()
+        )
+    );
+
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    map["libraries"][0]["hash"] = UInt256::from([1; 32]).as_hex_string().into();
+    check_err(
+        parse_state(&map),
+        &format!("root/libraries item 0 has the hash {} but the lib cell hash is", UInt256::from([1; 32]).as_hex_string())
+    );
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_parse_state_parallel_accounts() {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let accounts = map.get_mut("accounts").unwrap().as_array_mut().unwrap();
+    for i in 0..1000u32 {
+        let mut address = [0; 32];
+        address[..4].copy_from_slice(&i.to_be_bytes());
+        let address = MsgAddressInt::with_standart(None, 0, AccountId::from(address)).unwrap();
+        let account = Account::with_address_and_ballance(&address, &CurrencyCollection::with_grams(i as u64 + 1));
+        accounts.push(serde_json::json!({ "boc": base64_encode(account.write_to_bytes().unwrap()) }));
+    }
+    accounts.push(serde_json::json!({ "boc": base64_encode(Account::default().write_to_bytes().unwrap()) }));
+
+    let (sequential, sequential_warnings) = StateParser::for_zero_state().parse_state_with_report(&map).unwrap();
+    let (parallel, parallel_warnings) = StateParser::for_zero_state()
+        .with_parallel_accounts(true)
+        .parse_state_with_report(&map)
+        .unwrap();
+    assert_eq!(sequential.read_accounts().unwrap().len().unwrap(), 1004);
+    assert_eq!(
+        parallel.read_accounts().unwrap().data().map(|root| root.repr_hash()),
+        sequential.read_accounts().unwrap().data().map(|root| root.repr_hash())
+    );
+    assert_eq!(parallel.serialize().unwrap().repr_hash(), sequential.serialize().unwrap().repr_hash());
+    assert_eq!(parallel_warnings, sequential_warnings);
+
+    // the errors are reported the same way
+    let accounts = map.get_mut("accounts").unwrap().as_array_mut().unwrap();
+    accounts[500]["boc"] = "te6c".into();
+    assert!(StateParser::for_zero_state().with_parallel_accounts(true).parse_state_unchecked(&map).is_err());
+}
+
+#[test]
+fn test_parse_state_streaming() {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let accounts = map.get_mut("accounts").unwrap().as_array_mut().unwrap();
+    for i in 0..50_000u32 {
+        let mut address = [0; 32];
+        address[..4].copy_from_slice(&i.to_be_bytes());
+        let address = MsgAddressInt::with_standart(None, -1, AccountId::from(address)).unwrap();
+        let account = Account::with_address_and_ballance(&address, &CurrencyCollection::with_grams(i as u64 + 1));
+        accounts.push(serde_json::json!({ "boc": base64_encode(account.write_to_bytes().unwrap()) }));
+    }
+    accounts.push(serde_json::json!({ "boc": base64_encode(Account::default().write_to_bytes().unwrap()) }));
+    let text = serde_json::to_string(&map).unwrap();
+
+    // the accounts `Value`s are dropped one by one, so the memory is about the size of the state
+    // instead of the size of the document tree
+    let (expected, expected_warnings) = StateParser::for_zero_state().parse_state_with_report(&map).unwrap();
+    drop(map);
+    let (streamed, warnings) = StateParser::for_zero_state()
+        .parse_state_streaming_with_report(text.as_bytes())
+        .unwrap();
+    assert_eq!(streamed.read_accounts().unwrap().len().unwrap(), 50_004);
+    assert_eq!(streamed.serialize().unwrap().repr_hash(), expected.serialize().unwrap().repr_hash());
+    assert_eq!(warnings, expected_warnings);
+    assert_eq!(parse_state_streaming(ethalon.as_bytes()).unwrap(), parse_state(&serde_json::from_str(&ethalon).unwrap()).unwrap());
+
+    // the limits and the errors are the same
+    let limits = ParseLimits { max_accounts: 10, ..Default::default() };
+    check_err(
+        StateParser::for_zero_state().with_limits(limits).parse_state_streaming(text.as_bytes()),
+        "root/accounts exceeds the max_accounts limit of 10",
+    );
+    let accounts_at = ethalon.find("\"accounts\"").unwrap();
+    let broken = format!(
+        "{}{}", &ethalon[..accounts_at], ethalon[accounts_at..].replacen("\"boc\": \"", "\"boc\": \"AAAA", 1)
+    );
+    let err = parse_state_streaming(broken.as_bytes()).unwrap_err().to_string();
+    assert!(err.contains("at line"), "{}", err);
+    check_err(parse_state_streaming("[]".as_bytes()), "root must be the JSON object");
+}
+
+#[test]
+fn test_parse_state_expanded_accounts() {
+    let ethalon = std::fs::read_to_string(
+        "src/tests/data/states/main_ever_dev_zerostate_-1_D270B87B2952B5BA7DAA70AAF0A8C361BEFCF4D8D2DB92F9640D5443070838E4-ethalon.json"
+    ).unwrap();
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let expected = parse_state_unchecked(&map).unwrap().serialize().unwrap();
+
+    // special accounts: the config contract has tock, others tick and tock
+    let mut expanded = 0;
+    for account in map.get_mut("accounts").unwrap().as_array_mut().unwrap() {
+        let account = account.as_object_mut().unwrap();
+        if account.contains_key("tick") {
+            account.remove("boc");
+            expanded += 1;
+        }
+    }
+    assert_eq!(expanded, 3);
+    let parsed = parse_state_unchecked(&map).unwrap();
+    assert_eq!(parsed.serialize().unwrap().repr_hash(), expected.repr_hash());
+
+    // the documents may name the id `_key` or `_id`
+    let mut renamed = map.clone();
+    for (account, field) in renamed["accounts"].as_array_mut().unwrap().iter_mut()
+        .filter(|account| account.get("boc").is_none())
+        .zip(["_key", "_id", "id"])
+    {
+        let account = account.as_object_mut().unwrap();
+        let id = account.remove("id").unwrap();
+        account.insert(field.to_owned(), id);
+    }
+    assert_eq!(parse_state_unchecked(&renamed).unwrap().serialize().unwrap().repr_hash(), expected.repr_hash());
+
+    let config_addr = AccountId::from([0x55; 32]);
+    let account = parsed.read_accounts().unwrap()
+        .account(&config_addr).unwrap().unwrap()
+        .read_account().unwrap();
+    let special = account.state_init().unwrap().special().unwrap();
+    assert!(!special.tick);
+    assert!(special.tock);
+
+    // the init code hash must be the hash of the account code
+    let code_hash = account.get_code().unwrap().repr_hash();
+    let config_id = format!("-1:{}", "5".repeat(64));
+    let position = map["accounts"].as_array().unwrap().iter()
+        .position(|account| account["id"] == config_id.as_str())
+        .unwrap();
+    map["accounts"][position]["init_code_hash"] = code_hash.as_hex_string().into();
+    let account = parse_state_unchecked(&map).unwrap().read_accounts().unwrap()
+        .account(&config_addr).unwrap().unwrap()
+        .read_account().unwrap();
+    assert_eq!(account.init_code_hash(), Some(&code_hash));
+    map["accounts"][position]["init_code_hash"] = UInt256::default().as_hex_string().into();
+    check_err(
+        parse_state_unchecked(&map),
+        &format!("root/accounts/init_code_hash {} doesn't match the code hash {}",
+            UInt256::default().as_hex_string(), code_hash.as_hex_string())
+    );
+    map["accounts"][position].as_object_mut().unwrap().remove("init_code_hash");
+
+    let account = map["accounts"].as_array_mut().unwrap().iter_mut()
+        .find(|account| account.get("boc").is_none())
+        .unwrap();
+    account["acc_type"] = 2.into();
+    check_err(
+        parse_state_unchecked(&map),
+        "root/accounts/acc_type must be 1 (active) for the account without boc but 2 given"
+    );
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_complaint_pricing() {
+    let boc = "te6ccgEBAQEADQAAFRpRdIdugAEBIB9I";
+    let from_boc = parse_config(serde_json::json!({ "p13": { "boc": boc } }).as_object().unwrap()).unwrap();
+    let structured = serde_json::json!({
+        "deposit": "100000000000",
+        "bit_price": "1",
+        "cell_price": "500",
+    });
+    let from_fields = parse_config(serde_json::json!({ "p13": structured }).as_object().unwrap()).unwrap();
+    assert_eq!(from_fields.config(13).unwrap(), from_boc.config(13).unwrap());
+
+    let param = serialize_config_param(&from_fields, 13).unwrap();
+    let p13 = &serde_json::from_str::<Value>(&param).unwrap()["p13"];
+    assert_eq!(p13["deposit_dec"], "100000000000");
+    assert_eq!(p13["bit_price_dec"], "1");
+    assert_eq!(p13["cell_price_dec"], "500");
+    assert_eq!(p13["boc"], boc);
+    // the serialized form with both the fields and the boc is parsed back
+    let parsed = parse_config(serde_json::json!({ "p13": p13 }).as_object().unwrap()).unwrap();
+    assert_eq!(parsed.config(13).unwrap(), from_boc.config(13).unwrap());
+
+    // any cell is accepted as the boc, no fields are serialized for it
+    let cell = BuilderData::with_raw(vec![0x55], 8).unwrap().into_cell().unwrap();
+    let other = base64_encode(write_boc(&cell).unwrap());
+    let config = parse_config(serde_json::json!({ "p13": { "boc": other } }).as_object().unwrap()).unwrap();
+    let param = serialize_config_param(&config, 13).unwrap();
+    assert_eq!(serde_json::from_str::<Value>(&param).unwrap()["p13"], serde_json::json!({ "boc": other }));
+
+    let mut mismatch = structured;
+    mismatch["boc"] = other.into();
+    check_err(
+        parse_config(serde_json::json!({ "p13": mismatch }).as_object().unwrap()),
+        "root/p13/boc doesn't match deposit, bit_price and cell_price"
+    );
+    check_err(
+        parse_config(serde_json::json!({ "p13": { "deposit": "1" } }).as_object().unwrap()),
+        "root/p13/bit_price must be the integer or a string with the integer"
+    );
+}
+
+#[test]
+fn test_parse_state_config_boc() {
+    let ethalon = std::fs::read_to_string(
+        "src/tests/data/states/main_ever_dev_zerostate_-1_D270B87B2952B5BA7DAA70AAF0A8C361BEFCF4D8D2DB92F9640D5443070838E4-ethalon.json"
+    ).unwrap();
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let expected = parse_state(&map).unwrap();
+    let config = expected.read_custom().unwrap().unwrap().config;
+    let config_boc = base64_encode(write_boc(config.config_params.data().unwrap()).unwrap());
+
+    let master = map["master"].as_object_mut().unwrap();
+    master.insert("config_boc".to_string(), config_boc.into());
+    let parsed = parse_state(&map).unwrap();
+    assert_eq!(parsed.serialize().unwrap().repr_hash(), expected.serialize().unwrap().repr_hash());
+
+    // boc only
+    let config_json = map["master"].as_object_mut().unwrap().remove("config").unwrap();
+    let parsed = parse_state(&map).unwrap();
+    assert_eq!(parsed.serialize().unwrap().repr_hash(), expected.serialize().unwrap().repr_hash());
+
+    let mut config_json = config_json;
+    config_json["p1"] = "4444444444444444444444444444444444444444444444444444444444444444".into();
+    map["master"]["config"] = config_json;
+    check_err(parse_state(&map), "root/master/config_boc doesn't match root/master/config");
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_global_version_round_trip() {
+    let boc = std::fs::read("src/tests/data/046784ea72574ace66375629229700afa4c7e032a360fc94df4c20231fddea45.boc").unwrap();
+    let block = Block::construct_from_bytes(&boc).unwrap();
+    let info = block.read_info().unwrap();
+    let gen_software = match info.gen_software() {
+        Some(gs) => GlobalVersion { version: gs.version, capabilities: gs.capabilities },
+        None => panic!("the block must have gen_software")
+    };
+    assert_ne!(gen_software.capabilities, 0);
+    let known = GLOBAL_CAPABILITIES.iter().fold(0, |mask, (_, bit)| mask | bit);
+
+    for mode in [SerializationMode::Standart, SerializationMode::QServer, SerializationMode::Debug] {
+        let mut json = crate::serialize_global_version(&gen_software, mode);
+        let parsed = parse_global_version(&PathMap::new(&json)).unwrap();
+        assert_eq!(parsed, gen_software);
+
+        // by the names only
+        json.remove("capabilities");
+        json.remove("capabilities_dec");
+        let parsed = parse_global_version(&PathMap::new(&json)).unwrap();
+        assert_eq!(parsed.version, gen_software.version);
+        assert_eq!(parsed.capabilities, gen_software.capabilities & known);
+    }
+}
+
+#[test]
+fn test_get_bool_lenient() {
+    let json = serde_json::json!({
+        "bool": true,
+        "string": "TRUE",
+        "string_false": "False",
+        "one": 1,
+        "zero": 0,
+        "two": 2,
+        "yes": "yes",
+    });
+    let map = PathMap::new(json.as_object().unwrap());
+    assert!(map.get_bool_lenient("bool").unwrap());
+    assert!(map.get_bool_lenient("string").unwrap());
+    assert!(!map.get_bool_lenient("string_false").unwrap());
+    assert!(map.get_bool_lenient("one").unwrap());
+    assert!(!map.get_bool_lenient("zero").unwrap());
+    check_err(map.get_bool_lenient("two"), "root/two must be boolean, \"true\", \"false\", 0 or 1 but 2 given");
+    check_err(map.get_bool_lenient("yes"), "root/yes must be boolean, \"true\", \"false\", 0 or 1 but \"yes\" given");
+    assert_eq!(map.get_bool_lenient_opt("absent").unwrap(), None);
+
+    // in the config
+    let config = serde_json::json!({
+        "p28": {
+            "shuffle_mc_validators": "true",
+            "isolate_mc_validators": 0,
+            "mc_catchain_lifetime": 250,
+            "shard_catchain_lifetime": 250,
+            "shard_validators_lifetime": 1000,
+            "shard_validators_num": 7
+        }
+    });
+    let config = parse_config(config.as_object().unwrap()).unwrap();
+    match config.config(28).unwrap() {
+        Some(ConfigParamEnum::ConfigParam28(p28)) => {
+            assert!(p28.shuffle_mc_validators);
+            assert!(!p28.isolate_mc_validators);
+        }
+        other => panic!("{:?}", other)
+    }
+    let mut json = serde_json::json!({
+        "workchain_id": 0, "enabled_since": 0, "actual_min_split": 0, "min_split": 2, "max_split": 32,
+        "active": "false", "accept_msgs": 1, "basic": "TRUE", "flags": 0, "version": 0,
+        "zerostate_root_hash": "0000000000000000000000000000000000000000000000000000000000000000",
+        "zerostate_file_hash": "0000000000000000000000000000000000000000000000000000000000000000",
+    });
+    let (_, descr) = parse_workchain_descr(&PathMap::new(json.as_object().unwrap())).unwrap();
+    assert!(!descr.active);
+    assert!(descr.accept_msgs);
+    json["accept_msgs"] = "yes".into();
+    check_err(
+        parse_workchain_descr(&PathMap::new(json.as_object().unwrap())).map(|_| ()),
+        "root/accept_msgs must be boolean"
+    );
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_catchain_and_consensus_config() {
+    let mut cp = ConfigParams::default();
+    let mut c28 = get_cat_chain_config();
+    c28.isolate_mc_validators = true;
+    cp.set_config(ConfigParamEnum::ConfigParam28(c28.clone())).unwrap();
+    cp.set_config(ConfigParamEnum::ConfigParam29(get_config_param29())).unwrap();
+    let mut json = serde_json::Map::<String, Value>::new();
+    serialize_config(&mut json, &cp, SerializationMode::Standart).unwrap();
+    let config = json["config"].as_object().unwrap();
+    let parsed = parse_config(config).unwrap();
+    for num in [28, 29] {
+        assert_eq!(parsed.config(num).unwrap(), cp.config(num).unwrap());
+    }
+    assert_eq!(parsed.config_params.data(), cp.config_params.data());
+
+    // the older dumps have no `isolate_mc_validators`
+    let mut p28 = config["p28"].clone();
+    p28.as_object_mut().unwrap().remove("isolate_mc_validators");
+    let parsed = parse_config(serde_json::json!({ "p28": p28 }).as_object().unwrap()).unwrap();
+    c28.isolate_mc_validators = false;
+    assert_eq!(parsed.config(28).unwrap(), Some(ConfigParamEnum::ConfigParam28(c28)));
+
+    p28["isolate_mc_validators"] = "yes".into();
+    check_err(
+        parse_config(serde_json::json!({ "p28": p28 }).as_object().unwrap()),
+        "root/p28/isolate_mc_validators must be boolean, \"true\", \"false\", 0 or 1 but \"yes\" given"
+    );
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_fundamental_smc_addresses() {
+    let a = UInt256::from([0xaa; 32]);
+    let b = UInt256::from([0xbb; 32]);
+    let c = UInt256::from([0x3c; 32]);
+    let config = serde_json::json!({
+        "p31": [
+            format!("-1:{}", a.as_hex_string()),
+            b.as_hex_string().to_uppercase(),
+            c.as_hex_string(),
+            c.as_hex_string(),
+        ]
+    });
+    let config = parse_config(config.as_object().unwrap()).unwrap();
+    let p31 = match config.config(31).unwrap() {
+        Some(ConfigParamEnum::ConfigParam31(p31)) => p31,
+        other => panic!("{:?}", other)
+    };
+    let mut addresses = Vec::new();
+    p31.fundamental_smc_addr.iterate_keys(|key: UInt256| {
+        addresses.push(key);
+        Ok(true)
+    }).unwrap();
+    assert_eq!(addresses, vec![c.clone(), a.clone(), b.clone()]);
+
+    // the serializer writes the bare lowercase hex
+    let json = serialize_config_param(&config, 31).unwrap();
+    let expected = serde_json::json!({
+        "p31": [c.as_hex_string(), a.as_hex_string(), b.as_hex_string()]
+    });
+    assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), expected);
+
+    let config = serde_json::json!({ "p31": [c.as_hex_string(), format!("-1:{}", c.as_hex_string())] });
+    check_err(
+        parse_config(config.as_object().unwrap()),
+        &format!("root/p31 has the address {} twice", c.as_hex_string())
+    );
+    let config = serde_json::json!({ "p31": [format!("0:{}", a.as_hex_string())] });
+    check_err(
+        parse_config(config.as_object().unwrap()),
+        "root/p31 must have the masterchain addresses but"
+    );
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_out_msg_queue() {
+    let read_transaction = |file: &str| {
+        let boc = std::fs::read(format!("src/tests/data/transactions/{}", file)).unwrap();
+        Transaction::construct_from_bytes(&boc).unwrap()
+    };
+    let in_msg = read_transaction("int_in.boc").in_msg_cell().unwrap();
+    let out_msg = read_transaction("ext_in&int_out.boc").get_out_msg(0).unwrap().unwrap();
+    let out_msg = out_msg.serialize().unwrap();
+    let envelope = MsgEnvelope::with_routing(
+        out_msg.clone(),
+        1_000_000u64.into(),
+        IntermediateAddress::Regular(IntermediateAddressRegular::with_use_src_bits(0).unwrap()),
+        IntermediateAddress::Simple(IntermediateAddressSimple::with_addr(0, 0x8000000000000000)),
+    );
+    let map = serde_json::json!({
+        "out_queue": [{
+            "msg": base64_encode(write_boc(&in_msg).unwrap()),
+            "lt": "0x1f",
+        }, {
+            "envelope": base64_encode(write_boc(&envelope.serialize().unwrap()).unwrap()),
+            "lt_dec": "1000001",
+        }],
+        "proc_info": [{
+            "shard": "8000000000000000",
+            "mc_seqno": 100,
+            "last_msg_lt": 1000001,
+            "last_msg_hash": in_msg.repr_hash().as_hex_string(),
+        }, {
+            "shard": "4000000000000000",
+            "mc_seqno": 99,
+            "last_msg_lt": "0x10",
+            "last_msg_hash": out_msg.repr_hash().as_hex_string(),
+        }],
+    });
+    let info = parse_out_msg_queue(map.as_object().unwrap()).unwrap();
+    assert_eq!(info.out_queue().len().unwrap(), 2);
+    assert_eq!(info.proc_info().len().unwrap(), 2);
+    assert!(info.ihr_pending().is_empty());
+
+    for mode in [SerializationMode::Standart, SerializationMode::QServer] {
+        let json = crate::serialize_out_msg_queue(&info, mode).unwrap();
+        let parsed = parse_out_msg_queue(&json).unwrap();
+        assert_eq!(parsed.serialize().unwrap(), info.serialize().unwrap());
+        let envelope_item = json["out_queue"].as_array().unwrap().iter()
+            .find(|item| item["msg_id"] == out_msg.repr_hash().as_hex_string())
+            .unwrap();
+        assert_eq!(envelope_item["next_addr"], "0:8000000000000000");
+    }
+
+    let mut map = map;
+    map["out_queue"][0]["envelope"] = map["out_queue"][1]["envelope"].clone();
+    check_err(
+        parse_out_msg_queue(map.as_object().unwrap()),
+        "root/out_queue must not have both fields `envelope` and `msg`"
+    );
+    map["out_queue"][0]["envelope"] = Value::Null;
+    map["proc_info"][1]["shard"] = "shard".into();
+    check_err(
+        parse_out_msg_queue(map.as_object().unwrap()),
+        "root/proc_info/shard must be the shard prefix in hex format"
+    );
+}
+
+#[test]
+fn test_parse_state_limits() {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    let accounts = map["accounts"].as_array().unwrap().len();
+    let parse = |map: &Map<String, Value>, limits: ParseLimits| {
+        StateParser::for_zero_state().with_limits(limits).parse_state_unchecked(map)
+    };
+    let state = parse(&map, ParseLimits::default()).unwrap();
+    assert_eq!(state.write_to_bytes().unwrap(), parse_state(&map).unwrap().write_to_bytes().unwrap());
+
+    let limits = ParseLimits { max_accounts: accounts - 1, ..Default::default() };
+    check_err(
+        parse(&map, limits),
+        &format!("root/accounts exceeds the max_accounts limit of {}: {} items given", accounts - 1, accounts)
+    );
+    assert!(parse(&map, ParseLimits { max_accounts: accounts, ..Default::default() }).is_ok());
+
+    let err = parse(&map, ParseLimits { max_boc_bytes: 100, ..Default::default() }).unwrap_err().to_string();
+    assert!(err.contains("exceeds the max_boc_bytes limit of 100: "), "{}", err);
+
+    check_err(
+        parse(&map, ParseLimits { max_json_nodes: 1000, ..Default::default() }),
+        "root exceeds the max_json_nodes limit of 1000: 1001 nodes counted"
+    );
+    let config = map["master"]["config"].as_object().unwrap();
+    check_err(
+        StateParser::new().with_limits(ParseLimits { max_json_nodes: 10, ..Default::default() })
+            .parse_config_params(config),
+        "root exceeds the max_json_nodes limit of 10: 11 nodes counted"
+    );
+
+    // the items are counted before any of them is read
+    map.insert("libraries".to_owned(), serde_json::json!([{}, {}]));
+    check_err(
+        parse(&map, ParseLimits { max_libraries: 1, ..Default::default() }),
+        "root/libraries exceeds the max_libraries limit of 1: 2 items given"
+    );
+}
+
+#[test]
+fn test_path_map_unvisited_keys() {
+    let json = serde_json::json!({
+        "read": 1,
+        "extra": 2,
+        "obj": { "read": "a", "extra": "b" },
+        "whole": { "inner": true },
+        "list": [{ "read": 1 }, { "read": 2, "extra": 3 }],
+    });
+    let map = json.as_object().unwrap();
+    let read = |map_path: &PathMap| {
+        map_path.get_num("read").unwrap();
+        map_path.get_obj("obj").unwrap().get_str("read").unwrap();
+        map_path.get_item("whole").unwrap();
+        for item in map_path.get_vec("list").unwrap() {
+            PathMap::cont(map_path, "list", item).unwrap().get_num("read").unwrap();
+        }
+        // absent fields are not reported
+        assert!(!map_path.has_field("absent"));
+    };
+
+    let tracked = PathMap::track(map);
+    read(&tracked);
+    assert_eq!(tracked.unvisited_keys(), ["root/extra", "root/obj/extra", "root/list/1/extra"]);
+
+    let untracked = PathMap::new(map);
+    read(&untracked);
+    assert!(untracked.unvisited_keys().is_empty());
+}
+
+#[test]
+fn test_parse_strict_fields() {
+    let json = serde_json::json!({
+        "config": {
+            "p15": {
+                "validators_elected_for": 14400,
+                "elections_start_before": 7200,
+                "elections_end_before": 1800,
+                "stake_held_for": 7200,
+            }
+        }
+    });
+    let mut config = json.as_object().unwrap().clone();
+    StateParser::new().with_strict_fields(true).parse_config_params(&config).unwrap();
+    config["config"]["p15"].as_object_mut().unwrap().insert("stake_hold_for".to_owned(), 7200.into());
+    StateParser::new().parse_config_params(&config).unwrap();
+    check_err(
+        StateParser::new().with_strict_fields(true).parse_config_params(&config),
+        "the fields are not read by the parser: root/config/p15/stake_hold_for"
+    );
+
+    let json = serde_json::json!({ "global_id": 42, "gen_utime": 1 });
+    let mut state = json.as_object().unwrap().clone();
+    StateParser::new().with_strict_fields(true).parse_state_unchecked(&state).unwrap();
+    state.insert("gen_lt".to_owned(), 5.into());
+    check_err(
+        StateParser::new().with_strict_fields(true).parse_state_unchecked(&state),
+        "the fields are not read by the parser: root/gen_lt"
+    );
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_parse_storage_prices_since_string() {
+    let mut config = ConfigParams::default();
+    let mut p18 = ConfigParam18::default();
+    let mut prices = get_storage_prices();
+    prices.utime_since = 1677672000;
+    p18.insert(&prices).unwrap();
+    config.set_config(ConfigParamEnum::ConfigParam18(p18)).unwrap();
+
+    let param: Value = serde_json::from_str(&serialize_config_param(&config, 18).unwrap()).unwrap();
+    assert_eq!(param["p18"][0]["utime_since"], 1677672000);
+    assert_eq!(param["p18"][0]["utime_since_string"], "2023-03-01T12:00:00Z");
+    let parsed = parse_config(param.as_object().unwrap()).unwrap();
+    assert_eq!(parsed.config(18).unwrap(), config.config(18).unwrap());
+
+    let parse = |item: Value| parse_config(serde_json::json!({ "p18": [item] }).as_object().unwrap());
+    let mut item = param["p18"][0].clone();
+    item.as_object_mut().unwrap().remove("utime_since");
+    assert_eq!(parse(item.clone()).unwrap().config(18).unwrap(), config.config(18).unwrap());
+    item["utime_since_string"] = "2023-03-01T15:00:00+03:00".into();
+    assert_eq!(parse(item.clone()).unwrap().config(18).unwrap(), config.config(18).unwrap());
+
+    item["utime_since"] = 1677672001.into();
+    check_err(
+        parse(item.clone()),
+        "root/p18/utime_since_string 2023-03-01T15:00:00+03:00 doesn't match utime_since 1677672001"
+    );
+    for broken in ["2023-03-01 12:00:00Z", "2023-02-29T12:00:00Z", "2023-03-01T12:00:00.5Z", "1677672000"] {
+        item["utime_since_string"] = broken.into();
+        check_err(parse(item.clone()), "root/p18/utime_since_string must be the RFC 3339 time");
+    }
+}
+
+#[test]
+fn test_parse_state_global_balance_other() {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    let mut map = serde_json::from_str::<Map<String, Value>>(&ethalon).unwrap();
+    map.insert("total_balance_other".to_owned(), serde_json::json!([{ "currency": 239, "value": "1000" }]));
+    map["master"].as_object_mut().unwrap()
+        .insert("global_balance_other".to_owned(), serde_json::json!([{ "currency": 239, "value": "1000" }]));
+    let parse = |map: &Map<String, Value>| {
+        StateParser::for_zero_state().with_global_balance_check(true).parse_state_with_report(map).unwrap()
+    };
+
+    let (state, warnings) = parse(&map);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    let global_balance = state.read_custom().unwrap().unwrap().global_balance;
+    assert_eq!(global_balance.other.get(&239).unwrap().unwrap().value().to_string(), "1000");
+
+    let json: crate::JsonState = (&state).try_into().unwrap();
+    assert!(json.0["master"]["global_balance_other"].is_array());
+    assert_eq!(parse_state(&json.0).unwrap(), state);
+
+    // the grams and the extra currency are both short
+    map["master"]["global_balance"] = "1000".into();
+    map["total_balance_other"][0]["value"] = "2000".into();
+    let (_, warnings) = parse(&map);
+    let messages: Vec<_> = warnings.iter().map(|warning| (warning.path.as_str(), warning.message.as_str())).collect();
+    assert_eq!(messages, [
+        ("root/master/global_balance", "global_balance 1000 is less than total_balance 1234567890123456789012345678901234567"),
+        ("root/master/global_balance", "global_balance of the currency 239 is 1000 and less than total_balance 2000"),
+    ]);
+    // the check is opt-in
+    let (_, warnings) = StateParser::for_zero_state().parse_state_with_report(&map).unwrap();
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    // and skipped for the states parsed without the mandatory params
+    let (_, warnings) = StateParser::new().with_global_balance_check(true).parse_state_with_report(&map).unwrap();
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
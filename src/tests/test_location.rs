@@ -0,0 +1,79 @@
+/*
+ * Copyright (C) ton.dev. All Rights Reserved.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.  You may obtain a copy of the
+ * License at:
+ *
+ * https://www.ever.dev/licenses
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific ton.dev software governing permissions and limitations
+ * under the License.
+ */
+
+use super::*;
+
+fn read_zerostate() -> String {
+    std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap()
+}
+
+// 1-based line of the first occurrence of the fragment
+fn line_of(text: &str, fragment: &str) -> usize {
+    text[..text.find(fragment).unwrap()].matches('\n').count() + 1
+}
+
+#[test]
+fn test_parse_state_from_str() {
+    let text = read_zerostate();
+    parse_state_from_str(&text).unwrap();
+
+    let err = parse_state_from_str("{\n  \"id\": \n}").unwrap_err().to_string();
+    assert!(err.contains("line 3 column 1"), "{}", err);
+}
+
+#[test]
+fn test_broken_field_position() {
+    let text = read_zerostate().replacen("\"validators_elected_for\": 14400", "\"validators_elected_for\": \"soon\"", 1);
+    let err = parse_state_from_str(&text).unwrap_err().to_string();
+    assert!(err.starts_with("root/master/config/p15/validators_elected_for must be"), "{}", err);
+    let line = line_of(&text, "\"validators_elected_for\"");
+    assert!(err.ends_with(&format!("(line {}, column 9)", line)), "{}", err);
+}
+
+#[test]
+fn test_missing_field_position() {
+    // the second validator of p34 loses its public key
+    let key = "\"public_key\": \"d3ccd99924c61509fc6f1c940a3b027cc2c68f351be9eecb2ce259b4721d9aee\"";
+    let text = read_zerostate().replacen(key, "\"public_kei\": \"\"", 1);
+    let err = parse_state_from_str(&text).unwrap_err().to_string();
+    assert!(err.contains("must have the field `public_key`"), "{}", err);
+    // the position of the item object opening brace
+    let line = line_of(&text, "\"public_kei\"") - 1;
+    assert!(err.ends_with(&format!("(line {}, column 11)", line)), "{}", err);
+}
+
+#[test]
+fn test_locate_error() {
+    let text = "{\n  \"a\": [\n    { \"b\": 1 },\n    { \"c\": { \"d\": \"x\" } }\n  ]\n}";
+    let locate = |message| locate_error(text, message).map(|pos| (pos.line, pos.column));
+    assert_eq!(locate("root/a/c/d must be the integer"), Some((4, 14)));
+    assert_eq!(locate("root/a must have the field `b`"), Some((4, 5)));
+    assert_eq!(locate("root must have the field `z`"), Some((1, 1)));
+    // unknown fields point to the nearest known parent
+    assert_eq!(locate("root/a/c/e must be the integer"), Some((4, 7)));
+    assert_eq!(locate("Invalid BLS public key length"), None);
+}
+
+#[test]
+fn test_locate_error_truncated() {
+    let message = "root/a/b must be the integer";
+    for text in ["", "{", "{\"", "{\"a", "{\"a\"", "{\"a\":", "{\"a\": \"x", "{\"a\": \"x\\", "{\"a\": {", "{\"a\": [{\"b\"", "[", "[{"] {
+        let position = locate_error(text, message).unwrap();
+        assert_eq!(position.line, 1, "{}", text);
+        assert!(position.column <= text.chars().count() + 1, "{}", text);
+    }
+    assert_eq!(locate_error("{\n  \"a\": {\n    \"b\"", message), Some(TextPosition { line: 3, column: 5 }));
+}
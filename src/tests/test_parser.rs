@@ -16,10 +16,21 @@
 
 use super::*;
 use crate::block_parser::{reducers::JsonFieldsReducer, MINTER_ADDRESS};
-use crate::{NoTrace, ParsedBlock};
-use ton_dev_block::{read_single_root_boc, Block, GetRepresentationHash, InMsg, OutMsg, UInt256};
+use crate::{DocIdField, NoTrace, ParsedBlock};
+use ton_dev_block::{
+    base64_decode, base64_encode, read_single_root_boc, write_boc, Block, Cell, ChildCell,
+    GetRepresentationHash, HashUpdate, InMsg, IntermediateAddress, IntermediateAddressRegular,
+    MerkleProof, Message, MsgAddressInt, MsgEnvelope, OutMsg, ShardAccountBlocks, UInt256,
+};
+#[cfg(feature = "deserialize")]
+use ton_dev_block::{generate_test_account_by_init_code_hash, ShardAccount, ShardStateUnsplit};
 use serde_json::Map;
-use std::{collections::HashMap, fs::read, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::read,
+    path::Path,
+    str::FromStr,
+};
 
 #[derive(Default)]
 pub struct ParseOptions {
@@ -28,6 +39,16 @@ pub struct ParseOptions {
     blocks: Option<EntryConfig<JsonFieldsReducer>>,
     transactions: Option<EntryConfig<JsonFieldsReducer>>,
     messages: Option<EntryConfig<JsonFieldsReducer>>,
+    transaction_proofs: bool,
+    remp_receipts: bool,
+    account_summaries: Option<EntryConfig<JsonFieldsReducer>>,
+    out_msg_queue_updates: Option<EntryConfig<JsonFieldsReducer>>,
+    key_block_configs: Option<EntryConfig<JsonFieldsReducer>>,
+    raw: bool,
+    doc_id_field: DocIdField,
+    omit_bodies: bool,
+    special_accounts: Option<HashSet<UInt256>>,
+    normalized_msg_hashes: bool,
 }
 
 impl ParseOptions {
@@ -43,6 +64,7 @@ impl ParseOptions {
             Some(EntryConfig {
                 reducer: None,
                 sharding_depth: Some(depth),
+                max_boc_field_size: None,
             })
         }
         Self {
@@ -59,12 +81,93 @@ impl ParseOptions {
             ..self
         }
     }
+
+    fn transaction_proofs(self) -> Self {
+        Self {
+            transaction_proofs: true,
+            ..self
+        }
+    }
+
+    #[cfg(feature = "remp")]
+    fn remp_receipts(self) -> Self {
+        Self {
+            remp_receipts: true,
+            ..self
+        }
+    }
+
+    fn account_summaries(self, sharding_depth: u32) -> Self {
+        Self {
+            account_summaries: Some(EntryConfig {
+                reducer: None,
+                sharding_depth: Some(sharding_depth),
+                max_boc_field_size: None,
+            }),
+            ..self
+        }
+    }
+
+    fn out_msg_queue_updates(self, sharding_depth: u32) -> Self {
+        Self {
+            out_msg_queue_updates: Some(EntryConfig {
+                reducer: None,
+                sharding_depth: Some(sharding_depth),
+                max_boc_field_size: None,
+            }),
+            ..self
+        }
+    }
+
+    fn raw(self) -> Self {
+        Self { raw: true, ..self }
+    }
+
+    fn doc_id_field(self, doc_id_field: DocIdField) -> Self {
+        Self {
+            doc_id_field,
+            ..self
+        }
+    }
+
+    fn omit_bodies(self) -> Self {
+        Self {
+            omit_bodies: true,
+            ..self
+        }
+    }
+
+    fn special_accounts(self, special_accounts: HashSet<UInt256>) -> Self {
+        Self {
+            special_accounts: Some(special_accounts),
+            ..self
+        }
+    }
+
+    fn normalized_msg_hashes(self) -> Self {
+        Self {
+            normalized_msg_hashes: true,
+            ..self
+        }
+    }
+
+    fn key_block_configs(self) -> Self {
+        Self {
+            key_block_configs: Some(EntryConfig {
+                reducer: None,
+                sharding_depth: None,
+                max_boc_field_size: None,
+            }),
+            ..self
+        }
+    }
 }
 
 fn reducer(config: &str) -> Option<EntryConfig<JsonFieldsReducer>> {
     Some(EntryConfig {
         reducer: Some(JsonFieldsReducer::with_config(config).unwrap()),
         sharding_depth: None,
+        max_boc_field_size: None,
     })
 }
 
@@ -74,6 +177,13 @@ fn parse_block(
 ) -> (Vec<u8>, UInt256, ParsedBlock) {
     let in_path = Path::new("src/tests/data").join(file_rel_path);
     let boc = read(in_path.clone()).unwrap_or_else(|_| panic!("Error reading file {:?}", in_path));
+    parse_block_boc(boc, options)
+}
+
+fn parse_block_boc(
+    boc: Vec<u8>,
+    options: Option<ParseOptions>,
+) -> (Vec<u8>, UInt256, ParsedBlock) {
     let cell = read_single_root_boc(&boc).expect("Error deserializing single root BOC");
 
     let block = Block::construct_from_cell(cell.clone()).unwrap();
@@ -89,15 +199,33 @@ fn parse_block(
         file_hash,
     );
     let mc_seq_no = options.as_ref().and_then(|x| x.mc_seq_no);
-    let (blocks, transactions, messages) = options
-        .map(|x| (x.blocks, x.transactions, x.messages))
-        .unwrap_or((None, None, None));
+    let transaction_proofs = options.as_ref().map_or(false, |x| x.transaction_proofs);
+    let remp_receipts = options.as_ref().map_or(false, |x| x.remp_receipts);
+    let raw = options.as_ref().map_or(false, |x| x.raw);
+    let doc_id_field = options.as_ref().map_or(DocIdField::Id, |x| x.doc_id_field);
+    let omit_bodies = options.as_ref().map_or(false, |x| x.omit_bodies);
+    let special_accounts = options.as_ref().and_then(|x| x.special_accounts.clone());
+    let normalized_msg_hashes = options.as_ref().map_or(false, |x| x.normalized_msg_hashes);
+    let (blocks, transactions, messages, account_summaries, out_msg_queue_updates, key_block_configs) =
+        options
+            .map(|x| {
+                (
+                    x.blocks,
+                    x.transactions,
+                    x.messages,
+                    x.account_summaries,
+                    x.out_msg_queue_updates,
+                    x.key_block_configs,
+                )
+            })
+            .unwrap_or((None, None, None, None, None, None));
     fn entry_config(
         opt: Option<EntryConfig<JsonFieldsReducer>>,
     ) -> Option<EntryConfig<JsonFieldsReducer>> {
         Some(opt.unwrap_or(EntryConfig {
             reducer: None,
             sharding_depth: None,
+            max_boc_field_size: None,
         }))
     }
 
@@ -106,10 +234,17 @@ fn parse_block(
             blocks: entry_config(blocks),
             transactions: entry_config(transactions),
             messages: entry_config(messages),
-            accounts: None,
-            proofs: None,
-            max_account_bytes_size: None,
-            is_node_se: false,
+            transaction_proofs,
+            remp_receipts,
+            account_summaries,
+            out_msg_queue_updates,
+            key_block_configs,
+            raw,
+            doc_id_field,
+            omit_bodies,
+            special_accounts,
+            normalized_msg_hashes,
+            ..Default::default()
         },
         None,
     );
@@ -120,6 +255,7 @@ fn parse_block(
                 block: &block,
                 root: &cell,
                 shard_state: None,
+                shard_accounts: None,
                 data: &boc,
                 mc_seq_no,
                 proof: None,
@@ -165,6 +301,51 @@ fn test_transaction_code_hash() {
     );
 }
 
+#[test]
+fn test_transaction_types() {
+    let (_, _, parsed) = parse_block(
+        "3FAFAAB7B5D17E439CD9DDEF7EEC3C3BC9D28E7C181BA374218E13599F8F6657.boc",
+        None,
+    );
+    assert_eq!(parsed.transactions.len(), 2);
+    for tr in &parsed.transactions {
+        assert_eq!(tr.body["tr_type"], 0);
+        assert_eq!(tr.body["tr_kind"], "ordinary");
+        assert!(tr.body.get("tr_type_name").is_none());
+        assert!(tr.body.get("is_special").is_none());
+    }
+
+    // the elector and the config transactions of the masterchain block are tick-tock ones
+    let elector = UInt256::from([0x33; 32]);
+    let config = UInt256::from([0x55; 32]);
+    let (_, _, parsed) = parse_block(
+        "9C9906A80D020952E0192DC60C0B2BF1F55FE9A9E065606E8FE25C08BD1AA6B2.boc",
+        Some(
+            ParseOptions::default()
+                .special_accounts(HashSet::from([elector.clone(), config.clone()])),
+        ),
+    );
+    let mut names = Vec::new();
+    for tr in &parsed.transactions {
+        let name = tr.body["tr_kind"].as_str().unwrap();
+        let expected = match tr.body["tr_type"].as_u64().unwrap() {
+            0 => "ordinary",
+            2 => "tick",
+            3 => "tock",
+            tr_type => panic!("unexpected tr_type {}", tr_type),
+        };
+        assert_eq!(name, expected);
+        let account_addr = tr.body["account_addr"].as_str().unwrap();
+        let special = [&elector, &config]
+            .iter()
+            .any(|id| account_addr == format!("-1:{}", id.as_hex_string()));
+        assert_eq!(tr.body["is_special"], special, "{}", account_addr);
+        names.push(name);
+    }
+    assert!(names.contains(&"tick") && names.contains(&"tock"), "{:?}", names);
+    assert!(parsed.transactions.iter().any(|tr| tr.body["is_special"] == false));
+}
+
 #[test]
 fn test_parse_block() {
     //crate::init_logger(None);
@@ -730,3 +911,1298 @@ fn test_transaction_id_in_msg() {
         );
     }
 }
+
+#[test]
+fn test_account_transition() {
+    let none = crate::block_parser::ACCOUNT_NONE_HASH;
+    let state = UInt256::from([1; 32]);
+
+    assert_eq!(account_transition(&state, &state, true), AccountTransition::Changed);
+    assert_eq!(account_transition(&none, &state, true), AccountTransition::Changed);
+    // deleted within the block
+    assert_eq!(account_transition(&state, &none, true), AccountTransition::Deleted);
+    // nonexistent account which only bounced an incoming message
+    assert_eq!(account_transition(&none, &none, false), AccountTransition::None);
+    // account was created and deleted within the same block
+    assert_eq!(account_transition(&none, &none, true), AccountTransition::Deleted);
+}
+
+#[test]
+fn test_deleted_account_tombstone() {
+    let boc = read("src/tests/data/89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc").unwrap();
+    let mut block = Block::construct_from_bytes(&boc).unwrap();
+    let shard_accounts = crate::block_parser::accounts::read_accounts(
+        block.read_state_update().unwrap().new
+    ).unwrap();
+
+    // the account `-1:33..33` is deleted by its last transaction, the others are left as is
+    let deleted = format!("-1:{}", "33".repeat(32));
+    let mut last_transaction = None;
+    let mut extra = block.read_extra().unwrap();
+    let mut account_blocks = ShardAccountBlocks::default();
+    extra.read_account_blocks().unwrap().iterate_objects(&mut |account_block: AccountBlock| {
+        if hex::encode(account_block.account_id().get_bytestring(0)) != deleted[3..] {
+            account_blocks.insert(&account_block)?;
+            return Ok(true)
+        }
+        account_block.transactions().iterate_slices(&mut |_, slice: SliceData| {
+            let cell = slice.reference(0)?;
+            let lt = Transaction::construct_from_cell(cell.clone())?.logical_time();
+            if last_transaction.as_ref().map_or(true, |(last_lt, _)| lt > *last_lt) {
+                last_transaction = Some((lt, cell.repr_hash()));
+            }
+            Ok(true)
+        })?;
+        let state_update = account_block.read_state_update()?;
+        let state_update = HashUpdate::with_hashes(
+            state_update.old_hash,
+            crate::block_parser::ACCOUNT_NONE_HASH,
+        );
+        account_blocks.insert(&AccountBlock::with_params(
+            account_block.account_id(),
+            account_block.transactions(),
+            &state_update,
+        )?)?;
+        Ok(true)
+    }).unwrap();
+    let (_, last_transaction_hash) = last_transaction.expect("the fixture must contain the account");
+    extra.write_account_blocks(&account_blocks).unwrap();
+    block.write_extra(&extra).unwrap();
+
+    let cell = block.serialize().unwrap();
+    let boc = write_boc(&cell).unwrap();
+    let info = block.read_info().unwrap();
+    let id = BlockIdExt::with_params(
+        info.shard().clone(),
+        info.seq_no(),
+        cell.repr_hash(),
+        UInt256::calc_file_hash(&boc),
+    );
+    let parser = BlockParser::<NoTrace, JsonFieldsReducer>::new(
+        BlockParserConfig {
+            accounts: Some(EntryConfig { reducer: None, sharding_depth: None, max_boc_field_size: None }),
+            ..Default::default()
+        },
+        None,
+    );
+    let parsed = parser
+        .parse(
+            ParsingBlock {
+                id: &id,
+                block: &block,
+                root: &cell,
+                shard_state: None,
+                shard_accounts: Some(&shard_accounts),
+                data: &boc,
+                mc_seq_no: None,
+                proof: None,
+            },
+            false,
+        )
+        .unwrap();
+
+    assert_eq!(parsed.accounts.len(), 3);
+    for account in &parsed.accounts {
+        if account.id == deleted {
+            assert_eq!(account.body["deleted"], true);
+            assert_eq!(account.body["acc_type"], 3);
+            assert_eq!(account.body["last_trans_hash"], last_transaction_hash.as_hex_string());
+            assert!(account.body.get("boc").is_none());
+        } else {
+            assert!(account.body.get("deleted").is_none(), "{}", account.id);
+            assert!(account.body["boc"].is_string());
+        }
+    }
+}
+
+#[test]
+fn test_message_block_time() {
+    let (boc, _, parsed) = parse_block(
+        "6ce37a48b76f9ab9a5b33b727baf3e19da18a7bdee1cf3242ddb2a79c20715e4.boc",
+        None,
+    );
+    let block = Block::construct_from_bytes(&boc).unwrap();
+    let gen_utime = block.read_info().unwrap().gen_utime().as_u32();
+
+    let ext_in = parsed
+        .messages
+        .iter()
+        .find(|msg| msg.body["msg_type"] == 1)
+        .expect("block must contain external inbound message");
+    assert_eq!(ext_in.body["block_time"], gen_utime);
+    assert!(ext_in.body["tr_lt"].is_string());
+    assert_ne!(ext_in.body.get("created_at"), Some(&Value::from(gen_utime)));
+}
+
+#[test]
+fn test_message_directions() {
+    let mut classified = HashMap::new();
+    for file in [
+        "18AFCDD25BE0989CE516504263EB351818A0FF8F6AB3689501C8E3B767EF413C.boc",
+        "3FAFAAB7B5D17E439CD9DDEF7EEC3C3BC9D28E7C181BA374218E13599F8F6657.boc",
+        "558651b80d5361fd7f31882d4df90bf8e3c0c58422684e752a47c6b57b7be62c.boc",
+        "6ce37a48b76f9ab9a5b33b727baf3e19da18a7bdee1cf3242ddb2a79c20715e4.boc",
+        "c594aec0b3e52b4177e90d3bf82542151b0edc3e5d86d745689ec25f3d943624.boc",
+    ] {
+        let (boc, _, parsed) = parse_block(file, None);
+        let block = Block::construct_from_cell(read_single_root_boc(&boc).unwrap()).unwrap();
+        let extra = block.read_extra().unwrap();
+
+        let mut expected = HashMap::new();
+        extra
+            .read_out_msg_descr()
+            .unwrap()
+            .iterate_objects(|out_msg| {
+                if let OutMsg::External(_) | OutMsg::New(_) | OutMsg::Immediate(_) = out_msg {
+                    let id = out_msg.message_cell().unwrap().unwrap().repr_hash();
+                    expected.insert(id.as_hex_string(), "out");
+                }
+                Ok(true)
+            })
+            .unwrap();
+        extra
+            .read_in_msg_descr()
+            .unwrap()
+            .iterate_objects(|in_msg| {
+                let direction = match in_msg {
+                    InMsg::Transit(_) | InMsg::DiscardedTransit(_) => "transit",
+                    _ => "in",
+                };
+                let id = in_msg.message_cell().unwrap().repr_hash();
+                expected.insert(id.as_hex_string(), direction);
+                Ok(true)
+            })
+            .unwrap();
+
+        assert_eq!(parsed.messages.len(), expected.len(), "{}", file);
+        for message in &parsed.messages {
+            let direction = message.body["direction"].as_str().unwrap();
+            assert_eq!(direction, expected[&message.id], "{}", file);
+            let msg_type_name = message.body["msg_type_name"].as_str().unwrap();
+            // the inbound internal messages of the other blocks only get the transaction fields
+            if let Some(msg_type) = message.body.get("msg_type") {
+                assert_eq!(["internal", "extIn", "extOut"][msg_type.as_u64().unwrap() as usize], msg_type_name);
+            } else {
+                assert_eq!((msg_type_name, direction), ("internal", "in"), "{}", file);
+            }
+            *classified.entry((msg_type_name.to_owned(), direction.to_owned())).or_insert(0) += 1;
+        }
+    }
+
+    // none of the fixtures routes a message through its shard, so a transit pair is added
+    let boc = read("src/tests/data/18AFCDD25BE0989CE516504263EB351818A0FF8F6AB3689501C8E3B767EF413C.boc").unwrap();
+    let mut block = Block::construct_from_bytes(&boc).unwrap();
+    let mut extra = block.read_extra().unwrap();
+    let transaction = read("src/tests/data/transactions/ext_in&int_out.boc").unwrap();
+    let message = Transaction::construct_from_bytes(&transaction).unwrap().get_out_msg(0).unwrap().unwrap();
+    let message_id = message.serialize().unwrap().repr_hash().as_hex_string();
+    let envelope = |fee: u64| MsgEnvelope::with_routing(
+        message.serialize().unwrap(),
+        fee.into(),
+        IntermediateAddress::Regular(IntermediateAddressRegular::with_use_src_bits(0).unwrap()),
+        IntermediateAddress::Regular(IntermediateAddressRegular::with_use_src_bits(0).unwrap()),
+    );
+    let in_msg = InMsg::transit(
+        ChildCell::with_struct(&envelope(2_000_000)).unwrap(),
+        ChildCell::with_struct(&envelope(1_000_000)).unwrap(),
+        1_000_000u64.into(),
+    );
+    let out_msg = OutMsg::transit(
+        ChildCell::with_struct(&envelope(1_000_000)).unwrap(),
+        ChildCell::with_struct(&in_msg).unwrap(),
+        false,
+    );
+    let mut in_msg_descr = extra.read_in_msg_descr().unwrap();
+    in_msg_descr.insert(&in_msg).unwrap();
+    extra.write_in_msg_descr(&in_msg_descr).unwrap();
+    let mut out_msg_descr = extra.read_out_msg_descr().unwrap();
+    out_msg_descr.insert(&out_msg).unwrap();
+    extra.write_out_msg_descr(&out_msg_descr).unwrap();
+    block.write_extra(&extra).unwrap();
+
+    let (_, _, parsed) = parse_block_boc(write_boc(&block.serialize().unwrap()).unwrap(), None);
+    let transit = parsed.messages.iter().find(|message| message.id == message_id).unwrap();
+    assert_eq!(transit.body["direction"], "transit");
+    assert_eq!(transit.body["msg_type"], 0);
+    let msg_type_name = transit.body["msg_type_name"].as_str().unwrap();
+    *classified.entry((msg_type_name.to_owned(), "transit".to_owned())).or_insert(0) += 1;
+
+    for classification in [
+        ("extIn", "in"),
+        ("extOut", "out"),
+        ("internal", "in"),
+        ("internal", "out"),
+        ("internal", "transit"),
+    ] {
+        let key = (classification.0.to_owned(), classification.1.to_owned());
+        assert!(classified.contains_key(&key), "{:?}", classification);
+    }
+}
+
+fn contains_cell(cell: &Cell, hash: &UInt256) -> bool {
+    if &cell.repr_hash() == hash {
+        return true;
+    }
+    (0..cell.references_count()).any(|i| contains_cell(&cell.reference(i).unwrap(), hash))
+}
+
+#[test]
+fn test_transaction_proofs() {
+    let (_, block_hash, parsed) = parse_block(
+        "3FAFAAB7B5D17E439CD9DDEF7EEC3C3BC9D28E7C181BA374218E13599F8F6657.boc",
+        Some(ParseOptions::default().transaction_proofs()),
+    );
+    assert!(!parsed.transactions.is_empty());
+    for transaction in &parsed.transactions {
+        let proof = base64_decode(transaction.body["proof"].as_str().unwrap()).unwrap();
+        let proof = MerkleProof::construct_from_bytes(&proof).unwrap();
+        assert_eq!(proof.hash, block_hash);
+        let tr_hash = transaction.id.parse::<UInt256>().unwrap();
+        assert!(contains_cell(&proof.proof.virtualize(1), &tr_hash));
+    }
+    // messages do not get proofs unless parsing with_proofs
+    assert!(parsed.messages.iter().all(|msg| msg.body.get("proof").is_none()));
+}
+
+#[test]
+fn test_touched_account_snapshots() {
+    let boc = read("src/tests/data/3FAFAAB7B5D17E439CD9DDEF7EEC3C3BC9D28E7C181BA374218E13599F8F6657.boc").unwrap();
+    let cell = read_single_root_boc(&boc).unwrap();
+    let block = Block::construct_from_cell(cell.clone()).unwrap();
+    let info = block.read_info().unwrap();
+    let id = BlockIdExt::with_params(
+        info.shard().clone(),
+        info.seq_no(),
+        cell.repr_hash(),
+        UInt256::calc_file_hash(&boc),
+    );
+    // post-block accounts, untouched ones are pruned in the state update
+    let shard_accounts = crate::block_parser::accounts::read_accounts(
+        block.read_state_update().unwrap().new
+    ).unwrap();
+
+    let mut touched = HashMap::new();
+    block.read_extra().unwrap().read_account_blocks().unwrap().iterate_objects(&mut |account_block: AccountBlock| {
+        let mut last_lt = 0;
+        account_block.transactions().iterate_slices(&mut |_, slice: SliceData| {
+            let transaction = Transaction::construct_from_cell(slice.reference(0)?)?;
+            last_lt = last_lt.max(transaction.logical_time() + transaction.out_msgs.len()? as u64 + 1);
+            Ok(true)
+        })?;
+        touched.insert(hex::encode(account_block.account_id().get_bytestring(0)), last_lt);
+        Ok(true)
+    }).unwrap();
+
+    let parser = BlockParser::<NoTrace, JsonFieldsReducer>::new(
+        BlockParserConfig {
+            accounts: Some(EntryConfig { reducer: None, sharding_depth: None, max_boc_field_size: None }),
+            ..Default::default()
+        },
+        None,
+    );
+    let parsed = parser
+        .parse(
+            ParsingBlock {
+                id: &id,
+                block: &block,
+                root: &cell,
+                shard_state: None,
+                shard_accounts: Some(&shard_accounts),
+                data: &boc,
+                mc_seq_no: None,
+                proof: None,
+            },
+            false,
+        )
+        .unwrap();
+
+    assert!(!touched.is_empty());
+    assert_eq!(parsed.accounts.len(), touched.len());
+    for account in &parsed.accounts {
+        let address = account.body["id"].as_str().unwrap();
+        let (_, account_id) = address.split_once(':').unwrap();
+        let last_lt = touched.get(account_id).expect("untouched account must not be emitted");
+        assert_eq!(account.body["last_trans_lt"], crate::u64_to_string(*last_lt));
+        assert!(account.body["boc"].is_string());
+    }
+}
+
+#[test]
+fn test_entries_block_location() {
+    for (file, workchain_id, shard) in [
+        ("046784ea72574ace66375629229700afa4c7e032a360fc94df4c20231fddea45.boc", Some(-1), Some("8000000000000000")),
+        ("6ce37a48b76f9ab9a5b33b727baf3e19da18a7bdee1cf3242ddb2a79c20715e4.boc", None, None),
+    ] {
+        let (boc, _, parsed) = parse_block(file, None);
+        let block = Block::construct_from_bytes(&boc).unwrap();
+        let info = block.read_info().unwrap();
+        let (block_workchain_id, block_shard) = crate::shard_ident_to_json(info.shard());
+        if let Some(workchain_id) = workchain_id {
+            assert_eq!(block_workchain_id, workchain_id);
+        }
+        if let Some(shard) = shard {
+            assert_eq!(block_shard, shard);
+        }
+
+        let entries = parsed.block.iter().chain(&parsed.transactions).chain(&parsed.messages);
+        let mut count = 0;
+        for entry in entries {
+            assert_eq!(entry.body["workchain_id"], block_workchain_id, "{}", entry.id);
+            assert_eq!(entry.body["shard"], block_shard, "{}", entry.id);
+            assert_eq!(entry.body["seq_no"], info.seq_no(), "{}", entry.id);
+            count += 1;
+        }
+        assert!(count > 1, "{}", file);
+    }
+}
+
+#[cfg(feature = "deserialize")]
+#[test]
+fn test_deflate_boc_fields() {
+    // about 100KB of code in a chain of cells
+    let mut cell = Cell::default();
+    for i in 0..800u32 {
+        let mut builder = ton_dev_block::BuilderData::new();
+        let bytes = (0..127u32).map(|j| ((i * 7 + j) % 16) as u8).collect::<Vec<_>>();
+        builder.append_raw(&bytes, 127 * 8).unwrap();
+        if i > 0 {
+            builder.checked_append_reference(cell).unwrap();
+        }
+        cell = builder.into_cell().unwrap();
+    }
+    let code = base64_encode(ton_dev_block::write_boc(&cell).unwrap());
+    assert!(code.len() > 100_000);
+
+    let mut doc = Map::new();
+    doc.insert("id".to_owned(), "test".into());
+    doc.insert("code".to_owned(), code.clone().into());
+    doc.insert("data".to_owned(), "te6ccgEBAQEAAgAAAA==".into());
+    let config = Some(EntryConfig::<JsonFieldsReducer> {
+        reducer: None,
+        sharding_depth: None,
+        max_boc_field_size: Some(1024),
+    });
+    let entry = ParsedEntry::reduced(doc, None, &config).unwrap();
+
+    assert!(entry.body.get("code").is_none());
+    assert_eq!(entry.body["code_encoding"], "deflate");
+    assert!(entry.body["code_deflated"].as_str().unwrap().len() < code.len());
+    // small fields are left as is
+    assert_eq!(entry.body["data"], "te6ccgEBAQEAAgAAAA==");
+    assert!(entry.body.get("data_deflated").is_none());
+
+    let map = crate::PathMap::new(&entry.body);
+    assert_eq!(map.get_cell_maybe_deflated("code").unwrap(), cell);
+    assert_eq!(
+        map.get_cell_maybe_deflated("data").unwrap(),
+        read_single_root_boc(base64_decode("te6ccgEBAQEAAgAAAA==").unwrap()).unwrap()
+    );
+}
+
+fn parse_block_into(file_rel_path: &str, sink: &mut impl ParsedEntrySink) -> Result<()> {
+    let boc = read(Path::new("src/tests/data").join(file_rel_path)).unwrap();
+    let cell = read_single_root_boc(&boc).unwrap();
+    let block = Block::construct_from_cell(cell.clone()).unwrap();
+    let info = block.read_info().unwrap();
+    let id = BlockIdExt::with_params(
+        info.shard().clone(),
+        info.seq_no(),
+        cell.repr_hash(),
+        UInt256::calc_file_hash(&boc),
+    );
+    let entry_config = || Some(EntryConfig { reducer: None, sharding_depth: None, max_boc_field_size: None });
+    let parser = BlockParser::<NoTrace, JsonFieldsReducer>::new(
+        BlockParserConfig {
+            blocks: entry_config(),
+            transactions: entry_config(),
+            messages: entry_config(),
+            ..Default::default()
+        },
+        None,
+    );
+    parser.parse_into(
+        ParsingBlock {
+            id: &id,
+            block: &block,
+            root: &cell,
+            shard_state: None,
+            shard_accounts: None,
+            data: &boc,
+            mc_seq_no: None,
+            proof: None,
+        },
+        false,
+        sink,
+    )
+}
+
+#[derive(Default)]
+struct CountingSink {
+    blocks: usize,
+    transactions: usize,
+    messages: usize,
+    fail_on_transaction: Option<usize>,
+}
+
+impl ParsedEntrySink for CountingSink {
+    fn on_block(&mut self, _entry: ParsedEntry) -> Result<()> {
+        self.blocks += 1;
+        Ok(())
+    }
+    fn on_proof(&mut self, _entry: ParsedEntry) -> Result<()> {
+        unreachable!("proofs are not configured")
+    }
+    fn on_transaction(&mut self, _entry: ParsedEntry) -> Result<()> {
+        self.transactions += 1;
+        if Some(self.transactions) == self.fail_on_transaction {
+            fail!("kafka is unavailable")
+        }
+        Ok(())
+    }
+    fn on_message(&mut self, _entry: ParsedEntry) -> Result<()> {
+        self.messages += 1;
+        Ok(())
+    }
+    fn on_account(&mut self, _entry: ParsedEntry) -> Result<()> {
+        unreachable!("accounts are not configured")
+    }
+}
+
+#[test]
+fn test_parse_into_sink() {
+    let file = "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc";
+    let (_, _, parsed) = parse_block(file, None);
+
+    let mut sink = CountingSink::default();
+    parse_block_into(file, &mut sink).unwrap();
+    assert_eq!(sink.blocks, 1);
+    assert_eq!(sink.transactions, parsed.transactions.len());
+    assert_eq!(sink.messages, parsed.messages.len());
+    assert!(sink.transactions >= 3);
+
+    let mut sink = CountingSink { fail_on_transaction: Some(3), ..Default::default() };
+    let err = parse_block_into(file, &mut sink).expect_err("sink error must abort parsing");
+    assert_eq!(sink.transactions, 3);
+    assert_eq!(sink.messages, 0);
+    assert_eq!(sink.blocks, 0);
+    let text = err.to_string();
+    assert!(text.starts_with("Entry sink failed on transaction "), "{}", text);
+    assert!(text.ends_with(": kafka is unavailable"), "{}", text);
+}
+
+#[test]
+fn test_parse_metrics() {
+    let file = "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc";
+    let boc = read(Path::new("src/tests/data").join(file)).unwrap();
+    let cell = read_single_root_boc(&boc).unwrap();
+    let block = Block::construct_from_cell(cell.clone()).unwrap();
+    let info = block.read_info().unwrap();
+    let id = BlockIdExt::with_params(
+        info.shard().clone(),
+        info.seq_no(),
+        cell.repr_hash(),
+        UInt256::calc_file_hash(&boc),
+    );
+    let entry_config = || Some(EntryConfig { reducer: None, sharding_depth: None, max_boc_field_size: None });
+    let parser = BlockParser::<NoTrace, JsonFieldsReducer>::new(
+        BlockParserConfig {
+            blocks: entry_config(),
+            transactions: entry_config(),
+            messages: entry_config(),
+            remp_receipts: cfg!(feature = "remp"),
+            account_summaries: entry_config(),
+            out_msg_queue_updates: entry_config(),
+            doc_id_field: DocIdField::Key,
+            ..Default::default()
+        },
+        None,
+    );
+    let parsing_block = || ParsingBlock {
+        id: &id,
+        block: &block,
+        root: &cell,
+        shard_state: None,
+        shard_accounts: None,
+        data: &boc,
+        mc_seq_no: None,
+        proof: None,
+    };
+
+    let (parsed, metrics) = parser.parse_with_metrics(parsing_block(), false, false).unwrap();
+    assert_eq!(metrics.blocks, 1);
+    assert_eq!(metrics.proofs, 0);
+    assert_eq!(metrics.transactions, parsed.transactions.len() as u64);
+    assert_eq!(metrics.messages, parsed.messages.len() as u64);
+    assert_eq!(metrics.remp_receipts, parsed.remp_receipts.len() as u64);
+    assert_eq!(metrics.account_summaries, parsed.account_summaries.len() as u64);
+    assert_eq!(metrics.out_msg_queue_updates, parsed.out_msg_queue_updates.len() as u64);
+    assert_eq!(metrics.accounts + metrics.key_block_configs, 0);
+    assert!(metrics.transactions >= 3);
+    assert!(metrics.timings.is_none());
+
+    // the delivered bodies, with the renamed id field
+    let entries = parsed.block.iter()
+        .chain(&parsed.transactions)
+        .chain(&parsed.messages)
+        .chain(&parsed.remp_receipts)
+        .chain(&parsed.account_summaries)
+        .chain(&parsed.out_msg_queue_updates);
+    let mut count = 0;
+    for entry in entries {
+        assert!(entry.body.contains_key("_key"));
+        count += 1;
+    }
+    assert_eq!(metrics.entries(), count);
+
+    let timed = parser
+        .parse_into_with_metrics(parsing_block(), false, true, &mut ParsedBlock::default())
+        .unwrap();
+    assert_eq!(ParseMetrics { timings: None, ..timed.clone() }, metrics);
+    assert!(timed.timings.is_some());
+}
+
+#[test]
+fn test_parse_block_header_with_pruned_extra() {
+    let file = "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc";
+    let boc = read(Path::new("src/tests/data").join(file)).unwrap();
+    let cell = read_single_root_boc(&boc).unwrap();
+    let block = Block::construct_from_cell(cell.clone()).unwrap();
+    let info = block.read_info().unwrap();
+
+    let brief = parse_block_header(&boc).unwrap();
+    assert_eq!(brief.id.root_hash(), &cell.repr_hash());
+    assert_eq!(brief.id.file_hash(), &UInt256::calc_file_hash(&boc));
+    assert_eq!(brief.id.seq_no(), info.seq_no());
+    assert_eq!(brief.gen_utime, info.gen_utime().as_u32());
+    assert_eq!(brief.key_block, info.key_block());
+    assert_eq!(brief.prev_ref, info.read_prev_ref().unwrap().prev1().unwrap());
+
+    // the whole block info with its prev_ref and master_ref is kept, only the extra is pruned
+    let info_cell = cell.reference(0).unwrap();
+    let extra_hash = cell.reference(3).unwrap().repr_hash();
+    let proof = MerkleProof::create(&cell, |hash| {
+        *hash != extra_hash && (*hash == cell.repr_hash() || contains_cell(&info_cell, hash))
+    })
+    .unwrap();
+    let pruned = ton_dev_block::write_boc(&proof.proof).unwrap();
+    assert!(pruned.len() < boc.len() / 4, "{} {}", pruned.len(), boc.len());
+    let pruned_block = Block::construct_from_bytes(&pruned).unwrap();
+    pruned_block.read_extra().expect_err("extra must be pruned");
+    let pruned_info = pruned_block.read_info().unwrap();
+    assert_eq!(pruned_info.read_prev_ref().unwrap(), info.read_prev_ref().unwrap());
+    assert_eq!(pruned_info.read_master_ref().unwrap(), info.read_master_ref().unwrap());
+
+    let pruned_brief = parse_block_header(&pruned).unwrap();
+    assert_eq!(pruned_brief.id.root_hash(), brief.id.root_hash());
+    assert_eq!(pruned_brief.id.seq_no(), brief.id.seq_no());
+    assert_eq!(pruned_brief.gen_utime, brief.gen_utime);
+    assert_eq!(pruned_brief.key_block, brief.key_block);
+    assert_eq!(pruned_brief.prev_ref, brief.prev_ref);
+    assert_eq!(pruned_brief.master_ref_seq_no, brief.master_ref_seq_no);
+
+    let json = brief_block_json(&brief);
+    assert_eq!(json["id"], cell.repr_hash().as_hex_string());
+    assert_eq!(json["seq_no"], info.seq_no());
+    assert_eq!(json["prev_ref"]["seq_no"], info.read_prev_ref().unwrap().prev1().unwrap().seq_no);
+}
+
+#[test]
+fn test_normalized_msg_hashes() {
+    // one external with the body in a reference and two internals
+    let file = "c594aec0b3e52b4177e90d3bf82542151b0edc3e5d86d745689ec25f3d943624.boc";
+    let (_, _, plain) = parse_block(file, None);
+    let (_, _, parsed) = parse_block(file, Some(ParseOptions::default().normalized_msg_hashes()));
+    assert_eq!(plain.messages.len(), parsed.messages.len());
+
+    let mut externals = 0;
+    for (plain, entry) in plain.messages.iter().zip(&parsed.messages) {
+        let boc = base64_decode(entry.body["boc"].as_str().unwrap()).unwrap();
+        let message = Message::construct_from_bytes(&boc).unwrap();
+        let mut body = entry.body.clone();
+        match crate::normalized_message_hash(&message).unwrap() {
+            Some(hash) => {
+                assert_eq!(entry.body["msg_type"], 1);
+                assert_eq!(body.remove("msg_hash_normalized").unwrap(), hash.as_hex_string());
+                externals += 1;
+            }
+            None => assert!(!body.contains_key("msg_hash_normalized")),
+        }
+        assert_eq!(&body, &plain.body);
+    }
+    assert_eq!(externals, 1);
+}
+
+#[cfg(all(feature = "deserialize", feature = "remp"))]
+#[test]
+fn test_remp_receipts_for_external_messages() {
+    // two externals; one external and two internals
+    for (file, expected) in [
+        ("6ce37a48b76f9ab9a5b33b727baf3e19da18a7bdee1cf3242ddb2a79c20715e4.boc", 2),
+        ("c594aec0b3e52b4177e90d3bf82542151b0edc3e5d86d745689ec25f3d943624.boc", 1),
+    ] {
+        let (raw_block, block_id, parsed) =
+            parse_block(file, Some(ParseOptions::default().remp_receipts()));
+        let block = Block::construct_from_bytes(&raw_block).unwrap();
+        let info = block.read_info().unwrap();
+
+        let mut externals = Vec::new();
+        let mut internals = Vec::new();
+        block.read_extra().unwrap().read_in_msg_descr().unwrap()
+            .iterate_objects(&mut |msg: InMsg| {
+                let id = msg.message_cell().unwrap().repr_hash().as_hex_string();
+                match msg {
+                    InMsg::External(_) => externals.push(id),
+                    _ => internals.push(id),
+                }
+                Ok(true)
+            })
+            .unwrap();
+        assert_eq!(externals.len(), expected, "{}", file);
+
+        let mut receipts = parsed.remp_receipts.iter().map(|entry| entry.id.clone()).collect::<Vec<_>>();
+        receipts.sort();
+        externals.sort();
+        assert_eq!(receipts, externals, "{}", file);
+        assert!(internals.iter().all(|id| !receipts.contains(id)));
+
+        for entry in &parsed.remp_receipts {
+            assert_eq!(entry.body["kind"], "IncludedIntoBlock");
+            assert_eq!(entry.body["block_id"], block_id.as_hex_string());
+            assert_eq!(entry.body["block_seqno"], info.seq_no());
+            assert_eq!(entry.body["timestamp"], info.gen_utime().as_u32());
+
+            let (receipt, signature) = crate::parse_remp_status(&entry.body).unwrap();
+            assert_eq!(receipt.message_id().as_hex_string(), entry.id);
+            let mut body = crate::db_serialize_remp_status(&receipt, &signature).unwrap();
+            body.insert("id".to_owned(), entry.id.clone().into());
+            assert_eq!(body, entry.body);
+        }
+    }
+
+    // not configured
+    let (_, _, parsed) = parse_block(
+        "6ce37a48b76f9ab9a5b33b727baf3e19da18a7bdee1cf3242ddb2a79c20715e4.boc",
+        None,
+    );
+    assert!(parsed.remp_receipts.is_empty());
+}
+
+#[test]
+fn test_account_summaries() {
+    let (_, block_id, parsed) = parse_block(
+        "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc",
+        Some(ParseOptions::default().account_summaries(2)),
+    );
+    assert!(!parsed.account_summaries.is_empty());
+
+    let mut tr_count = 0;
+    for summary in &parsed.account_summaries {
+        let transactions = parsed
+            .transactions
+            .iter()
+            .filter(|tr| tr.body["account_addr"] == summary.id)
+            .collect::<Vec<_>>();
+        assert!(!transactions.is_empty(), "{}", summary.id);
+        let gas_used = transactions
+            .iter()
+            .map(|tr| tr.body["compute"].get("gas_used").and_then(|gas| gas.as_u64()).unwrap_or(0))
+            .sum::<u64>();
+        let total_fees = transactions
+            .iter()
+            .map(|tr| tr.body["total_fees_dec"].as_str().unwrap().parse::<u128>().unwrap())
+            .sum::<u128>();
+        // lt strings are length prefixed so they are ordered as the numbers
+        let lts = transactions.iter().map(|tr| tr.body["lt"].as_str().unwrap());
+        let min_lt = lts.clone().min().unwrap();
+        let max_lt = lts.max().unwrap();
+
+        assert_eq!(summary.body["tr_count"], transactions.len(), "{}", summary.id);
+        assert_eq!(summary.body["gas_used"], crate::u64_to_string(gas_used), "{}", summary.id);
+        assert_eq!(summary.body["gas_used_dec"], gas_used.to_string(), "{}", summary.id);
+        assert_eq!(summary.body["total_fees_dec"], total_fees.to_string(), "{}", summary.id);
+        assert_eq!(summary.body["min_lt"], min_lt, "{}", summary.id);
+        assert_eq!(summary.body["max_lt"], max_lt, "{}", summary.id);
+        assert_eq!(summary.body["block_id"], block_id.as_hex_string());
+        assert!(summary.partition.unwrap() < 4);
+        tr_count += transactions.len();
+    }
+    assert_eq!(tr_count, parsed.transactions.len());
+
+    // not configured
+    let (_, _, parsed) = parse_block(
+        "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc",
+        None,
+    );
+    assert!(parsed.account_summaries.is_empty());
+}
+
+#[test]
+fn test_out_msg_queue_updates() {
+    let mut enqueued = 0;
+    let mut dequeued = 0;
+    for file in [
+        "18AFCDD25BE0989CE516504263EB351818A0FF8F6AB3689501C8E3B767EF413C.boc",
+        "3FAFAAB7B5D17E439CD9DDEF7EEC3C3BC9D28E7C181BA374218E13599F8F6657.boc",
+        "558651b80d5361fd7f31882d4df90bf8e3c0c58422684e752a47c6b57b7be62c.boc",
+        "6ce37a48b76f9ab9a5b33b727baf3e19da18a7bdee1cf3242ddb2a79c20715e4.boc",
+        "c594aec0b3e52b4177e90d3bf82542151b0edc3e5d86d745689ec25f3d943624.boc",
+    ] {
+        let (boc, block_id, parsed) =
+            parse_block(file, Some(ParseOptions::default().out_msg_queue_updates(1)));
+        let block = Block::construct_from_cell(read_single_root_boc(&boc).unwrap()).unwrap();
+
+        let mut expected = HashMap::new();
+        let out_msg_descr = block.read_extra().unwrap().read_out_msg_descr().unwrap();
+        out_msg_descr
+            .iterate_objects(|out_msg| {
+                let kind = match out_msg {
+                    OutMsg::New(_) | OutMsg::Transit(_) | OutMsg::TransitRequeued(_) => "enqueue",
+                    OutMsg::Dequeue(_) | OutMsg::DequeueImmediate(_) | OutMsg::DequeueShort(_) => {
+                        "dequeue"
+                    }
+                    _ => return Ok(true),
+                };
+                let env_hash = match &out_msg {
+                    OutMsg::DequeueShort(msg) => msg.msg_env_hash.clone(),
+                    _ => out_msg.out_message_cell().unwrap().repr_hash(),
+                };
+                expected.insert(env_hash.as_hex_string(), kind);
+                Ok(true)
+            })
+            .unwrap();
+
+        assert_eq!(parsed.out_msg_queue_updates.len(), expected.len(), "{}", file);
+        for update in &parsed.out_msg_queue_updates {
+            let env_hash = update.body["msg_env_hash"].as_str().unwrap();
+            assert_eq!(update.id, format!("{}:{}", block_id.as_hex_string(), env_hash));
+            assert_eq!(update.body["kind"], expected[env_hash], "{}", file);
+            assert_eq!(update.body["block_id"], block_id.as_hex_string());
+            assert_eq!(update.body["dst_prefix"].as_str().unwrap().len(), 16);
+            assert!(update.body["lt"].is_string());
+            let dst_prefix = u64::from_str_radix(update.body["dst_prefix"].as_str().unwrap(), 16).unwrap();
+            assert_eq!(update.partition, Some((dst_prefix >> 63) as u32));
+            match expected[env_hash] {
+                "enqueue" => enqueued += 1,
+                _ => dequeued += 1,
+            }
+        }
+
+        // not configured
+        let (_, _, parsed) = parse_block(file, None);
+        assert!(parsed.out_msg_queue_updates.is_empty());
+    }
+    assert_ne!(enqueued, 0);
+    assert_ne!(dequeued, 0);
+}
+
+#[test]
+fn test_out_msg_queue_updates_without_section() {
+    let file = "18AFCDD25BE0989CE516504263EB351818A0FF8F6AB3689501C8E3B767EF413C.boc";
+    let boc = read(Path::new("src/tests/data").join(file)).unwrap();
+    let cell = read_single_root_boc(&boc).unwrap();
+    let block = Block::construct_from_cell(cell.clone()).unwrap();
+    let config = BlockParserConfig::<JsonFieldsReducer> {
+        out_msg_queue_updates: Some(EntryConfig { reducer: None, sharding_depth: None, max_boc_field_size: None }),
+        ..Default::default()
+    };
+    let block_id = BlockIdExt::default();
+    let updates = ParserOutQueueUpdates::new(&config, &block_id);
+
+    let mut parsed = ParsedBlock::default();
+    updates.insert_entries(&block, &mut parsed).unwrap();
+    assert!(!parsed.out_msg_queue_updates.is_empty());
+
+    // the whole extra or only the out msg descr is pruned
+    let extra = cell.reference(3).unwrap();
+    for pruned_hash in [extra.repr_hash(), extra.reference(1).unwrap().repr_hash()] {
+        let proof = MerkleProof::create(&cell, |hash| *hash != pruned_hash).unwrap();
+        let pruned = Block::construct_from_bytes(&ton_dev_block::write_boc(&proof.proof).unwrap()).unwrap();
+        let mut parsed = ParsedBlock::default();
+        updates.insert_entries(&pruned, &mut parsed).unwrap();
+        assert!(parsed.out_msg_queue_updates.is_empty());
+    }
+}
+
+#[cfg(feature = "deserialize")]
+#[test]
+fn test_key_block_config() {
+    let file = "9C9906A80D020952E0192DC60C0B2BF1F55FE9A9E065606E8FE25C08BD1AA6B2.boc";
+    let (boc, block_id, parsed) =
+        parse_block(file, Some(ParseOptions::default().key_block_configs()));
+    let block = Block::construct_from_cell(read_single_root_boc(&boc).unwrap()).unwrap();
+    let info = block.read_info().unwrap();
+    assert!(info.key_block());
+    let master = block.read_extra().unwrap().read_custom().unwrap().unwrap();
+    let config = master.config().unwrap();
+
+    let entry = parsed.key_block_config.unwrap();
+    assert_eq!(entry.id, info.seq_no().to_string());
+    assert_eq!(entry.body["block_id"], block_id.as_hex_string());
+    assert_eq!(entry.body["seq_no"], info.seq_no());
+    assert_eq!(entry.body["config_addr"], config.config_addr.as_hex_string());
+    let parsed_config = crate::parse_config(entry.body["config"].as_object().unwrap()).unwrap();
+    for number in 0..=100 {
+        assert_eq!(config.config(number).unwrap(), parsed_config.config(number).unwrap(), "p{}", number);
+    }
+
+    // not a key block
+    let (_, _, parsed) = parse_block(
+        "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc",
+        Some(ParseOptions::default().key_block_configs()),
+    );
+    assert!(parsed.key_block_config.is_none());
+}
+
+#[test]
+fn test_raw_entries() {
+    let file = "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc";
+    let ids = |entries: &[ParsedEntry]| {
+        let mut ids = entries.iter().map(|entry| entry.id.clone()).collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+
+    let (_, _, full) = parse_block(file, Some(ParseOptions::default().sharding(2, 2, 2)));
+    let (boc, block_id, raw) = parse_block(file, Some(ParseOptions::default().sharding(2, 2, 2).raw()));
+
+    let block = raw.block.unwrap();
+    assert_eq!(block.id, full.block.unwrap().id);
+    assert_eq!(block.body.keys().collect::<Vec<_>>(), ["id", "block_id", "kind", "boc"]);
+    assert_eq!(block.body["kind"], "block");
+    assert_eq!(block.body["boc"], base64_encode(&boc));
+    assert!(!raw.transactions.is_empty());
+    assert!(!raw.messages.is_empty());
+    assert_eq!(ids(&raw.transactions), ids(&full.transactions));
+    assert_eq!(ids(&raw.messages), ids(&full.messages));
+    let partitions = |entries: &[ParsedEntry]| {
+        let mut partitions = entries.iter().map(|entry| (entry.id.clone(), entry.partition)).collect::<Vec<_>>();
+        partitions.sort();
+        partitions
+    };
+    assert_eq!(partitions(&raw.transactions), partitions(&full.transactions));
+    assert_eq!(partitions(&raw.messages), partitions(&full.messages));
+
+    let full_entries = full.transactions.iter().chain(&full.messages)
+        .map(|entry| (entry.id.clone(), entry))
+        .collect::<HashMap<_, _>>();
+    for (kind, entries) in [("transaction", &raw.transactions), ("message", &raw.messages)] {
+        for entry in entries {
+            assert_eq!(entry.body.keys().collect::<Vec<_>>(), ["id", "block_id", "kind", "boc"]);
+            assert_eq!(entry.body["id"], entry.id);
+            assert_eq!(entry.body["kind"], kind);
+            assert_eq!(entry.body["block_id"], block_id.as_hex_string());
+            let cell = read_single_root_boc(base64_decode(entry.body["boc"].as_str().unwrap()).unwrap()).unwrap();
+            assert_eq!(entry.id, cell.repr_hash().as_hex_string());
+            // the same cell as in the full entry
+            assert_eq!(entry.body["boc"], full_entries[&entry.id].body["boc"], "{}", entry.id);
+        }
+    }
+}
+
+#[test]
+fn test_doc_id_field() {
+    let file = "9C9906A80D020952E0192DC60C0B2BF1F55FE9A9E065606E8FE25C08BD1AA6B2.boc";
+    let entries = |parsed: ParsedBlock| {
+        let mut entries = Vec::new();
+        entries.extend(parsed.block);
+        entries.extend(parsed.transactions);
+        entries.extend(parsed.messages);
+        entries.extend(parsed.account_summaries);
+        entries.extend(parsed.key_block_config);
+        entries
+    };
+    let parse = |field| {
+        let options = ParseOptions::default()
+            .account_summaries(0)
+            .key_block_configs()
+            .doc_id_field(field);
+        entries(parse_block(file, Some(options)).2)
+    };
+
+    let expected = parse(DocIdField::Id);
+    for field in [DocIdField::Key, DocIdField::UnderscoreId] {
+        let renamed = parse(field);
+        assert_eq!(renamed.len(), expected.len());
+        for (renamed, expected) in renamed.iter().zip(&expected) {
+            assert_eq!(renamed.id, expected.id);
+            assert_eq!(renamed.partition, expected.partition);
+            let keys = renamed.body.keys().map(|key| match key.as_str() {
+                key if key == field.name() => "id",
+                key => key,
+            });
+            assert!(keys.eq(expected.body.keys().map(String::as_str)));
+            assert!(!renamed.body.contains_key("id"));
+            assert!(renamed.body.values().eq(expected.body.values()));
+        }
+    }
+}
+
+#[cfg(feature = "deserialize")]
+fn long_base64_strings(value: &Value, found: &mut Vec<String>) {
+    match value {
+        Value::String(text) if text.len() > 64 && base64_decode(text).is_ok() => found.push(text.clone()),
+        Value::Array(items) => items.iter().for_each(|item| long_base64_strings(item, found)),
+        Value::Object(map) => map.values().for_each(|item| long_base64_strings(item, found)),
+        _ => (),
+    }
+}
+
+#[cfg(feature = "deserialize")]
+#[test]
+fn test_omit_bodies() {
+    let file = "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc";
+    let (_, _, full) = parse_block(file, None);
+    let (_, _, parsed) = parse_block(file, Some(ParseOptions::default().omit_bodies()));
+    assert!(full.messages.iter().any(|entry| entry.body.contains_key("body")));
+    assert_eq!(parsed.messages.len(), full.messages.len());
+    assert_eq!(parsed.transactions.len(), full.transactions.len());
+
+    let mut entries = Vec::new();
+    entries.extend(parsed.block);
+    entries.extend(parsed.transactions);
+    entries.extend(parsed.messages);
+    for entry in &entries {
+        let mut found = Vec::new();
+        long_base64_strings(&Value::Object(entry.body.clone()), &mut found);
+        assert!(found.is_empty(), "{} has the base64 fields {:?}", entry.id, found);
+    }
+    // the hashes and the fee fields are kept
+    for (entry, full) in entries.iter().skip(1 + full.transactions.len()).zip(&full.messages) {
+        assert_eq!(entry.body.get("body_hash"), full.body.get("body_hash"));
+        assert_eq!(entry.body.get("fwd_fee"), full.body.get("fwd_fee"));
+        assert!(!entry.body.contains_key("boc"));
+    }
+
+    // the accounts without the cells are still parsed
+    let boc = read(
+        "src/tests/data/states/state_4723_0_c800000000000000_81832210A895E93967B7D2A0638159FC5FD88C1DB402545AAAABA509BE93017F"
+    ).unwrap();
+    let state = ShardStateUnsplit::construct_from_cell(read_single_root_boc(&boc).unwrap()).unwrap();
+    let mut accounts = Vec::new();
+    state.read_accounts().unwrap().iterate_objects(&mut |shard_account: ShardAccount| {
+        let set = crate::AccountSerializationSet {
+            account: shard_account.read_account()?,
+            prev_code_hash: None,
+            boc: ton_dev_block::write_boc(&shard_account.account_cell())?,
+            boc1: None,
+            proof: None,
+        };
+        let mut account = crate::db_serialize_account_ex("id", &set, crate::SerializationMode::Standart)?;
+        if account["acc_type"] == 1 {
+            crate::omit_body_fields(&mut account);
+            accounts.push(Value::Object(account));
+        }
+        Ok(true)
+    }).unwrap();
+    assert!(!accounts.is_empty());
+    let mut found = Vec::new();
+    long_base64_strings(&Value::Array(accounts.clone()), &mut found);
+    assert!(found.is_empty());
+    let map = serde_json::json!({ "accounts": accounts });
+    let parsed = crate::parse_state_unchecked(map.as_object().unwrap()).unwrap();
+    assert_eq!(parsed.read_accounts().unwrap().len().unwrap(), accounts.len());
+
+    // the same for the account entries of the parser, one of them has the init code hash
+    let boc = read(Path::new("src/tests/data").join(file)).unwrap();
+    let cell = read_single_root_boc(&boc).unwrap();
+    let block = Block::construct_from_cell(cell.clone()).unwrap();
+    let info = block.read_info().unwrap();
+    let id = BlockIdExt::with_params(
+        info.shard().clone(),
+        info.seq_no(),
+        cell.repr_hash(),
+        UInt256::calc_file_hash(&boc),
+    );
+    let mut shard_accounts = crate::block_parser::accounts::read_accounts(
+        block.read_state_update().unwrap().new
+    ).unwrap();
+    let mut account_id = None;
+    block.read_extra().unwrap().read_account_blocks().unwrap().iterate_objects(&mut |account_block: AccountBlock| {
+        account_id.get_or_insert(account_block.account_id().clone());
+        Ok(true)
+    }).unwrap();
+    let account = generate_test_account_by_init_code_hash(true);
+    let init_code_hash = account.init_code_hash().unwrap().as_hex_string();
+    let aug = account.aug().unwrap();
+    let shard_account = ShardAccount::with_params(&account, UInt256::default(), 0).unwrap();
+    shard_accounts.set_builder_serialized(
+        account_id.unwrap(),
+        &shard_account.write_to_new_cell().unwrap(),
+        &aug,
+    ).unwrap();
+
+    let parser = BlockParser::<NoTrace, JsonFieldsReducer>::new(
+        BlockParserConfig {
+            accounts: Some(EntryConfig { reducer: None, sharding_depth: None, max_boc_field_size: None }),
+            omit_bodies: true,
+            ..Default::default()
+        },
+        None,
+    );
+    let parsed = parser
+        .parse(
+            ParsingBlock {
+                id: &id,
+                block: &block,
+                root: &cell,
+                shard_state: None,
+                shard_accounts: Some(&shard_accounts),
+                data: &boc,
+                mc_seq_no: None,
+                proof: None,
+            },
+            false,
+        )
+        .unwrap();
+    let accounts = parsed.accounts.into_iter().map(|entry| Value::Object(entry.body)).collect::<Vec<_>>();
+    assert!(accounts.iter().any(|account| account["init_code_hash"] == init_code_hash.as_str()));
+    let mut found = Vec::new();
+    long_base64_strings(&Value::Array(accounts.clone()), &mut found);
+    assert!(found.is_empty(), "{:?}", found);
+    let map = serde_json::json!({ "accounts": accounts });
+    let parsed = crate::parse_state_unchecked(map.as_object().unwrap()).unwrap();
+    assert_eq!(parsed.read_accounts().unwrap().len().unwrap(), accounts.len());
+}
+
+#[test]
+fn test_entries_use_doc_ids() {
+    let (_, block_id, parsed) = parse_block(
+        "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc",
+        Some(ParseOptions::default().account_summaries(0)),
+    );
+    let is_doc_hash = |id: &str| {
+        id.len() == 64 && id.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+    };
+
+    let block = parsed.block.unwrap();
+    assert_eq!(block.id, block_id.as_hex_string());
+    assert!(is_doc_hash(&block.id));
+    assert!(!parsed.transactions.is_empty());
+    for entry in &parsed.transactions {
+        let cell = read_single_root_boc(base64_decode(entry.body["boc"].as_str().unwrap()).unwrap()).unwrap();
+        let transaction = Transaction::construct_from_cell(cell).unwrap();
+        assert_eq!(entry.id, crate::transaction_doc_id(&transaction).unwrap());
+        assert!(is_doc_hash(&entry.id));
+        assert_eq!(entry.body["block_id"], block.id);
+    }
+    let transaction_ids = parsed.transactions.iter().map(|entry| entry.id.as_str()).collect::<HashSet<_>>();
+    for entry in &parsed.messages {
+        if let Some(boc) = entry.body.get("boc") {
+            let cell = read_single_root_boc(base64_decode(boc.as_str().unwrap()).unwrap()).unwrap();
+            let message = Message::construct_from_cell(cell).unwrap();
+            assert_eq!(entry.id, crate::message_doc_id(&message).unwrap());
+        }
+        assert!(is_doc_hash(&entry.id));
+        for field in ["src_transaction_id", "dst_transaction_id"] {
+            if let Some(id) = entry.body.get(field) {
+                assert!(transaction_ids.contains(id.as_str().unwrap()), "{} {}", field, id);
+            }
+        }
+    }
+    for entry in &parsed.account_summaries {
+        let address = MsgAddressInt::from_str(&entry.id).unwrap();
+        assert_eq!(entry.id, crate::account_doc_id(&address));
+        let (workchain_id, account_id) = entry.id.split_once(':').unwrap();
+        assert_eq!(workchain_id, block.body["workchain_id"].to_string());
+        assert!(is_doc_hash(account_id));
+    }
+}
+
+fn resume_block_into(
+    file_rel_path: &str,
+    checkpoint: Option<&ParserCheckpoint>,
+    sink: &mut impl ParsedEntrySink,
+) -> Result<()> {
+    let boc = read(Path::new("src/tests/data").join(file_rel_path)).unwrap();
+    let cell = read_single_root_boc(&boc).unwrap();
+    let block = Block::construct_from_cell(cell.clone()).unwrap();
+    let info = block.read_info().unwrap();
+    let id = BlockIdExt::with_params(
+        info.shard().clone(),
+        info.seq_no(),
+        cell.repr_hash(),
+        UInt256::calc_file_hash(&boc),
+    );
+    let entry_config = || Some(EntryConfig { reducer: None, sharding_depth: None, max_boc_field_size: None });
+    let parser = BlockParser::<NoTrace, JsonFieldsReducer>::new(
+        BlockParserConfig {
+            blocks: entry_config(),
+            transactions: entry_config(),
+            messages: entry_config(),
+            account_summaries: entry_config(),
+            out_msg_queue_updates: entry_config(),
+            key_block_configs: entry_config(),
+            ..Default::default()
+        },
+        None,
+    );
+    parser.resume_into(
+        ParsingBlock {
+            id: &id,
+            block: &block,
+            root: &cell,
+            shard_state: None,
+            shard_accounts: None,
+            data: &boc,
+            mc_seq_no: None,
+            proof: None,
+        },
+        false,
+        checkpoint,
+        sink,
+    )
+}
+
+#[derive(Default)]
+struct RecordingSink {
+    ids: Vec<String>,
+    checkpoint: Option<String>,
+    fail_after: Option<usize>,
+}
+
+impl RecordingSink {
+    fn record(&mut self, kind: &str, entry: ParsedEntry) -> Result<()> {
+        if Some(self.ids.len()) == self.fail_after {
+            fail!("the process is killed")
+        }
+        self.ids.push(format!("{} {}", kind, entry.id));
+        Ok(())
+    }
+}
+
+impl ParsedEntrySink for RecordingSink {
+    fn on_block(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.record("block", entry)
+    }
+    fn on_proof(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.record("proof", entry)
+    }
+    fn on_transaction(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.record("transaction", entry)
+    }
+    fn on_message(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.record("message", entry)
+    }
+    fn on_account(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.record("account", entry)
+    }
+    fn on_remp_receipt(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.record("remp receipt", entry)
+    }
+    fn on_account_summary(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.record("account summary", entry)
+    }
+    fn on_out_msg_queue_update(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.record("out msg queue update", entry)
+    }
+    fn on_key_block_config(&mut self, entry: ParsedEntry) -> Result<()> {
+        self.record("key block config", entry)
+    }
+    fn on_checkpoint(&mut self, checkpoint: &ParserCheckpoint) -> Result<()> {
+        self.checkpoint = Some(checkpoint.to_string());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_resume_parsing_from_checkpoint() {
+    let file = "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc";
+    let mut full = RecordingSink::default();
+    resume_block_into(file, None, &mut full).unwrap();
+    assert!(full.ids.len() >= 4);
+    let (_, block_id, _) = parse_block(file, None);
+    assert_eq!(
+        full.checkpoint.as_ref().unwrap(),
+        &format!("{}:{}", &block_id.as_hex_string()[..16], full.ids.len())
+    );
+
+    let half = full.ids.len() / 2;
+    let mut first = RecordingSink { fail_after: Some(half), ..Default::default() };
+    resume_block_into(file, None, &mut first).expect_err("the sink must fail");
+    assert_eq!(first.ids.len(), half);
+
+    let checkpoint = ParserCheckpoint::from_str(first.checkpoint.as_ref().unwrap()).unwrap();
+    assert_eq!(checkpoint.delivered(), half as u64);
+    let mut second = RecordingSink::default();
+    resume_block_into(file, Some(&checkpoint), &mut second).unwrap();
+    assert_eq!(second.checkpoint, full.checkpoint);
+
+    let mut resumed = first.ids;
+    resumed.extend(second.ids);
+    assert_eq!(resumed, full.ids);
+
+    let other = ParserCheckpoint::from_str("0000000000000000:1").unwrap();
+    let err = resume_block_into(file, Some(&other), &mut RecordingSink::default()).unwrap_err();
+    assert!(err.to_string().starts_with("The parser checkpoint 0000000000000000:1 was taken on another block"));
+    assert!(ParserCheckpoint::from_str("0000000000000000").is_err());
+    assert!(ParserCheckpoint::from_str("89ed:1").is_err());
+}
+
+#[test]
+fn test_parse_batch_latest_accounts() {
+    let files = [
+        "046784ea72574ace66375629229700afa4c7e032a360fc94df4c20231fddea45.boc",
+        "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc",
+        "9C9906A80D020952E0192DC60C0B2BF1F55FE9A9E065606E8FE25C08BD1AA6B2.boc",
+    ];
+    let blocks = files.map(|file| {
+        let boc = read(Path::new("src/tests/data").join(file)).unwrap();
+        let cell = read_single_root_boc(&boc).unwrap();
+        let block = Block::construct_from_cell(cell.clone()).unwrap();
+        let info = block.read_info().unwrap();
+        let id = BlockIdExt::with_params(
+            info.shard().clone(),
+            info.seq_no(),
+            cell.repr_hash(),
+            UInt256::calc_file_hash(&boc),
+        );
+        let shard_accounts = crate::block_parser::accounts::read_accounts(
+            block.read_state_update().unwrap().new
+        ).unwrap();
+        (boc, cell, block, id, shard_accounts)
+    });
+    let parsing_blocks = || blocks.iter().map(|(boc, cell, block, id, shard_accounts)| ParsingBlock {
+        id,
+        block,
+        root: cell,
+        shard_state: None,
+        shard_accounts: Some(shard_accounts),
+        data: boc,
+        mc_seq_no: None,
+        proof: None,
+    });
+    let entry_config = || Some(EntryConfig { reducer: None, sharding_depth: None, max_boc_field_size: None });
+    let parser = BlockParser::<NoTrace, JsonFieldsReducer>::new(
+        BlockParserConfig {
+            transactions: entry_config(),
+            accounts: entry_config(),
+            ..Default::default()
+        },
+        None,
+    );
+
+    let separate: Vec<_> = parsing_blocks().map(|block| parser.parse(block, false).unwrap()).collect();
+    let mut batch = ParsedBlock::default();
+    parser.parse_batch_into(parsing_blocks(), false, &mut batch).unwrap();
+
+    // the transactions are emitted per block as usual
+    let transactions: Vec<_> = separate.iter().flat_map(|parsed| &parsed.transactions).map(|entry| &entry.id).collect();
+    assert_eq!(batch.transactions.iter().map(|entry| &entry.id).collect::<Vec<_>>(), transactions);
+
+    // the elector and the config accounts are touched by every block
+    assert_eq!(separate.iter().map(|parsed| parsed.accounts.len()).sum::<usize>(), 9);
+    let ids: Vec<_> = batch.accounts.iter().map(|entry| entry.id.as_str()).collect();
+    assert_eq!(ids, [
+        "-1:04f64c6afbff3dd10d8ba6707790ac9670d540f37a9448b0337baa6a5a92acac",
+        "-1:3333333333333333333333333333333333333333333333333333333333333333",
+        "-1:34517c7bdf5187c55af4f8b61fdc321588c7ab768dee24b006df29106458d7cf",
+        "-1:5555555555555555555555555555555555555555555555555555555555555555",
+    ]);
+    for account in &batch.accounts {
+        let last = separate.iter().rposition(|parsed| parsed.accounts.iter().any(|entry| entry.id == account.id)).unwrap();
+        let latest = separate[last].accounts.iter().find(|entry| entry.id == account.id).unwrap();
+        assert_eq!(account.body["last_block_id"], crate::block_doc_id(&blocks[last].3), "{}", account.id);
+        assert_eq!(account.body["boc"], latest.body["boc"], "{}", account.id);
+        assert_eq!(account.body["seq_no"], latest.body["seq_no"], "{}", account.id);
+    }
+}
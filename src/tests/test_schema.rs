@@ -0,0 +1,215 @@
+/*
+ * Copyright (C) ton.dev. All Rights Reserved.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.  You may obtain a copy of the
+ * License at:
+ *
+ * https://www.ever.dev/licenses
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific ton.dev software governing permissions and limitations
+ * under the License.
+ */
+
+use super::*;
+use crate::{
+    db_serialize_account_ex, db_serialize_message_ex, db_serialize_transaction_ex,
+    AccountSerializationSet, MessageSerializationSet, TransactionSerializationSet,
+};
+use jsonschema::{Draft, JSONSchema};
+use ton_dev_block::{
+    read_single_root_boc, write_boc, Deserializable, Message, MessageProcessingStatus,
+    ShardAccount, ShardStateUnsplit, Transaction, TransactionProcessingStatus,
+};
+
+const MODES: [SerializationMode; 3] = [
+    SerializationMode::Standart, SerializationMode::QServer, SerializationMode::Debug
+];
+
+fn validation_errors(schema: &Value, document: &Value) -> Vec<String> {
+    let schema = JSONSchema::options()
+        .with_draft(Draft::Draft202012)
+        .compile(schema)
+        .expect("schema must compile");
+    match schema.validate(document) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors.map(|err| format!("{}: {}", err.instance_path, err)).collect(),
+    }
+}
+
+// the schemas leave the objects open, so the fields missing from them are looked up separately
+fn unknown_fields(schema: &Value, document: &Value, path: &str, unknown: &mut Vec<String>) {
+    match document {
+        // the config params are matched by the name pattern
+        Value::Object(map) if schema.get("propertyNames").is_none() => {
+            let properties = match schema.get("properties").and_then(|p| p.as_object()) {
+                Some(properties) => properties,
+                None => return
+            };
+            for (name, value) in map {
+                let path = format!("{}/{}", path, name);
+                match properties.get(name) {
+                    Some(schema) => unknown_fields(schema, value, &path, unknown),
+                    None => unknown.push(path),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                unknown_fields(&schema["items"], item, &format!("{}/{}", path, index), unknown);
+            }
+        }
+        _ => ()
+    }
+}
+
+fn assert_valid(schema: &Value, document: &Value, name: &str) {
+    let errors = validation_errors(schema, document);
+    assert!(errors.is_empty(), "{} does not match the schema:\n{}", name, errors.join("\n"));
+    let mut unknown = Vec::new();
+    unknown_fields(schema, document, "", &mut unknown);
+    assert!(unknown.is_empty(), "{} has the fields missing from the schema:\n{}", name, unknown.join("\n"));
+}
+
+fn read_ethalon(name: &str) -> Value {
+    let ethalon = std::fs::read_to_string(format!("src/tests/data/{}-ethalon.json", name)).unwrap();
+    serde_json::from_str(&ethalon).unwrap()
+}
+
+#[test]
+fn test_block_ethalons_match_schema() {
+    for name in [
+        "046784ea72574ace66375629229700afa4c7e032a360fc94df4c20231fddea45",
+        "18AFCDD25BE0989CE516504263EB351818A0FF8F6AB3689501C8E3B767EF413C",
+        "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE",
+        "9C9906A80D020952E0192DC60C0B2BF1F55FE9A9E065606E8FE25C08BD1AA6B2",
+        "crafted-key-block",
+        "ea67954c1c58997c66b5d91b4a3369cfa795b96662c7f7ea7daad677266fb7a3",
+        "f7fdf140aa14f8dd8426e6c6aa339ae65e3bf492ce26dd7ce08916066d6d64c6",
+    ] {
+        assert_valid(&block_schema("id", SerializationMode::Standart), &read_ethalon(name), name);
+    }
+    let name = "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE-Q";
+    let ethalon = read_ethalon(name);
+    assert_valid(&block_schema("id", SerializationMode::QServer), &ethalon, name);
+
+    // the number conventions differ between the modes
+    assert!(!validation_errors(&block_schema("id", SerializationMode::Standart), &ethalon).is_empty());
+
+    let mut ethalon = ethalon;
+    ethalon["value_flow"]["minted_other"] = Value::Array(Vec::new());
+    ethalon["prev_ref"]["unknown"] = Value::Bool(true);
+    ethalon["unknown"] = Value::Bool(true);
+    let mut unknown = Vec::new();
+    unknown_fields(&block_schema("id", SerializationMode::QServer), &ethalon, "", &mut unknown);
+    assert_eq!(unknown, ["/prev_ref/unknown", "/unknown"]);
+}
+
+#[test]
+fn test_config_matches_schema() {
+    let master = &read_ethalon("9C9906A80D020952E0192DC60C0B2BF1F55FE9A9E065606E8FE25C08BD1AA6B2")["master"];
+    let config = serde_json::json!({
+        "config_addr": master["config_addr"],
+        "config": master["config"],
+    });
+    assert_valid(&config_schema(SerializationMode::Standart), &config, "key block config");
+
+    let mut config = config;
+    config["config"]["param"] = Value::Null;
+    assert!(!validation_errors(&config_schema(SerializationMode::Standart), &config).is_empty());
+}
+
+#[test]
+fn test_block_proof_ethalon_matches_schema() {
+    assert_valid(&block_proof_schema("_id", SerializationMode::Standart), &read_ethalon("proof"), "proof");
+}
+
+#[test]
+fn test_state_ethalons_match_schema() {
+    for name in [
+        "main_ever_dev_zerostate_-1_D270B87B2952B5BA7DAA70AAF0A8C361BEFCF4D8D2DB92F9640D5443070838E4",
+        "main_ever_dev_zerostate_0_97AF4602A57FC884F68BB4659BAB8875DC1F5E45A9FD4FBAFD0C9BC10AA5067C",
+        "state_4723_0_c800000000000000_81832210A895E93967B7D2A0638159FC5FD88C1DB402545AAAABA509BE93017F",
+    ] {
+        let name = format!("states/{}", name);
+        assert_valid(&state_schema("id", SerializationMode::Standart), &read_ethalon(&name), &name);
+        let name = format!("{}-Q", name);
+        assert_valid(&state_schema("id", SerializationMode::QServer), &read_ethalon(&name), &name);
+    }
+    assert_valid(&state_schema("id", SerializationMode::Debug), &read_ethalon("zerostate"), "zerostate");
+}
+
+fn serialize_message(cell: ton_dev_block::Cell, mode: SerializationMode) -> Value {
+    let set = MessageSerializationSet {
+        message: Message::construct_from_cell(cell.clone()).unwrap(),
+        id: cell.repr_hash(),
+        block_id: None,
+        transaction_id: None,
+        transaction_now: None,
+        status: MessageProcessingStatus::Finalized,
+        boc: write_boc(&cell).unwrap(),
+        proof: None,
+    };
+    db_serialize_message_ex("id", &set, mode).unwrap().into()
+}
+
+#[test]
+fn test_serialized_transactions_and_messages_match_schema() {
+    for file in ["aborted_bounced.boc", "ext_in&int_out.boc", "ext_in&int_out_special.boc", "int_in.boc"] {
+        let boc = std::fs::read(format!("src/tests/data/transactions/{}", file)).unwrap();
+        let cell = read_single_root_boc(&boc).unwrap();
+        let set = TransactionSerializationSet {
+            id: cell.repr_hash(),
+            transaction: Transaction::construct_from_cell(cell).unwrap(),
+            status: TransactionProcessingStatus::Finalized,
+            block_id: None,
+            workchain_id: 0,
+            boc,
+            proof: None,
+        };
+        let mut messages = Vec::new();
+        if let Some(cell) = set.transaction.in_msg_cell() {
+            messages.push(cell);
+        }
+        set.transaction.out_msgs.iterate_slices(|slice| {
+            messages.extend(slice.reference_opt(0));
+            Ok(true)
+        }).unwrap();
+        for mode in MODES {
+            let transaction = db_serialize_transaction_ex("id", &set, mode).unwrap().into();
+            assert_valid(&transaction_schema("id", mode), &transaction, file);
+            for cell in &messages {
+                let message = serialize_message(cell.clone(), mode);
+                assert_valid(&message_schema("id", mode), &message, &format!("message of {}", file));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_serialized_accounts_match_schema() {
+    let boc = std::fs::read(
+        "src/tests/data/states/state_4723_0_c800000000000000_81832210A895E93967B7D2A0638159FC5FD88C1DB402545AAAABA509BE93017F"
+    ).unwrap();
+    let state = ShardStateUnsplit::construct_from_cell(read_single_root_boc(&boc).unwrap()).unwrap();
+    let mut count = 0;
+    state.read_accounts().unwrap().iterate_objects(&mut |shard_account: ShardAccount| {
+        let set = AccountSerializationSet {
+            account: shard_account.read_account()?,
+            prev_code_hash: None,
+            boc: write_boc(&shard_account.account_cell())?,
+            boc1: None,
+            proof: None,
+        };
+        for mode in MODES {
+            let account = db_serialize_account_ex("id", &set, mode)?.into();
+            assert_valid(&account_schema("id", mode), &account, "account");
+        }
+        count += 1;
+        Ok(true)
+    }).unwrap();
+    assert_ne!(count, 0);
+}
@@ -21,7 +21,13 @@ use ton_dev_block::{
 };
 use pretty_assertions::assert_eq;
 use std::{fs::read, path::Path};
-use ton_api::{ton::ton_node::rempmessagestatus, IntoBoxed};
+// the round trip tests need the deserializer
+#[cfg(all(feature = "deserialize", feature = "remp"))]
+use ton_api::{ton::ton_node::{rempmessagestatus, RempMessageLevel}, IntoBoxed};
+#[cfg(all(feature = "deserialize", feature = "remp"))]
+use crate::{remp_kind_to_status_template, RempStatusKind};
+#[cfg(feature = "deserialize")]
+use crate::parse_block_info_refs;
 
 include!("./test_common.rs");
 
@@ -766,6 +772,7 @@ r#"{
   "body": "te6ccgEBAQEACgAADz/////////0",
   "body_hash": "7a0b957a15e93cca3ce96ccb4aecf275a3718a263c8aeca2ab14fe6e1e62172c",
   "msg_type": 1,
+  "msg_type_name": "extIn",
   "src": ":0102030405",
   "dst": "0:0000000000000000000000000000000000000000000000000000000000000000",
   "dst_workchain_id": 0,
@@ -935,6 +942,7 @@ r#"{
   "body": "te6ccgECAwEAAVUAAd7///////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////8BAd7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v7+/v4CAN6mpqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqampqY=",
   "body_hash": "7cedc673b096999b859bad6d552c4574bec3c5aafca70fe586794cc6aff7326b",
   "msg_type": 0,
+  "msg_type_name": "internal",
   "src": "",
   "dst": "0:0000000000000000000000000000000000000000000000000000000000000000",
   "dst_workchain_id": 0,
@@ -1250,6 +1258,35 @@ fn test_masterblock_with_copyleft_into_json() {
     )
 }
 
+#[test]
+fn test_masterblock_with_mint_msg_into_json() {
+    let boc = read("src/tests/data/89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc").unwrap();
+    let mut block = Block::construct_from_bytes(&boc).unwrap();
+    let mut extra = block.read_extra().unwrap();
+    let mut custom = extra.read_custom().unwrap().unwrap();
+    let recover_msg = custom.read_recover_create_msg().unwrap().unwrap();
+    custom.write_mint_msg(Some(&recover_msg)).unwrap();
+    extra.write_custom(Some(&custom)).unwrap();
+    block.write_extra(&extra).unwrap();
+
+    let id = block.hash().unwrap();
+    let set = BlockSerializationSet { block, id, status: BlockProcessingStatus::Finalized, boc };
+    let json = Value::from(db_serialize_block("id", &set).unwrap());
+    let mint_msg = &json["master"]["mint_msg"];
+    assert_eq!(mint_msg, &json["master"]["recover_create_msg"]);
+    assert_eq!(mint_msg["msg_type"], 2);
+    let message_cell = recover_msg.message_cell().unwrap();
+    assert_eq!(mint_msg["in_msg"]["msg_id"], message_cell.repr_hash().as_hex_string());
+    assert_eq!(mint_msg["in_msg"]["boc"], base64_encode(write_boc(&message_cell).unwrap()));
+
+    // workchain blocks have no masterchain extra
+    let boc = read("src/tests/data/18AFCDD25BE0989CE516504263EB351818A0FF8F6AB3689501C8E3B767EF413C.boc").unwrap();
+    let block = Block::construct_from_bytes(&boc).unwrap();
+    let id = block.hash().unwrap();
+    let set = BlockSerializationSet { block, id, status: BlockProcessingStatus::Finalized, boc };
+    assert!(db_serialize_block("id", &set).unwrap().get("master").is_none());
+}
+
 fn get_validator_set() -> ValidatorSet {
     let keydat = base64_decode("7w3fX5jiuo8PyQoFaEL+K9pE/XvbKjH63i0JcraLlBM=").unwrap();
 
@@ -1625,6 +1662,22 @@ fn test_balance_delta() {
   check_transaction_field("int_in.boc", "balance_delta", "0c71b149203e800", "0x71b149203e800");
 }
 
+#[test]
+fn test_balance_delta_dec() {
+  // in value minus out values minus total fees, the fees are taken from the aborted
+  // and the bounced transactions too
+  check_transaction_field("aborted_bounced.boc", "balance_delta_dec", "0", Value::Null);
+  check_transaction_field("ext_in&int_out.boc", "balance_delta_dec", "-518017110", Value::Null);
+  check_transaction_field("int_in.boc", "balance_delta_dec", "2000100000000000", Value::Null);
+
+  let boc = std::fs::read("src/tests/data/transactions/aborted_bounced.boc").unwrap();
+  let transaction = Transaction::construct_from_bytes(&boc).unwrap();
+  match transaction.read_description().unwrap() {
+    TransactionDescr::Ordinary(descr) => assert!(descr.aborted && descr.bounce.is_some()),
+    other => panic!("{:?}", other)
+  }
+}
+
 #[test]
 fn test_ext_in_msg_fee() {
   check_transaction_field("aborted_bounced.boc", "ext_in_msg_fee", Value::Null, Value::Null);
@@ -1633,6 +1686,57 @@ fn test_ext_in_msg_fee() {
   check_transaction_field("int_in.boc", "ext_in_msg_fee", Value::Null, Value::Null);
 }
 
+#[test]
+fn test_transaction_flat() {
+    let boc = std::fs::read("src/tests/data/transactions/ext_in&int_out.boc").unwrap();
+    let cell = read_single_root_boc(&boc).unwrap();
+    let set = TransactionSerializationSet {
+        block_id: None,
+        boc,
+        id: cell.repr_hash(),
+        proof: None,
+        status: TransactionProcessingStatus::Finalized,
+        workchain_id: 0,
+        transaction: Transaction::construct_from_cell(cell).unwrap(),
+    };
+    for mode in [SerializationMode::Standart, SerializationMode::QServer] {
+        let nested = serde_json::json!(db_serialize_transaction_ex("id", &set, mode).unwrap());
+        let flat = serde_json::json!(db_serialize_transaction_flat("id", &set, mode).unwrap());
+        assert_eq!(flat["compute_gas_used"], nested["compute"]["gas_used"]);
+        assert_eq!(flat["compute_exit_code"], nested["compute"]["exit_code"]);
+        assert_eq!(flat["compute_success"], nested["compute"]["success"]);
+        assert_eq!(flat["action_result_code"], nested["action"]["result_code"]);
+        assert_eq!(flat["action_total_fwd_fees"], nested["action"]["total_fwd_fees"]);
+        assert_eq!(flat["storage_fees_collected"], nested["storage"]["storage_fees_collected"]);
+        assert_eq!(flat["aborted"], nested["aborted"]);
+        // the nested phases are kept
+        assert_eq!(flat["compute"], nested["compute"]);
+        assert!(!flat["compute_gas_used"].is_null());
+        assert!(!flat["action_result_code"].is_null());
+    }
+    let flat = db_serialize_transaction_flat("id", &set, SerializationMode::Standart).unwrap();
+    assert!(flat.contains_key("action_total_fwd_fees_dec"));
+    assert!(flat.contains_key("storage_fees_collected_dec"));
+
+    // skipped compute phase
+    let transaction = generate_tranzaction(AccountId::from([55; 32]));
+    let id = transaction.hash().unwrap();
+    let set = TransactionSerializationSetEx {
+        transaction: &transaction,
+        id: &id,
+        status: TransactionProcessingStatus::Finalized,
+        block_id: None,
+        workchain_id: None,
+        boc: &[],
+        proof: None,
+    };
+    let flat = db_serialize_transaction_flat("id", set, SerializationMode::QServer).unwrap();
+    assert_eq!(flat["compute"]["skipped_reason"], 0);
+    for field in ["compute_gas_used", "compute_exit_code", "compute_success"] {
+        assert!(!flat.contains_key(field), "{}", field);
+    }
+}
+
 #[test]
 fn test_serialize_deleted_account_s() {
   let account = generate_test_account_by_init_code_hash(true);
@@ -1650,7 +1754,8 @@ r#"{
   "id": "0:0000000000000000000000000000000000000000000000000000000000000000",
   "workchain_id": 0,
   "acc_type": 3,
-  "prev_code_hash": "3c28164f21b76a53cfe73510197b99c735d4d97b652e6950f317bcbfe955848a"
+  "prev_code_hash": "3c28164f21b76a53cfe73510197b99c735d4d97b652e6950f317bcbfe955848a",
+  "deleted": true
 }"#)
 }
 
@@ -1672,7 +1777,8 @@ r#"{
   "workchain_id": 0,
   "acc_type": 3,
   "acc_type_name": "NonExist",
-  "prev_code_hash": "3c28164f21b76a53cfe73510197b99c735d4d97b652e6950f317bcbfe955848a"
+  "prev_code_hash": "3c28164f21b76a53cfe73510197b99c735d4d97b652e6950f317bcbfe955848a",
+  "deleted": true
 }"#)
 }
 
@@ -1690,6 +1796,7 @@ fn test_block_order() {
     assert_eq!("17b00540960604", block_order(&block, 123).unwrap());
 }
 
+#[cfg(all(feature = "deserialize", feature = "remp"))]
 fn se_deserialise_remp_status(status: RempMessageStatus) {
     let rr = ton_api::ton::ton_node::rempreceipt::RempReceipt {
         message_id: "18AFCDD25BE0989CE516504263EB351818A0FF8F6AB3689501C8E3B767EF413C".parse().unwrap(),
@@ -1707,8 +1814,15 @@ fn se_deserialise_remp_status(status: RempMessageStatus) {
 
     assert_eq!(rr, rr1);
     assert_eq!(signature, signature1);
+
+    let options = SerializationOptions { composite_shards: true, ..Default::default() };
+    let composite = db_serialize_remp_status_with_options(&rr, &signature, options).unwrap();
+    assert!(composite.get("wc").is_none());
+    let (rr2, _) = crate::deserialize::parse_remp_status(&composite).unwrap();
+    assert_eq!(rr, rr2);
 }
 
+#[cfg(all(feature = "deserialize", feature = "remp"))]
 #[test]
 fn test_se_deserialise_remp_accepted() {
     se_deserialise_remp_status(
@@ -1732,6 +1846,7 @@ fn test_se_deserialise_remp_accepted() {
     );
 }
 
+#[cfg(all(feature = "deserialize", feature = "remp"))]
 #[test]
 fn test_se_deserialise_remp_duplicate() {
     se_deserialise_remp_status(
@@ -1748,6 +1863,7 @@ fn test_se_deserialise_remp_duplicate() {
     );
 }
 
+#[cfg(all(feature = "deserialize", feature = "remp"))]
 #[test]
 fn test_se_deserialise_remp_ignored() {
     se_deserialise_remp_status(
@@ -1765,11 +1881,13 @@ fn test_se_deserialise_remp_ignored() {
     );
 }
 
+#[cfg(all(feature = "deserialize", feature = "remp"))]
 #[test]
 fn test_se_deserialise_remp_new() {
     se_deserialise_remp_status(RempMessageStatus::TonNode_RempNew);
 }
 
+#[cfg(all(feature = "deserialize", feature = "remp"))]
 #[test]
 fn test_se_deserialise_remp_rejected() {
     se_deserialise_remp_status(
@@ -1788,6 +1906,7 @@ fn test_se_deserialise_remp_rejected() {
     );
 }
 
+#[cfg(all(feature = "deserialize", feature = "remp"))]
 #[test]
 fn test_se_deserialise_remp_sent() {
     se_deserialise_remp_status(
@@ -1800,11 +1919,54 @@ fn test_se_deserialise_remp_sent() {
     );
 }
 
+#[cfg(all(feature = "deserialize", feature = "remp"))]
 #[test]
 fn test_se_deserialise_remp_timeout() {
     se_deserialise_remp_status(RempMessageStatus::TonNode_RempTimeout);
 }
 
+#[cfg(all(feature = "deserialize", feature = "remp"))]
+#[test]
+fn test_remp_kind_strings() {
+    let block_id = BlockIdExt::with_params(
+        ton_dev_block::ShardIdent::with_tagged_prefix(0, 0x3800_0000_0000_0000).unwrap(),
+        1830539,
+        "18AFCDD25BE0989CE516504263EB356618A0FF8F6AB3689501C8E3B767EF413C".parse().unwrap(),
+        "18AFCDD25BE0989CE516554263EB351818A0FF8F6AB3689501C8E3B767EF413C".parse().unwrap()
+    );
+    let mut names = std::collections::HashSet::new();
+    for kind in RempStatusKind::ALL {
+        let name = kind.name();
+        assert!(names.insert(name), "{} is used twice", name);
+        assert_eq!(remp_kind_to_status_template(name).unwrap(), kind);
+
+        let status = match kind.clone() {
+            RempStatusKind::Accepted(level) => RempMessageStatus::TonNode_RempAccepted(
+                rempmessagestatus::RempAccepted { level, block_id: block_id.clone(), master_id: Default::default() }
+            ),
+            RempStatusKind::Duplicate => RempMessageStatus::TonNode_RempDuplicate(
+                rempmessagestatus::RempDuplicate { block_id: block_id.clone() }
+            ),
+            RempStatusKind::Ignored(level) => RempMessageStatus::TonNode_RempIgnored(
+                rempmessagestatus::RempIgnored { level, block_id: block_id.clone() }
+            ),
+            RempStatusKind::New => RempMessageStatus::TonNode_RempNew,
+            RempStatusKind::Rejected(level) => RempMessageStatus::TonNode_RempRejected(
+                rempmessagestatus::RempRejected { level, block_id: block_id.clone(), error: "error".to_string() }
+            ),
+            RempStatusKind::SentToValidators => RempMessageStatus::TonNode_RempSentToValidators(
+                rempmessagestatus::RempSentToValidators { sent_to: 1, total_validators: 2 }
+            ),
+            RempStatusKind::Timeout => RempMessageStatus::TonNode_RempTimeout,
+        };
+        assert_eq!(remp_status_kind_string(&status), name);
+        // the serializer writes the kind the parser reads back
+        se_deserialise_remp_status(status);
+    }
+    assert!(remp_kind_to_status_template("Accepted").is_err());
+}
+
+#[cfg(feature = "deserialize")]
 #[test]
 fn test_se_deserialise_mesh_config() {
 
@@ -1844,7 +2006,7 @@ fn test_se_deserialise_mesh_config() {
 
     let json = serde_json::to_string_pretty(
         &serde_json::json!({
-            "p58": serialize_mesh_config(&mesh_config).unwrap()
+            "p58": serialize_mesh_config(&mesh_config, SerializationOptions::default()).unwrap()
         })
     ).unwrap();
     println!("{}", json);
@@ -1853,6 +2015,19 @@ fn test_se_deserialise_mesh_config() {
     let config_params = crate::deserialize::parse_config(&map).unwrap();
 
     assert_eq!(mesh_config, config_params.mesh_config().unwrap().unwrap());
+
+    let options = SerializationOptions { composite_shards: true, ..Default::default() };
+    let mut composite = serde_json::json!({
+        "p58": serialize_mesh_config(&mesh_config, options).unwrap()
+    });
+    assert_eq!(composite["p58"][0]["zerostate"]["shard"], "-1,8000000000000000");
+    assert!(composite["p58"][0]["zerostate"].get("wc").is_none());
+    let config_params = crate::deserialize::parse_config(composite.as_object().unwrap()).unwrap();
+    assert_eq!(mesh_config, config_params.mesh_config().unwrap().unwrap());
+
+    composite["p58"][1]["zerostate"]["wc"] = 0.into();
+    let err = crate::deserialize::parse_config(composite.as_object().unwrap()).unwrap_err();
+    assert!(err.to_string().contains("has conflicting fields `shard` = -1 and `wc` = 0"), "{}", err);
 }
 
 
@@ -1975,3 +2150,435 @@ r#"{
   }
 }"#)
 }
+
+#[test]
+fn test_canonical_block_json() {
+    let boc = read("src/tests/data/89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc").unwrap();
+    let cell = read_single_root_boc(&boc).unwrap();
+    let block = Block::construct_from_cell(cell).unwrap();
+    let id = block.hash().unwrap();
+    let set = BlockSerializationSet {
+        block,
+        id,
+        status: BlockProcessingStatus::Proposed,
+        boc
+    };
+
+    // the digest of the ethalon document with the sorted keys and without whitespace
+    let digest = "926f137f19ce173bc8a205600938d4b3d3d5d70187a72e7687f23f4241bbab5e";
+    let canonical = db_serialize_block_canonical("id", &set, SerializationMode::Standart).unwrap();
+    assert!(canonical.starts_with(concat!(
+        r#"{"account_blocks":[{"account_addr":"-1:3333333333333333333333333333333333333333333333333333333333333333","#,
+        r#""new_hash":"4c1391ab591e27eae25cbf548d3864794e5fb3760ead0a05518a73500509f8b4","#,
+    )), "{}", &canonical[..200]);
+    assert_eq!(canonical.len(), 27598);
+    assert_eq!(hex::encode(sha256_digest(canonical.as_bytes())), digest);
+
+    let value = Value::from(db_serialize_block_ex("id", &set, SerializationMode::Standart).unwrap());
+    let mut streamed = Vec::new();
+    write_canonical(&value, &mut streamed).unwrap();
+    assert_eq!(hex::encode(sha256_digest(&streamed)), digest);
+
+    let value = serde_json::json!({ "b": [1, "2", null], "a": { "d": true, "c": -3 } });
+    assert_eq!(to_canonical_string(&value).unwrap(), r#"{"a":{"c":-3,"d":true},"b":[1,"2",null]}"#);
+    assert!(to_canonical_string(&serde_json::json!({ "a": 1.5 })).is_err());
+}
+
+#[cfg(feature = "deserialize")]
+fn serialize_block_refs(block: Block) -> Map<String, Value> {
+    let boc = write_boc(&block.serialize().unwrap()).unwrap();
+    let id = block.hash().unwrap();
+    let set = BlockSerializationSet { block, id, status: BlockProcessingStatus::Finalized, boc };
+    db_serialize_block_ex("id", &set, SerializationMode::Standart).unwrap()
+}
+
+#[cfg(feature = "deserialize")]
+#[test]
+fn test_block_prev_refs() {
+    let boc = read("src/tests/data/89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE.boc").unwrap();
+    let mut block = Block::construct_from_bytes(&boc).unwrap();
+    let info = block.read_info().unwrap();
+    let prev1 = info.read_prev_ref().unwrap().prev1().unwrap();
+    assert!(!info.after_merge());
+
+    // ordinary block
+    let map = serialize_block_refs(block.clone());
+    let prev_refs = map["prev_refs"].as_array().unwrap();
+    assert_eq!(prev_refs.len(), 1);
+    assert_eq!(prev_refs[0], map["prev_ref"]);
+    assert_eq!(prev_refs[0]["seq_no"], prev1.seq_no);
+    let mut parsed = BlockInfo::default();
+    parse_block_info_refs(&map, &mut parsed).unwrap();
+    assert_eq!(parsed.read_prev_ref().unwrap(), info.read_prev_ref().unwrap());
+    assert_eq!(parsed.read_master_ref().unwrap(), info.read_master_ref().unwrap());
+    assert!(!parsed.after_merge());
+    assert_eq!(parsed.after_split(), info.after_split());
+
+    // after merge block
+    let prev2 = ExtBlkRef {
+        end_lt: prev1.end_lt + 3,
+        seq_no: prev1.seq_no + 1,
+        root_hash: UInt256::from([2; 32]),
+        file_hash: UInt256::from([3; 32]),
+    };
+    let mut merged = info.clone();
+    merged.set_prev_stuff(true, &BlkPrevInfo::new(vec![prev1.clone(), prev2.clone()]).unwrap()).unwrap();
+    merged.write_master_ref(Some(&BlkMasterInfo { master: prev2.clone() })).unwrap();
+    block.write_info(&merged).unwrap();
+    let map = serialize_block_refs(block);
+    assert_eq!(map["after_merge"], true);
+    let prev_refs = map["prev_refs"].as_array().unwrap();
+    assert_eq!(prev_refs.len(), 2);
+    assert_eq!(prev_refs[0], map["prev_ref"]);
+    assert_eq!(prev_refs[1], map["prev_alt_ref"]);
+    assert_eq!(prev_refs[1]["end_lt_dec"], (prev1.end_lt + 3).to_string());
+    let mut parsed = BlockInfo::default();
+    parse_block_info_refs(&map, &mut parsed).unwrap();
+    assert!(parsed.after_merge());
+    assert_eq!(parsed.read_prev_ref().unwrap().prev2().unwrap(), Some(prev2.clone()));
+    assert_eq!(parsed.read_master_ref().unwrap().unwrap().master, prev2);
+
+    // the count of the prev blocks must agree with the after_merge flag
+    let mut map = map;
+    map.insert("after_merge".to_string(), false.into());
+    let err = parse_block_info_refs(&map, &mut BlockInfo::default()).unwrap_err();
+    assert!(err.to_string().starts_with("root/prev_refs must have 1 item for the block not after merge but 2 given"));
+}
+
+#[cfg(all(feature = "deserialize", feature = "remp"))]
+#[test]
+fn test_block_id_ext_layouts() {
+    let ids = [
+        BlockIdExt::with_params(
+            ton_dev_block::ShardIdent::with_tagged_prefix(0, 0x3800_0000_0000_0000).unwrap(),
+            1830539,
+            UInt256::from([1; 32]),
+            UInt256::from([2; 32]),
+        ),
+        BlockIdExt::with_params(
+            ton_dev_block::ShardIdent::masterchain(),
+            17,
+            UInt256::from([3; 32]),
+            UInt256::from([4; 32]),
+        ),
+    ];
+    for id in ids {
+        let nested = serialize_block_id_ext(&id, true);
+        assert_eq!(nested["wc"], id.shard().workchain_id());
+        assert_eq!(nested["seqno"], id.seq_no());
+        assert_eq!(crate::parse_separated_block_id_ext(nested.as_object().unwrap()).unwrap(), id);
+
+        // the flat layout is the one of the REMP receipts
+        let flat = serialize_block_id_ext(&id, false);
+        let rr = ton_api::ton::ton_node::rempreceipt::RempReceipt {
+            message_id: UInt256::from([5; 32]),
+            status: RempMessageStatus::TonNode_RempDuplicate(
+                rempmessagestatus::RempDuplicate { block_id: id.clone() }
+            ),
+            timestamp: 1640011209924,
+            source_id: UInt256::from([6; 32]),
+        }.into_boxed();
+        let mut map = db_serialize_remp_status(&rr, &[1, 2, 3]).unwrap();
+        for (key, value) in flat.as_object().unwrap() {
+            assert_eq!(map.remove(key).as_ref(), Some(value), "{}", key);
+        }
+        map.extend(flat.as_object().unwrap().clone());
+        let (rr1, _) = crate::deserialize::parse_remp_status(&map).unwrap();
+        assert_eq!(rr, rr1);
+    }
+
+    let err = crate::parse_separated_block_id_ext(&Map::new()).unwrap_err();
+    assert!(err.to_string().starts_with("root must have the field `shard`"));
+}
+
+#[test]
+fn test_normalized_message_hash() {
+    // wallet v3 transfer: signature, subwallet id 698983191, valid until, seqno 5,
+    // send mode 3 and the internal message of 1 token in the reference
+    let boc = base64_decode(
+        "te6ccgEBAgEAqQAB34gAtLS0tLS0tLS0tLS0tLS0tLS0tLS0tLS0tLS0tLS0tLQACBAYICgwOEBIUFhgaHB4gIiQmKCo\
+        sLjAyNDY4Ojw+QEJERkhKTE5QUlRWWFpcXmBiZGZoamxucHJ0dnh6fH6AU1NGLsqn4gAAAAAKBwBAGhiABERERERERER\
+        ERERERERERERERERERERERERERERERERIdzWUAAAAAAAAAAAAAAAAAAA"
+    ).unwrap();
+    let cell = read_single_root_boc(&boc).unwrap();
+    assert_eq!(cell.repr_hash().as_hex_string(), "89bc0da92900e71dd5c745aa6f709b7d471581e022cdeb29ff7463b16422e650");
+    let message = Message::construct_from_cell(cell.clone()).unwrap();
+    let normalized = "56bcfdee2e85a1c43abb2b4a99852739d32b0746187c388f18876a5868551e8e";
+    assert_eq!(normalized_message_hash(&message).unwrap().unwrap().as_hex_string(), normalized);
+
+    let set = MessageSerializationSet {
+        message,
+        id: cell.repr_hash(),
+        block_id: None,
+        transaction_id: None,
+        transaction_now: None,
+        status: MessageProcessingStatus::Finalized,
+        boc,
+        proof: None,
+    };
+    let map = db_serialize_message_with_normalized_hash("id", &set, SerializationMode::Standart).unwrap();
+    assert_eq!(map["msg_hash_normalized"], normalized);
+    let mut plain = db_serialize_message_ex("id", &set, SerializationMode::Standart).unwrap();
+    plain.insert("msg_hash_normalized".to_string(), normalized.into());
+    assert_eq!(plain, map);
+
+    // the internal messages and the short bodies are left alone
+    let internal = Message::construct_from_cell(cell.reference(0).unwrap()).unwrap();
+    assert!(internal.is_internal());
+    assert_eq!(normalized_message_hash(&internal).unwrap(), None);
+    let mut short = set.message.clone();
+    short.set_body(SliceData::new(vec![0x3F, 0xFF, 0xF4]));
+    assert_eq!(normalized_message_hash(&short).unwrap(), None);
+}
+
+#[test]
+fn test_deserialise_remp_status_with_defaults() {
+    let rr = ton_api::ton::ton_node::rempreceipt::RempReceipt {
+        message_id: "18AFCDD25BE0989CE516504263EB351818A0FF8F6AB3689501C8E3B767EF413C".parse().unwrap(),
+        status: RempMessageStatus::TonNode_RempTimeout,
+        timestamp: 1640011209924,
+        source_id: UInt256::default(),
+    }.into_boxed();
+    let mut map = db_serialize_remp_status(&rr, &[1, 2, 3, 4]).unwrap();
+    map.remove("timestamp");
+    map.remove("source_id");
+
+    assert!(crate::deserialize::parse_remp_status(&map).is_err());
+    assert!(crate::deserialize::parse_remp_status_with_defaults(&map, Some(1640011209924), false).is_err());
+    assert!(crate::deserialize::parse_remp_status_with_defaults(&map, None, true).is_err());
+
+    let (rr1, _) = crate::deserialize::parse_remp_status_with_defaults(&map, Some(1640011209924), true).unwrap();
+    assert_eq!(rr, rr1);
+}
+
+#[test]
+fn test_serialize_url_safe_base64() {
+    let mut builder = BuilderData::new();
+    builder.append_raw(&[0xfb, 0xff, 0xbf, 0xfe, 0xef], 40).unwrap();
+    let cell = builder.into_cell().unwrap();
+    let mut json = serde_json::json!({
+        "id": "00",
+        "boc": base64_encode(write_boc(&cell).unwrap()),
+        "messages": [{ "body": base64_encode([0xfb, 0xff]) }],
+        "out_queue": [{ "msg": base64_encode([0xfb, 0xff, 0xbf]) }],
+        "name": "a+b/c=",
+    });
+    convert_base64_fields(&mut json, Base64Format::UrlSafeUnpadded);
+    let boc = json["boc"].as_str().unwrap();
+    assert!(!boc.contains(['+', '/', '=']), "{}", boc);
+    assert_eq!(boc, base64_encode_as(write_boc(&cell).unwrap(), Base64Format::UrlSafeUnpadded));
+    assert_eq!(json["messages"][0]["body"], "-_8");
+    assert_eq!(json["out_queue"][0]["msg"], "-_-_");
+    assert_eq!(json["name"], "a+b/c=");
+}
+
+#[cfg(feature = "code_label")]
+#[test]
+fn test_account_code_label() {
+    let account = generate_test_account_by_init_code_hash(false);
+    let code_hash = account.get_code_hash().unwrap();
+    let boc = account.write_to_bytes().unwrap();
+    let set = AccountSerializationSet {
+        account,
+        prev_code_hash: None,
+        boc,
+        boc1: None,
+        proof: None,
+    };
+
+    let mut labels = HashMap::new();
+    labels.insert(code_hash, "wallet".to_string());
+    labels.insert(UInt256::from([1; 32]), "multisig".to_string());
+    let json = db_serialize_account_with_code_labels("id", &set, SerializationMode::Standart, &labels).unwrap();
+    assert_eq!(json["code_label"], "wallet");
+    assert_eq!(json["code_hash"], "3c28164f21b76a53cfe73510197b99c735d4d97b652e6950f317bcbfe955848a");
+
+    labels.remove(&set.account.get_code_hash().unwrap());
+    let json = db_serialize_account_with_code_labels("id", &set, SerializationMode::Standart, &labels).unwrap();
+    assert!(json.get("code_label").is_none());
+    assert!(json.get("code_hash").is_some());
+
+    // no labels without the feature entry point
+    let json = db_serialize_account("id", &set).unwrap();
+    assert!(json.get("code_label").is_none());
+}
+
+#[test]
+fn test_skip_default_fields() {
+    let name = "89ED400A43E76664437EFC9C79B84AC387493A9EE5E789338FF71C25F54218BE";
+    let ethalon = std::fs::read_to_string(format!("src/tests/data/{}-ethalon.json", name)).unwrap();
+    let mut json: Value = serde_json::from_str(&ethalon).unwrap();
+    let full_size = serde_json::to_string(&json).unwrap().len();
+
+    let original = json.clone();
+    skip_default_fields(&mut json, DocumentKind::Block);
+    let reduced_size = serde_json::to_string(&json).unwrap().len();
+    assert!(reduced_size < full_size);
+    let fields = &BLOCK_DEFAULT_FIELDS;
+    for field in fields.falsy.iter().chain(fields.zero).chain(fields.empty) {
+        let is_default = match &original[field] {
+            Value::Bool(value) => !value,
+            Value::Array(items) => items.is_empty(),
+            value => is_zero_value(value),
+        };
+        assert_eq!(json.get(*field).is_none(), is_default, "{}", field);
+    }
+    for field in ["after_merge", "key_block", "vert_seqno_incr", "vert_seq_no", "out_msg_descr"] {
+        assert!(json.get(field).is_none(), "{}", field);
+    }
+    // non default values are kept
+    assert_eq!(json["want_merge"], true);
+    assert_eq!(json["seq_no"], 814551);
+
+    let mut message = serde_json::json!({
+        "bounce": false,
+        "bounced": true,
+        "value": "000",
+        "value_dec": "0",
+        "value_other": [],
+        "fwd_fee": "0x1",
+    });
+    skip_default_fields(&mut message, DocumentKind::Message);
+    assert_eq!(message, serde_json::json!({ "bounced": true, "fwd_fee": "0x1" }));
+}
+
+#[test]
+fn test_message_routing() {
+    let routing = MessageRouting::with_shards(vec![
+        ShardIdent::with_tagged_prefix(0, 0x4000_0000_0000_0000).unwrap(),
+        ShardIdent::with_tagged_prefix(0, 0xc000_0000_0000_0000).unwrap(),
+    ]);
+    let serialize = |dst: MsgAddressInt| {
+        let message = Message::with_int_header(InternalMessageHeader::with_addresses(
+            MsgAddressInt::default(),
+            dst,
+            CurrencyCollection::with_grams(1),
+        ));
+        let cell = message.serialize().unwrap();
+        let set = MessageSerializationSet {
+            message,
+            id: cell.repr_hash(),
+            block_id: None,
+            transaction_id: None,
+            transaction_now: None,
+            status: MessageProcessingStatus::Finalized,
+            boc: write_boc(&cell).unwrap(),
+            proof: None,
+        };
+        db_serialize_message_with_routing("id", &set, SerializationMode::Standart, &routing).unwrap()
+    };
+
+    let json = serialize(MsgAddressInt::with_standart(None, 0, AccountId::from([0x11; 32])).unwrap());
+    assert_eq!(json["dst_shard"], "4000000000000000");
+    assert_eq!(json["dst_workchain"], 0);
+
+    let json = serialize(MsgAddressInt::with_standart(None, 0, AccountId::from([0x99; 32])).unwrap());
+    assert_eq!(json["dst_shard"], "c000000000000000");
+    assert_eq!(json["dst_workchain"], 0);
+
+    // no shards of the workchain
+    let json = serialize(MsgAddressInt::with_standart(None, -1, AccountId::from([0x11; 32])).unwrap());
+    assert_eq!(json["dst_shard"], Value::Null);
+    assert_eq!(json["dst_workchain"], Value::Null);
+    assert_eq!(json["dst_workchain_id"], -1);
+
+    // the masterchain is added to the shards of the masterchain block
+    let routing = MessageRouting::with_shard_hashes(&ShardHashes::default()).unwrap();
+    let address = MsgAddressInt::with_standart(None, -1, AccountId::from([0x11; 32])).unwrap();
+    assert_eq!(routing.find_shard(&address).unwrap(), Some(&ShardIdent::masterchain()));
+}
+
+#[test]
+fn test_diff_configs() {
+    let boc = read("src/tests/data/9C9906A80D020952E0192DC60C0B2BF1F55FE9A9E065606E8FE25C08BD1AA6B2.boc").unwrap();
+    let block = Block::construct_from_cell(read_single_root_boc(&boc).unwrap()).unwrap();
+    let old = block.read_extra().unwrap().read_custom().unwrap().unwrap().config().unwrap().clone();
+    assert!(!old.config_present(44).unwrap());
+    assert!(diff_configs(&old, &old).unwrap().is_empty());
+
+    let mut new = old.clone();
+    let mut p15 = match new.config(15).unwrap() {
+        Some(ConfigParamEnum::ConfigParam15(p15)) => p15,
+        other => panic!("{:?}", other)
+    };
+    let elections_end_before = p15.elections_end_before;
+    p15.elections_end_before += 100;
+    new.set_config(ConfigParamEnum::ConfigParam15(p15)).unwrap();
+    let mut suspended = SuspendedAddresses::new();
+    suspended.add_suspended_address(0, UInt256::from([1; 32])).unwrap();
+    new.set_config(ConfigParamEnum::ConfigParam44(suspended)).unwrap();
+
+    let changes = diff_configs(&old, &new).unwrap();
+    assert_eq!(changes.len(), 2, "{:?}", changes);
+    assert_eq!(changes[0], ConfigChange {
+        param: 15,
+        field_path: "elections_end_before".to_string(),
+        old: Some(elections_end_before.into()),
+        new: Some((elections_end_before + 100).into()),
+    });
+    assert_eq!(
+        changes[0].to_string(),
+        format!("p15/elections_end_before: {} -> {}", elections_end_before, elections_end_before + 100)
+    );
+    assert_eq!(changes[1].param, 44);
+    assert_eq!(changes[1].field_path, "");
+    assert!(changes[1].old.is_none());
+    assert!(changes[1].new.is_some());
+    assert!(changes[1].to_string().starts_with("p44: added "));
+
+    let changes = diff_configs(&new, &old).unwrap();
+    assert_eq!(changes.len(), 2);
+    assert!(changes[1].new.is_none());
+    assert!(changes[1].to_string().starts_with("p44: removed "));
+}
+
+#[test]
+fn test_block_with_envelopes() {
+    const IN_TAGS: [&str; 7] = [
+        "msg_import_ext", "msg_import_ihr", "msg_import_imm", "msg_import_fin",
+        "msg_import_tr", "msg_discard_fin", "msg_discard_tr",
+    ];
+    const OUT_TAGS: [&str; 8] = [
+        "msg_export_ext", "msg_export_imm", "msg_export_new", "msg_export_tr",
+        "msg_export_deq_imm", "msg_export_deq", "msg_export_tr_req", "msg_export_deq_short",
+    ];
+    let mut descriptors = 0;
+    for file in [
+        "18AFCDD25BE0989CE516504263EB351818A0FF8F6AB3689501C8E3B767EF413C.boc",
+        "3FAFAAB7B5D17E439CD9DDEF7EEC3C3BC9D28E7C181BA374218E13599F8F6657.boc",
+        "558651b80d5361fd7f31882d4df90bf8e3c0c58422684e752a47c6b57b7be62c.boc",
+        "6ce37a48b76f9ab9a5b33b727baf3e19da18a7bdee1cf3242ddb2a79c20715e4.boc",
+        "c594aec0b3e52b4177e90d3bf82542151b0edc3e5d86d745689ec25f3d943624.boc",
+    ] {
+        let boc = read(format!("src/tests/data/{}", file)).unwrap();
+        let block = Block::construct_from_bytes(&boc).unwrap();
+        let id = block.hash().unwrap();
+        let set = BlockSerializationSet { block, id, status: BlockProcessingStatus::Finalized, boc };
+        let plain = db_serialize_block_ex("id", &set, SerializationMode::Standart).unwrap();
+        let mut json = db_serialize_block_with_envelopes("id", &set, SerializationMode::Standart).unwrap();
+
+        for (name, tags) in [("in_msg_descr", &IN_TAGS[..]), ("out_msg_descr", &OUT_TAGS[..])] {
+            for entry in json[name].as_array_mut().unwrap() {
+                let envelope = entry.as_object_mut().unwrap().remove("envelope").unwrap();
+                let msg_type = entry["msg_type"].as_u64().unwrap() as usize;
+                assert_eq!(envelope["tag"], tags[msg_type], "{}", file);
+                for side in ["in", "out"] {
+                    if let Some(routing) = envelope.get(side) {
+                        assert!(routing["cur_addr"].is_string(), "{}", file);
+                        assert!(routing["next_addr"].is_string(), "{}", file);
+                        assert!(routing["fwd_fee_remaining"].is_string(), "{}", file);
+                    }
+                }
+                // transit messages have both envelopes
+                if envelope["tag"] == "msg_import_tr" {
+                    assert!(envelope["in"].is_object() && envelope["out"].is_object());
+                    assert!(envelope["transit_fee"].is_string());
+                }
+                descriptors += 1;
+            }
+        }
+        // nothing else is changed
+        assert_eq!(json, plain, "{}", file);
+    }
+    assert_ne!(descriptors, 0);
+}
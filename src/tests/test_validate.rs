@@ -0,0 +1,148 @@
+/*
+ * Copyright (C) ton.dev. All Rights Reserved.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.  You may obtain a copy of the
+ * License at:
+ *
+ * https://www.ever.dev/licenses
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific ton.dev software governing permissions and limitations
+ * under the License.
+ */
+
+use super::*;
+
+fn read_zerostate() -> Map<String, Value> {
+    let ethalon = std::fs::read_to_string("src/tests/data/zerostate-ethalon.json").unwrap();
+    serde_json::from_str(&ethalon).unwrap()
+}
+
+fn errors(diagnostics: &[Diagnostic]) -> Vec<&Diagnostic> {
+    diagnostics.iter().filter(|d| d.severity == Severity::Error).collect()
+}
+
+#[test]
+fn test_validate_valid_zerostate() {
+    let diagnostics = validate_state_json(&read_zerostate());
+    assert!(errors(&diagnostics).is_empty(), "{:?}", diagnostics);
+}
+
+#[test]
+fn test_validate_broken_invariants() {
+    let mut map = read_zerostate();
+    let config = map["master"]["config"].as_object_mut().unwrap();
+    config["p16"]["min_validators"] = 2000.into();
+    config["p17"]["min_stake"] = "20000000000000000".into();
+
+    let diagnostics = validate_state_json(&map);
+    let errors = errors(&diagnostics);
+    assert_eq!(errors.len(), 2, "{:?}", diagnostics);
+    // the same checks as `check_config_economics`, with the paths of the state
+    assert_eq!(errors[0].path, "root/master/config/p16/min_validators");
+    assert_eq!(errors[0].params, [16]);
+    assert_eq!(errors[1].path, "root/master/config/p17/min_stake");
+    assert_eq!(errors[1].params, [17]);
+}
+
+#[test]
+fn test_validate_arbitrary_json() {
+    for json in [
+        serde_json::json!({}),
+        serde_json::json!({ "master": 1 }),
+        serde_json::json!({ "master": { "config": { "p16": [], "p99": {}, "q": null, "p100000": 1 } } }),
+        serde_json::json!({ "master": { "config": { "p34": { "utime_since": "x", "list": 5 } } } }),
+    ] {
+        let diagnostics = validate_state_json(json.as_object().unwrap());
+        assert!(!errors(&diagnostics).is_empty());
+    }
+}
+
+#[test]
+fn test_check_config_economics() {
+    let state = StateParser::for_zero_state().parse_state_unchecked(&read_zerostate()).unwrap();
+    let config = state.read_custom().unwrap().unwrap().config;
+    let diagnostics = check_config_economics(&config);
+    assert!(errors(&diagnostics).is_empty(), "{:?}", diagnostics);
+
+    let mut map = read_zerostate();
+    let config = map["master"]["config"].as_object_mut().unwrap();
+    config["p16"]["max_main_validators"] = 2000.into();
+    config["p17"]["min_stake"] = "20000000000000000".into();
+    config["p20"]["gas_price"] = "0".into();
+
+    let state = StateParser::for_zero_state().parse_state_unchecked(&map).unwrap();
+    let diagnostics = check_config_economics(&state.read_custom().unwrap().unwrap().config);
+    let errors = errors(&diagnostics);
+    assert_eq!(errors.len(), 3, "{:?}", diagnostics);
+    assert_eq!(errors[0].path, "p16/max_main_validators");
+    assert_eq!(errors[0].params, [16]);
+    assert_eq!(errors[1].path, "p17/min_stake");
+    assert_eq!(errors[1].params, [17]);
+    assert_eq!(errors[2].path, "p20/gas_price");
+    assert_eq!(errors[2].params, [20]);
+
+    let err = StateParser::for_zero_state().with_economics_check(true).parse_state_unchecked(&map).unwrap_err();
+    assert!(err.to_string().starts_with("config economics check failed: p16/max_main_validators"), "{}", err);
+}
+
+// the serialized transactions of one account, each one points to the previous
+#[cfg(feature = "serialize")]
+fn serialized_transaction_chain(lts: &[u64], mode: crate::SerializationMode) -> Vec<Value> {
+    use ton_dev_block::{AccountId, AccountStatus, HashUpdate, Serializable, Transaction, TransactionDescr};
+    let mut prev = (UInt256::ZERO, 0);
+    lts.iter().map(|lt| {
+        let mut transaction = Transaction::with_address_and_status(
+            AccountId::from([0x11; 32]), AccountStatus::AccStateActive
+        );
+        transaction.set_logical_time(*lt);
+        transaction.set_prev_trans_hash(prev.0.clone());
+        transaction.set_prev_trans_lt(prev.1);
+        transaction.write_state_update(&HashUpdate::default()).unwrap();
+        transaction.write_description(&TransactionDescr::default()).unwrap();
+        let cell = transaction.serialize().unwrap();
+        let set = crate::TransactionSerializationSet {
+            transaction,
+            id: cell.repr_hash(),
+            status: ton_dev_block::TransactionProcessingStatus::Finalized,
+            block_id: None,
+            workchain_id: 0,
+            boc: ton_dev_block::write_boc(&cell).unwrap(),
+            proof: None,
+        };
+        prev = (cell.repr_hash(), *lt);
+        Value::from(crate::db_serialize_transaction_ex("id", &set, mode).unwrap())
+    }).collect()
+}
+
+#[cfg(feature = "serialize")]
+#[test]
+fn test_verify_transaction_chain() {
+    for mode in [crate::SerializationMode::Standart, crate::SerializationMode::QServer] {
+        let mut chain = serialized_transaction_chain(&[1000, 2000, 3000], mode);
+        verify_transaction_chain(&chain).unwrap();
+        // the order of the documents doesn't matter
+        chain.reverse();
+        verify_transaction_chain(&chain).unwrap();
+        verify_transaction_chain(&chain[..1]).unwrap();
+        verify_transaction_chain(&[]).unwrap();
+
+        // the middle transaction is missing
+        chain.remove(1);
+        let err = verify_transaction_chain(&chain).unwrap_err().to_string();
+        assert!(err.starts_with("transaction chain is broken between lt 1000 and lt 3000: "), "{}", err);
+        assert!(err.contains(" at lt 2000 but the previous one is "), "{}", err);
+    }
+
+    let mut chain = serialized_transaction_chain(&[1000, 2000], crate::SerializationMode::Standart);
+    chain[1]["account_addr"] = "0:2222222222222222222222222222222222222222222222222222222222222222".into();
+    let err = verify_transaction_chain(&chain).unwrap_err().to_string();
+    assert!(err.starts_with("transactions at lt 1000 and lt 2000 are of different accounts"), "{}", err);
+
+    chain[1] = Value::from(1);
+    let err = verify_transaction_chain(&chain).unwrap_err().to_string();
+    assert_eq!(err, "transaction 1 can't be read : root must be the object");
+}
@@ -0,0 +1,52 @@
+/*
+ * Copyright (C) ton.dev. All Rights Reserved.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.  You may obtain a copy of the
+ * License at:
+ *
+ * https://www.ever.dev/licenses
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific ton.dev software governing permissions and limitations
+ * under the License.
+ */
+
+// Only the functions of the `deserialize` feature and no file system: the documents are
+// embedded, so the same tests build for wasm32-unknown-unknown, see scripts/check_wasm.sh
+
+use crate::{check_config_economics, parse_config, validate_state_json, Severity, StateParser};
+use serde_json::{Map, Value};
+
+const ZEROSTATE: &str = include_str!("data/zerostate-ethalon.json");
+
+fn read_zerostate() -> Map<String, Value> {
+    serde_json::from_str(ZEROSTATE).unwrap()
+}
+
+#[test]
+fn test_parse_zerostate_without_files() {
+    let map = read_zerostate();
+    let diagnostics = validate_state_json(&map);
+    assert!(diagnostics.iter().all(|d| d.severity != Severity::Error), "{:?}", diagnostics);
+
+    let state = StateParser::for_zero_state().parse_state_unchecked(&map).unwrap();
+    let config = state.read_custom().unwrap().unwrap().config;
+    assert_eq!(parse_config(map["master"]["config"].as_object().unwrap()).unwrap(), config);
+    assert!(check_config_economics(&config).iter().all(|d| d.severity != Severity::Error));
+
+    // the text is read the same way as the tree
+    let streamed = StateParser::for_zero_state().parse_state_streaming(ZEROSTATE.as_bytes()).unwrap();
+    assert_eq!(streamed, state);
+}
+
+#[test]
+fn test_validate_broken_config_without_files() {
+    let mut map = read_zerostate();
+    map["master"]["config"]["p16"]["min_validators"] = 2000.into();
+    let diagnostics = validate_state_json(&map);
+    assert!(diagnostics.iter().any(|d| d.severity == Severity::Error
+        && d.path == "root/master/config/p16/min_validators"), "{:?}", diagnostics);
+}
@@ -0,0 +1,325 @@
+/*
+ * Copyright (C) ton.dev. All Rights Reserved.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.  You may obtain a copy of the
+ * License at:
+ *
+ * https://www.ever.dev/licenses
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific ton.dev software governing permissions and limitations
+ * under the License.
+ */
+
+use serde_json::{Map, Value};
+use ton_dev_block::{ConfigParamEnum, ConfigParams, GasLimitsPrices, MsgForwardPrices, Result};
+use ton_dev_block::{error, fail, UInt256};
+
+use crate::{is_supported_config_param, DocIdField, PathMap, StateParser};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub path: String,
+    pub severity: Severity,
+    pub message: String,
+    /// Config parameters the diagnostic is about, empty if it is not bound to the config
+    pub params: Vec<u32>,
+}
+
+impl Diagnostic {
+    pub fn error(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { path: path.into(), severity: Severity::Error, message: message.into(), params: Vec::new() }
+    }
+    pub fn warning(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { path: path.into(), severity: Severity::Warning, message: message.into(), params: Vec::new() }
+    }
+    pub fn with_params(mut self, params: &[u32]) -> Self {
+        self.params = params.to_vec();
+        self
+    }
+}
+
+// the same set of parameters as StateParser::for_zero_state requires
+const ZEROSTATE_MANDATORY_PARAMS: [u32; 24] = [
+    0, 1, 2, 7, 8, 9, 10, 11, 12, 14, 15, 16, 17, 18, 20, 21, 22, 23, 24, 25, 28, 29, 31, 34
+];
+
+/// Validates zerostate JSON without building the state.
+/// Every config parameter is parsed on its own so all the broken ones are reported at once.
+pub fn validate_state_json(map: &Map<String, Value>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let config = match PathMap::new(map).get_path_obj("master.config") {
+        Ok(config) => config,
+        Err(err) => {
+            diagnostics.push(Diagnostic::error("root/master/config", err.to_string()));
+            return diagnostics
+        }
+    };
+
+    let mut config_is_valid = true;
+    let mut valid_params = Map::new();
+    for (name, value) in config.iter() {
+        let path = format!("root/master/config/{}", name);
+        let number = match name.strip_prefix('p').and_then(|number| number.parse::<u32>().ok()) {
+            Some(number) if is_supported_config_param(number) => number,
+            _ => {
+                diagnostics.push(Diagnostic::warning(path, "unsupported config parameter is ignored"));
+                continue
+            }
+        };
+        // `auto` p0 needs `master.config_addr`, it is checked by the state parsing below
+        if number == 0 && value == "auto" {
+            continue
+        }
+        let mut single = Map::new();
+        single.insert(name.clone(), value.clone());
+        match StateParser::new().with_mandatory_params(&[number]).parse_config_params(&single) {
+            Ok(_) => {
+                valid_params.insert(name.clone(), value.clone());
+            }
+            Err(err) => {
+                config_is_valid = false;
+                diagnostics.push(Diagnostic::error(path, err.to_string()));
+            }
+        }
+    }
+    for number in ZEROSTATE_MANDATORY_PARAMS {
+        let name = format!("p{}", number);
+        if !config.iter().any(|(key, _)| *key == name) {
+            config_is_valid = false;
+            diagnostics.push(Diagnostic::error(
+                format!("root/master/config/{}", name), "mandatory parameter is missing"
+            ));
+        }
+    }
+
+    // the economics are checked on the parameters parsed above, the broken ones are reported already
+    if let Ok(params) = StateParser::new().parse_config_params(&valid_params) {
+        for mut diagnostic in check_config_economics(&params) {
+            diagnostic.path = match diagnostic.path.as_str() {
+                "config" => "root/master/config".to_string(),
+                path => format!("root/master/config/{}", path),
+            };
+            diagnostics.push(diagnostic);
+        }
+    }
+    check_validator_set_times(&config, &mut diagnostics);
+
+    if config_is_valid {
+        if let Err(err) = StateParser::for_zero_state().parse_state_unchecked(map) {
+            diagnostics.push(Diagnostic::error("root", err.to_string()));
+        }
+    }
+    diagnostics
+}
+
+fn check_validator_set_times(config: &PathMap, diagnostics: &mut Vec<Diagnostic>) {
+    let root = "root/master/config";
+    for name in ["p32", "p33", "p34", "p35", "p36", "p37"] {
+        if let Ok(set) = config.get_obj(name) {
+            if let (Ok(since), Ok(until)) = (set.get_num("utime_since"), set.get_num("utime_until")) {
+                if until <= since {
+                    diagnostics.push(Diagnostic::error(
+                        format!("{}/{}/utime_until", root, name),
+                        format!("utime_until ({}) must be greater than utime_since ({})", until, since)
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Cross-parameter sanity checks of the economics of the parsed config: stakes, election
+/// windows, validator counts, storage, gas and forwarding prices.
+/// The parameters absent in the config are skipped.
+pub fn check_config_economics(config: &ConfigParams) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    if let Err(err) = check_economics(config, &mut diagnostics) {
+        diagnostics.push(Diagnostic::error("config", format!("config can't be read : {}", err)));
+    }
+    diagnostics
+}
+
+fn check_economics(config: &ConfigParams, diagnostics: &mut Vec<Diagnostic>) -> Result<()> {
+    let p16 = match config.config(16)? {
+        Some(ConfigParamEnum::ConfigParam16(p16)) => Some(p16),
+        _ => None
+    };
+    if let Some(p16) = &p16 {
+        let (min, max, max_main) = (
+            p16.min_validators.as_u32(), p16.max_validators.as_u32(), p16.max_main_validators.as_u32()
+        );
+        if min > max {
+            diagnostics.push(Diagnostic::error(
+                "p16/min_validators",
+                format!("min_validators ({}) must not exceed max_validators ({})", min, max)
+            ).with_params(&[16]));
+        }
+        if max_main > max {
+            diagnostics.push(Diagnostic::error(
+                "p16/max_main_validators",
+                format!("max_main_validators ({}) must not exceed max_validators ({})", max_main, max)
+            ).with_params(&[16]));
+        }
+    }
+    if let Some(ConfigParamEnum::ConfigParam17(p17)) = config.config(17)? {
+        let (min, max) = (p17.min_stake.as_u128(), p17.max_stake.as_u128());
+        if min > max {
+            diagnostics.push(Diagnostic::error(
+                "p17/min_stake",
+                format!("min_stake ({}) must not exceed max_stake ({})", min, max)
+            ).with_params(&[17]));
+        }
+        if let Some(p16) = &p16 {
+            let max_total = max.saturating_mul(p16.max_validators.as_u32() as u128);
+            if p17.min_total_stake.as_u128() > max_total {
+                diagnostics.push(Diagnostic::error(
+                    "p17/min_total_stake",
+                    format!("min_total_stake ({}) must not exceed max_stake * max_validators ({})",
+                        p17.min_total_stake.as_u128(), max_total)
+                ).with_params(&[16, 17]));
+            }
+        }
+    }
+    if let Some(ConfigParamEnum::ConfigParam15(p15)) = config.config(15)? {
+        if p15.elections_start_before > p15.validators_elected_for {
+            diagnostics.push(Diagnostic::error(
+                "p15/elections_start_before",
+                format!("elections_start_before ({}) must not exceed validators_elected_for ({})",
+                    p15.elections_start_before, p15.validators_elected_for)
+            ).with_params(&[15]));
+        }
+        if p15.elections_end_before >= p15.elections_start_before {
+            diagnostics.push(Diagnostic::error(
+                "p15/elections_end_before",
+                format!("elections_end_before ({}) must be less than elections_start_before ({})",
+                    p15.elections_end_before, p15.elections_start_before)
+            ).with_params(&[15]));
+        }
+    }
+    if let Some(ConfigParamEnum::ConfigParam18(p18)) = config.config(18)? {
+        let mut prev = None;
+        p18.map.iterate(|prices| {
+            if let Some(prev) = prev {
+                if prices.utime_since <= prev {
+                    diagnostics.push(Diagnostic::error(
+                        "p18/utime_since",
+                        format!("storage prices must be sorted by utime_since but {} follows {}",
+                            prices.utime_since, prev)
+                    ).with_params(&[18]));
+                }
+            }
+            prev = Some(prices.utime_since);
+            Ok(true)
+        })?;
+    }
+    for number in [20, 21] {
+        let prices = match config.config(number)? {
+            Some(ConfigParamEnum::ConfigParam20(prices)) | Some(ConfigParamEnum::ConfigParam21(prices)) => prices,
+            _ => continue
+        };
+        check_gas_prices(number, &prices, diagnostics);
+    }
+    for number in [24, 25] {
+        let prices = match config.config(number)? {
+            Some(ConfigParamEnum::ConfigParam24(prices)) | Some(ConfigParamEnum::ConfigParam25(prices)) => prices,
+            _ => continue
+        };
+        check_fwd_prices(number, &prices, diagnostics);
+    }
+    Ok(())
+}
+
+fn check_gas_prices(number: u32, prices: &GasLimitsPrices, diagnostics: &mut Vec<Diagnostic>) {
+    if prices.gas_price == 0 {
+        diagnostics.push(Diagnostic::error(
+            format!("p{}/gas_price", number), "gas_price must not be zero"
+        ).with_params(&[number]));
+    }
+    if prices.flat_gas_limit != 0 && prices.flat_gas_price == 0 {
+        diagnostics.push(Diagnostic::error(
+            format!("p{}/flat_gas_price", number), "flat_gas_price must not be zero with non-zero flat_gas_limit"
+        ).with_params(&[number]));
+    }
+}
+
+fn check_fwd_prices(number: u32, prices: &MsgForwardPrices, diagnostics: &mut Vec<Diagnostic>) {
+    if prices.first_frac > prices.next_frac {
+        diagnostics.push(Diagnostic::warning(
+            format!("p{}/first_frac", number),
+            format!("first_frac ({}) exceeds next_frac ({})", prices.first_frac, prices.next_frac)
+        ).with_params(&[number]));
+    }
+}
+
+// the fields of a serialized transaction linking it to the previous one of the account
+struct ChainLink {
+    id: UInt256,
+    lt: u64,
+    prev_trans_hash: UInt256,
+    prev_trans_lt: u64,
+    account: String,
+}
+
+fn read_chain_link(doc: &Value) -> Result<ChainLink> {
+    let doc = PathMap::new(doc.as_object().ok_or_else(|| error!("root must be the object"))?);
+    let id = DocIdField::ALL.into_iter().map(DocIdField::name).find(|name| doc.has_field(name))
+        .ok_or_else(|| error!("root must have the field `id`, `_key` or `_id`"))?;
+    Ok(ChainLink {
+        id: doc.get_uint256(id)?,
+        lt: doc.get_u64_radix("lt")?,
+        prev_trans_hash: doc.get_uint256("prev_trans_hash")?,
+        prev_trans_lt: doc.get_u64_radix("prev_trans_lt")?,
+        // the documents serialized without the workchain have only the account id
+        account: match doc.has_field("account_addr") {
+            true => doc.get_str("account_addr")?.to_owned(),
+            false => doc.get_str("account_id")?.to_owned(),
+        },
+    })
+}
+
+/// Checks the transaction documents of one account written by `db_serialize_transaction_ex`
+/// in the `Standart` or `QServer` mode form an unbroken chain: ordered by `lt`, every
+/// `prev_trans_hash` and `prev_trans_lt` must be the id and `lt` of the previous document.
+/// The first one is not checked, its previous transaction is not given.
+/// Fails on the first break naming the lts of both transactions
+pub fn verify_transaction_chain(entries: &[Value]) -> Result<()> {
+    let mut chain = entries.iter().enumerate()
+        .map(|(index, entry)| read_chain_link(entry)
+            .map_err(|err| error!("transaction {} can't be read : {}", index, err)))
+        .collect::<Result<Vec<_>>>()?;
+    chain.sort_by_key(|link| link.lt);
+    for pair in chain.windows(2) {
+        let (prev, next) = (&pair[0], &pair[1]);
+        if next.account != prev.account {
+            fail!("transactions at lt {} and lt {} are of different accounts {} and {}",
+                prev.lt, next.lt, prev.account, next.account)
+        }
+        if next.lt == prev.lt {
+            fail!("transactions {} and {} have the same lt {}",
+                prev.id.as_hex_string(), next.id.as_hex_string(), next.lt)
+        }
+        if next.prev_trans_hash != prev.id || next.prev_trans_lt != prev.lt {
+            fail!(
+                "transaction chain is broken between lt {} and lt {}: transaction {} points \
+                to {} at lt {} but the previous one is {}",
+                prev.lt, next.lt, next.id.as_hex_string(), next.prev_trans_hash.as_hex_string(),
+                next.prev_trans_lt, prev.id.as_hex_string()
+            )
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "tests/test_validate.rs"]
+mod tests;